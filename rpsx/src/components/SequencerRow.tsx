@@ -86,7 +86,7 @@ export default function SequencerRow({ soundId, part }: { soundId: string; part:
     else seq.setModuleKind('synth');
   }, [browser?.moduleKindById?.[soundId]]);
 
-  // Keyboard: 7 toggle menu, 8 legato, W/R step left/right; Q add, A remove; Space-hold selection; C copy; V paste
+  // Keyboard: 7 toggle menu, 8 legato, 9 accent, 0 slide, W/R step left/right; Q add, A remove; Space-hold selection; C copy; V paste
   useEffect(() => {
     const onKeyDown = (e: KeyboardEvent) => {
       // 7 toggles the Sequence Options menu
@@ -108,6 +108,9 @@ export default function SequencerRow({ soundId, part }: { soundId: string; part:
       if (keyIs(e, ['KeyA'], ['a','A'])) { e.preventDefault(); seq.removeNoteAtSelection(); return; }
       // 8 toggles legato
       if (keyIs(e, ['Digit8','Numpad8'], ['8'])) { e.preventDefault(); seq.toggleLegatoAtSelection(); return; }
+      // 9 / 0 toggle the 303-style accent and slide step flags
+      if (keyIs(e, ['Digit9','Numpad9'], ['9'])) { e.preventDefault(); seq.toggleAccentAtSelection(); return; }
+      if (keyIs(e, ['Digit0','Numpad0'], ['0'])) { e.preventDefault(); seq.toggleSlideAtSelection(); return; }
     };
     const onKeyUp = (e: KeyboardEvent) => {
       if (menu) return;
@@ -352,16 +355,19 @@ export default function SequencerRow({ soundId, part }: { soundId: string; part:
         const cy = (CIRCLE/2) + Math.sin(theta) * baseR;
         const r = 3 + Math.round(n.vel * 3);
               const isSelectedNote = selected && j === Math.max(0, Math.min(notes.length - 1, seq.noteIndex));
+              // Accent fills the dot with the accent colour; slide adds an outer ring
+              const fill = n.accent ? 'var(--accent-2)' : (n.legato ? 'var(--text)' : '#fff');
+              const ring = isSelectedNote ? '0 0 0 2px var(--accent)' : (n.slide ? '0 0 0 1px var(--text)' : 'none');
               if (n.legato) {
                 // High-contrast donut with clear punched hole: thin 2px ring
                 const innerR = Math.max(1, r - 2);
                 return (
-                  <div key={j} style={{ position: 'absolute', left: cx - r, top: cy - r, width: r * 2, height: r * 2, background: 'var(--text)', borderRadius: r * 2, boxShadow: isSelectedNote ? '0 0 0 2px var(--accent)' : 'none', zIndex: isSelectedNote ? 2 : 1 }}>
+                  <div key={j} style={{ position: 'absolute', left: cx - r, top: cy - r, width: r * 2, height: r * 2, background: fill, borderRadius: r * 2, boxShadow: ring, zIndex: isSelectedNote ? 2 : 1 }}>
                     <div style={{ position: 'absolute', left: r - innerR, top: r - innerR, width: innerR * 2, height: innerR * 2, background: 'var(--bg, #000)', borderRadius: innerR * 2 }} />
                   </div>
                 );
               }
-              return <div key={j} style={{ position: 'absolute', left: cx - r, top: cy - r, width: r * 2, height: r * 2, background: '#fff', borderRadius: r * 2, boxShadow: isSelectedNote ? '0 0 0 2px var(--accent)' : 'none', zIndex: isSelectedNote ? 2 : 1 }} />
+              return <div key={j} style={{ position: 'absolute', left: cx - r, top: cy - r, width: r * 2, height: r * 2, background: fill, borderRadius: r * 2, boxShadow: ring, zIndex: isSelectedNote ? 2 : 1 }} />
             })}
             </div>
             {/* Ghost rectangles for other sequences at this same step index */}
@@ -79,7 +79,7 @@ export default function Drubbles() {
     <div style={{ flex:1, overflowY:'auto', padding:4, display:'flex', flexWrap:'wrap', gap:12, alignContent:'flex-start' }}>
       {samples.length===0 && <div style={{ fontSize:11, opacity:0.6, padding:6 }}>Select a Drubbles pack (Q)</div>}
       {samples.map((sm: string, i: number) => {
-        const baseName = sm.replace(/\.[a-z0-9]+$/i,'');
+        const baseName = s.drumSlots?.[i]?.name || sm.replace(/\.[a-z0-9]+$/i,'');
         const sel = i===sampleSel;
         return (
           <div
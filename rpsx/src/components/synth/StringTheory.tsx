@@ -25,6 +25,9 @@ export default function StringTheory() {
     damp: typeof raw.damp === 'number' && isFinite(raw.damp) ? raw.damp : 0.5,
     excite: typeof raw.excite === 'number' && isFinite(raw.excite) ? raw.excite : 0.7,
     tune: typeof raw.tune === 'number' && isFinite(raw.tune) ? raw.tune : 0.0,
+    pick: typeof raw.pick === 'number' && isFinite(raw.pick) ? raw.pick : 0.25,
+    body: typeof raw.body === 'number' && isFinite(raw.body) ? raw.body : 0.0,
+    sympathetic: typeof raw.sympathetic === 'number' && isFinite(raw.sympathetic) ? raw.sympathetic : 0.0,
   };
   const update = (patch: Partial<typeof karplus>) => s.updateSynthUI((u: any) => ({ ...u, karplus: { ...(u.karplus || {}), ...patch } }));
 
@@ -51,6 +54,12 @@ export default function StringTheory() {
         <Knob label="Excite" value={karplus.excite} onChange={(v) => { update({ excite: v }); s.setSynthParam(`part/${part}/ks/excite`, v); }} format={(v) => `${(v * 100).toFixed(0)}%`} />
         <Knob label="Tune" value={karplus.tune} onChange={(v) => { update({ tune: v }); s.setSynthParam(`part/${part}/ks/tune`, v); }} format={(v) => `${(v * 12 - 6).toFixed(1)} st`} />
       </Row>
+      <div style={{ height: 12 }} />
+      <Row>
+        <Knob label="Pick" value={karplus.pick} onChange={(v) => { update({ pick: v }); s.setSynthParam(`part/${part}/ks/pick`, v); }} format={(v) => `${(v * 100).toFixed(0)}%`} />
+        <Knob label="Body" value={karplus.body} onChange={(v) => { update({ body: v }); s.setSynthParam(`part/${part}/ks/body`, v); }} format={(v) => `${(v * 100).toFixed(0)}%`} />
+        <Knob label="Sympathy" value={karplus.sympathetic} onChange={(v) => { update({ sympathetic: v }); s.setSynthParam(`part/${part}/ks/sympathetic`, v); }} format={(v) => `${(v * 100).toFixed(0)}%`} />
+      </Row>
 
   {/* Help text removed per spec */}
     </Page>
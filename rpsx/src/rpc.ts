@@ -20,24 +20,50 @@ async function safeInvoke<T>(cmd: string, payload?: any, fallback?: T): Promise<
   }
 }
 
+export type DrumSlotZone = { file: string; vel_lo: number; vel_hi: number };
+export type DrumSlot = { file: string; zones: DrumSlotZone[]; name: string; note: number; volume: number; pan: number; pitch: number };
+export type DrumSlotAssign = { file: string; note: number };
+
+export type SampleEdit =
+  | { op: "trim"; start: number; end: number }
+  | { op: "normalize"; mode: "peak" | "lufs"; target_db: number }
+  | { op: "reverse" }
+  | { op: "fade"; fade_in_ms?: number; fade_out_ms?: number }
+  | { op: "remove_dc" }
+  | { op: "crop_to_loop"; sample_start: number; sample_end: number; loop_start: number; loop_end: number }
+  | { op: "channels"; count: 1 | 2 };
+
 export const rpc = {
   startAudio: () => safeInvoke<void>("start_audio", undefined),
-  noteOn: (part: number, note: number, vel: number) => safeInvoke<void>("note_on", { part, note, vel }),
+  noteOn: (part: number, note: number, vel: number, accent = false, slide = false, channel = 0) => safeInvoke<void>("note_on", { part, note, vel, accent, slide, channel }),
   noteOff: (part: number, note: number) => safeInvoke<void>("note_off", { part, note }),
+  pitchBend: (part: number, value: number) => safeInvoke<void>("pitch_bend", { part, value }),
+  modWheel: (part: number, value: number) => safeInvoke<void>("mod_wheel", { part, value }),
+  aftertouch: (part: number, value: number, note?: number) => safeInvoke<void>("aftertouch", { part, note: note ?? null, value }),
+  sustain: (part: number, on: boolean) => safeInvoke<void>("sustain", { part, on }),
+  noteBend: (part: number, channel: number, value: number) => safeInvoke<void>("note_bend", { part, channel, value }),
+  notePressure: (part: number, channel: number, value: number) => safeInvoke<void>("note_pressure", { part, channel, value }),
+  noteSlide: (part: number, channel: number, value: number) => safeInvoke<void>("note_slide", { part, channel, value }),
   setParam: (path: string, value: any) => safeInvoke<void>("set_param", { path, value }),
   startRecording: () => safeInvoke<void>("start_recording", undefined),
   stopRecording: () => safeInvoke<void>("stop_recording", undefined),
   listSubsamples: () => safeInvoke<string[]>("list_subsamples", undefined, []),
   loadSample: (part: number, path: string) => safeInvoke<void>("load_sample", { part, path }),
   clearSample: (part: number) => safeInvoke<void>("clear_sample", { part }),
+  loadWavetable: (part: number, path: string) => safeInvoke<void>("load_wavetable", { part, path }),
+  loadResonatorProfile: (part: number, path: string) => safeInvoke<void>("load_resonator_profile", { part, path }),
   previewSample: (path: string) => safeInvoke<void>("preview_sample", { path }),
   stopPreview: () => safeInvoke<void>("stop_preview"),
   getSampleWaveform: (path: string) => safeInvoke<number[]>("get_sample_waveform", { path }, []),
   getSampleInfo: (path: string) => safeInvoke<{ length_samples: number; sample_rate: number; channels: number }>("get_sample_info", { path }, { length_samples: 0, sample_rate: 44100, channels: 1 }),
   getSamplerPlayhead: (part: number) => safeInvoke<any>("get_sampler_playhead", { part }, null),
   deleteSubsample: (path: string) => safeInvoke<void>("delete_subsample", { path }),
+  editSample: (path: string, edit: SampleEdit) => safeInvoke<string>("edit_sample", { path, edit }),
+  undoSampleEdit: (path: string) => safeInvoke<string>("undo_sample_edit", { path }),
   setTempo: (bpm: number) => safeInvoke<void>("set_tempo", { bpm }),
   listDrumPacks: () => safeInvoke<string[]>("list_drum_packs", undefined, []),
   listDrumSamples: (pack: string) => safeInvoke<string[]>("list_drum_samples", { pack }, []),
-  loadDrumPack: (part: number, pack: string) => safeInvoke<void>("load_drum_pack", { part, pack }),
+  loadDrumPack: (part: number, pack: string, slotMap?: DrumSlotAssign[]) => safeInvoke<DrumSlot[]>("load_drum_pack", { part, pack, slotMap }, []),
+  setDrumSlotNote: (part: number, slot: number, note: number) => safeInvoke<void>("set_drum_slot_note", { part, slot, note }),
+  swapDrumSlots: (part: number, a: number, b: number) => safeInvoke<void>("swap_drum_slots", { part, a, b }),
 };
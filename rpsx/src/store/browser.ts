@@ -1,6 +1,6 @@
 import { useEffect, useSyncExternalStore } from "react";
 import { fsClient, Pattern, Project, Sound } from "../fsClient";
-import { rpc } from "../rpc";
+import { rpc, DrumSlot } from "../rpc";
 import { sequencerSetCurrentPattern, sequencerStopAll, sequencerDeleteForSound, sequencerDeleteForPattern, sequencerSetPart, sequencerSetAllowedSounds } from '../store/sequencer';
 import { envTimeFromNorm, envTimeMsFromNorm, envTimeNormFromMilliseconds, envTimeNormFromSeconds } from "../utils/envTime";
 import type { ViewName } from "../types/ui";
@@ -42,6 +42,7 @@ export interface BrowserState {
   drumPackSelected?: number;
   drumSampleItems?: string[];
   drumSampleSelected?: number;
+  drumSlots?: DrumSlot[]; // slot layout of the loaded pack (same order as drumSampleItems)
   drumPreviewing?: boolean;
   isRecording: boolean;
   // Project settings menu
@@ -64,6 +65,8 @@ export interface BrowserState {
   drumSampleMoveUp?: () => void;
   drumSampleMoveDown?: () => void;
   drumTogglePreview?: () => Promise<void>;
+  drumSetSlotNote?: (slot: number, note: number) => Promise<void>;
+  drumSwapSlots?: (a: number, b: number) => Promise<void>;
   confirmOpen?: boolean;
   confirmKind?: 'project'|'pattern'|'module';
   confirmLabel?: string;
@@ -166,6 +169,7 @@ const state: InternalState = {
   drumPackSelected: 0,
   drumSampleItems: [],
   drumSampleSelected: 0,
+  drumSlots: [],
   drumPreviewing: false,
   isRecording: false,
   projectSettingsOpen: false,
@@ -828,24 +832,57 @@ state.drumPackLoadSelected = async () => {
   const packs = state.drumPackItems||[]; const sel = state.drumPackSelected||0;
   const pack = packs[sel]; if (!pack) return;
   const part = state.selectedSoundPart ?? 0;
-  try { await rpc.loadDrumPack(part, pack); } catch(e){ console.error('loadDrumPack failed', e); }
+  let slots: DrumSlot[] = [];
+  try { slots = (await rpc.loadDrumPack(part, pack)) || []; } catch(e){ console.error('loadDrumPack failed', e); }
   // fetch samples for display
-  let samples: string[] = [];
-  try { samples = await rpc.listDrumSamples(pack); } catch(e){ console.error('listDrumSamples failed', e); }
-  // Persist selected pack into per-sound UI and save preset
+  let samples: string[] = slots.map(sl => sl.file);
+  if (!samples.length) {
+    try { samples = await rpc.listDrumSamples(pack); } catch(e){ console.error('listDrumSamples failed', e); }
+  }
+  // Persist selected pack and its slot map into per-sound UI and save preset
   try {
     const id = state.selectedSoundId;
     if (id) {
       const map = state.synthUIById || {} as any;
       const ui = map[id] ? { ...map[id] } : defaultSynthUI();
-      ui.drum = { ...(ui.drum||{}), current_pack: pack };
+      ui.drum = { ...(ui.drum||{}), current_pack: pack, slot_map: slots.map(sl => ({ file: sl.file, note: sl.note })) };
       map[id] = ui;
       set({ synthUIById: { ...map }, synthUIVersion: (state.synthUIVersion||0)+1 });
       const preset = uiToSchema(ui as any);
       const proj = state.projectName; if (proj) { await fsClient.saveSoundPreset(proj, id, preset); }
     }
   } catch (e) { console.error('persist drum pack failed', e); }
-  set({ drumPackBrowserOpen: false, drumSampleItems: samples, drumSampleSelected: 0 });
+  set({ drumPackBrowserOpen: false, drumSampleItems: samples, drumSampleSelected: 0, drumSlots: slots });
+};
+// Save the current slot->note layout into the sound preset so patterns keep their sounds
+async function persistDrumSlots(slots: DrumSlot[]) {
+  set({ drumSlots: slots });
+  const id = state.selectedSoundId; if (!id) return;
+  const map = state.synthUIById || {} as any;
+  const ui = map[id] ? { ...map[id] } : defaultSynthUI();
+  ui.drum = { ...(ui.drum||{}), slot_map: slots.map(sl => ({ file: sl.file, note: sl.note })) };
+  map[id] = ui;
+  set({ synthUIById: { ...map }, synthUIVersion: (state.synthUIVersion||0)+1 });
+  const proj = state.projectName; if (proj) { await fsClient.saveSoundPreset(proj, id, uiToSchema(ui as any)); }
+}
+state.drumSetSlotNote = async (slot: number, note: number) => {
+  const slots = (state.drumSlots||[]).map(sl => ({ ...sl }));
+  if (!slots[slot] || note < 0 || note > 127) return;
+  const part = state.selectedSoundPart ?? 0;
+  try { await rpc.setDrumSlotNote(part, slot, note); } catch(e){ console.error('setDrumSlotNote failed', e); return; }
+  // Mirror the engine: a slot already on that note takes the old one
+  const other = slots.findIndex(sl => sl.note === note);
+  if (other >= 0) slots[other].note = slots[slot].note;
+  slots[slot].note = note;
+  try { await persistDrumSlots(slots); } catch (e) { console.error('persist drum slots failed', e); }
+};
+state.drumSwapSlots = async (a: number, b: number) => {
+  const slots = (state.drumSlots||[]).map(sl => ({ ...sl }));
+  if (!slots[a] || !slots[b]) return;
+  const part = state.selectedSoundPart ?? 0;
+  try { await rpc.swapDrumSlots(part, a, b); } catch(e){ console.error('swapDrumSlots failed', e); return; }
+  const n = slots[a].note; slots[a].note = slots[b].note; slots[b].note = n;
+  try { await persistDrumSlots(slots); } catch (e) { console.error('persist drum slots failed', e); }
 };
 state.drumSampleMoveUp = () => {
   const items = state.drumSampleItems||[]; if (!items.length) return;
@@ -865,7 +902,7 @@ state.drumTogglePreview = async () => {
   const samples = state.drumSampleItems||[]; const sidx = state.drumSampleSelected||0;
   if (!samples.length) return;
   const part = state.selectedSoundPart ?? 0;
-  const note = 36 + sidx; // slot mapping logic matches drum slot_for_note
+  const note = state.drumSlots?.[sidx]?.note ?? (36 + sidx); // trigger note of the selected slot
   try { await rpc.startAudio(); } catch {}
   try { await rpc.noteOn(part, note, 0.9); } catch(e){ console.error('drum preview noteOn failed', e); }
   // mark previewing momentarily for UI feedback
@@ -1161,6 +1198,9 @@ export type SynthUI = {
     damp: number;
     excite: number;
     tune: number;
+    pick?: number;
+    body?: number;
+    sympathetic?: number;
   };
   resonator?: {
     pitch: number;
@@ -1255,6 +1295,9 @@ function defaultSynthUI(): SynthUI {
       damp: 0.5,
       excite: 0.1,
       tune: 0.0,
+      pick: 0.25,
+      body: 0.0,
+      sympathetic: 0.0,
     },
     // Add default ResonatorBank parameters
     resonator: {
@@ -1420,7 +1463,7 @@ function uiToSchema(ui: SynthUI) {
     params: {
       module_kind: getCurrentModuleKind(),
       // Persist selected Drubbles pack name if available (stored in per-sound UI)
-      drum: (ui as any).drum && (ui as any).drum.current_pack ? { current_pack: (ui as any).drum.current_pack } : undefined,
+      drum: (ui as any).drum && (ui as any).drum.current_pack ? { current_pack: (ui as any).drum.current_pack, slot_map: (ui as any).drum.slot_map } : undefined,
       oscA: { shape: Math.round(ui.oscA.shape*7), detune_cents: detuneCents(ui.oscA.detune), fm_to_B: ui.oscA.fm, level: ui.oscA.level },
       oscB: { shape: Math.round(ui.oscB.shape*7), detune_cents: detuneCents(ui.oscB.detune), fm_to_A: ui.oscB.fm, level: ui.oscB.level },
       amp_env: { attack: envTimeFromNorm(ui.ampEnv.a), decay: envTimeFromNorm(ui.ampEnv.d), sustain: ui.ampEnv.s, release: envTimeFromNorm(ui.ampEnv.r) },
@@ -1462,6 +1505,9 @@ function uiToSchema(ui: SynthUI) {
         damp: (ui as any).karplus.damp ?? 0.5,
         excite: (ui as any).karplus.excite ?? 0.7,
         tune: (ui as any).karplus.tune ?? 0.0,
+        pick: (ui as any).karplus.pick ?? 0.25,
+        body: (ui as any).karplus.body ?? 0.0,
+        sympathetic: (ui as any).karplus.sympathetic ?? 0.0,
       } : undefined,
       resonator: (ui as any).resonator ? {
         pitch: (ui as any).resonator.pitch ?? 0.5,
@@ -1563,7 +1609,7 @@ async function applyPreset(preset: any) {
   // Update UI state from preset
   state.updateSynthUI((ui: any) => ({
     ...ui,
-  drum: { current_pack: preset.params?.drum?.current_pack },
+  drum: { current_pack: preset.params?.drum?.current_pack, slot_map: preset.params?.drum?.slot_map },
     oscA: { shape: (p.oscA?.shape ?? 0)/7, detune: ((p.oscA?.detune_cents ?? 0)/200)+0.5, fm: p.oscA?.fm_to_B ?? 0, level: p.oscA?.level ?? 0.7 },
     oscB: { shape: (p.oscB?.shape ?? 0)/7, detune: ((p.oscB?.detune_cents ?? 0)/200)+0.5, fm: p.oscB?.fm_to_A ?? 0, level: p.oscB?.level ?? 0.0 },
     ampEnv: { a: invMapTime(p.amp_env?.attack ?? 0.01), d: invMapTime(p.amp_env?.decay ?? 0.2), s: p.amp_env?.sustain ?? 0.8, r: invMapTime(p.amp_env?.release ?? 0.2) },
@@ -1612,6 +1658,9 @@ async function applyPreset(preset: any) {
       damp: p.karplus?.damp ?? 0.5,
       excite: p.karplus?.excite ?? 0.7,
       tune: p.karplus?.tune ?? 0.0,
+      pick: p.karplus?.pick ?? 0.25,
+      body: p.karplus?.body ?? 0.0,
+      sympathetic: p.karplus?.sympathetic ?? 0.0,
     },
     resonator: {
       pitch: p.resonator?.pitch ?? 0.5,
@@ -1681,9 +1730,10 @@ async function applyPreset(preset: any) {
   try {
     const pack = p?.drum?.current_pack;
     if (typeof pack === 'string' && pack.length > 0) {
-      await rpc.loadDrumPack(part, pack);
-      // Also update UI drum sample list for display if available
-      try { const samples = await rpc.listDrumSamples(pack); set({ drumSampleItems: samples, drumSampleSelected: 0 }); } catch {}
+      const slots = (await rpc.loadDrumPack(part, pack, p.drum?.slot_map)) || [];
+      // Also update UI drum sample list for display
+      if (slots.length) { set({ drumSampleItems: slots.map(sl => sl.file), drumSampleSelected: 0, drumSlots: slots }); }
+      else { try { const samples = await rpc.listDrumSamples(pack); set({ drumSampleItems: samples, drumSampleSelected: 0, drumSlots: [] }); } catch {} }
     }
   } catch (e) { console.error('applyPreset drum pack load failed', e); }
   // Module kind - also update UI state module hint
@@ -1794,6 +1844,9 @@ async function applyPreset(preset: any) {
     send(`ks/damp`, { F32: p.karplus.damp ?? 0.5 });
     send(`ks/excite`, { F32: p.karplus.excite ?? 0.7 });
     send(`ks/tune`, { F32: p.karplus.tune ?? 0.0 });
+    send(`ks/pick`, { F32: p.karplus.pick ?? 0.25 });
+    send(`ks/body`, { F32: p.karplus.body ?? 0.0 });
+    send(`ks/sympathetic`, { F32: p.karplus.sympathetic ?? 0.0 });
   }
   // ResonatorBank macros
   if (p.resonator) {
@@ -1908,7 +1961,7 @@ async function preloadAndReplayProjectPresets(project: string) {
       if (typeof p.module_kind === 'number') { await set(`module_kind`, { I32: p.module_kind }); }
       // If drum with a saved pack, load it first
       if (p.drum && typeof p.drum.current_pack === 'string' && p.drum.current_pack.length > 0) {
-        try { await rpc.loadDrumPack(part, p.drum.current_pack); } catch (e) { console.error('preload drum pack failed', e); }
+        try { await rpc.loadDrumPack(part, p.drum.current_pack, p.drum.slot_map); } catch (e) { console.error('preload drum pack failed', e); }
       }
       // If sampler with a saved file, load it first
       if (p.sampler && typeof p.sampler.current_sample === 'string' && p.sampler.current_sample.length > 0) {
@@ -1994,6 +2047,9 @@ async function preloadAndReplayProjectPresets(project: string) {
         await set(`ks/damp`, { F32: p.karplus.damp ?? 0.5 });
         await set(`ks/excite`, { F32: p.karplus.excite ?? 0.7 });
         await set(`ks/tune`, { F32: p.karplus.tune ?? 0.0 });
+        await set(`ks/pick`, { F32: p.karplus.pick ?? 0.25 });
+        await set(`ks/body`, { F32: p.karplus.body ?? 0.0 });
+        await set(`ks/sympathetic`, { F32: p.karplus.sympathetic ?? 0.0 });
       }
       if (p.resonator) {
         await set(`resonator/pitch`, { F32: (p.resonator.pitch ?? 0.5) * 2 - 1 }); // Transform to ±1 range
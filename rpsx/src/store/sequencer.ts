@@ -4,7 +4,9 @@ import { rpc } from '../rpc';
 // Types
 export type SequencerResolution = '1/4' | '1/8' | '1/16' | '1/32' | '1/8t' | '1/16t';
 export type SequencerMode = 'tempo' | 'poly';
-export type SequencerNote = { midi: number; vel: number; legato?: boolean };
+// legato ties a note into the same pitch on the previous step; accent and slide are 303-style
+// step flags (slide glides from the previous step's note without retriggering)
+export type SequencerNote = { midi: number; vel: number; legato?: boolean; accent?: boolean; slide?: boolean };
 export type SequencerStep = { time: number; notes: SequencerNote[] };
 // Read-only ghost summary type for pattern-wide visualization
 export type PatternSeqGhost = { soundId: string; length: number; has: boolean[] };
@@ -496,7 +498,7 @@ function tick(ts: number) {
         for (const n of curr) {
           const cont = n.legato && prevSet.has(n.midi);
           if (!cont && typeof part === 'number') {
-            try { rpc.noteOn(part, n.midi, n.vel); } catch {}
+            try { rpc.noteOn(part, n.midi, n.vel, !!n.accent, false); } catch {}
             held.add(n.midi);
           }
         }
@@ -524,23 +526,10 @@ function tick(ts: number) {
         const to = (from + 1) % s.length;
         const curr = (s.steps[to]?.notes) || [];
         const prevNotes = (s.steps[from]?.notes) || [];
-        const prevSet = new Set(prevNotes.map(n=>n.midi));
         if (s.moduleKind === 'synth') {
           const held: Set<number> = (s as any)._held || new Set<number>();
           (s as any)._held = held;
-          // NoteOff for held midis not continued
-          const contMidis = new Set<number>();
-          for (const n of curr) { if (n.legato && prevSet.has(n.midi)) contMidis.add(n.midi); }
-          if (typeof part === 'number') {
-            for (const m of Array.from(held)) {
-              if (!contMidis.has(m)) { try { rpc.noteOff(part, m); } catch {}; held.delete(m); }
-            }
-          }
-          // NoteOn for current non-legato notes
-          for (const n of curr) {
-            const cont = n.legato && prevSet.has(n.midi);
-            if (!cont && typeof part === 'number') { try { rpc.noteOn(part, n.midi, n.vel); } catch {}; held.add(n.midi); }
-          }
+          if (typeof part === 'number') synthStepEdge(part, curr, prevNotes, held);
         } else {
           // drums/sampler: trigger all notes each step
           for (const n of curr) { if (typeof part === 'number') { try { rpc.noteOn(part, n.midi, n.vel); } catch {} } }
@@ -630,27 +619,35 @@ if (typeof window !== 'undefined') {
   schedId = setInterval(scheduleSteps, SCHED_INTERVAL_MS);
 }
 
+// Synth step edge: release held notes that are not continued, then start the step's notes.
+// Slide notes are sent before the release so a mono synth (Acid 303) sees the tie and glides
+// instead of retriggering; the engine ignores the late NoteOff for the note it slid away from.
+function synthStepEdge(part: number, curr: SequencerNote[], prevNotes: SequencerNote[], held: Set<number>) {
+  const prevSet = new Set(prevNotes.map(n=>n.midi));
+  const contMidis = new Set<number>();
+  for (const n of curr) { if (n.legato && prevSet.has(n.midi)) contMidis.add(n.midi); }
+  const slides = held.size > 0 ? curr.filter(n => n.slide && !contMidis.has(n.midi)) : [];
+  for (const n of slides) { try { rpc.noteOn(part, n.midi, n.vel, !!n.accent, true); } catch {}; }
+  for (const m of Array.from(held)) {
+    if (!contMidis.has(m) && !slides.some(n => n.midi === m)) { try { rpc.noteOff(part, m); } catch {}; held.delete(m); }
+  }
+  for (const n of slides) held.add(n.midi);
+  // NoteOn for current non-legato notes
+  for (const n of curr) {
+    if (contMidis.has(n.midi) || slides.includes(n)) continue;
+    try { rpc.noteOn(part, n.midi, n.vel, !!n.accent, !!n.slide); } catch {}
+    held.add(n.midi);
+  }
+}
+
 function triggerStepEdge(s: Seq, id: string, prev: number, step: number) {
   const part = typeof s.part === 'number' ? s.part : undefined;
   const curr = (s.steps[step]?.notes) || [];
   const prevNotes = (s.steps[prev >= 0 ? prev : 0]?.notes) || [];
-  const prevSet = new Set(prevNotes.map(n=>n.midi));
   if (s.moduleKind === 'synth') {
     const held: Set<number> = (s as any)._held || new Set<number>();
     (s as any)._held = held;
-    // NoteOff for held midis not continued
-    const contMidis = new Set<number>();
-    for (const n of curr) { if (n.legato && prevSet.has(n.midi)) contMidis.add(n.midi); }
-    if (typeof part === 'number') {
-      for (const m of Array.from(held)) {
-        if (!contMidis.has(m)) { try { rpc.noteOff(part, m); } catch {}; held.delete(m); }
-      }
-    }
-    // NoteOn for current non-legato notes
-    for (const n of curr) {
-      const cont = n.legato && prevSet.has(n.midi);
-      if (!cont && typeof part === 'number') { try { rpc.noteOn(part, n.midi, n.vel); } catch {}; held.add(n.midi); }
-    }
+    if (typeof part === 'number') synthStepEdge(part, curr, prevNotes, held);
   } else {
     // drums/sampler: trigger all notes each step
     for (const n of curr) { if (typeof part === 'number') { try { rpc.noteOn(part, n.midi, n.vel); } catch {} } }
@@ -659,6 +656,18 @@ function triggerStepEdge(s: Seq, id: string, prev: number, step: number) {
   setTimeout(() => { s.lastTriggered = false; touch(id); notify(); }, 80);
 }
 
+function toggleFlagAtSelection(soundId: string, flag: 'accent' | 'slide') {
+  const st = get(soundId);
+  const idx = st.stepIndex;
+  const steps = st.steps.slice();
+  const notes = (steps[idx]?.notes || []).slice();
+  if (!notes.length) return;
+  const ni = Math.max(0, Math.min(notes.length - 1, st.noteIndex));
+  notes[ni] = { ...notes[ni], [flag]: !notes[ni][flag] };
+  steps[idx] = { ...steps[idx], notes };
+  set(soundId, { steps });
+}
+
 function snapResolutionFromNorm(v: number): SequencerResolution {
   const items: SequencerResolution[] = ['1/4','1/8','1/16','1/32','1/8t','1/16t'];
   const idx = Math.max(0, Math.min(items.length - 1, Math.round(v * (items.length - 1))));
@@ -715,7 +724,7 @@ export function useSequencer(soundId: string) {
       const out: SequencerStep[] = [];
       for (let i = lo; i <= hi; i++) {
         const src = st.steps[i] || { time: i, notes: [] };
-        const notes = (src.notes || []).map(n => ({ midi: n.midi, vel: n.vel, legato: !!n.legato }));
+        const notes = (src.notes || []).map(n => ({ midi: n.midi, vel: n.vel, legato: !!n.legato, accent: !!n.accent, slide: !!n.slide }));
         out.push({ time: out.length, notes });
       }
       set(soundId, { copyBuffer: out });
@@ -733,7 +742,7 @@ export function useSequencer(soundId: string) {
         const src = buf[k];
         steps[t + k] = {
           time: t + k,
-          notes: (src.notes || []).map(n => ({ midi: n.midi, vel: n.vel, legato: !!n.legato })),
+          notes: (src.notes || []).map(n => ({ midi: n.midi, vel: n.vel, legato: !!n.legato, accent: !!n.accent, slide: !!n.slide })),
         };
       }
       const normalized = steps.slice(0, st.length).map((s, i) => ({ time: i, notes: (s?.notes || []) }));
@@ -821,6 +830,8 @@ export function useSequencer(soundId: string) {
       steps[idx] = { ...steps[idx], notes };
       set(soundId, { steps, noteIndex: targetIndex });
     },
+    toggleAccentAtSelection: () => toggleFlagAtSelection(soundId, 'accent'),
+    toggleSlideAtSelection: () => toggleFlagAtSelection(soundId, 'slide'),
     setResolutionNorm: (v: number) => {
       const res = snapResolutionFromNorm(v);
       set(soundId, { resolution: res, resolutionNorm: Math.max(0, Math.min(1, v)) });
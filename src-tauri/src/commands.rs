@@ -4,7 +4,8 @@ use std::path::{Path, PathBuf};
 use crossbeam_channel::Sender;
 use once_cell::sync::OnceCell;
 
-use crate::engine::{audio::AudioEngine, messages::{EngineMsg, ParamValue}};
+use crate::engine::{audio::AudioEngine, messages::{DrumZoneSpec, EngineMsg, ParamValue}};
+use crate::engine::modules::drum::{DRUM_BASE_NOTE, MAX_DRUM_SLOTS};
 use crate::engine::modules::sampler::PlayheadState;
 use crate::engine::state::get_playhead_state;
 use crossbeam_channel::{unbounded as chan, Receiver};
@@ -113,9 +114,9 @@ pub fn set_param(path: String, value: ParamValue) -> Result<(), String> {
 }
 
 #[tauri::command]
-pub fn note_on(part: usize, note: u8, vel: f32) -> Result<(), String> {
+pub fn note_on(part: usize, note: u8, vel: f32, accent: Option<bool>, slide: Option<bool>, channel: Option<u8>) -> Result<(), String> {
   if let Some(tx) = ENGINE_TX.get() {
-    let _ = tx.send(EngineMsg::NoteOn { part, note, vel });
+    let _ = tx.send(EngineMsg::NoteOn { part, note, vel, accent: accent.unwrap_or(false), slide: slide.unwrap_or(false), channel: channel.unwrap_or(0) });
     Ok(())
   } else { Err("engine not started".into()) }
 }
@@ -128,6 +129,62 @@ pub fn note_off(part: usize, note: u8) -> Result<(), String> {
   } else { Err("engine not started".into()) }
 }
 
+#[tauri::command]
+pub fn pitch_bend(part: usize, value: f32) -> Result<(), String> {
+  if let Some(tx) = ENGINE_TX.get() {
+    let _ = tx.send(EngineMsg::PitchBend { part, value });
+    Ok(())
+  } else { Err("engine not started".into()) }
+}
+
+#[tauri::command]
+pub fn mod_wheel(part: usize, value: f32) -> Result<(), String> {
+  if let Some(tx) = ENGINE_TX.get() {
+    let _ = tx.send(EngineMsg::ModWheel { part, value });
+    Ok(())
+  } else { Err("engine not started".into()) }
+}
+
+#[tauri::command]
+pub fn aftertouch(part: usize, note: Option<u8>, value: f32) -> Result<(), String> {
+  if let Some(tx) = ENGINE_TX.get() {
+    let _ = tx.send(EngineMsg::Aftertouch { part, note, value });
+    Ok(())
+  } else { Err("engine not started".into()) }
+}
+
+#[tauri::command]
+pub fn sustain(part: usize, on: bool) -> Result<(), String> {
+  if let Some(tx) = ENGINE_TX.get() {
+    let _ = tx.send(EngineMsg::Sustain { part, on });
+    Ok(())
+  } else { Err("engine not started".into()) }
+}
+
+#[tauri::command]
+pub fn note_bend(part: usize, channel: u8, value: f32) -> Result<(), String> {
+  if let Some(tx) = ENGINE_TX.get() {
+    let _ = tx.send(EngineMsg::NoteBend { part, channel, value });
+    Ok(())
+  } else { Err("engine not started".into()) }
+}
+
+#[tauri::command]
+pub fn note_pressure(part: usize, channel: u8, value: f32) -> Result<(), String> {
+  if let Some(tx) = ENGINE_TX.get() {
+    let _ = tx.send(EngineMsg::NotePressure { part, channel, value });
+    Ok(())
+  } else { Err("engine not started".into()) }
+}
+
+#[tauri::command]
+pub fn note_slide(part: usize, channel: u8, value: f32) -> Result<(), String> {
+  if let Some(tx) = ENGINE_TX.get() {
+    let _ = tx.send(EngineMsg::NoteSlide { part, channel, value });
+    Ok(())
+  } else { Err("engine not started".into()) }
+}
+
 #[tauri::command]
 pub fn set_tempo(bpm: f32) -> Result<(), String> {
   if let Some(tx) = ENGINE_TX.get() {
@@ -149,7 +206,7 @@ pub fn set_transport(playing: bool) -> Result<(), String> {
 pub fn debug_ping() -> Result<(), String> {
   if let Some(tx) = ENGINE_TX.get() {
     let tx2 = tx.clone();
-    let _ = tx.send(EngineMsg::NoteOn { part: 0, note: 60, vel: 0.8 });
+    let _ = tx.send(EngineMsg::NoteOn { part: 0, note: 60, vel: 0.8, accent: false, slide: false, channel: 0 });
     thread::spawn(move || {
       thread::sleep(Duration::from_millis(1000));
       let _ = tx2.send(EngineMsg::NoteOff { part: 0, note: 60 });
@@ -211,7 +268,7 @@ pub fn list_subsamples() -> Result<Vec<String>, String> {
   Ok(samples)
 }
 
-fn resolve_subsample_path(documents_dir: &Path, rel: &str) -> Result<PathBuf, String> {
+pub(crate) fn resolve_subsample_path(documents_dir: &Path, rel: &str) -> Result<PathBuf, String> {
   if rel.is_empty() { return Err("invalid_sample_path".to_string()); }
   let rel_path = Path::new(rel);
   if rel_path.is_absolute() { return Err("invalid_sample_path".to_string()); }
@@ -247,6 +304,33 @@ pub fn clear_sample(part: usize) -> Result<(), String> {
   } else { Err("engine not started".into()) }
 }
 
+#[tauri::command]
+pub fn load_wavetable(part: usize, path: String) -> Result<(), String> {
+  let documents_dir = dirs::document_dir()
+    .ok_or("Could not find documents directory")?;
+  // A file or a folder of single cycles, both under subsamples/
+  let table_path = resolve_subsample_path(&documents_dir, &path)?;
+
+  if let Some(tx) = ENGINE_TX.get() {
+    let path_str = table_path.to_string_lossy().to_string();
+    let _ = tx.send(EngineMsg::LoadWavetable { part, path: path_str });
+    Ok(())
+  } else { Err("engine not started".into()) }
+}
+
+#[tauri::command]
+pub fn load_resonator_profile(part: usize, path: String) -> Result<(), String> {
+  let documents_dir = dirs::document_dir()
+    .ok_or("Could not find documents directory")?;
+  let sample_path = resolve_subsample_path(&documents_dir, &path)?;
+
+  if let Some(tx) = ENGINE_TX.get() {
+    let path_str = sample_path.to_string_lossy().to_string();
+    let _ = tx.send(EngineMsg::LoadResonatorProfile { part, path: path_str });
+    Ok(())
+  } else { Err("engine not started".into()) }
+}
+
 #[tauri::command]
 pub fn preview_sample(path: String) -> Result<(), String> {
   let documents_dir = dirs::document_dir()
@@ -274,12 +358,11 @@ pub fn get_sample_waveform(path: String) -> Result<Vec<f32>, String> {
     .ok_or("Could not find documents directory")?;
   let sample_path = resolve_subsample_path(&documents_dir, &path)?;
   
-  // Load sample and generate waveform overview
+  // Decode at the file's native rate and generate waveform overview
   use crate::engine::modules::sampler::Sampler;
-  let mut sampler = Sampler::new(44100.0);
-  sampler.load_sample(&sample_path.to_string_lossy());
+  let buffer = Sampler::decode_file(&sample_path.to_string_lossy()).map_err(|e| e.to_string())?;
   
-  let waveform = sampler.get_waveform_overview(512); // 512 points for display
+  let waveform = buffer.overview(512); // 512 points for display
   Ok(waveform)
 }
 
@@ -301,10 +384,8 @@ pub fn get_sample_info(path: String) -> Result<SampleInfo, String> {
     .ok_or("Could not find documents directory")?;
   let sample_path = resolve_subsample_path(&documents_dir, &path)?;
   use crate::engine::modules::sampler::Sampler;
-  let mut sampler = Sampler::new(44100.0);
-  sampler.load_sample(&sample_path.to_string_lossy());
-  let (length_samples, sample_rate, channels) = sampler.get_sample_info();
-  Ok(SampleInfo { length_samples, sample_rate, channels })
+  let buffer = Sampler::decode_file(&sample_path.to_string_lossy()).map_err(|e| e.to_string())?;
+  Ok(SampleInfo { length_samples: buffer.length_samples, sample_rate: buffer.sample_rate, channels: buffer.channels })
 }
 
 // ---- Drum pack utilities ----
@@ -348,29 +429,316 @@ fn resolve_pack_dir(documents_dir: &Path, pack: &str) -> Result<PathBuf, String>
   Ok(pack_real)
 }
 
-#[tauri::command]
-pub fn list_drum_samples(pack: String) -> Result<Vec<String>, String> {
-  let documents_dir = dirs::document_dir().ok_or("Could not find documents directory")?;
-  let pack_dir = resolve_pack_dir(&documents_dir, &pack)?;
+// Optional `pack.json` in a pack folder pins files to slots and notes:
+// { "slots": [ { "file": "kick.wav", "name": "Kick", "note": 36, "volume": 0.85, "pan": 0.5, "pitch": 0 },
+//              { "name": "Snare", "samples": [ { "file": "sn_soft.wav", "vel_hi": 0.5 },
+//                                              { "file": "sn_hard_a.wav", "vel_lo": 0.5 },
+//                                              { "file": "sn_hard_b.wav", "vel_lo": 0.5 } ] } ] }
+// Samples sharing a velocity range are played round-robin. Files not listed follow in
+// alphabetical order on the next free notes from C2; `<name>_v<N>_rr<M>` files are grouped
+// into one slot with N as the velocity layer.
+#[derive(serde::Deserialize, Default)]
+struct DrumPackManifest {
+  #[serde(default)]
+  slots: Vec<DrumManifestSlot>,
+}
+
+#[derive(serde::Deserialize)]
+struct DrumManifestSlot {
+  #[serde(default)]
+  file: Option<String>,
+  #[serde(default)]
+  samples: Vec<DrumManifestZone>,
+  name: Option<String>,
+  note: Option<u8>,
+  volume: Option<f32>,
+  pan: Option<f32>,   // 0..1, 0.5 = centre
+  pitch: Option<f32>, // semitones
+}
+
+#[derive(serde::Deserialize)]
+struct DrumManifestZone {
+  file: String,
+  vel_lo: Option<f32>,
+  vel_hi: Option<f32>,
+}
+
+#[derive(serde::Serialize, Clone)]
+pub struct DrumSlotZone {
+  pub file: String,
+  pub vel_lo: f32,
+  pub vel_hi: f32,
+}
+
+#[derive(serde::Serialize, Clone)]
+pub struct DrumSlot {
+  pub file: String, // first zone's file; identifies the slot in saved slot maps
+  pub zones: Vec<DrumSlotZone>,
+  pub name: String,
+  pub note: u8,
+  pub volume: f32,
+  pub pan: f32,
+  pub pitch: f32,
+}
+
+// Per-sound override saved in the sound preset: which note each file plays on
+#[derive(serde::Deserialize, Clone)]
+pub struct DrumSlotAssign {
+  pub file: String,
+  pub note: u8,
+}
+
+// `snare_v2_rr1` -> ("snare", Some(2)); None when the stem carries no layer/round-robin tags
+fn parse_layer_name(stem: &str) -> Option<(String, Option<u32>)> {
+  let mut parts: Vec<&str> = stem.split('_').collect();
+  let mut layer = None;
+  let mut tagged = false;
+  while parts.len() > 1 {
+    let last = parts[parts.len() - 1].to_ascii_lowercase();
+    let num = |p: &str| last.strip_prefix(p).filter(|n| !n.is_empty()).and_then(|n| n.parse::<u32>().ok());
+    if num("rr").is_some() {
+      // round-robin index only distinguishes files; playback order comes from the engine
+    } else if let Some(n) = num("v") {
+      layer = Some(n);
+    } else { break; }
+    tagged = true;
+    parts.pop();
+  }
+  if tagged { Some((parts.join("_"), layer)) } else { None }
+}
+
+fn full_range_zone(file: &str) -> DrumSlotZone {
+  DrumSlotZone { file: file.to_string(), vel_lo: 0.0, vel_hi: 1.0 }
+}
+
+fn resolve_drum_slots(pack_dir: &Path, slot_map: Option<&[DrumSlotAssign]>) -> Result<Vec<DrumSlot>, String> {
   let mut files = Vec::new();
-  for ent in std::fs::read_dir(&pack_dir).map_err(|e| format!("read_dir: {e}"))? {
+  for ent in std::fs::read_dir(pack_dir).map_err(|e| format!("read_dir: {e}"))? {
     if let Ok(ent) = ent { if ent.path().is_file() { if let Some(name) = ent.file_name().to_str() { if is_audio_file(name) { files.push(name.to_string()); } } } }
   }
   files.sort();
-  Ok(files)
+
+  let manifest_path = pack_dir.join("pack.json");
+  let manifest: DrumPackManifest = if manifest_path.exists() {
+    let text = std::fs::read_to_string(&manifest_path).map_err(|e| format!("read pack.json: {e}"))?;
+    serde_json::from_str(&text).map_err(|e| format!("invalid_pack_manifest: {e}"))?
+  } else { DrumPackManifest::default() };
+
+  let stem = |f: &str| Path::new(f).file_stem().and_then(|s| s.to_str()).unwrap_or(f).to_string();
+  // (slot, explicit note)
+  let mut ordered: Vec<(DrumSlot, Option<u8>)> = Vec::new();
+  let mut claimed: Vec<String> = Vec::new();
+  for m in manifest.slots {
+    let mut zones: Vec<DrumSlotZone> = m.file.iter().map(|f| full_range_zone(f)).collect();
+    zones.extend(m.samples.into_iter().map(|z| DrumSlotZone {
+      vel_lo: z.vel_lo.unwrap_or(0.0).clamp(0.0, 1.0),
+      vel_hi: z.vel_hi.unwrap_or(1.0).clamp(0.0, 1.0),
+      file: z.file,
+    }));
+    zones.retain(|z| files.contains(&z.file) && !claimed.contains(&z.file));
+    if zones.is_empty() { continue; }
+    claimed.extend(zones.iter().map(|z| z.file.clone()));
+    let file = zones[0].file.clone();
+    ordered.push((DrumSlot {
+      name: m.name.unwrap_or_else(|| stem(&file)),
+      file,
+      zones,
+      note: 0,
+      volume: m.volume.unwrap_or(0.85).clamp(0.0, 1.5),
+      pan: m.pan.unwrap_or(0.5).clamp(0.0, 1.0),
+      pitch: m.pitch.unwrap_or(0.0).clamp(-24.0, 24.0),
+    }, m.note));
+  }
+  // Unlisted files: tagged ones grouped by name, velocity layers spread evenly over 0..1
+  let mut groups: Vec<(String, Vec<(String, Option<u32>)>)> = Vec::new();
+  for f in files.iter().filter(|f| !claimed.contains(f)) {
+    match parse_layer_name(&stem(f)) {
+      Some((base, layer)) => match groups.iter_mut().find(|(b, _)| b == &base) {
+        Some((_, members)) => members.push((f.clone(), layer)),
+        None => groups.push((base, vec![(f.clone(), layer)])),
+      },
+      None => groups.push((stem(f), vec![(f.clone(), None)])),
+    }
+  }
+  for (base, members) in groups {
+    let mut layers: Vec<u32> = members.iter().map(|(_, l)| l.unwrap_or(0)).collect();
+    layers.sort();
+    layers.dedup();
+    let count = layers.len() as f32;
+    let zones: Vec<DrumSlotZone> = members.iter().map(|(f, l)| {
+      let k = layers.iter().position(|&x| x == l.unwrap_or(0)).unwrap_or(0) as f32;
+      DrumSlotZone { file: f.clone(), vel_lo: k / count, vel_hi: (k + 1.0) / count }
+    }).collect();
+    let file = zones[0].file.clone();
+    ordered.push((DrumSlot { file, zones, name: base, note: 0, volume: 0.85, pan: 0.5, pitch: 0.0 }, None));
+  }
+
+  if let Some(map) = slot_map {
+    let mut remapped = Vec::with_capacity(ordered.len());
+    for a in map {
+      if let Some(i) = ordered.iter().position(|(s, _)| s.file == a.file) {
+        let (slot, _) = ordered.remove(i);
+        remapped.push((slot, Some(a.note)));
+      }
+    }
+    remapped.extend(ordered);
+    ordered = remapped;
+  }
+  ordered.truncate(MAX_DRUM_SLOTS);
+
+  // Explicit notes first (first claim wins), then fill the rest upwards from the base note
+  let mut used = [false; 128];
+  let mut pending = Vec::new();
+  for (i, (slot, note)) in ordered.iter_mut().enumerate() {
+    match note.filter(|&n| n < 128 && !used[n as usize]) {
+      Some(n) => { slot.note = n; used[n as usize] = true; }
+      None => pending.push(i),
+    }
+  }
+  let mut next = DRUM_BASE_NOTE as usize;
+  for i in pending {
+    while next < 128 && used[next] { next += 1; }
+    if next >= 128 { next = 0; while used[next] { next += 1; } }
+    ordered[i].0.note = next as u8;
+    used[next] = true;
+  }
+  Ok(ordered.into_iter().map(|(slot, _)| slot).collect())
 }
 
 #[tauri::command]
-pub fn load_drum_pack(part: usize, pack: String) -> Result<(), String> {
+pub fn list_drum_samples(pack: String) -> Result<Vec<String>, String> {
   let documents_dir = dirs::document_dir().ok_or("Could not find documents directory")?;
   let pack_dir = resolve_pack_dir(&documents_dir, &pack)?;
-  let mut paths: Vec<String> = Vec::new();
-  for ent in std::fs::read_dir(&pack_dir).map_err(|e| format!("read_dir: {e}"))? {
-    if let Ok(ent) = ent { if ent.path().is_file() { if let Some(name) = ent.file_name().to_str() { if is_audio_file(name) { paths.push(ent.path().to_string_lossy().to_string()); } } } }
-  }
-  paths.sort();
+  Ok(resolve_drum_slots(&pack_dir, None)?.into_iter().map(|s| s.file).collect())
+}
+
+/// Load a pack onto a part's drum player and return the resulting slot layout.
+/// `slot_map` (from the sound preset) overrides the manifest's file order and notes; without
+/// one the pack is newly chosen and the slot params are reset to the manifest defaults.
+#[tauri::command]
+pub fn load_drum_pack(part: usize, pack: String, slot_map: Option<Vec<DrumSlotAssign>>) -> Result<Vec<DrumSlot>, String> {
+  let documents_dir = dirs::document_dir().ok_or("Could not find documents directory")?;
+  let pack_dir = resolve_pack_dir(&documents_dir, &pack)?;
+  let slots = resolve_drum_slots(&pack_dir, slot_map.as_deref())?;
+  let zones: Vec<Vec<DrumZoneSpec>> = slots.iter().map(|s| s.zones.iter().map(|z| DrumZoneSpec {
+    path: pack_dir.join(&z.file).to_string_lossy().to_string(),
+    vel_lo: z.vel_lo,
+    vel_hi: z.vel_hi,
+  }).collect()).collect();
+  let notes: Vec<u8> = slots.iter().map(|s| s.note).collect();
+  if let Some(tx) = ENGINE_TX.get() {
+    let _ = tx.send(EngineMsg::LoadDrumPack { part, slots: zones, notes });
+    // Slot params follow the slot index, so a newly chosen pack starts from its defaults;
+    // reloading with a saved slot map keeps the values the slots already have
+    if slot_map.is_none() {
+      for (i, s) in slots.iter().enumerate() {
+        let base = format!("part/{}/drum/slot/{}", part, i);
+        let _ = tx.send(EngineMsg::SetParam { path: format!("{}/volume", base), value: ParamValue::F32(s.volume) });
+        let _ = tx.send(EngineMsg::SetParam { path: format!("{}/pan", base), value: ParamValue::F32(s.pan) });
+        let _ = tx.send(EngineMsg::SetParam { path: format!("{}/pitch_semitones", base), value: ParamValue::F32(s.pitch) });
+        let _ = tx.send(EngineMsg::SetParam { path: format!("{}/pitch_fine", base), value: ParamValue::F32(0.0) });
+      }
+    }
+    Ok(slots)
+  } else { Err("engine not started".into()) }
+}
+
+#[tauri::command]
+pub fn set_drum_slot_note(part: usize, slot: usize, note: u8) -> Result<(), String> {
+  if note > 127 { return Err("invalid_note".to_string()); }
+  if let Some(tx) = ENGINE_TX.get() {
+    let _ = tx.send(EngineMsg::SetDrumSlotNote { part, slot, note });
+    Ok(())
+  } else { Err("engine not started".into()) }
+}
+
+#[tauri::command]
+pub fn swap_drum_slots(part: usize, a: usize, b: usize) -> Result<(), String> {
   if let Some(tx) = ENGINE_TX.get() {
-    let _ = tx.send(EngineMsg::LoadDrumPack { part, paths });
+    let _ = tx.send(EngineMsg::SwapDrumSlots { part, a, b });
     Ok(())
   } else { Err("engine not started".into()) }
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  // A throwaway pack folder with empty files named like samples
+  fn make_pack(name: &str, files: &[&str], manifest: Option<&str>) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("drum_pack_test_{}_{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    for f in files { std::fs::write(dir.join(f), b"").unwrap(); }
+    if let Some(m) = manifest { std::fs::write(dir.join("pack.json"), m).unwrap(); }
+    dir
+  }
+
+  fn note_of(slots: &[DrumSlot], file: &str) -> u8 {
+    slots.iter().find(|s| s.file == file).unwrap().note
+  }
+
+  #[test]
+  fn layer_names() {
+    assert_eq!(parse_layer_name("snare_v2_rr1"), Some(("snare".to_string(), Some(2))));
+    assert_eq!(parse_layer_name("open_hat_rr3"), Some(("open_hat".to_string(), None)));
+    assert_eq!(parse_layer_name("kick_909"), None);
+    assert_eq!(parse_layer_name("kick"), None);
+  }
+
+  #[test]
+  fn manifest_notes_and_defaults() {
+    let dir = make_pack("manifest", &["kick.wav", "snare.wav", "clap.wav"], Some(r#"{ "slots": [
+      { "file": "snare.wav", "name": "Snare", "note": 38, "volume": 0.6, "pan": 0.25, "pitch": -2 },
+      { "file": "kick.wav", "note": 36 } ] }"#));
+    let slots = resolve_drum_slots(&dir, None).unwrap();
+    assert_eq!(slots.iter().map(|s| s.file.as_str()).collect::<Vec<_>>(), ["snare.wav", "kick.wav", "clap.wav"]);
+    assert_eq!((slots[0].name.as_str(), slots[0].note, slots[0].volume, slots[0].pan, slots[0].pitch), ("Snare", 38, 0.6, 0.25, -2.0));
+    assert_eq!((slots[1].name.as_str(), slots[1].note), ("kick", 36));
+    // Unlisted files take the next free note from C2
+    assert_eq!(note_of(&slots, "clap.wav"), 37);
+    std::fs::remove_dir_all(&dir).unwrap();
+  }
+
+  #[test]
+  fn added_file_does_not_shift_existing_notes() {
+    let manifest = r#"{ "slots": [ { "file": "kick.wav", "note": 36 }, { "file": "snare.wav", "note": 38 } ] }"#;
+    let dir = make_pack("added", &["kick.wav", "snare.wav", "hat.wav", "tom.wav"], Some(manifest));
+    let before = resolve_drum_slots(&dir, None).unwrap();
+    let saved: Vec<DrumSlotAssign> = before.iter().map(|s| DrumSlotAssign { file: s.file.clone(), note: s.note }).collect();
+    // Sorts ahead of the other unlisted files
+    std::fs::write(dir.join("agogo.wav"), b"").unwrap();
+    let after = resolve_drum_slots(&dir, Some(&saved)).unwrap();
+    for s in &before { assert_eq!(note_of(&after, &s.file), s.note, "{}", s.file); }
+    let new_note = note_of(&after, "agogo.wav");
+    assert!(before.iter().all(|s| s.note != new_note));
+    // Pinned manifest notes hold even without a saved map
+    let unsaved = resolve_drum_slots(&dir, None).unwrap();
+    assert_eq!((note_of(&unsaved, "kick.wav"), note_of(&unsaved, "snare.wav")), (36, 38));
+    std::fs::remove_dir_all(&dir).unwrap();
+  }
+
+  #[test]
+  fn slot_map_overrides_manifest() {
+    let dir = make_pack("slot_map", &["kick.wav", "snare.wav"], Some(r#"{ "slots": [ { "file": "kick.wav", "note": 36 } ] }"#));
+    let map = [DrumSlotAssign { file: "snare.wav".into(), note: 36 }, DrumSlotAssign { file: "missing.wav".into(), note: 40 }];
+    let slots = resolve_drum_slots(&dir, Some(&map)).unwrap();
+    // Mapped slots come first and win their note; the displaced kick moves to the next free one
+    assert_eq!((slots[0].file.as_str(), slots[0].note), ("snare.wav", 36));
+    assert_eq!((slots[1].file.as_str(), slots[1].note), ("kick.wav", 37));
+    assert_eq!(slots.len(), 2);
+    std::fs::remove_dir_all(&dir).unwrap();
+  }
+
+  #[test]
+  fn velocity_layers_group_into_one_slot() {
+    let dir = make_pack("layers", &["snare_v1_rr1.wav", "snare_v2_rr1.wav", "kick.wav"], None);
+    let slots = resolve_drum_slots(&dir, None).unwrap();
+    assert_eq!(slots.len(), 2);
+    let snare = slots.iter().find(|s| s.name == "snare").unwrap();
+    let zones: Vec<(&str, f32, f32)> = snare.zones.iter().map(|z| (z.file.as_str(), z.vel_lo, z.vel_hi)).collect();
+    assert_eq!(zones, [("snare_v1_rr1.wav", 0.0, 0.5), ("snare_v2_rr1.wav", 0.5, 1.0)]);
+    assert_eq!((note_of(&slots, "kick.wav"), snare.note), (36, 37));
+    std::fs::remove_dir_all(&dir).unwrap();
+  }
+}
@@ -86,6 +86,7 @@ struct TransportClock {
     bpm: f32,
     beats_per_sample: f64,
     phase: f64,
+    beats: f64, // absolute position since the engine started, for free-running sync
     running: bool,
     sample_counter: u64,
     debug: TransportDebug,
@@ -98,6 +99,7 @@ impl TransportClock {
             bpm: bpm.clamp(40.0, 300.0),
             beats_per_sample: 0.0,
             phase: 0.0,
+            beats: 0.0,
             running: true,
             sample_counter: 0,
             debug: TransportDebug::new(),
@@ -133,11 +135,17 @@ impl TransportClock {
                 }
             }
             self.phase = next;
+            self.beats += self.beats_per_sample;
             self.sample_counter = self.sample_counter.wrapping_add(1);
         }
         self.phase as f32
     }
 
+    #[inline]
+    fn beats(&self) -> f64 {
+        self.beats
+    }
+
     fn flush_debug(&mut self) {
         if self.debug.has_pending() {
             self.debug.flush();
@@ -379,7 +387,8 @@ impl AudioEngine {
                     if playing {
                         for frame in data.chunks_mut(2) {
                             let beat_phase = transport.phase_for_next_sample();
-                            let (l, r) = graph.render_frame(&params, beat_phase);
+                            let (l, r) =
+                                graph.render_frame(&mut params, beat_phase, transport.beats());
                             frame[0] = l;
                             if frame.len() > 1 {
                                 frame[1] = r;
@@ -478,9 +487,16 @@ fn apply_msg(
 ) {
     match msg {
         EngineMsg::SetParam { path, value } => params.set(path, value),
-        EngineMsg::NoteOn { part, note, vel } => {
+        EngineMsg::NoteOn {
+            part,
+            note,
+            vel,
+            accent,
+            slide,
+            channel,
+        } => {
             if part < graph.parts.len() {
-                graph.parts[part].note_on(&params, note, vel);
+                graph.parts[part].note_on(&params, note, vel, accent, slide, channel);
             }
         }
         EngineMsg::NoteOff { part, note } => {
@@ -488,6 +504,53 @@ fn apply_msg(
                 graph.parts[part].note_off(note);
             }
         }
+        EngineMsg::PitchBend { part, value } => {
+            if part < graph.parts.len() {
+                graph.parts[part].set_pitch_bend(value);
+            }
+        }
+        EngineMsg::ModWheel { part, value } => {
+            if part < graph.parts.len() {
+                graph.parts[part].set_mod_wheel(value);
+            }
+        }
+        EngineMsg::Aftertouch { part, note, value } => {
+            if part < graph.parts.len() {
+                graph.parts[part].set_aftertouch(note, value);
+            }
+        }
+        EngineMsg::Sustain { part, on } => {
+            if part < graph.parts.len() {
+                graph.parts[part].set_sustain(on);
+            }
+        }
+        EngineMsg::NoteBend {
+            part,
+            channel,
+            value,
+        } => {
+            if part < graph.parts.len() {
+                graph.parts[part].set_note_bend(&params, channel, value);
+            }
+        }
+        EngineMsg::NotePressure {
+            part,
+            channel,
+            value,
+        } => {
+            if part < graph.parts.len() {
+                graph.parts[part].set_note_pressure(&params, channel, value);
+            }
+        }
+        EngineMsg::NoteSlide {
+            part,
+            channel,
+            value,
+        } => {
+            if part < graph.parts.len() {
+                graph.parts[part].set_note_slide(&params, channel, value);
+            }
+        }
         EngineMsg::SetTempo { bpm } => {
             graph.set_tempo(bpm);
             transport.set_bpm(bpm);
@@ -521,14 +584,38 @@ fn apply_msg(
                 graph.parts[part].clear_sample();
             }
         }
+        EngineMsg::LoadWavetable { part, path } => {
+            if part < graph.parts.len() {
+                if let Err(e) = graph.parts[part].load_wavetable(&path) {
+                    eprintln!("Failed to load wavetable: {}", e);
+                }
+            }
+        }
+        EngineMsg::LoadResonatorProfile { part, path } => {
+            if part < graph.parts.len() {
+                if let Err(e) = graph.parts[part].load_resonator_profile(&path) {
+                    eprintln!("Failed to analyse resonator profile: {}", e);
+                }
+            }
+        }
         EngineMsg::PreviewSample { path } => {
             if let Err(e) = graph.load_preview_sample(&path) {
                 eprintln!("Failed to load preview sample: {}", e);
             }
         }
-        EngineMsg::LoadDrumPack { part, paths } => {
+        EngineMsg::LoadDrumPack { part, slots, notes } => {
+            if part < graph.parts.len() {
+                graph.parts[part].load_drum_pack(&slots, &notes);
+            }
+        }
+        EngineMsg::SetDrumSlotNote { part, slot, note } => {
+            if part < graph.parts.len() {
+                graph.parts[part].set_drum_slot_note(slot, note);
+            }
+        }
+        EngineMsg::SwapDrumSlots { part, a, b } => {
             if part < graph.parts.len() {
-                graph.parts[part].load_drum_pack(&paths);
+                graph.parts[part].swap_drum_slots(a, b);
             }
         }
         EngineMsg::StopPreview => {
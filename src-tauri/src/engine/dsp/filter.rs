@@ -0,0 +1,328 @@
+use crate::engine::dsp::svf::Svf;
+use std::f32::consts::PI;
+
+// Filter section of the analog voice
+//
+// `filterN/type` picks the response: 0 LP, 1 HP, 2 BP and 3 notch come from the state-variable
+// filter; 4 is a Moog-style 24 dB ladder, 5 a 303-style diode ladder, 6 a feedback comb tuned
+// to the cutoff and 7 a formant filter whose cutoff sweeps the vowels u-o-a-e-i. Q (0.3..10) is
+// the resonance for every type; on the ladders it self-oscillates at the top of the range.
+
+// Resonance 0..1 from the voice's Q range
+fn q_to_res(q: f32) -> f32 {
+    ((q - 0.5) / 9.5).clamp(0.0, 1.0)
+}
+
+// Pre-filter saturation; amount 0 is a clean pass-through
+#[inline]
+pub fn drive(x: f32, amount: f32) -> f32 {
+    if amount <= 1e-4 {
+        return x;
+    }
+    let g = 1.0 + 9.0 * amount.clamp(0.0, 1.0);
+    (x * g).tanh() / g.tanh()
+}
+
+// Four-pole ladder solved with zero-delay feedback (trapezoidal, one 4x4 solve per coefficient
+// change) and a tanh on the stage input. The Moog ladder is four buffered one-poles; in the diode
+// ladder each stage is also loaded by the next one (half strength), which gives the 303's softer
+// slope below the peak. Both are plain structs so other engines (e.g. Acid303) can use them.
+// Resonance 0..1 scales the feedback up to the self-oscillation point of each topology.
+#[derive(Clone)]
+pub struct Ladder {
+    a: [[f32; 4]; 4], // stage coupling (normalised to a 1 rad/s cutoff)
+    k_max: f32,       // feedback where the loop self-oscillates
+    peak_ratio: f32,  // resonant frequency / normalised cutoff
+    m: [[f32; 4]; 4], // (I - gA)^-1
+    p: [[f32; 4]; 4], // I + gA
+    d: [f32; 4],      // g M b: how the input reaches each stage
+    g: f32,
+    k: f32,
+    carry: [f32; 4],
+}
+
+impl Ladder {
+    pub fn moog() -> Self {
+        #[rustfmt::skip]
+        let a = [
+            [-1.0, 0.0, 0.0, 0.0],
+            [1.0, -1.0, 0.0, 0.0],
+            [0.0, 1.0, -1.0, 0.0],
+            [0.0, 0.0, 1.0, -1.0],
+        ];
+        // 1/(s+1)^4: -180 degrees at w = 1 with |H| = 1/4
+        Self::with_topology(a, 4.0, 1.0)
+    }
+
+    pub fn diode() -> Self {
+        #[rustfmt::skip]
+        let a = [
+            [-1.5, 0.5, 0.0, 0.0],
+            [1.0, -1.5, 0.5, 0.0],
+            [0.0, 1.0, -1.5, 0.5],
+            [0.0, 0.0, 1.0, -1.0],
+        ];
+        // 1/(s^4 + 5.5s^3 + 9.75s^2 + 6.125s + 1): -180 degrees at w^2 = 6.125/5.5
+        let w2 = 6.125f32 / 5.5;
+        Self::with_topology(a, 9.75 * w2 - w2 * w2 - 1.0, w2.sqrt())
+    }
+
+    fn with_topology(a: [[f32; 4]; 4], k_max: f32, peak_ratio: f32) -> Self {
+        let mut f = Self {
+            a,
+            k_max,
+            peak_ratio,
+            m: [[0.0; 4]; 4],
+            p: [[0.0; 4]; 4],
+            d: [0.0; 4],
+            g: 0.0,
+            k: 0.0,
+            carry: [0.0; 4],
+        };
+        f.set_params(1000.0, 0.0, 48000.0);
+        f
+    }
+
+    pub fn reset(&mut self) {
+        self.carry = [0.0; 4];
+    }
+
+    // cutoff: where the resonant peak sits (Hz), resonance: 0..1
+    pub fn set_params(&mut self, cutoff: f32, resonance: f32, sr: f32) {
+        let fc = cutoff.clamp(10.0, sr * 0.45);
+        let g = (PI * fc / sr).tan() / self.peak_ratio;
+        self.g = g;
+        self.k = resonance.clamp(0.0, 1.0) * self.k_max;
+        // I - gA is diagonally dominant, so plain Gauss-Jordan without pivoting is safe
+        let id = |r: usize, c: usize| if r == c { 1.0 } else { 0.0 };
+        let mut lhs: [[f32; 4]; 4] =
+            std::array::from_fn(|r| std::array::from_fn(|c| id(r, c) - g * self.a[r][c]));
+        self.p = std::array::from_fn(|r| std::array::from_fn(|c| id(r, c) + g * self.a[r][c]));
+        let mut inv: [[f32; 4]; 4] = std::array::from_fn(|r| std::array::from_fn(|c| id(r, c)));
+        for c in 0..4 {
+            let piv = 1.0 / lhs[c][c];
+            for j in 0..4 {
+                lhs[c][j] *= piv;
+                inv[c][j] *= piv;
+            }
+            for r in 0..4 {
+                if r != c {
+                    let f = lhs[r][c];
+                    for j in 0..4 {
+                        lhs[r][j] -= f * lhs[c][j];
+                        inv[r][j] -= f * inv[c][j];
+                    }
+                }
+            }
+        }
+        self.m = inv;
+        // The input only drives the first stage
+        self.d = std::array::from_fn(|r| g * inv[r][0]);
+    }
+
+    #[inline]
+    pub fn process(&mut self, x: f32) -> f32 {
+        let c: [f32; 4] =
+            std::array::from_fn(|r| (0..4).map(|j| self.m[r][j] * self.carry[j]).sum());
+        // Solve the feedback loop for the last stage, then saturate what enters the ladder
+        let u = ((x - self.k * c[3]) / (1.0 + self.k * self.d[3])).tanh();
+        let y: [f32; 4] = std::array::from_fn(|r| c[r] + self.d[r] * u);
+        self.carry = std::array::from_fn(|r| (0..4).map(|j| self.p[r][j] * y[j]).sum());
+        self.carry[0] += self.g * u;
+        // Partial make-up for the passband the feedback takes away
+        y[3] * (1.0 + self.k).sqrt()
+    }
+}
+
+// Feedback comb: peaks at the cutoff and its harmonics, resonance sets the feedback
+#[derive(Clone)]
+struct Comb {
+    buf: Vec<f32>,
+    pos: usize,
+    delay: f32,
+    fb: f32,
+    norm: f32,
+}
+
+impl Comb {
+    // Down to 20 Hz
+    fn new(sr: f32) -> Self {
+        Self {
+            buf: vec![0.0; (sr / 20.0) as usize + 2],
+            pos: 0,
+            delay: 1.0,
+            fb: 0.0,
+            norm: 1.0,
+        }
+    }
+
+    fn set_params(&mut self, cutoff: f32, resonance: f32, sr: f32) {
+        self.delay = (sr / cutoff.max(20.0)).clamp(1.0, (self.buf.len() - 2) as f32);
+        self.fb = 0.3 + 0.65 * resonance;
+        // Feedback raises the average power by 1/(1 - fb^2); take that back out
+        self.norm = (1.0 - self.fb * self.fb).sqrt();
+    }
+
+    #[inline]
+    fn process(&mut self, x: f32) -> f32 {
+        let len = self.buf.len();
+        let rd = self.pos as f32 + len as f32 - self.delay;
+        let i0 = rd as usize % len;
+        let t = rd.fract();
+        let d = self.buf[i0] + (self.buf[(i0 + 1) % len] - self.buf[i0]) * t;
+        let y = x + self.fb * d;
+        self.buf[self.pos] = y;
+        self.pos = (self.pos + 1) % len;
+        y * self.norm
+    }
+
+    fn reset(&mut self) {
+        self.buf.iter_mut().for_each(|x| *x = 0.0);
+    }
+}
+
+// First three formants (Hz) of the vowels u, o, a, e, i
+const VOWELS: [[f32; 3]; 5] = [
+    [300.0, 870.0, 2240.0],
+    [570.0, 840.0, 2410.0],
+    [730.0, 1090.0, 2440.0],
+    [530.0, 1840.0, 2480.0],
+    [270.0, 2290.0, 3010.0],
+];
+const FORMANT_GAIN: [f32; 3] = [1.0, 0.7, 0.4];
+
+#[derive(Clone)]
+pub struct VoiceFilter {
+    kind: i32,
+    svf: Svf,
+    ladder: Ladder,
+    diode: Ladder,
+    comb: Comb,
+    formants: [Svf; 3],
+    formant_norm: f32, // the Svf band-pass peaks at Q
+}
+
+impl VoiceFilter {
+    pub fn new(sr: f32) -> Self {
+        Self {
+            kind: 0,
+            svf: Svf::new(),
+            ladder: Ladder::moog(),
+            diode: Ladder::diode(),
+            comb: Comb::new(sr),
+            formants: [Svf::new(), Svf::new(), Svf::new()],
+            formant_norm: 1.0,
+        }
+    }
+
+    pub fn kind(&self) -> i32 {
+        self.kind
+    }
+
+    // Only the selected type is retuned; switching type clears the one taking over
+    pub fn set_params(&mut self, kind: i32, cutoff: f32, q: f32, sr: f32) {
+        if kind != self.kind {
+            match kind {
+                4 => self.ladder.reset(),
+                5 => self.diode.reset(),
+                6 => self.comb.reset(),
+                _ => {}
+            }
+            self.kind = kind;
+        }
+        let res = q_to_res(q);
+        match kind {
+            4 => self.ladder.set_params(cutoff, res, sr),
+            5 => self.diode.set_params(cutoff, res, sr),
+            6 => self.comb.set_params(cutoff, res, sr),
+            7 => {
+                // 100 Hz..8 kHz of cutoff spans the five vowels
+                let pos = ((cutoff / 100.0).max(1.0).ln() / 80.0f32.ln()).min(1.0) * 4.0;
+                let v0 = (pos as usize).min(3);
+                let t = pos - v0 as f32;
+                let fq = 4.0 + 16.0 * res;
+                self.formant_norm = 2.0 / fq;
+                for (i, f) in self.formants.iter_mut().enumerate() {
+                    let hz = VOWELS[v0][i] + (VOWELS[v0 + 1][i] - VOWELS[v0][i]) * t;
+                    f.set_params(hz.min(sr * 0.45), fq, sr);
+                }
+            }
+            _ => self.svf.set_params(cutoff, q, sr),
+        }
+    }
+
+    #[inline]
+    pub fn process(&mut self, x: f32) -> f32 {
+        match self.kind {
+            4 => self.ladder.process(x),
+            5 => self.diode.process(x),
+            6 => self.comb.process(x),
+            7 => {
+                let norm = self.formant_norm;
+                self.formants
+                    .iter_mut()
+                    .zip(FORMANT_GAIN)
+                    .map(|(f, g)| f.process(x).2 * g * norm)
+                    .sum()
+            }
+            _ => {
+                let (lp, hp, bp, nt) = self.svf.process(x);
+                match self.kind {
+                    1 => hp,
+                    2 => bp,
+                    3 => nt,
+                    _ => lp,
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Steady-state gain of a sine after the filter settles
+    fn gain(f: &mut Ladder, hz: f32, sr: f32) -> f32 {
+        let mut peak = 0.0f32;
+        for i in 0..(sr as usize) {
+            let y = f.process(0.01 * (2.0 * PI * hz * i as f32 / sr).sin());
+            if i > sr as usize / 2 {
+                peak = peak.max(y.abs());
+            }
+        }
+        peak / 0.01
+    }
+
+    #[test]
+    fn ladders_low_pass_and_peak_at_cutoff() {
+        let sr = 48000.0;
+        for mut f in [Ladder::moog(), Ladder::diode()] {
+            f.set_params(1000.0, 0.0, sr);
+            let low = gain(&mut f, 50.0, sr);
+            let high = gain(&mut f, 8000.0, sr);
+            assert!(low > 0.8 && high < 0.01, "low {low} high {high}");
+            f.set_params(1000.0, 0.9, sr);
+            let at = gain(&mut f, 1000.0, sr);
+            let below = gain(&mut f, 500.0, sr);
+            assert!(at > 2.0 * below, "peak {at} below {below}");
+        }
+    }
+
+    #[test]
+    fn full_resonance_rings_but_stays_bounded() {
+        let sr = 48000.0;
+        for mut f in [Ladder::moog(), Ladder::diode()] {
+            f.set_params(800.0, 1.0, sr);
+            let mut tail = 0.0f32;
+            for i in 0..48000 {
+                let y = f.process(if i == 0 { 0.5 } else { 0.0 });
+                assert!(y.is_finite() && y.abs() < 10.0);
+                if i > 40000 {
+                    tail = tail.max(y.abs());
+                }
+            }
+            assert!(tail > 1e-3, "no self-oscillation {tail}");
+        }
+    }
+}
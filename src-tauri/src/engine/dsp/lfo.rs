@@ -0,0 +1,204 @@
+use crate::engine::params::{hash_path, ParamStore};
+use std::f32::consts::PI;
+
+// Tempo-synced and free-running LFO, three per part
+//
+// Keys live under `part/N/lfo/` (LFO 1, the one the analog LFO rows use), `lfo2/` and `lfo3/`:
+// shape (0 sine, 1 tri, 2 square, 3 saw, 4 sample & hold, 5 smooth random), rate_hz, sync
+// (0 = free in Hz, else an index into SYNC_DIVISIONS + 1), retrig (0 free-run, 1 restart on
+// note), phase (0..1 offset), fade (s, fade-in after each note), amount and drive.
+
+pub const LFOS_PER_PART: usize = 3;
+
+// Sync lengths in beats: 4 bars down to 1/32, each straight, dotted and triplet
+#[rustfmt::skip]
+pub const SYNC_DIVISIONS: [f32; 24] = [
+    16.0, 24.0, 32.0 / 3.0,    // 4 bars
+    8.0, 12.0, 16.0 / 3.0,     // 2 bars
+    4.0, 6.0, 8.0 / 3.0,       // 1 bar
+    2.0, 3.0, 4.0 / 3.0,       // 1/2
+    1.0, 1.5, 2.0 / 3.0,       // 1/4
+    0.5, 0.75, 1.0 / 3.0,      // 1/8
+    0.25, 0.375, 1.0 / 6.0,    // 1/16
+    0.125, 0.1875, 1.0 / 12.0, // 1/32
+];
+
+#[derive(Clone, Copy)]
+pub struct LfoParamKeys {
+    pub shape: u64,
+    pub rate_hz: u64,
+    pub sync: u64,
+    pub retrig: u64,
+    pub phase: u64,
+    pub fade: u64,
+    pub amount: u64,
+    pub drive: u64,
+}
+
+impl LfoParamKeys {
+    // lfo: 0-based LFO index within the part
+    pub fn new(part: usize, lfo: usize) -> Self {
+        let dir = if lfo == 0 {
+            "lfo".to_string()
+        } else {
+            format!("lfo{}", lfo + 1)
+        };
+        let p = |name: &str| hash_path(&format!("part/{}/{}/{}", part, dir, name));
+        Self {
+            shape: p("shape"),
+            rate_hz: p("rate_hz"),
+            sync: p("sync"),
+            retrig: p("retrig"),
+            phase: p("phase"),
+            fade: p("fade"),
+            amount: p("amount"),
+            drive: p("drive"),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct Lfo {
+    sr: f32,
+    phase: f32,
+    // Transport position at the previous tick, None until the first
+    last_beats: Option<f64>,
+    hold: f32, // de-zippered amount
+    fade: f32, // 0..1 ramp since the last note
+    rand_prev: f32,
+    rand_next: f32,
+    rng: u32,
+}
+
+impl Lfo {
+    pub fn new(sr: f32, seed: u32) -> Self {
+        let mut lfo = Self {
+            sr,
+            phase: 0.0,
+            last_beats: None,
+            hold: 0.0,
+            fade: 1.0,
+            rand_prev: 0.0,
+            rand_next: 0.0,
+            rng: seed | 1,
+        };
+        lfo.rand_next = lfo.next_random();
+        lfo
+    }
+
+    fn next_random(&mut self) -> f32 {
+        self.rng ^= self.rng << 13;
+        self.rng ^= self.rng >> 17;
+        self.rng ^= self.rng << 5;
+        (self.rng as f32 / u32::MAX as f32) * 2.0 - 1.0
+    }
+
+    // Note-on: restart the fade-in and, in retrigger mode, the cycle
+    pub fn note_on(&mut self, params: &ParamStore, keys: &LfoParamKeys) {
+        self.fade = 0.0;
+        if params.get_i32_h(keys.retrig, 0) != 0 {
+            self.phase = 0.0;
+            self.rand_prev = self.rand_next;
+            self.rand_next = self.next_random();
+        }
+    }
+
+    // Advance one sample; beats is the engine transport's absolute position in beats, so
+    // free-running synced LFOs share one grid however late they were created.
+    // Returns the depth-scaled value (-1..1).
+    pub fn tick(&mut self, params: &ParamStore, keys: &LfoParamKeys, beats: f64) -> f32 {
+        // Seeded on the first tick so a late LFO does not see the whole transport as one step
+        let beat_delta = (beats - self.last_beats.unwrap_or(beats)).max(0.0) as f32;
+        self.last_beats = Some(beats);
+
+        let sync = params.get_i32_h(keys.sync, 0);
+        let retrig = params.get_i32_h(keys.retrig, 0) != 0;
+        let prev = self.phase;
+        if sync >= 1 {
+            let len = SYNC_DIVISIONS[(sync as usize - 1).min(SYNC_DIVISIONS.len() - 1)];
+            if retrig {
+                self.phase = (self.phase + beat_delta / len).fract();
+            } else {
+                // Locked to the transport grid
+                self.phase = (beats / len as f64).rem_euclid(1.0) as f32;
+            }
+        } else {
+            let rate_hz = params.get_f32_h(keys.rate_hz, 1.0).max(0.01);
+            self.phase = (self.phase + rate_hz / self.sr).fract();
+        }
+        if self.phase < prev {
+            // New cycle: next random step
+            self.rand_prev = self.rand_next;
+            self.rand_next = self.next_random();
+        }
+
+        let x = (self.phase + params.get_f32_h(keys.phase, 0.0).clamp(0.0, 1.0)).fract();
+        let mut v = match params.get_i32_h(keys.shape, 0) {
+            1 => 2.0 * (x - (x + 0.5).floor()).abs() - 1.0,
+            2 => {
+                if x < 0.5 {
+                    1.0
+                } else {
+                    -1.0
+                }
+            }
+            3 => 2.0 * x - 1.0,
+            4 => self.rand_prev,
+            5 => {
+                // Cosine ease from the previous random value to the next one over a cycle
+                let t = 0.5 - 0.5 * (PI * self.phase).cos();
+                self.rand_prev + (self.rand_next - self.rand_prev) * t
+            }
+            _ => (2.0 * PI * x).sin(),
+        };
+        let drive = params.get_f32_h(keys.drive, 0.0);
+        if drive > 0.001 {
+            let k = 1.0 + 8.0 * drive;
+            v = (v * k).tanh() / k.tanh();
+        }
+
+        let fade_s = params.get_f32_h(keys.fade, 0.0).max(0.0);
+        if self.fade < 1.0 {
+            self.fade = if fade_s > 0.0005 {
+                (self.fade + 1.0 / (fade_s * self.sr)).min(1.0)
+            } else {
+                1.0
+            };
+        }
+
+        // simple de-zipper (~10ms): alpha = 1 - exp(-1/(ms*sr))
+        let target_amt = params.get_f32_h(keys.amount, 1.0).clamp(0.0, 1.0);
+        let alpha = 1.0 - (-1.0f32 / (0.01 * self.sr)).exp();
+        self.hold += (target_amt - self.hold) * alpha;
+        v * self.hold * self.fade
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::messages::ParamValue;
+
+    #[test]
+    fn free_running_sync_locks_to_the_transport() {
+        let mut params = ParamStore::new();
+        params.set("part/0/lfo/sync".into(), ParamValue::I32(8)); // 1 bar, dotted
+        let keys = LfoParamKeys::new(0, 0);
+        let bps = 2.0 / 48000.0; // 120 BPM
+        let mut early = Lfo::new(48000.0, 1);
+        let mut beats = 0.0;
+        for _ in 0..30_001 {
+            early.tick(&params, &keys, beats);
+            beats += bps;
+        }
+        // Created mid-bar: picks up the same phase on its first tick
+        let mut late = Lfo::new(48000.0, 2);
+        for _ in 0..48_000 {
+            early.tick(&params, &keys, beats);
+            late.tick(&params, &keys, beats);
+            assert!((early.phase - late.phase).abs() < 1e-5);
+            beats += bps;
+        }
+        assert!((late.phase - (beats - bps).rem_euclid(6.0) as f32 / 6.0).abs() < 1e-4);
+    }
+}
@@ -1,5 +1,11 @@
 pub mod bitcrusher;
 pub mod delay;
+pub mod filter;
+pub mod lfo;
 pub mod mod_delay;
 pub mod phaser;
+pub mod resample;
 pub mod reverb;
+pub mod svf;
+pub mod unison;
+pub mod velocity;
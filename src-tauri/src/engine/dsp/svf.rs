@@ -0,0 +1,41 @@
+use std::f32::consts::PI;
+
+// Trapezoidal (TPT) state-variable filter: one update gives LP, HP, BP and notch
+#[derive(Clone)]
+pub struct Svf {
+    ic1eq: f32,
+    ic2eq: f32,
+    g: f32,
+    k: f32,
+}
+
+impl Svf {
+    pub fn new() -> Self {
+        Self {
+            ic1eq: 0.0,
+            ic2eq: 0.0,
+            g: 0.1,
+            k: 0.5,
+        }
+    }
+    pub fn set_params(&mut self, cutoff: f32, q: f32, sr: f32) {
+        let g = (PI * (cutoff / sr)).tan();
+        self.g = g;
+        self.k = 1.0 / q.max(0.001);
+    }
+    // Returns (lp, hp, bp, notch)
+    pub fn process(&mut self, x: f32) -> (f32, f32, f32, f32) {
+        let g = self.g;
+        let k = self.k;
+        let v0 = x;
+        let v1 = (self.ic1eq + g * (v0 - self.ic2eq)) / (1.0 + g * (g + k));
+        let v2 = self.ic2eq + g * v1;
+        self.ic1eq = 2.0 * v1 - self.ic1eq;
+        self.ic2eq = 2.0 * v2 - self.ic2eq;
+        let lp = v2;
+        let bp = v1;
+        let hp = v0 - k * bp - lp;
+        let notch = hp + lp;
+        (lp, hp, bp, notch)
+    }
+}
@@ -0,0 +1,78 @@
+use std::f32::consts::{FRAC_PI_4, SQRT_2};
+
+// Unison voice layout shared by the analog voice and Korus
+//
+// Each note plays `count` copies of its oscillators, detuned symmetrically over
+// ±detune_cents and panned over ±spread. A count of 1 is the plain centred oscillator, so
+// it sounds exactly like the engine did before unison existed.
+
+pub const MAX_UNISON: usize = 8;
+
+#[derive(Clone)]
+pub struct Unison {
+    pub count: usize,
+    pub ratio: [f32; MAX_UNISON], // frequency multiplier per copy
+    pub gain_l: [f32; MAX_UNISON],
+    pub gain_r: [f32; MAX_UNISON],
+    last: (usize, f32, f32),
+}
+
+impl Unison {
+    pub fn new() -> Self {
+        let mut u = Self {
+            count: 1,
+            ratio: [1.0; MAX_UNISON],
+            gain_l: [1.0; MAX_UNISON],
+            gain_r: [1.0; MAX_UNISON],
+            last: (0, -1.0, -1.0),
+        };
+        u.update(1, 0.0, 0.0);
+        u
+    }
+
+    // Recomputes the layout only when a setting changed, so it is cheap to call per sample
+    pub fn update(&mut self, count: i32, detune_cents: f32, spread: f32) {
+        let n = (count.max(1) as usize).min(MAX_UNISON);
+        let detune_cents = detune_cents.clamp(0.0, 100.0);
+        let spread = spread.clamp(0.0, 1.0);
+        if self.last == (n, detune_cents, spread) {
+            return;
+        }
+        self.last = (n, detune_cents, spread);
+        self.count = n;
+        if n == 1 {
+            self.ratio[0] = 1.0;
+            self.gain_l[0] = 1.0;
+            self.gain_r[0] = 1.0;
+            return;
+        }
+        // Equal-power pan with unity gain in the centre, scaled so the stack keeps its level
+        let norm = 1.0 / (n as f32).sqrt();
+        for k in 0..n {
+            let pos = 2.0 * k as f32 / (n - 1) as f32 - 1.0; // -1..1
+            self.ratio[k] = (2.0_f32).powf(pos * detune_cents / 1200.0);
+            let theta = (pos * spread + 1.0) * FRAC_PI_4;
+            self.gain_l[k] = theta.cos() * SQRT_2 * norm;
+            self.gain_r[k] = theta.sin() * SQRT_2 * norm;
+        }
+    }
+
+    // Whether the copies are panned apart, i.e. the left and right channels differ
+    pub fn is_stereo(&self) -> bool {
+        self.count > 1 && self.last.2 > 1e-4
+    }
+}
+
+impl Default for Unison {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Start phase for a unison copy: uniform over `amount` of a cycle (0 keeps free-running phase)
+pub fn random_phase(rng: &mut u32, amount: f32) -> f32 {
+    *rng ^= *rng << 13;
+    *rng ^= *rng >> 17;
+    *rng ^= *rng << 5;
+    (*rng as f32 / u32::MAX as f32) * amount.clamp(0.0, 1.0)
+}
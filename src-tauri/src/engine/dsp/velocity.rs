@@ -0,0 +1,30 @@
+// Velocity response shared by the synth modules
+//
+// All mappings leave a full-velocity note untouched, so an amount of 0 means "ignore velocity".
+
+// Default for every module's `vel_amp` / `vel_filter` amount: a patch responds to velocity only
+// once it asks to, so the same note sounds the same on every module until then
+pub const DEFAULT_AMOUNT: f32 = 0.0;
+
+// Part-wide curve: 0 = linear, > 0 = soft (loud notes come easier), < 0 = hard
+#[inline]
+pub fn apply_curve(vel: f32, curve: f32) -> f32 {
+    let vel = vel.clamp(0.0, 1.0);
+    let curve = curve.clamp(-1.0, 1.0);
+    if curve.abs() < 1e-4 {
+        return vel;
+    }
+    vel.powf(4.0f32.powf(-curve))
+}
+
+// Gain for a `vel_amp` amount (0..1): at 1 the gain follows velocity linearly
+#[inline]
+pub fn amp_gain(vel: f32, amount: f32) -> f32 {
+    1.0 - amount.clamp(0.0, 1.0) * (1.0 - vel.clamp(0.0, 1.0))
+}
+
+// Cutoff multiplier for a `vel_filter` amount (0..1): at 1 a zero-velocity note sits 4 octaves down
+#[inline]
+pub fn filter_scale(vel: f32, amount: f32) -> f32 {
+    2.0f32.powf(-4.0 * amount.clamp(0.0, 1.0) * (1.0 - vel.clamp(0.0, 1.0)))
+}
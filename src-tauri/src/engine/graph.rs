@@ -2,50 +2,27 @@
 use std::f32::consts::PI;
 
 use crate::engine::params::{ParamStore, hash_path};
-use crate::engine::dsp::{delay::SimpleDelay, mod_delay::ModDelay, phaser::Phaser, reverb::OnePoleLP, bitcrusher::Bitcrusher};
+use crate::engine::dsp::{delay::SimpleDelay, mod_delay::ModDelay, phaser::Phaser, reverb::OnePoleLP, bitcrusher::Bitcrusher, filter::{self, VoiceFilter}, velocity, lfo::{Lfo, LfoParamKeys, LFOS_PER_PART}, unison::{self, Unison, MAX_UNISON}};
 use crate::engine::modules::acid303::{Acid303, AcidParamKeys};
 use crate::engine::modules::karplus_strong::{KarplusStrong, KSParamKeys};
 use crate::engine::modules::korus::{Korus, KorusParamKeys};
-use crate::engine::modules::resonator_bank::{ResonatorBank, ResonatorParamKeys};
+use crate::engine::modules::resonator_bank::{ResonatorBank, ResonatorParamKeys, SpectralProfile};
 use crate::engine::modules::sampler::{Sampler, SamplerParamKeys};
-use crate::engine::modules::drum::{DrumPlayer, DrumParamKeys};
+use crate::engine::modules::drum::{DrumBusFrame, DrumPlayer, DrumParamKeys, DRUM_BUSES};
+use crate::engine::modules::drum_synth::{DrumSynth, DrumSynthParamKeys};
+use crate::engine::modules::expression::NoteExpression;
+use crate::engine::mod_matrix::{ModMatrix, ModSources, MOD_BLOCK};
+use crate::engine::modules::granular::{Granular, GranularParamKeys};
+use crate::engine::modules::wavetable::WavetableBank;
+use crate::engine::modules::fm::{FmSynth, FmParamKeys};
 use crate::engine::state::{init_playhead_states, set_playhead_state};
+use crate::engine::messages::DrumZoneSpec;
 use freeverb::Freeverb;
 
 #[inline]
 fn midi_to_freq(m: u8) -> f32 { 440.0 * (2.0_f32).powf((m as f32 - 69.0) / 12.0) }
 
 
-#[derive(Clone)]
-struct Svf {
-  ic1eq: f32,
-  ic2eq: f32,
-  g: f32,
-  k: f32,
-}
-
-impl Svf {
-  fn new() -> Self { Self { ic1eq: 0.0, ic2eq: 0.0, g: 0.1, k: 0.5 } }
-  fn set_params(&mut self, cutoff: f32, q: f32, sr: f32) {
-    let g = (PI * (cutoff / sr)).tan();
-    self.g = g;
-    self.k = 1.0 / q.max(0.001);
-  }
-  fn process(&mut self, x: f32) -> (f32, f32, f32, f32) {
-    let g = self.g; let k = self.k;
-    let v0 = x;
-    let v1 = (self.ic1eq + g * (v0 - self.ic2eq)) / (1.0 + g * (g + k));
-    let v2 = self.ic2eq + g * v1;
-    self.ic1eq = 2.0 * v1 - self.ic1eq;
-    self.ic2eq = 2.0 * v2 - self.ic2eq;
-    let lp = v2;
-    let bp = v1;
-    let hp = v0 - k * bp - lp;
-    let notch = hp + lp;
-    (lp, hp, bp, notch)
-  }
-}
-
 // Simple RBJ biquad for peaking EQ
 #[derive(Clone, Copy)]
 struct Biquad {
@@ -136,11 +113,29 @@ struct Osc {
   sr: f32,
 }
 
+// PolyBLEP residual for a unit step at t = 0 (t: phase 0..1, dt: phase increment)
+#[inline]
+fn poly_blep(t: f32, dt: f32) -> f32 {
+  if t < dt { let x = t / dt; x + x - x * x - 1.0 }
+  else if t > 1.0 - dt { let x = (t - 1.0) / dt; x * x + x + x + 1.0 }
+  else { 0.0 }
+}
+
 impl Osc {
   fn new(sr: f32) -> Self { Self { phase: 0.0, sr } }
-  fn next_pm(&mut self, freq: f32, shape: OscShape, pulse_w: f32, pm: f32) -> f32 {
+  // raw: naive waveforms (the lofi, aliasing sound); otherwise saw/square/pulse edges are PolyBLEP-corrected
+  fn next_pm(&mut self, freq: f32, shape: OscShape, pulse_w: f32, pm: f32, raw: bool) -> f32 {
     let p = (self.phase + pm) % 1.0;
     self.phase = (self.phase + freq / self.sr) % 1.0;
+    if !raw && matches!(shape, OscShape::Saw | OscShape::Square | OscShape::Pulse) {
+      let p = p.rem_euclid(1.0);
+      let dt = (freq.abs() / self.sr).min(0.5);
+      return match shape {
+        OscShape::Saw => 2.0 * (p - 0.5) - poly_blep(p, dt),
+        OscShape::Square => (if p < 0.5 { 1.0 } else { -1.0 }) + poly_blep(p, dt) - poly_blep((p + 0.5) % 1.0, dt),
+        _ => (if p < pulse_w { 1.0 } else { -1.0 }) + poly_blep(p, dt) - poly_blep((p + 1.0 - pulse_w) % 1.0, dt),
+      };
+    }
     match shape {
       OscShape::Sine => (2.0 * PI * p).sin(),
       OscShape::Saw => 2.0 * (p - 0.5),
@@ -152,6 +147,12 @@ impl Osc {
       OscShape::NoiseBrown => 0.0,
     }
   }
+  // Table read at the oscillator phase (band limiting comes from the table's mip-maps)
+  fn next_wt(&mut self, freq: f32, pm: f32, bank: &WavetableBank, position: f32) -> f32 {
+    let p = self.phase + pm;
+    self.phase = (self.phase + freq / self.sr) % 1.0;
+    bank.sample(p, position, freq / self.sr)
+  }
 }
 
 #[derive(Clone)]
@@ -160,13 +161,21 @@ pub struct Voice {
   pub note: u8,
   age: u64,
   base_freq: f32,
+  pitch: f32,        // current pitch in semitones (MIDI note units), gliding toward target_pitch
+  target_pitch: f32,
+  glide_step: f32,   // semitones per sample, 0 when not gliding
   vel: f32,
-  osc_a: Osc,
-  osc_b: Osc,
+  channel: u8,          // MIDI channel the note came in on; selects the per-channel expression
+  expr: NoteExpression, // bend, pressure and slide pushed by the part each sample
+  osc_a: [Osc; MAX_UNISON], // one per unison copy
+  osc_b: [Osc; MAX_UNISON],
   env_amp: Adsr,
   env_mod: Adsr,
-  filt1: Svf,
-  filt2: Svf,
+  filt1: VoiceFilter,
+  filt2: VoiceFilter,
+  // Side-channel copies of the filters, run only while the unison stack is panned apart
+  filt1_s: VoiceFilter,
+  filt2_s: VoiceFilter,
   last_fa_fc: f32,
   last_fa_q: f32,
   last_fb_fc: f32,
@@ -180,22 +189,48 @@ pub struct Voice {
 }
 
 #[derive(Clone, Copy)]
-struct ModFrame { cents_a: f32, cents_b: f32, lvl_a: f32, lvl_b: f32, filt1: f32, filt2: f32 }
+struct ModFrame { cents_a: f32, cents_b: f32, lvl_a: f32, lvl_b: f32, filt1: f32, filt2: f32, wt_pos: f32 }
 
 impl Voice {
-  pub fn new(sr: f32) -> Self { Self { active: false, note: 0, age: 0, base_freq: 0.0, vel: 0.0, osc_a: Osc::new(sr), osc_b: Osc::new(sr), env_amp: Adsr::new(sr), env_mod: Adsr::new(sr), filt1: Svf::new(), filt2: Svf::new(), last_fa_fc: -1.0, last_fa_q: -1.0, last_fb_fc: -1.0, last_fb_q: -1.0, last_a: 0.0, last_b: 0.0, filt_upd_phase: 0, rng: 0x12345678, pink: 0.0, brown: 0.0 } }
+  pub fn new(sr: f32) -> Self { Self { active: false, note: 0, age: 0, base_freq: 0.0, pitch: 0.0, target_pitch: 0.0, glide_step: 0.0, vel: 0.0, channel: 0, expr: NoteExpression::default(), osc_a: std::array::from_fn(|_| Osc::new(sr)), osc_b: std::array::from_fn(|_| Osc::new(sr)), env_amp: Adsr::new(sr), env_mod: Adsr::new(sr), filt1: VoiceFilter::new(sr), filt2: VoiceFilter::new(sr), filt1_s: VoiceFilter::new(sr), filt2_s: VoiceFilter::new(sr), last_fa_fc: -1.0, last_fa_q: -1.0, last_fb_fc: -1.0, last_fb_q: -1.0, last_a: 0.0, last_b: 0.0, filt_upd_phase: 0, rng: 0x12345678, pink: 0.0, brown: 0.0 } }
   pub fn is_active(&self) -> bool { self.active || self.env_amp.env > 1e-4 }
   pub fn note_on(&mut self, _params: &ParamStore, note: u8, vel: f32) {
     self.active = true; self.note = note; self.base_freq = midi_to_freq(note); self.vel = vel; self.env_amp.gate_on(); self.env_mod.gate_on();
+    self.pitch = note as f32; self.target_pitch = self.pitch; self.glide_step = 0.0;
     // Reseed noise states per note for stability
     self.rng = (note as u32).wrapping_mul(747796405).wrapping_add(2891336453);
     self.pink = 0.0; self.brown = 0.0;
   }
+  // Scatter the unison copies' start phases over `amount` of a cycle
+  fn scatter_phases(&mut self, amount: f32, rng: &mut u32) {
+    for o in self.osc_a.iter_mut().chain(self.osc_b.iter_mut()) { o.phase = unison::random_phase(rng, amount); }
+  }
+  // Move to `note` from the current pitch without touching the envelopes (mono/legato).
+  // glide_s: constant-time glide length, or seconds per octave when `per_octave`
+  fn glide_to(&mut self, note: u8, glide_s: f32, per_octave: bool, sr: f32) {
+    self.note = note;
+    self.target_pitch = note as f32;
+    let dist = (self.target_pitch - self.pitch).abs();
+    if glide_s <= 0.0005 || dist < 1e-4 || !self.is_active() {
+      self.pitch = self.target_pitch; self.glide_step = 0.0; self.base_freq = midi_to_freq(note);
+    } else {
+      self.glide_step = if per_octave { 12.0 / (glide_s * sr) } else { dist / (glide_s * sr) };
+    }
+  }
+  // Restart the envelopes at the current (possibly gliding) pitch
+  fn retrigger(&mut self, vel: f32) { self.active = true; self.vel = vel; self.env_amp.gate_on(); self.env_mod.gate_on(); }
   pub fn note_off(&mut self) { self.env_amp.gate_off(); self.env_mod.gate_off(); self.active = false; }
-  fn render(&mut self, params: &ParamStore, paths: &ParamPaths, _sr: f32, modf: &ModFrame) -> f32 {
+  // Returns (left, right); identical channels unless the unison stack is spread.
+  // wt: the part's wavetable when the module is the wavetable synth; oscillator A then reads it
+  fn render(&mut self, params: &ParamStore, paths: &ParamPaths, _sr: f32, modf: &ModFrame, uni: &Unison, wt: Option<&WavetableBank>) -> (f32, f32) {
     self.age = self.age.wrapping_add(1);
+    if self.glide_step > 0.0 {
+      let d = self.target_pitch - self.pitch;
+      if d.abs() <= self.glide_step { self.pitch = self.target_pitch; self.glide_step = 0.0; } else { self.pitch += self.glide_step * d.signum(); }
+      self.base_freq = 440.0 * (2.0_f32).powf((self.pitch - 69.0) / 12.0);
+    }
     // Shapes arrive as I32 0..3
-    let sh_a = params.get_i32_h(paths.oscA_shape, 0);
+    let sh_a = if wt.is_some() { 0 } else { params.get_i32_h(paths.oscA_shape, 0) };
     let sh_b = params.get_i32_h(paths.oscB_shape, 0);
     // Update envelope parameters from ParamStore
     let a = params.get_f32_h(paths.amp_attack, 0.01);
@@ -217,11 +252,18 @@ impl Voice {
     let mut lvl_b_m = modf.lvl_b;
   let mut _filt1_m = modf.filt1;
   let mut _filt2_m = modf.filt2;
-    for i in 0..5 {
-      let dest = params.get_i32_h(paths.env_dest[i], 0) as u16;
+    let mut wt_pos_m = modf.wt_pos;
+    // ENV rows, then aftertouch and slide rows (per voice, so per-note expression only moves its own note)
+    for i in 0..15 {
+      let (dest_p, amt_p, src) = match i / 5 {
+        0 => (paths.env_dest[i], paths.env_row_amount[i], env_mod_v),
+        1 => (paths.at_dest[i - 5], paths.at_row_amount[i - 5], self.expr.pressure),
+        _ => (paths.slide_dest[i - 10], paths.slide_row_amount[i - 10], self.expr.slide),
+      };
+      let dest = params.get_i32_h(dest_p, 0) as u16;
       if dest == 0 { continue; }
-      let row_amt = params.get_f32_h(paths.env_row_amount[i], 1.0).clamp(-1.0, 1.0);
-      let v = env_mod_v * row_amt;
+      let row_amt = params.get_f32_h(amt_p, 1.0).clamp(-1.0, 1.0);
+      let v = src * row_amt;
       match dest {
         1 => cents_a += 100.0 * v,
         2 => cents_b += 100.0 * v,
@@ -229,11 +271,12 @@ impl Voice {
         4 => lvl_b_m += v,
             5 => _filt1_m += v,
             6 => _filt2_m += v,
+        7 => wt_pos_m += v,
         _ => {}
       }
     }
-    let det_a = params.get_f32_h(paths.oscA_detune_cents, 0.0) + cents_a;
-    let det_b = params.get_f32_h(paths.oscB_detune_cents, 0.0) + cents_b;
+    let det_a = params.get_f32_h(paths.oscA_detune_cents, 0.0) + cents_a + self.expr.bend_cents;
+    let det_b = params.get_f32_h(paths.oscB_detune_cents, 0.0) + cents_b + self.expr.bend_cents;
     // --- Oscillator frequency calculations ---
     let freq_a = self.base_freq * (2.0_f32).powf(det_a / 1200.0);
     let freq_b = self.base_freq * (2.0_f32).powf(det_b / 1200.0);
@@ -256,8 +299,19 @@ impl Voice {
     // Generate oscillator phases with simple phase modulation (FM)
     let pm_a = fm_a_from_b * self.last_b;
     let pm_b = fm_b_from_a * self.last_a;
-    let sig_a = if matches!(sh_a, 5 | 6 | 7) { 0.0 } else { self.osc_a.next_pm(freq_a, match sh_a { 1 => OscShape::Saw, 2 => OscShape::Square, 3 => OscShape::Tri, 4 => OscShape::Pulse, 5 => OscShape::NoiseWhite, 6 => OscShape::NoisePink, 7 => OscShape::NoiseBrown, _ => OscShape::Sine }, pw_a, pm_a) };
-    let sig_b = if matches!(sh_b, 5 | 6 | 7) { 0.0 } else { self.osc_b.next_pm(freq_b, match sh_b { 1 => OscShape::Saw, 2 => OscShape::Square, 3 => OscShape::Tri, 4 => OscShape::Pulse, 5 => OscShape::NoiseWhite, 6 => OscShape::NoisePink, 7 => OscShape::NoiseBrown, _ => OscShape::Sine }, pw_a, pm_b) };
+    let shape_a = match sh_a { 1 => OscShape::Saw, 2 => OscShape::Square, 3 => OscShape::Tri, 4 => OscShape::Pulse, _ => OscShape::Sine };
+    let raw_a = params.get_i32_h(paths.oscA_raw, 0) != 0;
+    let wt_pos = if wt.is_some() { (params.get_f32_h(paths.wt_position, 0.0) + wt_pos_m).clamp(0.0, 1.0) } else { 0.0 };
+    let raw_b = params.get_i32_h(paths.oscB_raw, 0) != 0;
+    let shape_b = match sh_b { 1 => OscShape::Saw, 2 => OscShape::Square, 3 => OscShape::Tri, 4 => OscShape::Pulse, _ => OscShape::Sine };
+    // Unison copies, panned into left/right sums
+    let (mut sig_a_l, mut sig_a_r, mut sig_b_l, mut sig_b_r) = (0.0f32, 0.0f32, 0.0f32, 0.0f32);
+    for k in 0..uni.count {
+      let (gl, gr) = (uni.gain_l[k], uni.gain_r[k]);
+      if let Some(bank) = wt { let x = self.osc_a[k].next_wt(freq_a * uni.ratio[k], pm_a, bank, wt_pos); sig_a_l += x * gl; sig_a_r += x * gr; }
+      else if !matches!(sh_a, 5 | 6 | 7) { let x = self.osc_a[k].next_pm(freq_a * uni.ratio[k], shape_a, pw_a, pm_a, raw_a); sig_a_l += x * gl; sig_a_r += x * gr; }
+      if !matches!(sh_b, 5 | 6 | 7) { let x = self.osc_b[k].next_pm(freq_b * uni.ratio[k], shape_b, pw_a, pm_b, raw_b); sig_b_l += x * gl; sig_b_r += x * gr; }
+    }
     // Noise handling (simple implementations)
     let noise_a = if sh_a >= 5 { let n = noise_sample(&mut self.rng); if sh_a == 5 { n } else if sh_a == 6 { // pink (leaky integrator)
         self.pink = 0.98 * self.pink + 0.02 * n; self.pink
@@ -265,16 +319,24 @@ impl Voice {
         self.brown = (self.brown + 0.02 * n).clamp(-1.0, 1.0); self.brown
       }} else { 0.0 };
     let noise_b = if sh_b >= 5 { let n = noise_sample(&mut self.rng); if sh_b == 5 { n } else if sh_b == 6 { self.pink = 0.98 * self.pink + 0.02 * n; self.pink } else { self.brown = (self.brown + 0.02 * n).clamp(-1.0,1.0); self.brown } } else { 0.0 };
-    let a_out = sig_a + noise_a;
-    let b_out = sig_b + noise_b;
-    self.last_a = a_out;
-    self.last_b = b_out;
+    // Noise stays centred; the stack is filtered as mid/side so both channels share one filter setting
+    let a_mid = 0.5 * (sig_a_l + sig_a_r) + noise_a;
+    let b_mid = 0.5 * (sig_b_l + sig_b_r) + noise_b;
+    self.last_a = a_mid;
+    self.last_b = b_mid;
+    let stereo = uni.is_stereo();
     // Pre-mix per-oscillator signals (post level) used for filter assignment
-    let in_a = a_out * lvl_a;
-    let in_b = b_out * lvl_b;
+    let in_a = a_mid * lvl_a;
+    let in_b = b_mid * lvl_b;
+    let side_a = 0.5 * (sig_a_l - sig_a_r) * lvl_a;
+    let side_b = 0.5 * (sig_b_l - sig_b_r) * lvl_b;
   // --- Filters with ENV/LFO modulation and per-filter Assign routing ---
+  // Routing: 0 = parallel, 1 = serial (filter 2 takes filter 1's output; its Assign only switches it on)
+  let serial = params.get_i32_h(paths.filter_routing, 0) == 1;
+  // Key tracking 1.0 moves the cutoff with the played pitch, 1:1 around middle C
+  let key_oct = (self.pitch - 60.0) / 12.0;
   // Filter 1
-  // Type is 0=LP, 1=HP, 2=BP, 3=Notch (driven by UI "Type" knob)
+  // Type is 0=LP, 1=HP, 2=BP, 3=Notch, 4=Ladder, 5=Diode, 6=Comb, 7=Formant (see dsp::filter)
   let f1_type = params.get_i32_h(paths.filter1_type, 0);
   // Assign: 0=None (bypass), 1=A, 2=B, 3=AB
   let f1_assign = params.get_i32_h(paths.filter1_assign, 0);
@@ -282,70 +344,88 @@ impl Voice {
   let w1_b: f32 = if f1_assign == 2 || f1_assign == 3 { 1.0_f32 } else { 0.0_f32 };
   let norm1 = (w1_a + w1_b).max(1.0_f32); // avoid doubling when AB
   let mut x1 = (w1_a * in_a + w1_b * in_b) / norm1;
+  let xs1 = (w1_a * side_a + w1_b * side_b) / norm1;
   let mut f1_cut = params.get_f32_h(paths.filter1_cutoff_hz, 1200.0);
     let mut f1_q = params.get_f32_h(paths.filter1_q, 0.707);
+  let f1_drive = params.get_f32_h(paths.filter1_drive, 0.0);
+  let f1_kt = params.get_f32_h(paths.filter1_key_track, 0.0);
+  if f1_kt != 0.0 { f1_cut = (f1_cut * (2.0_f32).powf(key_oct * f1_kt)).clamp(20.0, 18000.0); }
   // Apply modulation to cutoff from LFO/ENV (coarse mapping: +/- 24 semitones in log freq domain)
   // Use _filt1_m which already combines LFO and ENV rows targeting filter1
   if _filt1_m.abs() > 1e-6 { let ratio = (2.0_f32).powf(_filt1_m * 2.0); f1_cut = (f1_cut * ratio).clamp(20.0, 18000.0); }
     // Optionally add ENV influence via mod matrix already folded into modf.filt1 through env_dest
-    // Smooth-ish update every few samples to avoid CPU spikes
-    if self.filt_upd_phase & 3 == 0 {
-      if (f1_cut - self.last_fa_fc).abs() > 1e-3 || (f1_q - self.last_fa_q).abs() > 1e-3 {
-        self.filt1.set_params(f1_cut, f1_q.clamp(0.3, 10.0), _sr);
+    // Smooth-ish update every few samples to avoid CPU spikes; a type change retunes straight away
+    if self.filt_upd_phase & 3 == 0 || f1_type != self.filt1.kind() {
+      if (f1_cut - self.last_fa_fc).abs() > 1e-3 || (f1_q - self.last_fa_q).abs() > 1e-3 || f1_type != self.filt1.kind() {
+        self.filt1.set_params(f1_type, f1_cut, f1_q.clamp(0.3, 10.0), _sr);
+        self.filt1_s.set_params(f1_type, f1_cut, f1_q.clamp(0.3, 10.0), _sr);
         self.last_fa_fc = f1_cut; self.last_fa_q = f1_q;
       }
     }
-  let (lp1, hp1, bp1, nt1) = self.filt1.process(x1);
-  // Select output by filter type (from UI)
-  let y1 = match f1_type { 0 => lp1, 1 => hp1, 2 => bp1, 3 => nt1, _ => lp1 };
+  // Drive saturates ahead of the filter (mid and side alike)
+  let y1 = self.filt1.process(filter::drive(x1, f1_drive));
+  let y1s = if stereo { self.filt1_s.process(filter::drive(xs1, f1_drive)) } else { 0.0 };
 
     // Filter 2
   let f2_type = params.get_i32_h(paths.filter2_type, 0);
   let f2_assign = params.get_i32_h(paths.filter2_assign, 0);
-  let w2_a: f32 = if f2_assign == 1 || f2_assign == 3 { 1.0_f32 } else { 0.0_f32 };
-  let w2_b: f32 = if f2_assign == 2 || f2_assign == 3 { 1.0_f32 } else { 0.0_f32 };
+  // In serial mode no oscillator feeds filter 2 directly
+  let w2_a: f32 = if !serial && (f2_assign == 1 || f2_assign == 3) { 1.0_f32 } else { 0.0_f32 };
+  let w2_b: f32 = if !serial && (f2_assign == 2 || f2_assign == 3) { 1.0_f32 } else { 0.0_f32 };
   let norm2 = (w2_a + w2_b).max(1.0_f32);
-  let mut x2 = (w2_a * in_a + w2_b * in_b) / norm2;
+  let mut x2 = if serial { y1 } else { (w2_a * in_a + w2_b * in_b) / norm2 };
+  let xs2 = if serial { y1s } else { (w2_a * side_a + w2_b * side_b) / norm2 };
   let mut f2_cut = params.get_f32_h(paths.filter2_cutoff_hz, 1200.0);
     let mut f2_q = params.get_f32_h(paths.filter2_q, 0.707);
+  let f2_drive = params.get_f32_h(paths.filter2_drive, 0.0);
+  let f2_kt = params.get_f32_h(paths.filter2_key_track, 0.0);
+  if f2_kt != 0.0 { f2_cut = (f2_cut * (2.0_f32).powf(key_oct * f2_kt)).clamp(20.0, 18000.0); }
   if _filt2_m.abs() > 1e-6 { let ratio = (2.0_f32).powf(_filt2_m * 2.0); f2_cut = (f2_cut * ratio).clamp(20.0, 18000.0); }
-    if self.filt_upd_phase & 3 == 2 {
-      if (f2_cut - self.last_fb_fc).abs() > 1e-3 || (f2_q - self.last_fb_q).abs() > 1e-3 {
-        self.filt2.set_params(f2_cut, f2_q.clamp(0.3, 10.0), _sr);
+    if self.filt_upd_phase & 3 == 2 || f2_type != self.filt2.kind() {
+      if (f2_cut - self.last_fb_fc).abs() > 1e-3 || (f2_q - self.last_fb_q).abs() > 1e-3 || f2_type != self.filt2.kind() {
+        self.filt2.set_params(f2_type, f2_cut, f2_q.clamp(0.3, 10.0), _sr);
+        self.filt2_s.set_params(f2_type, f2_cut, f2_q.clamp(0.3, 10.0), _sr);
         self.last_fb_fc = f2_cut; self.last_fb_q = f2_q;
       }
     }
-  let (lp2, hp2, bp2, nt2) = self.filt2.process(x2);
-  let y2 = match f2_type { 0 => lp2, 1 => hp2, 2 => bp2, 3 => nt2, _ => lp2 };
+  let y2 = self.filt2.process(filter::drive(x2, f2_drive));
+  let y2s = if stereo { self.filt2_s.process(filter::drive(xs2, f2_drive)) } else { 0.0 };
+  // Serial: filter 2's output stands in for filter 1's
+  let (y1, y1s) = if serial && f2_assign != 0 { (y2, y2s) } else { (y1, y1s) };
 
     // Mix filters in parallel; average if both are active to maintain headroom
   let used1: f32 = if w1_a + w1_b > 0.0_f32 { 1.0_f32 } else { 0.0_f32 };
   let used2: f32 = if w2_a + w2_b > 0.0_f32 { 1.0_f32 } else { 0.0_f32 };
   let denom = (used1 + used2).max(1.0_f32);
-    let mut y = (y1 * used1 + y2 * used2) / denom;
-
-    // If an oscillator isn't assigned to any filter, pass it through as dry (bypass)
     let osc_a_used = (w1_a + w2_a) > 0.0_f32;
     let osc_b_used = (w1_b + w2_b) > 0.0_f32;
-    let dry_a = if osc_a_used { 0.0_f32 } else { in_a };
-    let dry_b = if osc_b_used { 0.0_f32 } else { in_b };
     let dry_count: i32 = (if !osc_a_used { 1 } else { 0 }) + (if !osc_b_used { 1 } else { 0 });
-    if dry_count > 0 {
-      let ydry = (dry_a + dry_b) / (dry_count as f32);
-      let fcount = used1 + used2; // number of active filter routes (0,1,2)
-      if fcount > 0.0_f32 {
-        // Normalize by total contributing paths (filters + dry oscillators)
-        let total = fcount + (dry_count as f32);
-        y = (y * fcount + ydry * (dry_count as f32)) / total;
-      } else {
-        // No filters active; fully bypass
-        y = ydry;
+    // Same combination for the mid and side paths
+    let combine = |y1: f32, y2: f32, in_a: f32, in_b: f32| -> f32 {
+      let mut y = (y1 * used1 + y2 * used2) / denom;
+      // If an oscillator isn't assigned to any filter, pass it through as dry (bypass)
+      let dry_a = if osc_a_used { 0.0_f32 } else { in_a };
+      let dry_b = if osc_b_used { 0.0_f32 } else { in_b };
+      if dry_count > 0 {
+        let ydry = (dry_a + dry_b) / (dry_count as f32);
+        let fcount = used1 + used2; // number of active filter routes (0,1,2)
+        if fcount > 0.0_f32 {
+          // Normalize by total contributing paths (filters + dry oscillators)
+          let total = fcount + (dry_count as f32);
+          y = (y * fcount + ydry * (dry_count as f32)) / total;
+        } else {
+          // No filters active; fully bypass
+          y = ydry;
+        }
       }
-    }
+      y
+    };
 
     // Amp envelope and velocity
-    y *= env_amp * self.vel;
-    y
+    let gain = env_amp * self.vel;
+    let y = combine(y1, y2, in_a, in_b) * gain;
+    let ys = if stereo { combine(y1s, y2s, side_a, side_b) * gain } else { 0.0 };
+    (y + ys, y - ys)
   }
 }
 
@@ -354,24 +434,37 @@ struct ParamPaths {
   oscA_shape: u64, oscB_shape: u64,
   oscA_detune_cents: u64, oscB_detune_cents: u64,
   oscA_pulse_width: u64, oscA_fm_to_B: u64, oscB_fm_to_A: u64,
+  oscA_raw: u64, oscB_raw: u64, // 1 = naive (aliasing) waveforms instead of band-limited
   amp_attack: u64, amp_decay: u64, amp_sustain: u64, amp_release: u64,
   mod_attack: u64, mod_decay: u64, mod_sustain: u64, mod_release: u64,
   filter1_type: u64, filter1_cutoff_hz: u64, filter1_q: u64, filter1_res_q: u64, filter1_assign: u64,
   filter2_type: u64, filter2_cutoff_hz: u64, filter2_q: u64, filter2_res_q: u64, filter2_assign: u64,
+  filter1_drive: u64, filter1_key_track: u64, filter2_drive: u64, filter2_key_track: u64,
+  filter_routing: u64, // 0 = parallel, 1 = serial
   oscA_level: u64, oscB_level: u64,
-  lfo_shape: u64, lfo_rate_hz: u64, lfo_amount: u64, lfo_drive: u64,
   lfo_dest: [u64;5], lfo_row_amount: [u64;5],
   env_dest: [u64;5], env_row_amount: [u64;5],
-  fx1_type: u64, fx1_p1: u64, fx1_p2: u64, fx1_p3: u64,
-  fx2_type: u64, fx2_p1: u64, fx2_p2: u64, fx2_p3: u64,
-  fx3_type: u64, fx3_p1: u64, fx3_p2: u64, fx3_p3: u64,
-  fx4_type: u64, fx4_p1: u64, fx4_p2: u64, fx4_p3: u64,
-  mix_width: u64, mix_pan: u64, mix_comp: u64, mix_volume: u64, mix_haas: u64,
-  eq_bands: [u64;8],
+  // Mod wheel and aftertouch rows (same destinations as the LFO/ENV rows)
+  wheel_dest: [u64;5], wheel_row_amount: [u64;5],
+  at_dest: [u64;5], at_row_amount: [u64;5],
+  slide_dest: [u64;5], slide_row_amount: [u64;5], // MPE slide (CC74)
+  bend_range: u64, // semitones
+  // MPE zone: 0 off (every channel independent), 1 lower (master ch 1), 2 upper (master ch 16);
+  // member channel count 1..15 and member bend range (semitones)
+  mpe_zone: u64, mpe_members: u64, mpe_bend_range: u64,
+  // FX, EQ and pan/volume live in the part's ChannelStrip (StripParamKeys)
+  mix_duck: u64, // sidechain ducking depth, 0..1
   mixer_gain_db: u64,
   module_kind: u64,
+  vel_curve: u64, // part-wide velocity curve, -1 (hard) .. 0 (linear) .. 1 (soft)
+  // Analog voice mode: 0 poly, 1 mono, 2 legato; glide (s), glide_mode 0 constant time / 1 per octave;
+  // priority 0 last, 1 low, 2 high
+  voice_mode: u64, voice_glide: u64, voice_glide_mode: u64, voice_priority: u64,
+  // Analog unison: voices 1..8, detune (cents, ±), spread 0..1, phase (random start phase amount 0..1)
+  unison_voices: u64, unison_detune: u64, unison_spread: u64, unison_phase: u64,
+  wt_position: u64, // wavetable frame position 0..1 (module_kind 9)
   // Acid303
-  acid_wave: u64, acid_cutoff: u64, acid_reso: u64, acid_envmod: u64, acid_decay: u64, acid_accent: u64, acid_slide: u64, acid_drive: u64, acid_step_accent: u64, acid_step_slide: u64,
+  acid_wave: u64, acid_cutoff: u64, acid_reso: u64, acid_envmod: u64, acid_decay: u64, acid_accent: u64, acid_slide: u64, acid_drive: u64,
   // Karplus
   ks_decay: u64, ks_damp: u64, ks_excite: u64, ks_tune: u64,
   // Resonator
@@ -384,32 +477,38 @@ impl ParamPaths {
   fn new(idx: usize) -> Self {
     let base = format!("part/{}/", idx);
     let p = |s: &str| -> u64 { hash_path(&(base.clone() + s)) };
-    let mut eq = [0u64; 8];
-    for i in 0..8 { eq[i] = hash_path(&format!("part/{}/eq/gain_db/b{}", idx, i+1)); }
     Self {
       oscA_shape: p("oscA/shape"), oscB_shape: p("oscB/shape"),
       oscA_detune_cents: p("oscA/detune_cents"), oscB_detune_cents: p("oscB/detune_cents"),
       oscA_pulse_width: p("oscA/pulse_width"), oscA_fm_to_B: p("oscA/fm_to_B"), oscB_fm_to_A: p("oscB/fm_to_A"),
+      oscA_raw: p("oscA/raw"), oscB_raw: p("oscB/raw"),
       amp_attack: p("amp_env/attack"), amp_decay: p("amp_env/decay"), amp_sustain: p("amp_env/sustain"), amp_release: p("amp_env/release"),
       mod_attack: p("mod_env/attack"), mod_decay: p("mod_env/decay"), mod_sustain: p("mod_env/sustain"), mod_release: p("mod_env/release"),
       filter1_type: p("filter1/type"), filter1_cutoff_hz: p("filter1/cutoff_hz"), filter1_q: p("filter1/q"), filter1_res_q: p("filter1/res_q"), filter1_assign: p("filter1/assign"),
       filter2_type: p("filter2/type"), filter2_cutoff_hz: p("filter2/cutoff_hz"), filter2_q: p("filter2/q"), filter2_res_q: p("filter2/res_q"), filter2_assign: p("filter2/assign"),
+      filter1_drive: p("filter1/drive"), filter1_key_track: p("filter1/key_track"), filter2_drive: p("filter2/drive"), filter2_key_track: p("filter2/key_track"),
+      filter_routing: p("filter/routing"),
       oscA_level: p("oscA/level"), oscB_level: p("oscB/level"),
-      lfo_shape: p("lfo/shape"), lfo_rate_hz: p("lfo/rate_hz"), lfo_amount: p("lfo/amount"), lfo_drive: p("lfo/drive"),
       lfo_dest: [p("mod/lfo/row0/dest"), p("mod/lfo/row1/dest"), p("mod/lfo/row2/dest"), p("mod/lfo/row3/dest"), p("mod/lfo/row4/dest")],
       lfo_row_amount: [p("mod/lfo/row0/amount"), p("mod/lfo/row1/amount"), p("mod/lfo/row2/amount"), p("mod/lfo/row3/amount"), p("mod/lfo/row4/amount")],
       env_dest: [p("mod/env/row0/dest"), p("mod/env/row1/dest"), p("mod/env/row2/dest"), p("mod/env/row3/dest"), p("mod/env/row4/dest")],
       env_row_amount: [p("mod/env/row0/amount"), p("mod/env/row1/amount"), p("mod/env/row2/amount"), p("mod/env/row3/amount"), p("mod/env/row4/amount")],
-      fx1_type: p("fx1/type"), fx1_p1: p("fx1/p1"), fx1_p2: p("fx1/p2"), fx1_p3: p("fx1/p3"),
-      fx2_type: p("fx2/type"), fx2_p1: p("fx2/p1"), fx2_p2: p("fx2/p2"), fx2_p3: p("fx2/p3"),
-      fx3_type: p("fx3/type"), fx3_p1: p("fx3/p1"), fx3_p2: p("fx3/p2"), fx3_p3: p("fx3/p3"),
-      fx4_type: p("fx4/type"), fx4_p1: p("fx4/p1"), fx4_p2: p("fx4/p2"), fx4_p3: p("fx4/p3"),
-      mix_width: p("mixer/width"), mix_pan: p("mixer/pan"), mix_comp: p("mixer/comp"), mix_volume: p("mixer/volume"),
-      mix_haas: p("mixer/haas"),
-      eq_bands: eq,
+      wheel_dest: std::array::from_fn(|i| p(&format!("mod/wheel/row{}/dest", i))),
+      wheel_row_amount: std::array::from_fn(|i| p(&format!("mod/wheel/row{}/amount", i))),
+      at_dest: std::array::from_fn(|i| p(&format!("mod/at/row{}/dest", i))),
+      at_row_amount: std::array::from_fn(|i| p(&format!("mod/at/row{}/amount", i))),
+      slide_dest: std::array::from_fn(|i| p(&format!("mod/slide/row{}/dest", i))),
+      slide_row_amount: std::array::from_fn(|i| p(&format!("mod/slide/row{}/amount", i))),
+      bend_range: p("bend_range"),
+      mpe_zone: p("mpe/zone"), mpe_members: p("mpe/members"), mpe_bend_range: p("mpe/bend_range"),
+      mix_duck: p("mixer/duck"),
       mixer_gain_db: hash_path(&format!("mixer/part{}/gain_db", idx)),
       // Module select & Acid303 params
       module_kind: p("module_kind"),
+      vel_curve: p("vel_curve"),
+      voice_mode: p("voice/mode"), voice_glide: p("voice/glide"), voice_glide_mode: p("voice/glide_mode"), voice_priority: p("voice/priority"),
+      unison_voices: p("unison/voices"), unison_detune: p("unison/detune"), unison_spread: p("unison/spread"), unison_phase: p("unison/phase"),
+      wt_position: p("wavetable/position"),
       acid_wave: p("acid/wave"),
       acid_cutoff: p("acid/cutoff"),
       acid_reso: p("acid/reso"),
@@ -418,8 +517,6 @@ impl ParamPaths {
       acid_accent: p("acid/accent"),
       acid_slide: p("acid/slide"),
       acid_drive: p("acid/drive"),
-      acid_step_accent: p("acid/step/accent"),
-      acid_step_slide: p("acid/step/slide"),
       // Karplus-Strong params
       ks_decay: p("ks/decay"),
       ks_damp: p("ks/damp"),
@@ -460,22 +557,130 @@ impl ParamPaths {
   }
 }
 
-pub struct Part {
-  voices: Vec<Voice>,
+// Continuous performance data received per part (MIDI-style controllers)
+struct Performance {
+  bend: f32,                // -1..1, scaled by the part's bend range
+  wheel: f32,               // 0..1
+  pressure: f32,            // channel aftertouch, 0..1
+  poly_pressure: [f32; 128], // poly aftertouch per key
+  channels: [NoteExpression; 16], // per-channel bend (cents), pressure and slide (MPE member channels)
+  sustain: bool,
+  held: [bool; 128],        // keys currently down
+  sustained: [bool; 128],   // released while the pedal was down; let go on pedal up
+}
+
+impl Performance {
+  fn new() -> Self { Self { bend: 0.0, wheel: 0.0, pressure: 0.0, poly_pressure: [0.0; 128], channels: [NoteExpression::default(); 16], sustain: false, held: [false; 128], sustained: [false; 128] } }
+
+  // What a voice playing `note` on `channel` sees: the part (master channel) bend and pressure
+  // with the channel's own on top. Keyed by channel so two member channels on the same key stay apart
+  fn voice_expr(&self, note: u8, channel: u8, part_bend_cents: f32) -> NoteExpression {
+    let c = &self.channels[channel as usize & 15];
+    let pressure = (self.pressure.max(self.poly_pressure[note as usize & 127]) + c.pressure).min(1.0);
+    NoteExpression { bend_cents: part_bend_cents + c.bend_cents, pressure, slide: c.slide }
+  }
+}
+
+// Where a channel-tagged controller lands under the part's MPE zone
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum ChannelRole { Master, Member, Outside }
+
+// MPE spec defaults: member channels bend ±48 semitones, a zone takes every other channel
+const MPE_MEMBER_BEND_RANGE: f32 = 48.0;
+const MPE_MEMBERS: i32 = 15;
+
+// Channels are 0-based: the lower zone is master 0 with members 1..=n, the upper zone master 15
+// with members 15-n..=14. With the zone off every channel is its own member
+fn channel_role(zone: i32, members: i32, channel: u8) -> ChannelRole {
+  let ch = channel & 15;
+  let n = members.clamp(1, 15) as u8;
+  match zone {
+    1 if ch == 0 => ChannelRole::Master,
+    1 if ch <= n => ChannelRole::Member,
+    2 if ch == 15 => ChannelRole::Master,
+    2 if ch >= 15 - n => ChannelRole::Member,
+    1 | 2 => ChannelRole::Outside,
+    _ => ChannelRole::Member,
+  }
+}
+
+// Mono/legato state for the analog voice; settings are latched at note-on so key-up can
+// return to a held note without reading params
+struct MonoState {
+  mode: i32,
+  priority: i32,
+  glide_s: f32,
+  per_octave: bool,
+  keys: Vec<u8>, // held keys in press order
+}
+
+impl MonoState {
+  fn new() -> Self { Self { mode: 0, priority: 0, glide_s: 0.0, per_octave: false, keys: Vec::with_capacity(128) } }
+
+  fn pick(&self) -> Option<u8> {
+    match self.priority {
+      1 => self.keys.iter().copied().min(),
+      2 => self.keys.iter().copied().max(),
+      _ => self.keys.last().copied(),
+    }
+  }
+}
+
+// Part-wide state behind the mod matrix sources
+struct MatrixState {
+  matrix: ModMatrix,
+  env: Adsr,     // mod_env ADSR gated while any key is down
+  lfos: [Lfo; LFOS_PER_PART],
+  lfo_keys: [LfoParamKeys; LFOS_PER_PART],
+  lfo_out: [f32; LFOS_PER_PART], // ticked once per sample; LFO 1 also feeds the analog LFO rows
+  note: u8,      // last note played
+  vel: f32,
+  random: f32,   // new value per note-on, -1..1
+  rng: u32,
+  follower: f32, // output level of this part, read by other parts' rows
+}
+
+impl MatrixState {
+  fn new(sr: f32, idx: usize) -> Self {
+    Self {
+      matrix: ModMatrix::new(idx), env: Adsr::new(sr),
+      lfos: std::array::from_fn(|i| Lfo::new(sr, 0x2545F491 ^ (((idx * LFOS_PER_PART + i) as u32) << 8))),
+      lfo_keys: std::array::from_fn(|i| LfoParamKeys::new(idx, i)),
+      lfo_out: [0.0; LFOS_PER_PART],
+      note: 60, vel: 0.0, random: 0.0, rng: 0x9E3779B9 ^ idx as u32, follower: 0.0,
+    }
+  }
+}
+
+// FX1..4 -> 8-band EQ -> pan/volume/haas/comp for one mixer channel. Every part owns one, and the
+// mixer buses (aux, drum sub-channels) are nothing but a strip on an external input.
+struct StripParamKeys {
+  fx1_type: u64, fx1_p1: u64, fx1_p2: u64, fx1_p3: u64,
+  fx2_type: u64, fx2_p1: u64, fx2_p2: u64, fx2_p3: u64,
+  fx3_type: u64, fx3_p1: u64, fx3_p2: u64, fx3_p3: u64,
+  fx4_type: u64, fx4_p1: u64, fx4_p2: u64, fx4_p3: u64,
+  eq_bands: [u64;8],
+  mix_pan: u64, mix_volume: u64, mix_haas: u64, mix_comp: u64,
+}
+
+impl StripParamKeys {
+  // `base` is the channel prefix, e.g. `part/0/` or `mixer/aux/`
+  fn new(base: &str) -> Self {
+    let p = |s: &str| -> u64 { hash_path(&(base.to_string() + s)) };
+    Self {
+      fx1_type: p("fx1/type"), fx1_p1: p("fx1/p1"), fx1_p2: p("fx1/p2"), fx1_p3: p("fx1/p3"),
+      fx2_type: p("fx2/type"), fx2_p1: p("fx2/p1"), fx2_p2: p("fx2/p2"), fx2_p3: p("fx2/p3"),
+      fx3_type: p("fx3/type"), fx3_p1: p("fx3/p1"), fx3_p2: p("fx3/p2"), fx3_p3: p("fx3/p3"),
+      fx4_type: p("fx4/type"), fx4_p1: p("fx4/p1"), fx4_p2: p("fx4/p2"), fx4_p3: p("fx4/p3"),
+      eq_bands: std::array::from_fn(|i| p(&format!("eq/gain_db/b{}", i + 1))),
+      mix_pan: p("mixer/pan"), mix_volume: p("mixer/volume"), mix_haas: p("mixer/haas"), mix_comp: p("mixer/comp"),
+    }
+  }
+}
+
+struct ChannelStrip {
   sr: f32,
-  next_voice: usize,
-  acid: Acid303,
-  acid_keys: AcidParamKeys,
-  karplus: KarplusStrong,
-  karplus_keys: KSParamKeys,
-  korus: Korus,
-  korus_keys: KorusParamKeys,
-  resonator: ResonatorBank,
-  resonator_keys: ResonatorParamKeys,
-  sampler: Sampler,
-  sampler_keys: SamplerParamKeys,
-  drum: DrumPlayer,
-  drum_keys: DrumParamKeys,
+  keys: StripParamKeys,
   delay1: ModDelay, delay2: ModDelay, delay3: ModDelay, delay4: ModDelay,
   sdelay1: SimpleDelay, sdelay2: SimpleDelay, sdelay3: SimpleDelay, sdelay4: SimpleDelay,
   fx1_reverb: Option<Freeverb>, fx2_reverb: Option<Freeverb>, fx3_reverb: Option<Freeverb>, fx4_reverb: Option<Freeverb>,
@@ -485,84 +690,16 @@ pub struct Part {
   fx3_wet_lp_l: OnePoleLP, fx3_wet_lp_r: OnePoleLP,
   fx4_wet_lp_l: OnePoleLP, fx4_wet_lp_r: OnePoleLP,
   phaser1: Phaser, phaser2: Phaser, phaser3: Phaser, phaser4: Phaser,
-  eq_lp: Svf, eq_hp: Svf,
   eq_bands: [Biquad;8], eq_centers: [f32;8], eq_last_db: [f32;8],
-  paths: ParamPaths,
-  lfo_phase: f32, lfo_hold: f32,
+  eq_bands_r: [Biquad;8], // right channel of the stereo path; the mono path uses `eq_bands` only
   haas_buf: Vec<f32>, haas_wr: usize, haas_len: usize, haas_d: usize,
 }
 
-impl Part {
-  pub fn new(sr: f32, poly: usize, idx: usize) -> Self {
-    let mut voices = Vec::with_capacity(poly);
-    for _ in 0..poly { voices.push(Voice::new(sr)); }
+impl ChannelStrip {
+  fn new(sr: f32, base: &str) -> Self {
     // Allocate modulated delay buffers for FX and explicit delays for TIME/FEEDBACK
-    let mut p = Self { voices, sr, next_voice: 0,
-      acid: Acid303::new(sr),
-      acid_keys: AcidParamKeys {
-        module_kind: hash_path(&format!("part/{}/module_kind", idx)),
-        wave: hash_path(&format!("part/{}/acid/wave", idx)),
-        cutoff: hash_path(&format!("part/{}/acid/cutoff", idx)),
-        reso: hash_path(&format!("part/{}/acid/reso", idx)),
-        envmod: hash_path(&format!("part/{}/acid/envmod", idx)),
-        decay: hash_path(&format!("part/{}/acid/decay", idx)),
-        accent: hash_path(&format!("part/{}/acid/accent", idx)),
-        slide: hash_path(&format!("part/{}/acid/slide", idx)),
-        drive: hash_path(&format!("part/{}/acid/drive", idx)),
-        step_accent: hash_path(&format!("part/{}/acid/step/accent", idx)),
-        step_slide: hash_path(&format!("part/{}/acid/step/slide", idx)),
-      },
-      karplus: KarplusStrong::new(sr),
-      karplus_keys: KSParamKeys {
-        module_kind: hash_path(&format!("part/{}/module_kind", idx)),
-        decay: hash_path(&format!("part/{}/ks/decay", idx)),
-        damp: hash_path(&format!("part/{}/ks/damp", idx)),
-        excite: hash_path(&format!("part/{}/ks/excite", idx)),
-        tune: hash_path(&format!("part/{}/ks/tune", idx)),
-      },
-      korus: Korus::new(sr),
-      korus_keys: KorusParamKeys::new(idx),
-      resonator: ResonatorBank::new(sr),
-      resonator_keys: ResonatorParamKeys {
-        module_kind: hash_path(&format!("part/{}/module_kind", idx)),
-        pitch: hash_path(&format!("part/{}/resonator/pitch", idx)),
-        decay: hash_path(&format!("part/{}/resonator/decay", idx)),
-        brightness: hash_path(&format!("part/{}/resonator/brightness", idx)),
-        bank_size: hash_path(&format!("part/{}/resonator/bank_size", idx)),
-        mode: hash_path(&format!("part/{}/resonator/mode", idx)),
-        inharmonicity: hash_path(&format!("part/{}/resonator/inharmonicity", idx)),
-        feedback: hash_path(&format!("part/{}/resonator/feedback", idx)),
-        drive: hash_path(&format!("part/{}/resonator/drive", idx)),
-        exciter_type: hash_path(&format!("part/{}/resonator/exciter_type", idx)),
-        exciter_amount: hash_path(&format!("part/{}/resonator/exciter_amount", idx)),
-        noise_color: hash_path(&format!("part/{}/resonator/noise_color", idx)),
-        strike_rate: hash_path(&format!("part/{}/resonator/strike_rate", idx)),
-        stereo_width: hash_path(&format!("part/{}/resonator/stereo_width", idx)),
-        randomize: hash_path(&format!("part/{}/resonator/randomize", idx)),
-        body_blend: hash_path(&format!("part/{}/resonator/body_blend", idx)),
-        output_gain: hash_path(&format!("part/{}/resonator/output_gain", idx)),
-      },
-  sampler: Sampler::new(sr),
-      sampler_keys: SamplerParamKeys {
-        module_kind: hash_path(&format!("part/{}/module_kind", idx)),
-        sample_start: hash_path(&format!("part/{}/sampler/sample_start", idx)),
-        sample_end: hash_path(&format!("part/{}/sampler/sample_end", idx)),
-        pitch_semitones: hash_path(&format!("part/{}/sampler/pitch_semitones", idx)),
-        pitch_cents: hash_path(&format!("part/{}/sampler/pitch_cents", idx)),
-        playback_mode: hash_path(&format!("part/{}/sampler/playback_mode", idx)),
-        loop_start: hash_path(&format!("part/{}/sampler/loop_start", idx)),
-        loop_end: hash_path(&format!("part/{}/sampler/loop_end", idx)),
-        loop_mode: hash_path(&format!("part/{}/sampler/loop_mode", idx)),
-        smoothness: hash_path(&format!("part/{}/sampler/smoothness", idx)),
-  retrig_mode: hash_path(&format!("part/{}/sampler/retrig_mode", idx)),
-        attack: hash_path(&format!("part/{}/sampler/attack", idx)),
-        decay: hash_path(&format!("part/{}/sampler/decay", idx)),
-        sustain: hash_path(&format!("part/{}/sampler/sustain", idx)),
-        release: hash_path(&format!("part/{}/sampler/release", idx)),
-      },
-  drum: DrumPlayer::new(sr),
-  drum_keys: DrumParamKeys::new(idx),
-  delay1: ModDelay::new(1500.0, sr), delay2: ModDelay::new(1500.0, sr),
+    let mut c = Self { sr, keys: StripParamKeys::new(base),
+      delay1: ModDelay::new(1500.0, sr), delay2: ModDelay::new(1500.0, sr),
       delay3: ModDelay::new(1500.0, sr), delay4: ModDelay::new(1500.0, sr),
       sdelay1: SimpleDelay::new(1200.0, sr), sdelay2: SimpleDelay::new(1200.0, sr),
       sdelay3: SimpleDelay::new(1200.0, sr), sdelay4: SimpleDelay::new(1200.0, sr),
@@ -573,89 +710,44 @@ impl Part {
       fx3_wet_lp_l: OnePoleLP::new(), fx3_wet_lp_r: OnePoleLP::new(),
       fx4_wet_lp_l: OnePoleLP::new(), fx4_wet_lp_r: OnePoleLP::new(),
       phaser1: Phaser::new(), phaser2: Phaser::new(), phaser3: Phaser::new(), phaser4: Phaser::new(),
-      eq_lp: Svf::new(), eq_hp: Svf::new(),
-  eq_bands: [Biquad::new(), Biquad::new(), Biquad::new(), Biquad::new(), Biquad::new(), Biquad::new(), Biquad::new(), Biquad::new()],
-  eq_centers: [60.0,120.0,250.0,500.0,1000.0,2000.0,4000.0,8000.0], eq_last_db: [0.0; 8],
-  paths: ParamPaths::new(idx), lfo_phase: 0.0, lfo_hold: 0.0,
+      eq_bands: [Biquad::new(), Biquad::new(), Biquad::new(), Biquad::new(), Biquad::new(), Biquad::new(), Biquad::new(), Biquad::new()],
+      eq_centers: [60.0,120.0,250.0,500.0,1000.0,2000.0,4000.0,8000.0], eq_last_db: [0.0; 8],
+      eq_bands_r: [Biquad::new(), Biquad::new(), Biquad::new(), Biquad::new(), Biquad::new(), Biquad::new(), Biquad::new(), Biquad::new()],
       haas_buf: Vec::new(), haas_wr: 0, haas_len: 0, haas_d: 0 };
-    // Initialize helper filters used for pseudo-side width
-    p.eq_lp.set_params(250.0, 0.707, sr);
-    p.eq_hp.set_params(2000.0, 0.707, sr);
     // Haas delay buffers
     let max_len = ((0.02 * sr).ceil() as usize).max(2);
     let d_samp = ((0.015 * sr).round() as usize).min(max_len - 1);
-    p.haas_buf = vec![0.0; max_len];
-    p.haas_wr = 0;
-    p.haas_len = max_len;
-    p.haas_d = d_samp;
-    p
-  }
-  pub fn note_on(&mut self, params: &ParamStore, note: u8, vel: f32) {
-    let mk = params.get_i32_h(self.paths.module_kind, 0);
-    match mk {
-      0 => { // Analog poly
-        for v in &mut self.voices { if v.note == note && v.is_active() { v.note_off(); } }
-        let mut idx = None; for (i, v) in self.voices.iter().enumerate() { if !v.is_active() { idx = Some(i); break; } }
-        let i = idx.unwrap_or_else(|| { let i = self.next_voice; self.next_voice = (self.next_voice + 1) % self.voices.len(); i });
-        self.voices[i].note_on(params, note, vel);
-      }
-      1 => { self.acid.note_on(note, vel); }
-      2 => { self.karplus.note_on(note, vel); }
-      3 => { self.resonator.note_on(note, vel); }
-      4 => { // Sampler
-        let retrig_i = params.get_i32_h(self.sampler_keys.retrig_mode, 0);
-        let retrig_mode = crate::engine::modules::sampler::RetrigMode::from_index(retrig_i);
-        self.sampler.note_on(note, vel, retrig_mode);
-      }
-      5 => { self.drum.note_on(note, vel); }
-      6 => { self.korus.note_on(note, vel); }
-      _ => {}
-    }
-  }
-  pub fn note_off(&mut self, note: u8) {
-    // Stop all voices with this note to guarantee preview stops fully
-    for v in &mut self.voices { if v.note == note && v.is_active() { v.note_off(); } }
-    self.acid.note_off(note);
-    self.karplus.note_off();
-    self.resonator.note_off(note);
-  self.sampler.note_off(note);
-  // Drum voices may have been triggered; attempt to stop matching slot
-  self.drum.note_off(note);
-  self.korus.note_off(note);
-  }
-
-  pub fn load_sample(&mut self, path: &str) -> Result<(), String> {
-    self.sampler.load_sample(path);
-    Ok(())
-  }
-
-  pub fn clear_sample(&mut self) {
-    self.sampler.clear_sample();
+    c.haas_buf = vec![0.0; max_len];
+    c.haas_wr = 0;
+    c.haas_len = max_len;
+    c.haas_d = d_samp;
+    c
   }
 
-  pub fn load_drum_pack(&mut self, paths: &[String]) {
-    self.drum.load_pack(paths);
+  fn render_input(&mut self, params: &ParamStore, input: f32, pan_local: f32) -> (f32, f32) {
+    match self.process_fx_eq_mono(params, input) {
+      Some(out) => self.apply_standard_mixer(params, out, pan_local),
+      None => (0.0, 0.0),
+    }
   }
 
-  pub fn drum_mut(&mut self) -> &mut DrumPlayer { &mut self.drum }
-
   fn process_fx_eq_mono(&mut self, params: &ParamStore, dry: f32) -> Option<f32> {
-    let fx1_t = params.get_i32_h(self.paths.fx1_type, 0);
-    let fx1_p1 = params.get_f32_h(self.paths.fx1_p1, 0.0);
-    let fx1_p2 = params.get_f32_h(self.paths.fx1_p2, 0.0);
-    let fx1_mix = params.get_f32_h(self.paths.fx1_p3, 0.0).clamp(0.0, 1.0);
-    let fx2_t = params.get_i32_h(self.paths.fx2_type, 0);
-    let fx2_p1 = params.get_f32_h(self.paths.fx2_p1, 0.0);
-    let fx2_p2 = params.get_f32_h(self.paths.fx2_p2, 0.0);
-    let fx2_mix = params.get_f32_h(self.paths.fx2_p3, 0.0).clamp(0.0, 1.0);
-    let fx3_t = params.get_i32_h(self.paths.fx3_type, 0);
-    let fx3_p1 = params.get_f32_h(self.paths.fx3_p1, 0.0);
-    let fx3_p2 = params.get_f32_h(self.paths.fx3_p2, 0.0);
-    let fx3_mix = params.get_f32_h(self.paths.fx3_p3, 0.0).clamp(0.0, 1.0);
-    let fx4_t = params.get_i32_h(self.paths.fx4_type, 0);
-    let fx4_p1 = params.get_f32_h(self.paths.fx4_p1, 0.0);
-    let fx4_p2 = params.get_f32_h(self.paths.fx4_p2, 0.0);
-    let fx4_mix = params.get_f32_h(self.paths.fx4_p3, 0.0).clamp(0.0, 1.0);
+    let fx1_t = params.get_i32_h(self.keys.fx1_type, 0);
+    let fx1_p1 = params.get_f32_h(self.keys.fx1_p1, 0.0);
+    let fx1_p2 = params.get_f32_h(self.keys.fx1_p2, 0.0);
+    let fx1_mix = params.get_f32_h(self.keys.fx1_p3, 0.0).clamp(0.0, 1.0);
+    let fx2_t = params.get_i32_h(self.keys.fx2_type, 0);
+    let fx2_p1 = params.get_f32_h(self.keys.fx2_p1, 0.0);
+    let fx2_p2 = params.get_f32_h(self.keys.fx2_p2, 0.0);
+    let fx2_mix = params.get_f32_h(self.keys.fx2_p3, 0.0).clamp(0.0, 1.0);
+    let fx3_t = params.get_i32_h(self.keys.fx3_type, 0);
+    let fx3_p1 = params.get_f32_h(self.keys.fx3_p1, 0.0);
+    let fx3_p2 = params.get_f32_h(self.keys.fx3_p2, 0.0);
+    let fx3_mix = params.get_f32_h(self.keys.fx3_p3, 0.0).clamp(0.0, 1.0);
+    let fx4_t = params.get_i32_h(self.keys.fx4_type, 0);
+    let fx4_p1 = params.get_f32_h(self.keys.fx4_p1, 0.0);
+    let fx4_p2 = params.get_f32_h(self.keys.fx4_p2, 0.0);
+    let fx4_mix = params.get_f32_h(self.keys.fx4_p3, 0.0).clamp(0.0, 1.0);
 
     if dry.abs() < 1e-9
       && (fx1_t <= 0 || fx1_mix <= 0.0005)
@@ -1090,9 +1182,10 @@ impl Part {
     let q = 1.0_f32;
     let mut any_nonzero = false;
     for i in 0..8 {
-      let db = params.get_f32_h(self.paths.eq_bands[i], 0.0).clamp(-12.0, 12.0);
+      let db = params.get_f32_h(self.keys.eq_bands[i], 0.0).clamp(-12.0, 12.0);
       if (db - self.eq_last_db[i]).abs() > 1e-6 {
         self.eq_bands[i].set_peaking(self.sr, self.eq_centers[i], q, db);
+        self.eq_bands_r[i].set_peaking(self.sr, self.eq_centers[i], q, db);
         self.eq_last_db[i] = db;
       }
       if db.abs() > 1e-3 {
@@ -1111,16 +1204,16 @@ impl Part {
   fn apply_standard_mixer(&mut self, params: &ParamStore, input: f32, pan_local: f32) -> (f32, f32) {
     let mut l = input;
     let mut r = input;
-    let pan = (params.get_f32_h(self.paths.mix_pan, 0.0) + pan_local).clamp(-1.0, 1.0);
+    let pan = (params.get_f32_h(self.keys.mix_pan, 0.0) + pan_local).clamp(-1.0, 1.0);
     let theta = (pan + 1.0) * std::f32::consts::FRAC_PI_4;
     let gl = theta.cos();
     let gr = theta.sin();
     l *= gl;
     r *= gr;
-    let vol = params.get_f32_h(self.paths.mix_volume, 1.0).clamp(0.0, 1.0);
+    let vol = params.get_f32_h(self.keys.mix_volume, 1.0).clamp(0.0, 1.0);
     l *= vol;
     r *= vol;
-    let haas = params.get_f32_h(self.paths.mix_haas, 0.0).clamp(0.0, 1.0);
+    let haas = params.get_f32_h(self.keys.mix_haas, 0.0).clamp(0.0, 1.0);
     if haas > 0.0005 {
       let rd = if self.haas_wr >= self.haas_d { self.haas_wr - self.haas_d } else { self.haas_wr + self.haas_len - self.haas_d };
       let delayed_l = self.haas_buf[rd];
@@ -1133,7 +1226,7 @@ impl Part {
       self.haas_wr += 1;
       if self.haas_wr >= self.haas_len { self.haas_wr = 0; }
     }
-    let comp = params.get_f32_h(self.paths.mix_comp, 0.0).clamp(0.0, 1.0);
+    let comp = params.get_f32_h(self.keys.mix_comp, 0.0).clamp(0.0, 1.0);
     if comp > 0.001 {
       let drive = 1.0 + 8.0 * comp;
       let id = 1.0 / drive.tanh();
@@ -1143,1743 +1236,718 @@ impl Part {
     (l, r)
   }
 
-  pub fn render(&mut self, params: &ParamStore, _part_idx: usize, beat_phase: f32) -> (f32, f32) {
-    // Module dispatch (0 = Analog, 1 = Acid303, 2 = KarplusStrong, 3 = ResonatorBank, 4 = Sampler)
-    let module = params.get_i32_h(self.paths.module_kind, 0);
-    
-    // Debug: Log module kind for part 0 when it changes
-  // (debug logging removed for stability)
-    
-    if module == 5 {
-      // Drum Sampler render path (mono aggregation -> FX -> EQ -> Mixer)
-      let dframe = self.drum.render(params, &self.drum_keys);
-      // Allow FX tails like the Sampler: only early-out if dry is silent and all FX mixes are ~zero
-      let fx1_t_peek = params.get_i32_h(self.paths.fx1_type, 0);
-      let fx1_mix_peek = params.get_f32_h(self.paths.fx1_p3, 0.0).clamp(0.0, 1.0);
-      let fx2_t_peek = params.get_i32_h(self.paths.fx2_type, 0);
-      let fx2_mix_peek = params.get_f32_h(self.paths.fx2_p3, 0.0).clamp(0.0, 1.0);
-      let fx3_t_peek = params.get_i32_h(self.paths.fx3_type, 0);
-      let fx3_mix_peek = params.get_f32_h(self.paths.fx3_p3, 0.0).clamp(0.0, 1.0);
-      let fx4_t_peek = params.get_i32_h(self.paths.fx4_type, 0);
-      let fx4_mix_peek = params.get_f32_h(self.paths.fx4_p3, 0.0).clamp(0.0, 1.0);
-      if dframe.mono.abs() < 1e-9 && (fx1_t_peek <= 0 || fx1_mix_peek <= 0.0005) && (fx2_t_peek <= 0 || fx2_mix_peek <= 0.0005) && (fx3_t_peek <= 0 || fx3_mix_peek <= 0.0005) && (fx4_t_peek <= 0 || fx4_mix_peek <= 0.0005) {
-        return (0.0, 0.0);
-      }
-      let mut out = dframe.mono; // summed mono
-      // --- FX1 chain (copied from other module branches) ---
-      let fx1_t = params.get_i32_h(self.paths.fx1_type, 0);
-      let fx1_p1 = params.get_f32_h(self.paths.fx1_p1, 0.0);
-      let fx1_p2 = params.get_f32_h(self.paths.fx1_p2, 0.0);
-      let fx1_mix = params.get_f32_h(self.paths.fx1_p3, 0.0).clamp(0.0, 1.0);
-      if fx1_t <= 0 || fx1_mix <= 0.0005 {
-        if fx1_t <= 0 { self.fx1_reverb = None; self.fx1_crusher = None; }
-      } else if fx1_t == 2 {
-        let time_ms = 10.0 + fx1_p1.clamp(0.0, 1.0) * 990.0;
-        let fb = (fx1_p2.clamp(0.0, 1.0) * 0.95).min(0.95);
-        let mut lbuf = [out]; let mut rbuf = [out];
-        self.sdelay1.process_block(&mut lbuf, &mut rbuf, self.sr, time_ms, fb, fx1_mix, false);
-        out = 0.5 * (lbuf[0] + rbuf[0]);
-        self.fx1_reverb = None; self.fx1_crusher = None;
-      } else if fx1_t == 1 {
-        if self.fx1_reverb.is_none() {
-          let mut rv = Freeverb::new(self.sr as usize);
-          rv.set_room_size(0.35); rv.set_dampening(0.6); rv.set_wet(1.0); rv.set_dry(0.0); rv.set_width(0.9);
-          self.fx1_reverb = Some(rv);
-        }
-        if let Some(rv) = &mut self.fx1_reverb {
-          let room = 0.2 + fx1_p1.clamp(0.0, 1.0) * 0.8; let damp = 0.2 + fx1_p2.clamp(0.0, 1.0) * 0.8; let mix = fx1_mix;
-          rv.set_room_size(room as f64); rv.set_dampening(damp as f64); rv.set_wet(1.0); rv.set_dry(0.0); rv.set_width(0.9);
-          let dry = out; let (wl, wr) = rv.tick((dry as f64, dry as f64));
-          let lp_amt = 0.5 + 0.5 * (damp as f32); self.fx1_wet_lp_l.set_hf_damp(lp_amt); self.fx1_wet_lp_r.set_hf_damp(lp_amt);
-          let wet_l = self.fx1_wet_lp_l.tick(wl as f32) as f32; let wet_r = self.fx1_wet_lp_r.tick(wr as f32) as f32; let wet_m = 0.5 * (wet_l + wet_r);
-          out = dry * (1.0 - mix) + wet_m * mix;
-        }
-      } else if fx1_t == 3 || fx1_t == 4 || fx1_t == 5 {
-        let rate = 0.05 + fx1_p1 * (5.0 - 0.05);
-        let depth_ms = match fx1_t { 4 => 6.0 * fx1_p2, 5 => 12.0 * fx1_p2, _ => 4.0 * fx1_p2 };
-        if fx1_t == 3 {
-          let (wet, _) = self.phaser1.process_one(out, out, self.sr, rate, fx1_p2, 1.0);
-          out = out * (1.0 - fx1_mix) + wet * fx1_mix;
-        } else {
-          let base_ms = match fx1_t { 4 => 2.0, 5 => 15.0, _ => 3.0 };
-          let (wet, _) = self.delay1.process_one(out, out, self.sr, rate, base_ms, depth_ms, 1.0);
-          out = out * (1.0 - fx1_mix) + wet * fx1_mix;
-        }
-        self.fx1_reverb = None; self.fx1_crusher = None;
-      } else if fx1_t == 6 {
-        let dry = out; let drive_db = (fx1_p1.clamp(0.0, 1.0)) * 20.0; let g = (10.0_f32).powf(drive_db / 20.0);
-        let x = (dry * g).tanh(); let tone = fx1_p2.clamp(0.0, 1.0);
-        let lp_amt = 0.3 + 0.6 * (1.0 - tone); self.fx1_wet_lp_l.set_hf_damp(lp_amt);
-        let y_lp = self.fx1_wet_lp_l.tick(x); let y_hp = x - y_lp; let shaped = y_lp * (1.0 - tone) + y_hp * tone;
-        out = dry * (1.0 - fx1_mix) + shaped * fx1_mix; self.fx1_reverb = None;
-      } else if fx1_t == 7 {
-        let dry = out; let drive = fx1_p2.clamp(0.0, 1.0) * 10.0; let g = 1.0 + drive; let cur = fx1_p1.clamp(0.0, 1.0);
-        let xin = dry * g;
-        let shaped = if cur < 0.34 { xin.tanh() } else if cur < 0.67 { xin.clamp(-1.0, 1.0) } else { let m = (xin + 1.0).abs().rem_euclid(4.0); ((m - 2.0).abs() - 1.0).clamp(-1.0, 1.0) };
-        out = dry * (1.0 - fx1_mix) + shaped * fx1_mix; self.fx1_reverb = None; self.fx1_crusher = None;
-      } else if fx1_t == 8 {
-        if self.fx1_crusher.is_none() { self.fx1_crusher = Some(Bitcrusher::new()); }
-        if let Some(cr) = &mut self.fx1_crusher {
-          let bits = 4.0 + fx1_p1.clamp(0.0, 1.0) * 12.0; let fac = 1.0 + fx1_p2.clamp(0.0, 1.0) * 15.0; cr.set_bits(bits as u8); cr.set_factor(fac as u32); cr.set_mix(fx1_mix); let mut lbuf = [out]; let mut rbuf = [out]; cr.process(&mut lbuf, &mut rbuf); out = 0.5 * (lbuf[0] + rbuf[0]);
-        }
-      }
-      // FX2
-      let fx2_t = params.get_i32_h(self.paths.fx2_type, 0);
-      let fx2_p1 = params.get_f32_h(self.paths.fx2_p1, 0.0);
-      let fx2_p2 = params.get_f32_h(self.paths.fx2_p2, 0.0);
-      let fx2_mix = params.get_f32_h(self.paths.fx2_p3, 0.0).clamp(0.0, 1.0);
-      if fx2_t <= 0 || fx2_mix <= 0.0005 { if fx2_t <= 0 { self.fx2_reverb = None; self.fx2_crusher = None; } }
-      else if fx2_t == 2 {
-        let time_ms = 10.0 + fx2_p1.clamp(0.0, 1.0) * 990.0; let fb = (fx2_p2.clamp(0.0, 1.0) * 0.95).min(0.95);
-        let mut lbuf = [out]; let mut rbuf = [out]; self.sdelay2.process_block(&mut lbuf, &mut rbuf, self.sr, time_ms, fb, fx2_mix, false); out = 0.5 * (lbuf[0] + rbuf[0]); self.fx2_reverb = None; self.fx2_crusher = None;
-      } else if fx2_t == 1 {
-        if self.fx2_reverb.is_none() { let mut rv = Freeverb::new(self.sr as usize); rv.set_room_size(0.35); rv.set_dampening(0.6); rv.set_wet(1.0); rv.set_dry(0.0); rv.set_width(0.9); self.fx2_reverb = Some(rv); }
-        if let Some(rv) = &mut self.fx2_reverb {
-          let room = 0.2 + fx2_p1.clamp(0.0, 1.0) * 0.8; let damp = 0.2 + fx2_p2.clamp(0.0, 1.0) * 0.8; let mix = fx2_mix;
-          rv.set_room_size(room as f64); rv.set_dampening(damp as f64); rv.set_wet(1.0); rv.set_dry(0.0); rv.set_width(0.9);
-          let dry = out; let (wl, wr) = rv.tick((dry as f64, dry as f64)); let lp_amt = 0.5 + 0.5 * (damp as f32);
-          self.fx2_wet_lp_l.set_hf_damp(lp_amt); self.fx2_wet_lp_r.set_hf_damp(lp_amt);
-          let wet_l = self.fx2_wet_lp_l.tick(wl as f32) as f32; let wet_r = self.fx2_wet_lp_r.tick(wr as f32) as f32; let wet_m = 0.5 * (wet_l + wet_r);
-          out = dry * (1.0 - mix) + wet_m * mix;
-        }
-      } else if fx2_t == 3 || fx2_t == 4 || fx2_t == 5 {
-        let rate = 0.05 + fx2_p1 * (5.0 - 0.05); let depth_ms = match fx2_t { 4 => 6.0 * fx2_p2, 5 => 12.0 * fx2_p2, _ => 4.0 * fx2_p2 };
-        if fx2_t == 3 { let (wet, _) = self.phaser2.process_one(out, out, self.sr, rate, fx2_p2, 1.0); out = out * (1.0 - fx2_mix) + wet * fx2_mix; }
-        else { let base_ms = match fx2_t { 4 => 2.0, 5 => 15.0, _ => 3.0 }; let (wet, _) = self.delay2.process_one(out, out, self.sr, rate, base_ms, depth_ms, 1.0); out = out * (1.0 - fx2_mix) + wet * fx2_mix; }
-        self.fx2_reverb = None; self.fx2_crusher = None;
-      } else if fx2_t == 6 {
-        let dry = out; let drive_db = (fx2_p1.clamp(0.0, 1.0)) * 20.0; let g = (10.0_f32).powf(drive_db / 20.0); let x = (dry * g).tanh();
-        let tone = fx2_p2.clamp(0.0, 1.0); let lp_amt = 0.3 + 0.6 * (1.0 - tone); self.fx2_wet_lp_l.set_hf_damp(lp_amt);
-        let y_lp = self.fx2_wet_lp_l.tick(x); let y_hp = x - y_lp; let shaped = y_lp * (1.0 - tone) + y_hp * tone; out = dry * (1.0 - fx2_mix) + shaped * fx2_mix; self.fx2_reverb = None; self.fx2_crusher = None;
-      } else if fx2_t == 7 {
-        let dry = out; let drive = fx2_p2.clamp(0.0, 1.0) * 10.0; let g = 1.0 + drive; let cur = fx2_p1.clamp(0.0, 1.0);
-        let xin = dry * g; let shaped = if cur < 0.34 { xin.tanh() } else if cur < 0.67 { xin.clamp(-1.0, 1.0) } else { let m = (xin + 1.0).abs().rem_euclid(4.0); ((m - 2.0).abs() - 1.0).clamp(-1.0, 1.0) };
-        out = dry * (1.0 - fx2_mix) + shaped * fx2_mix; self.fx2_reverb = None; self.fx2_crusher = None;
-      } else if fx2_t == 8 {
-        if self.fx2_crusher.is_none() { self.fx2_crusher = Some(Bitcrusher::new()); }
-        if let Some(cr) = &mut self.fx2_crusher { let bits = 4.0 + fx2_p1.clamp(0.0, 1.0) * 12.0; let fac = 1.0 + fx2_p2.clamp(0.0, 1.0) * 15.0; cr.set_bits(bits as u8); cr.set_factor(fac as u32); cr.set_mix(fx2_mix); let mut lbuf = [out]; let mut rbuf = [out]; cr.process(&mut lbuf, &mut rbuf); out = 0.5 * (lbuf[0] + rbuf[0]); }
-      }
-      // FX3
-      let fx3_t = params.get_i32_h(self.paths.fx3_type, 0);
-      let fx3_p1 = params.get_f32_h(self.paths.fx3_p1, 0.0);
-      let fx3_p2 = params.get_f32_h(self.paths.fx3_p2, 0.0);
-      let fx3_mix = params.get_f32_h(self.paths.fx3_p3, 0.0).clamp(0.0, 1.0);
-      if fx3_t <= 0 || fx3_mix <= 0.0005 { if fx3_t <= 0 { self.fx3_reverb = None; self.fx3_crusher = None; } }
-      else if fx3_t == 2 {
-        let time_ms = 10.0 + fx3_p1.clamp(0.0, 1.0) * 990.0; let fb = (fx3_p2.clamp(0.0, 1.0) * 0.95).min(0.95);
-        let mut lbuf = [out]; let mut rbuf = [out]; self.sdelay3.process_block(&mut lbuf, &mut rbuf, self.sr, time_ms, fb, fx3_mix, false); out = 0.5 * (lbuf[0] + rbuf[0]); self.fx3_reverb = None; self.fx3_crusher = None;
-      } else if fx3_t == 1 {
-        if self.fx3_reverb.is_none() { let mut rv = Freeverb::new(self.sr as usize); rv.set_room_size(0.35); rv.set_dampening(0.6); rv.set_wet(1.0); rv.set_dry(0.0); rv.set_width(0.9); self.fx3_reverb = Some(rv); }
-        if let Some(rv) = &mut self.fx3_reverb {
-          let room = 0.2 + fx3_p1.clamp(0.0, 1.0) * 0.8; let damp = 0.2 + fx3_p2.clamp(0.0, 1.0) * 0.8; let mix = fx3_mix;
-          rv.set_room_size(room as f64); rv.set_dampening(damp as f64); rv.set_wet(1.0); rv.set_dry(0.0); rv.set_width(0.9);
-          let dry = out; let (wl, wr) = rv.tick((dry as f64, dry as f64)); let lp_amt = 0.5 + 0.5 * (damp as f32); self.fx3_wet_lp_l.set_hf_damp(lp_amt); self.fx3_wet_lp_r.set_hf_damp(lp_amt);
-          let wet_l = self.fx3_wet_lp_l.tick(wl as f32) as f32; let wet_r = self.fx3_wet_lp_r.tick(wr as f32) as f32; let wet_m = 0.5 * (wet_l + wet_r); out = dry * (1.0 - mix) + wet_m * mix;
-        }
-      } else if fx3_t == 3 || fx3_t == 4 || fx3_t == 5 {
-        let rate = 0.05 + fx3_p1 * (5.0 - 0.05); let depth_ms = match fx3_t { 4 => 6.0 * fx3_p2, 5 => 12.0 * fx3_p2, _ => 4.0 * fx3_p2 };
-        if fx3_t == 3 { let (wet, _) = self.phaser3.process_one(out, out, self.sr, rate, fx3_p2, 1.0); out = out * (1.0 - fx3_mix) + wet * fx3_mix; }
-        else { let base_ms = match fx3_t { 4 => 2.0, 5 => 15.0, _ => 3.0 }; let (wet, _) = self.delay3.process_one(out, out, self.sr, rate, base_ms, depth_ms, 1.0); out = out * (1.0 - fx3_mix) + wet * fx3_mix; }
-        self.fx3_reverb = None; self.fx3_crusher = None;
-      } else if fx3_t == 6 {
-        let dry = out; let drive_db = (fx3_p1.clamp(0.0, 1.0)) * 20.0; let g = (10.0_f32).powf(drive_db / 20.0); let x = (dry * g).tanh();
-        let tone = fx3_p2.clamp(0.0, 1.0); let lp_amt = 0.3 + 0.6 * (1.0 - tone); self.fx3_wet_lp_l.set_hf_damp(lp_amt);
-        let y_lp = self.fx3_wet_lp_l.tick(x); let y_hp = x - y_lp; let shaped = y_lp * (1.0 - tone) + y_hp * tone; out = dry * (1.0 - fx3_mix) + shaped * fx3_mix; self.fx3_reverb = None; self.fx3_crusher = None;
-      } else if fx3_t == 7 {
-        let dry = out; let drive = fx3_p2.clamp(0.0, 1.0) * 10.0; let g = 1.0 + drive; let cur = fx3_p1.clamp(0.0, 1.0);
-        let xin = dry * g; let shaped = if cur < 0.34 { xin.tanh() } else if cur < 0.67 { xin.clamp(-1.0, 1.0) } else { let m = (xin + 1.0).abs().rem_euclid(4.0); ((m - 2.0).abs() - 1.0).clamp(-1.0, 1.0) };
-        out = dry * (1.0 - fx3_mix) + shaped * fx3_mix; self.fx3_reverb = None; self.fx3_crusher = None;
-      } else if fx3_t == 8 {
-        if self.fx3_crusher.is_none() { self.fx3_crusher = Some(Bitcrusher::new()); }
-        if let Some(cr) = &mut self.fx3_crusher { let bits = 4.0 + fx3_p1.clamp(0.0, 1.0) * 12.0; let fac = 1.0 + fx3_p2.clamp(0.0, 1.0) * 15.0; cr.set_bits(bits as u8); cr.set_factor(fac as u32); cr.set_mix(fx3_mix); let mut lbuf = [out]; let mut rbuf = [out]; cr.process(&mut lbuf, &mut rbuf); out = 0.5 * (lbuf[0] + rbuf[0]); }
-      }
-      // FX4
-      let fx4_t = params.get_i32_h(self.paths.fx4_type, 0);
-      let fx4_p1 = params.get_f32_h(self.paths.fx4_p1, 0.0);
-      let fx4_p2 = params.get_f32_h(self.paths.fx4_p2, 0.0);
-      let fx4_mix = params.get_f32_h(self.paths.fx4_p3, 0.0).clamp(0.0, 1.0);
-      if fx4_t <= 0 || fx4_mix <= 0.0005 { if fx4_t <= 0 { self.fx4_reverb = None; self.fx4_crusher = None; } }
-      else if fx4_t == 2 {
-        let time_ms = 10.0 + fx4_p1.clamp(0.0, 1.0) * 990.0; let fb = (fx4_p2.clamp(0.0, 1.0) * 0.95).min(0.95);
-        let mut lbuf = [out]; let mut rbuf = [out]; self.sdelay4.process_block(&mut lbuf, &mut rbuf, self.sr, time_ms, fb, fx4_mix, false); out = 0.5 * (lbuf[0] + rbuf[0]); self.fx4_reverb = None; self.fx4_crusher = None;
-      } else if fx4_t == 1 {
-        if self.fx4_reverb.is_none() { let mut rv = Freeverb::new(self.sr as usize); rv.set_room_size(0.35); rv.set_dampening(0.6); rv.set_wet(1.0); rv.set_dry(0.0); rv.set_width(0.9); self.fx4_reverb = Some(rv); }
-        if let Some(rv) = &mut self.fx4_reverb {
-          let room = 0.2 + fx4_p1.clamp(0.0, 1.0) * 0.8; let damp = 0.2 + fx4_p2.clamp(0.0, 1.0) * 0.8; let mix = fx4_mix;
-          rv.set_room_size(room as f64); rv.set_dampening(damp as f64); rv.set_wet(1.0); rv.set_dry(0.0); rv.set_width(0.9);
-          let dry = out; let (wl, wr) = rv.tick((dry as f64, dry as f64)); let lp_amt = 0.5 + 0.5 * (damp as f32); self.fx4_wet_lp_l.set_hf_damp(lp_amt); self.fx4_wet_lp_r.set_hf_damp(lp_amt);
-          let wet_l = self.fx4_wet_lp_l.tick(wl as f32) as f32; let wet_r = self.fx4_wet_lp_r.tick(wr as f32) as f32; let wet_m = 0.5 * (wet_l + wet_r); out = dry * (1.0 - mix) + wet_m * mix;
-        }
-      } else if fx4_t == 3 || fx4_t == 4 || fx4_t == 5 {
-        let rate = 0.05 + fx4_p1 * (5.0 - 0.05); let depth_ms = match fx4_t { 4 => 6.0 * fx4_p2, 5 => 12.0 * fx4_p2, _ => 4.0 * fx4_p2 };
-        if fx4_t == 3 { let (wet, _) = self.phaser4.process_one(out, out, self.sr, rate, fx4_p2, 1.0); out = out * (1.0 - fx4_mix) + wet * fx4_mix; }
-        else { let base_ms = match fx4_t { 4 => 2.0, 5 => 15.0, _ => 3.0 }; let (wet, _) = self.delay4.process_one(out, out, self.sr, rate, base_ms, depth_ms, 1.0); out = out * (1.0 - fx4_mix) + wet * fx4_mix; }
-        self.fx4_reverb = None; self.fx4_crusher = None;
-      } else if fx4_t == 6 {
-        let dry = out; let drive_db = (fx4_p1.clamp(0.0, 1.0)) * 20.0; let g = (10.0_f32).powf(drive_db / 20.0); let x = (dry * g).tanh();
-        let tone = fx4_p2.clamp(0.0, 1.0); let lp_amt = 0.3 + 0.6 * (1.0 - tone); self.fx4_wet_lp_l.set_hf_damp(lp_amt);
-        let y_lp = self.fx4_wet_lp_l.tick(x); let y_hp = x - y_lp; let shaped = y_lp * (1.0 - tone) + y_hp * tone; out = dry * (1.0 - fx4_mix) + shaped * fx4_mix; self.fx4_reverb = None; self.fx4_crusher = None;
-      } else if fx4_t == 7 {
-        let dry = out; let drive = fx4_p2.clamp(0.0, 1.0) * 10.0; let g = 1.0 + drive; let cur = fx4_p1.clamp(0.0, 1.0);
-        let xin = dry * g; let shaped = if cur < 0.34 { xin.tanh() } else if cur < 0.67 { xin.clamp(-1.0, 1.0) } else { let m = (xin + 1.0).abs().rem_euclid(4.0); ((m - 2.0).abs() - 1.0).clamp(-1.0, 1.0) };
-        out = dry * (1.0 - fx4_mix) + shaped * fx4_mix; self.fx4_reverb = None; self.fx4_crusher = None;
-      } else if fx4_t == 8 {
-        if self.fx4_crusher.is_none() { self.fx4_crusher = Some(Bitcrusher::new()); }
-        if let Some(cr) = &mut self.fx4_crusher { let bits = 4.0 + fx4_p1.clamp(0.0, 1.0) * 12.0; let fac = 1.0 + fx4_p2.clamp(0.0, 1.0) * 15.0; cr.set_bits(bits as u8); cr.set_factor(fac as u32); cr.set_mix(fx4_mix); let mut lbuf = [out]; let mut rbuf = [out]; cr.process(&mut lbuf, &mut rbuf); out = 0.5 * (lbuf[0] + rbuf[0]); }
-      }
-      // EQ
-      let q = 1.0_f32; let mut any_nonzero = false;
-      for i in 0..8 {
-        let db = params.get_f32_h(self.paths.eq_bands[i], 0.0).clamp(-12.0, 12.0);
-        if (db - self.eq_last_db[i]).abs() > 1e-6 { self.eq_bands[i].set_peaking(self.sr, self.eq_centers[i], q, db); self.eq_last_db[i] = db; }
-        if db.abs() > 1e-3 { any_nonzero = true; }
-      }
-      if any_nonzero { for i in 0..8 { out = self.eq_bands[i].process(out); } }
-      // Mixer with per-voice pan blending
-      let pan_local = if dframe.mono.abs() > 1e-9 { (dframe.pan_accum / dframe.mono).clamp(-1.0, 1.0) } else { 0.0 };
-      let mut l = out; let mut r = out;
-      let pan = (params.get_f32_h(self.paths.mix_pan, 0.0) + pan_local).clamp(-1.0, 1.0);
-      let theta = (pan + 1.0) * std::f32::consts::FRAC_PI_4; let gl = theta.cos(); let gr = theta.sin(); l *= gl; r *= gr;
-      let vol = params.get_f32_h(self.paths.mix_volume, 1.0).clamp(0.0, 1.0); l *= vol; r *= vol;
-      let haas = params.get_f32_h(self.paths.mix_haas, 0.0).clamp(0.0, 1.0);
-      if haas > 0.0005 {
-        let rd = if self.haas_wr >= self.haas_d { self.haas_wr - self.haas_d } else { self.haas_wr + self.haas_len - self.haas_d };
-        let delayed_l = self.haas_buf[rd]; self.haas_buf[self.haas_wr] = l; self.haas_wr += 1; if self.haas_wr >= self.haas_len { self.haas_wr = 0; }
-        l = l * (1.0 - haas) + delayed_l * haas;
-      } else { self.haas_buf[self.haas_wr] = l; self.haas_wr += 1; if self.haas_wr >= self.haas_len { self.haas_wr = 0; } }
-      let comp = params.get_f32_h(self.paths.mix_comp, 0.0).clamp(0.0, 1.0);
-      if comp > 0.001 { let drive = 1.0 + 8.0 * comp; let id = 1.0 / drive.tanh(); l = (l * drive).tanh() * id; r = (r * drive).tanh() * id; }
-      return (l, r);
-  } else if module == 6 {
-      // Korus (6-voice Juno clone) stereo render
-      let (kl, kr) = self.korus.render_one(params, &self.korus_keys);
-      // Early-out if both channels silent and no FX tails
-      let fx1_t_peek = params.get_i32_h(self.paths.fx1_type, 0);
-      let fx1_mix_peek = params.get_f32_h(self.paths.fx1_p3, 0.0).clamp(0.0, 1.0);
-      let fx2_t_peek = params.get_i32_h(self.paths.fx2_type, 0);
-      let fx2_mix_peek = params.get_f32_h(self.paths.fx2_p3, 0.0).clamp(0.0, 1.0);
-      let fx3_t_peek = params.get_i32_h(self.paths.fx3_type, 0);
-      let fx3_mix_peek = params.get_f32_h(self.paths.fx3_p3, 0.0).clamp(0.0, 1.0);
-      let fx4_t_peek = params.get_i32_h(self.paths.fx4_type, 0);
-      let fx4_mix_peek = params.get_f32_h(self.paths.fx4_p3, 0.0).clamp(0.0, 1.0);
-      if (kl.abs() + kr.abs()) < 1e-9 && (fx1_t_peek <= 0 || fx1_mix_peek <= 0.0005) && (fx2_t_peek <= 0 || fx2_mix_peek <= 0.0005) && (fx3_t_peek <= 0 || fx3_mix_peek <= 0.0005) && (fx4_t_peek <= 0 || fx4_mix_peek <= 0.0005) {
-        return (0.0, 0.0);
-      }
-      // Process stereo through FX chain -> EQ -> Mixer (stereo-aware)
-      let mut l = kl;
-      let mut r = kr;
-      // --- FX1 (stereo) ---
-      let fx1_t = params.get_i32_h(self.paths.fx1_type, 0);
-      let fx1_p1 = params.get_f32_h(self.paths.fx1_p1, 0.0);
-      let fx1_p2 = params.get_f32_h(self.paths.fx1_p2, 0.0);
-      let fx1_mix = params.get_f32_h(self.paths.fx1_p3, 0.0).clamp(0.0, 1.0);
-      if fx1_t <= 0 || fx1_mix <= 0.0005 { if fx1_t <= 0 { self.fx1_reverb = None; self.fx1_crusher = None; } }
-      else if fx1_t == 2 {
-        let time_ms = 10.0 + fx1_p1.clamp(0.0, 1.0) * 990.0; let fb = (fx1_p2.clamp(0.0, 1.0) * 0.95).min(0.95);
-        let mut lbuf = [l]; let mut rbuf = [r]; self.sdelay1.process_block(&mut lbuf, &mut rbuf, self.sr, time_ms, fb, fx1_mix, false); l = lbuf[0]; r = rbuf[0];
-        self.fx1_reverb = None; self.fx1_crusher = None;
-      } else if fx1_t == 1 {
-        if self.fx1_reverb.is_none() { let mut rv = Freeverb::new(self.sr as usize); rv.set_room_size(0.35); rv.set_dampening(0.6); rv.set_wet(1.0); rv.set_dry(0.0); rv.set_width(0.9); self.fx1_reverb = Some(rv); }
-        if let Some(rv) = &mut self.fx1_reverb {
-          let room = 0.2 + fx1_p1.clamp(0.0, 1.0) * 0.8; let damp = 0.2 + fx1_p2.clamp(0.0, 1.0) * 0.8; let mix = fx1_mix;
-          rv.set_room_size(room as f64); rv.set_dampening(damp as f64); rv.set_wet(1.0); rv.set_dry(0.0); rv.set_width(0.9);
-          let (wl, wr) = rv.tick((l as f64, r as f64)); let lp_amt = 0.5 + 0.5 * (damp as f32); self.fx1_wet_lp_l.set_hf_damp(lp_amt); self.fx1_wet_lp_r.set_hf_damp(lp_amt);
-          let wet_l = self.fx1_wet_lp_l.tick(wl as f32); let wet_r = self.fx1_wet_lp_r.tick(wr as f32);
-          l = l * (1.0 - mix) + wet_l * mix; r = r * (1.0 - mix) + wet_r * mix;
-        }
-      } else if fx1_t == 3 || fx1_t == 4 || fx1_t == 5 {
-        let rate = 0.05 + fx1_p1 * (5.0 - 0.05); let depth_ms = match fx1_t { 4 => 6.0 * fx1_p2, 5 => 12.0 * fx1_p2, _ => 4.0 * fx1_p2 };
-        if fx1_t == 3 { let (wl, wr) = self.phaser1.process_one(l, r, self.sr, rate, fx1_p2, 1.0); l = l * (1.0 - fx1_mix) + wl * fx1_mix; r = r * (1.0 - fx1_mix) + wr * fx1_mix; }
-        else { let base_ms = match fx1_t { 4 => 2.0, 5 => 15.0, _ => 3.0 }; let (wl, wr) = self.delay1.process_one(l, r, self.sr, rate, base_ms, depth_ms, 1.0); l = l * (1.0 - fx1_mix) + wl * fx1_mix; r = r * (1.0 - fx1_mix) + wr * fx1_mix; }
-        self.fx1_reverb = None; self.fx1_crusher = None;
-      } else if fx1_t == 6 {
-        let drive_db = fx1_p1.clamp(0.0, 1.0) * 20.0; let g = (10.0_f32).powf(drive_db / 20.0); let tone = fx1_p2.clamp(0.0, 1.0);
-        let xl = (l * g).tanh(); let xr = (r * g).tanh();
-        let lp_amt = 0.3 + 0.6 * (1.0 - tone); self.fx1_wet_lp_l.set_hf_damp(lp_amt); self.fx1_wet_lp_r.set_hf_damp(lp_amt);
-        let yl_lp = self.fx1_wet_lp_l.tick(xl); let yr_lp = self.fx1_wet_lp_r.tick(xr);
-        let sl = yl_lp * (1.0 - tone) + (xl - yl_lp) * tone; let sr = yr_lp * (1.0 - tone) + (xr - yr_lp) * tone;
-        l = l * (1.0 - fx1_mix) + sl * fx1_mix; r = r * (1.0 - fx1_mix) + sr * fx1_mix;
-        self.fx1_reverb = None;
-      } else if fx1_t == 7 {
-        let drive = fx1_p2.clamp(0.0, 1.0) * 10.0; let g = 1.0 + drive; let cur = fx1_p1.clamp(0.0, 1.0);
-        let shape = |x: f32| if cur < 0.34 { (x * g).tanh() } else if cur < 0.67 { (x * g).clamp(-1.0, 1.0) } else { let m = (x * g + 1.0).abs().rem_euclid(4.0); ((m - 2.0).abs() - 1.0).clamp(-1.0, 1.0) };
-        l = l * (1.0 - fx1_mix) + shape(l) * fx1_mix; r = r * (1.0 - fx1_mix) + shape(r) * fx1_mix;
-        self.fx1_reverb = None; self.fx1_crusher = None;
-      } else if fx1_t == 8 {
-        if self.fx1_crusher.is_none() { self.fx1_crusher = Some(Bitcrusher::new()); }
-        if let Some(cr) = &mut self.fx1_crusher { let bits = 4.0 + fx1_p1.clamp(0.0, 1.0) * 12.0; let fac = 1.0 + fx1_p2.clamp(0.0, 1.0) * 15.0; cr.set_bits(bits as u8); cr.set_factor(fac as u32); cr.set_mix(fx1_mix); let mut lbuf = [l]; let mut rbuf = [r]; cr.process(&mut lbuf, &mut rbuf); l = lbuf[0]; r = rbuf[0]; }
-      }
-      // --- FX2 (stereo) ---
-      let fx2_t = params.get_i32_h(self.paths.fx2_type, 0);
-      let fx2_p1 = params.get_f32_h(self.paths.fx2_p1, 0.0);
-      let fx2_p2 = params.get_f32_h(self.paths.fx2_p2, 0.0);
-      let fx2_mix = params.get_f32_h(self.paths.fx2_p3, 0.0).clamp(0.0, 1.0);
-      if fx2_t <= 0 || fx2_mix <= 0.0005 { if fx2_t <= 0 { self.fx2_reverb = None; self.fx2_crusher = None; } }
-      else if fx2_t == 2 { let time_ms = 10.0 + fx2_p1.clamp(0.0, 1.0) * 990.0; let fb = (fx2_p2.clamp(0.0, 1.0) * 0.95).min(0.95); let mut lbuf = [l]; let mut rbuf = [r]; self.sdelay2.process_block(&mut lbuf, &mut rbuf, self.sr, time_ms, fb, fx2_mix, false); l = lbuf[0]; r = rbuf[0]; self.fx2_reverb = None; self.fx2_crusher = None; }
-      else if fx2_t == 1 { if self.fx2_reverb.is_none() { let mut rv = Freeverb::new(self.sr as usize); rv.set_room_size(0.35); rv.set_dampening(0.6); rv.set_wet(1.0); rv.set_dry(0.0); rv.set_width(0.9); self.fx2_reverb = Some(rv); } if let Some(rv) = &mut self.fx2_reverb { let room = 0.2 + fx2_p1.clamp(0.0, 1.0) * 0.8; let damp = 0.2 + fx2_p2.clamp(0.0, 1.0) * 0.8; let mix = fx2_mix; rv.set_room_size(room as f64); rv.set_dampening(damp as f64); rv.set_wet(1.0); rv.set_dry(0.0); rv.set_width(0.9); let (wl, wr) = rv.tick((l as f64, r as f64)); let lp_amt = 0.5 + 0.5 * (damp as f32); self.fx2_wet_lp_l.set_hf_damp(lp_amt); self.fx2_wet_lp_r.set_hf_damp(lp_amt); let wet_l = self.fx2_wet_lp_l.tick(wl as f32); let wet_r = self.fx2_wet_lp_r.tick(wr as f32); l = l * (1.0 - mix) + wet_l * mix; r = r * (1.0 - mix) + wet_r * mix; } }
-      else if fx2_t == 3 || fx2_t == 4 || fx2_t == 5 { let rate = 0.05 + fx2_p1 * (5.0 - 0.05); let depth_ms = match fx2_t { 4 => 6.0 * fx2_p2, 5 => 12.0 * fx2_p2, _ => 4.0 * fx2_p2 }; if fx2_t == 3 { let (wl, wr) = self.phaser2.process_one(l, r, self.sr, rate, fx2_p2, 1.0); l = l * (1.0 - fx2_mix) + wl * fx2_mix; r = r * (1.0 - fx2_mix) + wr * fx2_mix; } else { let base_ms = match fx2_t { 4 => 2.0, 5 => 15.0, _ => 3.0 }; let (wl, wr) = self.delay2.process_one(l, r, self.sr, rate, base_ms, depth_ms, 1.0); l = l * (1.0 - fx2_mix) + wl * fx2_mix; r = r * (1.0 - fx2_mix) + wr * fx2_mix; } self.fx2_reverb = None; self.fx2_crusher = None; }
-      else if fx2_t == 6 { let drive_db = fx2_p1.clamp(0.0, 1.0) * 20.0; let g = (10.0_f32).powf(drive_db / 20.0); let tone = fx2_p2.clamp(0.0, 1.0); let xl = (l * g).tanh(); let xr = (r * g).tanh(); let lp_amt = 0.3 + 0.6 * (1.0 - tone); self.fx2_wet_lp_l.set_hf_damp(lp_amt); self.fx2_wet_lp_r.set_hf_damp(lp_amt); let yl_lp = self.fx2_wet_lp_l.tick(xl); let yr_lp = self.fx2_wet_lp_r.tick(xr); let sl = yl_lp * (1.0 - tone) + (xl - yl_lp) * tone; let sr = yr_lp * (1.0 - tone) + (xr - yr_lp) * tone; l = l * (1.0 - fx2_mix) + sl * fx2_mix; r = r * (1.0 - fx2_mix) + sr * fx2_mix; self.fx2_reverb = None; }
-      else if fx2_t == 7 { let drive = fx2_p2.clamp(0.0, 1.0) * 10.0; let g = 1.0 + drive; let cur = fx2_p1.clamp(0.0, 1.0); let shape = |x: f32| if cur < 0.34 { (x * g).tanh() } else if cur < 0.67 { (x * g).clamp(-1.0, 1.0) } else { let m = (x * g + 1.0).abs().rem_euclid(4.0); ((m - 2.0).abs() - 1.0).clamp(-1.0, 1.0) }; l = l * (1.0 - fx2_mix) + shape(l) * fx2_mix; r = r * (1.0 - fx2_mix) + shape(r) * fx2_mix; self.fx2_reverb = None; self.fx2_crusher = None; }
-      else if fx2_t == 8 { if self.fx2_crusher.is_none() { self.fx2_crusher = Some(Bitcrusher::new()); } if let Some(cr) = &mut self.fx2_crusher { let bits = 4.0 + fx2_p1.clamp(0.0, 1.0) * 12.0; let fac = 1.0 + fx2_p2.clamp(0.0, 1.0) * 15.0; cr.set_bits(bits as u8); cr.set_factor(fac as u32); cr.set_mix(fx2_mix); let mut lbuf = [l]; let mut rbuf = [r]; cr.process(&mut lbuf, &mut rbuf); l = lbuf[0]; r = rbuf[0]; } }
-      // --- FX3 (stereo) ---
-      let fx3_t = params.get_i32_h(self.paths.fx3_type, 0);
-      let fx3_p1 = params.get_f32_h(self.paths.fx3_p1, 0.0);
-      let fx3_p2 = params.get_f32_h(self.paths.fx3_p2, 0.0);
-      let fx3_mix = params.get_f32_h(self.paths.fx3_p3, 0.0).clamp(0.0, 1.0);
-      if fx3_t <= 0 || fx3_mix <= 0.0005 { if fx3_t <= 0 { self.fx3_reverb = None; self.fx3_crusher = None; } }
-      else if fx3_t == 2 { let time_ms = 10.0 + fx3_p1.clamp(0.0, 1.0) * 990.0; let fb = (fx3_p2.clamp(0.0, 1.0) * 0.95).min(0.95); let mut lbuf = [l]; let mut rbuf = [r]; self.sdelay3.process_block(&mut lbuf, &mut rbuf, self.sr, time_ms, fb, fx3_mix, false); l = lbuf[0]; r = rbuf[0]; self.fx3_reverb = None; self.fx3_crusher = None; }
-      else if fx3_t == 1 { if self.fx3_reverb.is_none() { let mut rv = Freeverb::new(self.sr as usize); rv.set_room_size(0.35); rv.set_dampening(0.6); rv.set_wet(1.0); rv.set_dry(0.0); rv.set_width(0.9); self.fx3_reverb = Some(rv); } if let Some(rv) = &mut self.fx3_reverb { let room = 0.2 + fx3_p1.clamp(0.0, 1.0) * 0.8; let damp = 0.2 + fx3_p2.clamp(0.0, 1.0) * 0.8; let mix = fx3_mix; rv.set_room_size(room as f64); rv.set_dampening(damp as f64); rv.set_wet(1.0); rv.set_dry(0.0); rv.set_width(0.9); let (wl, wr) = rv.tick((l as f64, r as f64)); let lp_amt = 0.5 + 0.5 * (damp as f32); self.fx3_wet_lp_l.set_hf_damp(lp_amt); self.fx3_wet_lp_r.set_hf_damp(lp_amt); let wet_l = self.fx3_wet_lp_l.tick(wl as f32); let wet_r = self.fx3_wet_lp_r.tick(wr as f32); l = l * (1.0 - mix) + wet_l * mix; r = r * (1.0 - mix) + wet_r * mix; } }
-      else if fx3_t == 3 || fx3_t == 4 || fx3_t == 5 { let rate = 0.05 + fx3_p1 * (5.0 - 0.05); let depth_ms = match fx3_t { 4 => 6.0 * fx3_p2, 5 => 12.0 * fx3_p2, _ => 4.0 * fx3_p2 }; if fx3_t == 3 { let (wl, wr) = self.phaser3.process_one(l, r, self.sr, rate, fx3_p2, 1.0); l = l * (1.0 - fx3_mix) + wl * fx3_mix; r = r * (1.0 - fx3_mix) + wr * fx3_mix; } else { let base_ms = match fx3_t { 4 => 2.0, 5 => 15.0, _ => 3.0 }; let (wl, wr) = self.delay3.process_one(l, r, self.sr, rate, base_ms, depth_ms, 1.0); l = l * (1.0 - fx3_mix) + wl * fx3_mix; r = r * (1.0 - fx3_mix) + wr * fx3_mix; } self.fx3_reverb = None; self.fx3_crusher = None; }
-      else if fx3_t == 6 { let drive_db = fx3_p1.clamp(0.0, 1.0) * 20.0; let g = (10.0_f32).powf(drive_db / 20.0); let tone = fx3_p2.clamp(0.0, 1.0); let xl = (l * g).tanh(); let xr = (r * g).tanh(); let lp_amt = 0.3 + 0.6 * (1.0 - tone); self.fx3_wet_lp_l.set_hf_damp(lp_amt); self.fx3_wet_lp_r.set_hf_damp(lp_amt); let yl_lp = self.fx3_wet_lp_l.tick(xl); let yr_lp = self.fx3_wet_lp_r.tick(xr); let sl = yl_lp * (1.0 - tone) + (xl - yl_lp) * tone; let sr = yr_lp * (1.0 - tone) + (xr - yr_lp) * tone; l = l * (1.0 - fx3_mix) + sl * fx3_mix; r = r * (1.0 - fx3_mix) + sr * fx3_mix; self.fx3_reverb = None; }
-      else if fx3_t == 7 { let drive = fx3_p2.clamp(0.0, 1.0) * 10.0; let g = 1.0 + drive; let cur = fx3_p1.clamp(0.0, 1.0); let shape = |x: f32| if cur < 0.34 { (x * g).tanh() } else if cur < 0.67 { (x * g).clamp(-1.0, 1.0) } else { let m = (x * g + 1.0).abs().rem_euclid(4.0); ((m - 2.0).abs() - 1.0).clamp(-1.0, 1.0) }; l = l * (1.0 - fx3_mix) + shape(l) * fx3_mix; r = r * (1.0 - fx3_mix) + shape(r) * fx3_mix; self.fx3_reverb = None; self.fx3_crusher = None; }
-      else if fx3_t == 8 { if self.fx3_crusher.is_none() { self.fx3_crusher = Some(Bitcrusher::new()); } if let Some(cr) = &mut self.fx3_crusher { let bits = 4.0 + fx3_p1.clamp(0.0, 1.0) * 12.0; let fac = 1.0 + fx3_p2.clamp(0.0, 1.0) * 15.0; cr.set_bits(bits as u8); cr.set_factor(fac as u32); cr.set_mix(fx3_mix); let mut lbuf = [l]; let mut rbuf = [r]; cr.process(&mut lbuf, &mut rbuf); l = lbuf[0]; r = rbuf[0]; } }
-      // --- FX4 (stereo) ---
-      let fx4_t = params.get_i32_h(self.paths.fx4_type, 0);
-      let fx4_p1 = params.get_f32_h(self.paths.fx4_p1, 0.0);
-      let fx4_p2 = params.get_f32_h(self.paths.fx4_p2, 0.0);
-      let fx4_mix = params.get_f32_h(self.paths.fx4_p3, 0.0).clamp(0.0, 1.0);
-      if fx4_t <= 0 || fx4_mix <= 0.0005 { if fx4_t <= 0 { self.fx4_reverb = None; self.fx4_crusher = None; } }
-      else if fx4_t == 2 { let time_ms = 10.0 + fx4_p1.clamp(0.0, 1.0) * 990.0; let fb = (fx4_p2.clamp(0.0, 1.0) * 0.95).min(0.95); let mut lbuf = [l]; let mut rbuf = [r]; self.sdelay4.process_block(&mut lbuf, &mut rbuf, self.sr, time_ms, fb, fx4_mix, false); l = lbuf[0]; r = rbuf[0]; self.fx4_reverb = None; self.fx4_crusher = None; }
-      else if fx4_t == 1 { if self.fx4_reverb.is_none() { let mut rv = Freeverb::new(self.sr as usize); rv.set_room_size(0.35); rv.set_dampening(0.6); rv.set_wet(1.0); rv.set_dry(0.0); rv.set_width(0.9); self.fx4_reverb = Some(rv); } if let Some(rv) = &mut self.fx4_reverb { let room = 0.2 + fx4_p1.clamp(0.0, 1.0) * 0.8; let damp = 0.2 + fx4_p2.clamp(0.0, 1.0) * 0.8; let mix = fx4_mix; rv.set_room_size(room as f64); rv.set_dampening(damp as f64); rv.set_wet(1.0); rv.set_dry(0.0); rv.set_width(0.9); let (wl, wr) = rv.tick((l as f64, r as f64)); let lp_amt = 0.5 + 0.5 * (damp as f32); self.fx4_wet_lp_l.set_hf_damp(lp_amt); self.fx4_wet_lp_r.set_hf_damp(lp_amt); let wet_l = self.fx4_wet_lp_l.tick(wl as f32); let wet_r = self.fx4_wet_lp_r.tick(wr as f32); l = l * (1.0 - mix) + wet_l * mix; r = r * (1.0 - mix) + wet_r * mix; } }
-      else if fx4_t == 3 || fx4_t == 4 || fx4_t == 5 { let rate = 0.05 + fx4_p1 * (5.0 - 0.05); let depth_ms = match fx4_t { 4 => 6.0 * fx4_p2, 5 => 12.0 * fx4_p2, _ => 4.0 * fx4_p2 }; if fx4_t == 3 { let (wl, wr) = self.phaser4.process_one(l, r, self.sr, rate, fx4_p2, 1.0); l = l * (1.0 - fx4_mix) + wl * fx4_mix; r = r * (1.0 - fx4_mix) + wr * fx4_mix; } else { let base_ms = match fx4_t { 4 => 2.0, 5 => 15.0, _ => 3.0 }; let (wl, wr) = self.delay4.process_one(l, r, self.sr, rate, base_ms, depth_ms, 1.0); l = l * (1.0 - fx4_mix) + wl * fx4_mix; r = r * (1.0 - fx4_mix) + wr * fx4_mix; } self.fx4_reverb = None; self.fx4_crusher = None; }
-      else if fx4_t == 6 { let drive_db = fx4_p1.clamp(0.0, 1.0) * 20.0; let g = (10.0_f32).powf(drive_db / 20.0); let tone = fx4_p2.clamp(0.0, 1.0); let xl = (l * g).tanh(); let xr = (r * g).tanh(); let lp_amt = 0.3 + 0.6 * (1.0 - tone); self.fx4_wet_lp_l.set_hf_damp(lp_amt); self.fx4_wet_lp_r.set_hf_damp(lp_amt); let yl_lp = self.fx4_wet_lp_l.tick(xl); let yr_lp = self.fx4_wet_lp_r.tick(xr); let sl = yl_lp * (1.0 - tone) + (xl - yl_lp) * tone; let sr = yr_lp * (1.0 - tone) + (xr - yr_lp) * tone; l = l * (1.0 - fx4_mix) + sl * fx4_mix; r = r * (1.0 - fx4_mix) + sr * fx4_mix; self.fx4_reverb = None; }
-      else if fx4_t == 7 { let drive = fx4_p2.clamp(0.0, 1.0) * 10.0; let g = 1.0 + drive; let cur = fx4_p1.clamp(0.0, 1.0); let shape = |x: f32| if cur < 0.34 { (x * g).tanh() } else if cur < 0.67 { (x * g).clamp(-1.0, 1.0) } else { let m = (x * g + 1.0).abs().rem_euclid(4.0); ((m - 2.0).abs() - 1.0).clamp(-1.0, 1.0) }; l = l * (1.0 - fx4_mix) + shape(l) * fx4_mix; r = r * (1.0 - fx4_mix) + shape(r) * fx4_mix; self.fx4_reverb = None; self.fx4_crusher = None; }
-      else if fx4_t == 8 { if self.fx4_crusher.is_none() { self.fx4_crusher = Some(Bitcrusher::new()); } if let Some(cr) = &mut self.fx4_crusher { let bits = 4.0 + fx4_p1.clamp(0.0, 1.0) * 12.0; let fac = 1.0 + fx4_p2.clamp(0.0, 1.0) * 15.0; cr.set_bits(bits as u8); cr.set_factor(fac as u32); cr.set_mix(fx4_mix); let mut lbuf = [l]; let mut rbuf = [r]; cr.process(&mut lbuf, &mut rbuf); l = lbuf[0]; r = rbuf[0]; } }
-      // --- EQ (stereo) ---
-      let q = 1.0_f32; let mut any_nonzero = false;
-      for i in 0..8 { let db = params.get_f32_h(self.paths.eq_bands[i], 0.0).clamp(-12.0, 12.0); if (db - self.eq_last_db[i]).abs() > 1e-6 { self.eq_bands[i].set_peaking(self.sr, self.eq_centers[i], q, db); self.eq_last_db[i] = db; } if db.abs() > 1e-3 { any_nonzero = true; } }
-      if any_nonzero { for i in 0..8 { l = self.eq_bands[i].process(l); r = self.eq_bands[i].process(r); } }
-      // --- Mixer (stereo pan, volume, haas, comp) ---
-      let pan = params.get_f32_h(self.paths.mix_pan, 0.0).clamp(-1.0, 1.0);
-      let theta = (pan + 1.0) * std::f32::consts::FRAC_PI_4; let gl = theta.cos(); let gr = theta.sin(); l *= gl; r *= gr;
-      let vol = params.get_f32_h(self.paths.mix_volume, 1.0).clamp(0.0, 1.0); l *= vol; r *= vol;
-      let haas = params.get_f32_h(self.paths.mix_haas, 0.0).clamp(0.0, 1.0);
-      if haas > 0.0005 { let rd = if self.haas_wr >= self.haas_d { self.haas_wr - self.haas_d } else { self.haas_wr + self.haas_len - self.haas_d }; let delayed_l = self.haas_buf[rd]; self.haas_buf[self.haas_wr] = l; self.haas_wr += 1; if self.haas_wr >= self.haas_len { self.haas_wr = 0; } l = l * (1.0 - haas) + delayed_l * haas; } else { self.haas_buf[self.haas_wr] = l; self.haas_wr += 1; if self.haas_wr >= self.haas_len { self.haas_wr = 0; } }
-      let comp = params.get_f32_h(self.paths.mix_comp, 0.0).clamp(0.0, 1.0);
-      if comp > 0.001 { let drive = 1.0 + 8.0 * comp; let id = 1.0 / drive.tanh(); l = (l * drive).tanh() * id; r = (r * drive).tanh() * id; }
-      return (l, r);
-  } else if module == 1 {
-      // Acid303 mono voice sample
-      let s = self.acid.render_one(params, &self.acid_keys);
-      // Early-out if dry is silent and all FX mixes are ~zero (no tails needed)
-      let fx1_t_peek = params.get_i32_h(self.paths.fx1_type, 0);
-      let fx1_mix_peek = params.get_f32_h(self.paths.fx1_p3, 0.0).clamp(0.0, 1.0);
-      let fx2_t_peek = params.get_i32_h(self.paths.fx2_type, 0);
-      let fx2_mix_peek = params.get_f32_h(self.paths.fx2_p3, 0.0).clamp(0.0, 1.0);
-      let fx3_t_peek = params.get_i32_h(self.paths.fx3_type, 0);
-      let fx3_mix_peek = params.get_f32_h(self.paths.fx3_p3, 0.0).clamp(0.0, 1.0);
-      let fx4_t_peek = params.get_i32_h(self.paths.fx4_type, 0);
-      let fx4_mix_peek = params.get_f32_h(self.paths.fx4_p3, 0.0).clamp(0.0, 1.0);
-      if s.abs() < 1e-9 && (fx1_t_peek <= 0 || fx1_mix_peek <= 0.0005) && (fx2_t_peek <= 0 || fx2_mix_peek <= 0.0005) && (fx3_t_peek <= 0 || fx3_mix_peek <= 0.0005) && (fx4_t_peek <= 0 || fx4_mix_peek <= 0.0005) {
-        return (0.0, 0.0);
-      }
-      // FX chain (identical to Analog)
-      let mut out = s;
-      let fx1_t = params.get_i32_h(self.paths.fx1_type, 0);
-      let fx1_p1 = params.get_f32_h(self.paths.fx1_p1, 0.0);
-      let fx1_p2 = params.get_f32_h(self.paths.fx1_p2, 0.0);
-      let fx1_mix = params.get_f32_h(self.paths.fx1_p3, 0.0).clamp(0.0, 1.0);
-      if fx1_t <= 0 || fx1_mix <= 0.0005 {
-        if fx1_t <= 0 { self.fx1_reverb = None; self.fx1_crusher = None; }
-      } else if fx1_t == 2 {
-        let time_ms = 10.0 + fx1_p1.clamp(0.0, 1.0) * 990.0;
-        let fb = (fx1_p2.clamp(0.0, 1.0) * 0.95).min(0.95);
-        let mut lbuf = [out]; let mut rbuf = [out];
-        self.sdelay1.process_block(&mut lbuf, &mut rbuf, self.sr, time_ms, fb, fx1_mix, false);
-        out = 0.5 * (lbuf[0] + rbuf[0]);
-        self.fx1_reverb = None; self.fx1_crusher = None;
-      } else if fx1_t == 1 {
-        if self.fx1_reverb.is_none() {
-          let mut rv = Freeverb::new(self.sr as usize);
-          rv.set_room_size(0.35); rv.set_dampening(0.6); rv.set_wet(1.0); rv.set_dry(0.0); rv.set_width(0.9);
-          self.fx1_reverb = Some(rv);
-        }
-        if let Some(rv) = &mut self.fx1_reverb {
-          let room = 0.2 + fx1_p1.clamp(0.0, 1.0) * 0.8;
-          let damp = 0.2 + fx1_p2.clamp(0.0, 1.0) * 0.8;
-          let mix = fx1_mix;
-          rv.set_room_size(room as f64); rv.set_dampening(damp as f64); rv.set_wet(1.0); rv.set_dry(0.0); rv.set_width(0.9);
-          let dry = out; let (wl, wr) = rv.tick((dry as f64, dry as f64));
-          let lp_amt = 0.5 + 0.5 * (damp as f32);
-          self.fx1_wet_lp_l.set_hf_damp(lp_amt); self.fx1_wet_lp_r.set_hf_damp(lp_amt);
-          let wet_l = self.fx1_wet_lp_l.tick(wl as f32) as f32; let wet_r = self.fx1_wet_lp_r.tick(wr as f32) as f32;
-          let wet_m = 0.5 * (wet_l + wet_r);
-          out = dry * (1.0 - mix) + wet_m * mix;
-        }
-      } else if fx1_t == 3 || fx1_t == 4 || fx1_t == 5 {
-        let rate = 0.05 + fx1_p1 * (5.0 - 0.05);
-        let depth_ms = match fx1_t { 4 => 6.0 * fx1_p2, 5 => 12.0 * fx1_p2, _ => 4.0 * fx1_p2 };
-        if fx1_t == 3 {
-          let (wet, _) = self.phaser1.process_one(out, out, self.sr, rate, fx1_p2, 1.0);
-          out = out * (1.0 - fx1_mix) + wet * fx1_mix;
-        } else {
-          let base_ms = match fx1_t { 4 => 2.0, 5 => 15.0, _ => 3.0 };
-          let (wet, _) = self.delay1.process_one(out, out, self.sr, rate, base_ms, depth_ms, 1.0);
-          out = out * (1.0 - fx1_mix) + wet * fx1_mix;
-        }
-        self.fx1_reverb = None; self.fx1_crusher = None;
-      } else if fx1_t == 6 {
-        let dry = out; let drive_db = (fx1_p1.clamp(0.0, 1.0)) * 20.0; let g = (10.0_f32).powf(drive_db / 20.0);
-        let x = (dry * g).tanh(); let tone = fx1_p2.clamp(0.0, 1.0);
-        let lp_amt = 0.3 + 0.6 * (1.0 - tone); self.fx1_wet_lp_l.set_hf_damp(lp_amt);
-        let y_lp = self.fx1_wet_lp_l.tick(x); let y_hp = x - y_lp; let shaped = y_lp * (1.0 - tone) + y_hp * tone;
-        out = dry * (1.0 - fx1_mix) + shaped * fx1_mix; self.fx1_reverb = None;
-      } else if fx1_t == 7 {
-        let dry = out; let drive = fx1_p2.clamp(0.0, 1.0) * 10.0; let g = 1.0 + drive; let cur = fx1_p1.clamp(0.0, 1.0);
-        let xin = dry * g;
-        let shaped = if cur < 0.34 { xin.tanh() } else if cur < 0.67 { xin.clamp(-1.0, 1.0) } else { let m = (xin + 1.0).abs().rem_euclid(4.0); ((m - 2.0).abs() - 1.0).clamp(-1.0, 1.0) };
-        out = dry * (1.0 - fx1_mix) + shaped * fx1_mix; self.fx1_reverb = None;
-      } else if fx1_t == 8 {
-        if self.fx1_crusher.is_none() { self.fx1_crusher = Some(Bitcrusher::new()); }
-        if let Some(cr) = &mut self.fx1_crusher {
-          let bits = 4.0 + fx1_p1.clamp(0.0, 1.0) * 12.0; let fac = 1.0 + fx1_p2.clamp(0.0, 1.0) * 15.0;
-          cr.set_bits(bits as u8); cr.set_factor(fac as u32); cr.set_mix(fx1_mix);
-          let mut lbuf = [out]; let mut rbuf = [out]; cr.process(&mut lbuf, &mut rbuf); out = 0.5 * (lbuf[0] + rbuf[0]);
-        }
-      }
-      // FX2 chain
-      let fx2_t = params.get_i32_h(self.paths.fx2_type, 0);
-      let fx2_p1 = params.get_f32_h(self.paths.fx2_p1, 0.0);
-      let fx2_p2 = params.get_f32_h(self.paths.fx2_p2, 0.0);
-      let fx2_mix = params.get_f32_h(self.paths.fx2_p3, 0.0).clamp(0.0, 1.0);
-      if fx2_t <= 0 || fx2_mix <= 0.0005 { if fx2_t <= 0 { self.fx2_reverb = None; self.fx2_crusher = None; } }
-      else if fx2_t == 2 {
-        let time_ms = 10.0 + fx2_p1.clamp(0.0, 1.0) * 990.0; let fb = (fx2_p2.clamp(0.0, 1.0) * 0.95).min(0.95);
-        let mut lbuf = [out]; let mut rbuf = [out]; self.sdelay2.process_block(&mut lbuf, &mut rbuf, self.sr, time_ms, fb, fx2_mix, false); out = 0.5 * (lbuf[0] + rbuf[0]);
-        self.fx2_reverb = None; self.fx2_crusher = None;
-      } else if fx2_t == 1 {
-        if self.fx2_reverb.is_none() {
-          let mut rv = Freeverb::new(self.sr as usize); rv.set_room_size(0.35); rv.set_dampening(0.6); rv.set_wet(1.0); rv.set_dry(0.0); rv.set_width(0.9); self.fx2_reverb = Some(rv);
-        }
-        if let Some(rv) = &mut self.fx2_reverb {
-          let room = 0.2 + fx2_p1.clamp(0.0, 1.0) * 0.8; let damp = 0.2 + fx2_p2.clamp(0.0, 1.0) * 0.8; let mix = fx2_mix;
-          rv.set_room_size(room as f64); rv.set_dampening(damp as f64); rv.set_wet(1.0); rv.set_dry(0.0); rv.set_width(0.9);
-          let dry = out; let (wl, wr) = rv.tick((dry as f64, dry as f64)); let lp_amt = 0.5 + 0.5 * (damp as f32);
-          self.fx2_wet_lp_l.set_hf_damp(lp_amt); self.fx2_wet_lp_r.set_hf_damp(lp_amt);
-          let wet_l = self.fx2_wet_lp_l.tick(wl as f32) as f32; let wet_r = self.fx2_wet_lp_r.tick(wr as f32) as f32; let wet_m = 0.5 * (wet_l + wet_r);
-          out = dry * (1.0 - mix) + wet_m * mix;
-        }
-      } else if fx2_t == 3 || fx2_t == 4 || fx2_t == 5 {
-        let rate = 0.05 + fx2_p1 * (5.0 - 0.05); let depth_ms = match fx2_t { 4 => 6.0 * fx2_p2, 5 => 12.0 * fx2_p2, _ => 4.0 * fx2_p2 };
-        if fx2_t == 3 {
-          let (wet, _) = self.phaser2.process_one(out, out, self.sr, rate, fx2_p2, 1.0); out = out * (1.0 - fx2_mix) + wet * fx2_mix;
-        } else {
-          let base_ms = match fx2_t { 4 => 2.0, 5 => 15.0, _ => 3.0 }; let (wet, _) = self.delay2.process_one(out, out, self.sr, rate, base_ms, depth_ms, 1.0); out = out * (1.0 - fx2_mix) + wet * fx2_mix;
-        }
-        self.fx2_reverb = None; self.fx2_crusher = None;
-      } else if fx2_t == 6 {
-        let dry = out; let drive_db = (fx2_p1.clamp(0.0, 1.0)) * 20.0; let g = (10.0_f32).powf(drive_db / 20.0); let x = (dry * g).tanh();
-        let tone = fx2_p2.clamp(0.0, 1.0); let lp_amt = 0.3 + 0.6 * (1.0 - tone); self.fx2_wet_lp_l.set_hf_damp(lp_amt);
-        let y_lp = self.fx2_wet_lp_l.tick(x); let y_hp = x - y_lp; let shaped = y_lp * (1.0 - tone) + y_hp * tone; out = dry * (1.0 - fx2_mix) + shaped * fx2_mix; self.fx2_reverb = None; self.fx2_crusher = None;
-      } else if fx2_t == 7 {
-        let dry = out; let drive = fx2_p2.clamp(0.0, 1.0) * 10.0; let g = 1.0 + drive; let cur = fx2_p1.clamp(0.0, 1.0);
-        let xin = dry * g; let shaped = if cur < 0.34 { xin.tanh() } else if cur < 0.67 { xin.clamp(-1.0, 1.0) } else { let m = (xin + 1.0).abs().rem_euclid(4.0); ((m - 2.0).abs() - 1.0).clamp(-1.0, 1.0) };
-        out = dry * (1.0 - fx2_mix) + shaped * fx2_mix; self.fx2_reverb = None; self.fx2_crusher = None;
-      } else if fx2_t == 8 {
-        if self.fx2_crusher.is_none() { self.fx2_crusher = Some(Bitcrusher::new()); }
-        if let Some(cr) = &mut self.fx2_crusher {
-          let bits = 4.0 + fx2_p1.clamp(0.0, 1.0) * 12.0; let fac = 1.0 + fx2_p2.clamp(0.0, 1.0) * 15.0; cr.set_bits(bits as u8); cr.set_factor(fac as u32); cr.set_mix(fx2_mix); let mut lbuf = [out]; let mut rbuf = [out]; cr.process(&mut lbuf, &mut rbuf); out = 0.5 * (lbuf[0] + rbuf[0]);
-        }
-      }
-      // FX3 chain
-      let fx3_t = params.get_i32_h(self.paths.fx3_type, 0);
-      let fx3_p1 = params.get_f32_h(self.paths.fx3_p1, 0.0);
-      let fx3_p2 = params.get_f32_h(self.paths.fx3_p2, 0.0);
-      let fx3_mix = params.get_f32_h(self.paths.fx3_p3, 0.0).clamp(0.0, 1.0);
-      if fx3_t <= 0 || fx3_mix <= 0.0005 { if fx3_t <= 0 { self.fx3_reverb = None; self.fx3_crusher = None; } }
-      else if fx3_t == 2 {
-        let time_ms = 10.0 + fx3_p1.clamp(0.0, 1.0) * 990.0; let fb = (fx3_p2.clamp(0.0, 1.0) * 0.95).min(0.95);
-        let mut lbuf = [out]; let mut rbuf = [out]; self.sdelay3.process_block(&mut lbuf, &mut rbuf, self.sr, time_ms, fb, fx3_mix, false); out = 0.5 * (lbuf[0] + rbuf[0]);
-        self.fx3_reverb = None; self.fx3_crusher = None;
-      } else if fx3_t == 1 {
-        if self.fx3_reverb.is_none() {
-          let mut rv = Freeverb::new(self.sr as usize); rv.set_room_size(0.35); rv.set_dampening(0.6); rv.set_wet(1.0); rv.set_dry(0.0); rv.set_width(0.9); self.fx3_reverb = Some(rv);
-        }
-        if let Some(rv) = &mut self.fx3_reverb {
-          let room = 0.2 + fx3_p1.clamp(0.0, 1.0) * 0.8; let damp = 0.2 + fx3_p2.clamp(0.0, 1.0) * 0.8; let mix = fx3_mix;
-          rv.set_room_size(room as f64); rv.set_dampening(damp as f64); rv.set_wet(1.0); rv.set_dry(0.0); rv.set_width(0.9);
-          let dry = out; let (wl, wr) = rv.tick((dry as f64, dry as f64)); let lp_amt = 0.5 + 0.5 * (damp as f32);
-          self.fx3_wet_lp_l.set_hf_damp(lp_amt); self.fx3_wet_lp_r.set_hf_damp(lp_amt);
-          let wet_l = self.fx3_wet_lp_l.tick(wl as f32) as f32; let wet_r = self.fx3_wet_lp_r.tick(wr as f32) as f32; let wet_m = 0.5 * (wet_l + wet_r);
-          out = dry * (1.0 - mix) + wet_m * mix;
-        }
-      } else if fx3_t == 3 || fx3_t == 4 || fx3_t == 5 {
-        let rate = 0.05 + fx3_p1 * (5.0 - 0.05); let depth_ms = match fx3_t { 4 => 6.0 * fx3_p2, 5 => 12.0 * fx3_p2, _ => 4.0 * fx3_p2 };
-        if fx3_t == 3 {
-          let (wet, _) = self.phaser3.process_one(out, out, self.sr, rate, fx3_p2, 1.0); out = out * (1.0 - fx3_mix) + wet * fx3_mix;
-        } else {
-          let base_ms = match fx3_t { 4 => 2.0, 5 => 15.0, _ => 3.0 }; let (wet, _) = self.delay3.process_one(out, out, self.sr, rate, base_ms, depth_ms, 1.0); out = out * (1.0 - fx3_mix) + wet * fx3_mix;
-        }
-        self.fx3_reverb = None; self.fx3_crusher = None;
-      } else if fx3_t == 6 {
-        let dry = out; let drive_db = (fx3_p1.clamp(0.0, 1.0)) * 20.0; let g = (10.0_f32).powf(drive_db / 20.0); let x = (dry * g).tanh();
-        let tone = fx3_p2.clamp(0.0, 1.0); let lp_amt = 0.3 + 0.6 * (1.0 - tone); self.fx3_wet_lp_l.set_hf_damp(lp_amt);
-        let y_lp = self.fx3_wet_lp_l.tick(x); let y_hp = x - y_lp; let shaped = y_lp * (1.0 - tone) + y_hp * tone; out = dry * (1.0 - fx3_mix) + shaped * fx3_mix; self.fx3_reverb = None; self.fx3_crusher = None;
-      } else if fx3_t == 7 {
-        let dry = out; let drive = fx3_p2.clamp(0.0, 1.0) * 10.0; let g = 1.0 + drive; let cur = fx3_p1.clamp(0.0, 1.0);
-        let xin = dry * g; let shaped = if cur < 0.34 { xin.tanh() } else if cur < 0.67 { xin.clamp(-1.0, 1.0) } else { let m = (xin + 1.0).abs().rem_euclid(4.0); ((m - 2.0).abs() - 1.0).clamp(-1.0, 1.0) };
-        out = dry * (1.0 - fx3_mix) + shaped * fx3_mix; self.fx3_reverb = None; self.fx3_crusher = None;
-      } else if fx3_t == 8 {
-        if self.fx3_crusher.is_none() { self.fx3_crusher = Some(Bitcrusher::new()); }
-        if let Some(cr) = &mut self.fx3_crusher {
-          let bits = 4.0 + fx3_p1.clamp(0.0, 1.0) * 12.0; let fac = 1.0 + fx3_p2.clamp(0.0, 1.0) * 15.0; cr.set_bits(bits as u8); cr.set_factor(fac as u32); cr.set_mix(fx3_mix); let mut lbuf = [out]; let mut rbuf = [out]; cr.process(&mut lbuf, &mut rbuf); out = 0.5 * (lbuf[0] + rbuf[0]);
-        }
-      }
-      // FX4 chain
-      let fx4_t = params.get_i32_h(self.paths.fx4_type, 0);
-      let fx4_p1 = params.get_f32_h(self.paths.fx4_p1, 0.0);
-      let fx4_p2 = params.get_f32_h(self.paths.fx4_p2, 0.0);
-      let fx4_mix = params.get_f32_h(self.paths.fx4_p3, 0.0).clamp(0.0, 1.0);
-      if fx4_t <= 0 || fx4_mix <= 0.0005 { if fx4_t <= 0 { self.fx4_reverb = None; self.fx4_crusher = None; } }
-      else if fx4_t == 2 {
-        let time_ms = 10.0 + fx4_p1.clamp(0.0, 1.0) * 990.0; let fb = (fx4_p2.clamp(0.0, 1.0) * 0.95).min(0.95);
-        let mut lbuf = [out]; let mut rbuf = [out]; self.sdelay4.process_block(&mut lbuf, &mut rbuf, self.sr, time_ms, fb, fx4_mix, false); out = 0.5 * (lbuf[0] + rbuf[0]);
-        self.fx4_reverb = None; self.fx4_crusher = None;
-      } else if fx4_t == 1 {
-        if self.fx4_reverb.is_none() {
-          let mut rv = Freeverb::new(self.sr as usize); rv.set_room_size(0.35); rv.set_dampening(0.6); rv.set_wet(1.0); rv.set_dry(0.0); rv.set_width(0.9); self.fx4_reverb = Some(rv);
-        }
-        if let Some(rv) = &mut self.fx4_reverb {
-          let room = 0.2 + fx4_p1.clamp(0.0, 1.0) * 0.8; let damp = 0.2 + fx4_p2.clamp(0.0, 1.0) * 0.8; let mix = fx4_mix;
-          rv.set_room_size(room as f64); rv.set_dampening(damp as f64); rv.set_wet(1.0); rv.set_dry(0.0); rv.set_width(0.9);
-          let dry = out; let (wl, wr) = rv.tick((dry as f64, dry as f64)); let lp_amt = 0.5 + 0.5 * (damp as f32);
-          self.fx4_wet_lp_l.set_hf_damp(lp_amt); self.fx4_wet_lp_r.set_hf_damp(lp_amt);
-          let wet_l = self.fx4_wet_lp_l.tick(wl as f32) as f32; let wet_r = self.fx4_wet_lp_r.tick(wr as f32) as f32; let wet_m = 0.5 * (wet_l + wet_r);
-          out = dry * (1.0 - mix) + wet_m * mix;
-        }
-      } else if fx4_t == 3 || fx4_t == 4 || fx4_t == 5 {
-        let rate = 0.05 + fx4_p1 * (5.0 - 0.05); let depth_ms = match fx4_t { 4 => 6.0 * fx4_p2, 5 => 12.0 * fx4_p2, _ => 4.0 * fx4_p2 };
-        if fx4_t == 3 {
-          let (wet, _) = self.phaser4.process_one(out, out, self.sr, rate, fx4_p2, 1.0); out = out * (1.0 - fx4_mix) + wet * fx4_mix;
-        } else {
-          let base_ms = match fx4_t { 4 => 2.0, 5 => 15.0, _ => 3.0 }; let (wet, _) = self.delay4.process_one(out, out, self.sr, rate, base_ms, depth_ms, 1.0); out = out * (1.0 - fx4_mix) + wet * fx4_mix;
-        }
-        self.fx4_reverb = None; self.fx4_crusher = None;
-      } else if fx4_t == 6 {
-        let dry = out; let drive_db = (fx4_p1.clamp(0.0, 1.0)) * 20.0; let g = (10.0_f32).powf(drive_db / 20.0); let x = (dry * g).tanh();
-        let tone = fx4_p2.clamp(0.0, 1.0); let lp_amt = 0.3 + 0.6 * (1.0 - tone); self.fx4_wet_lp_l.set_hf_damp(lp_amt);
-        let y_lp = self.fx4_wet_lp_l.tick(x); let y_hp = x - y_lp; let shaped = y_lp * (1.0 - tone) + y_hp * tone; out = dry * (1.0 - fx4_mix) + shaped * fx4_mix; self.fx4_reverb = None; self.fx4_crusher = None;
-      } else if fx4_t == 7 {
-        let dry = out; let drive = fx4_p2.clamp(0.0, 1.0) * 10.0; let g = 1.0 + drive; let cur = fx4_p1.clamp(0.0, 1.0);
-        let xin = dry * g; let shaped = if cur < 0.34 { xin.tanh() } else if cur < 0.67 { xin.clamp(-1.0, 1.0) } else { let m = (xin + 1.0).abs().rem_euclid(4.0); ((m - 2.0).abs() - 1.0).clamp(-1.0, 1.0) };
-        out = dry * (1.0 - fx4_mix) + shaped * fx4_mix; self.fx4_reverb = None; self.fx4_crusher = None;
-      } else if fx4_t == 8 {
-        if self.fx4_crusher.is_none() { self.fx4_crusher = Some(Bitcrusher::new()); }
-        if let Some(cr) = &mut self.fx4_crusher {
-          let bits = 4.0 + fx4_p1.clamp(0.0, 1.0) * 12.0; let fac = 1.0 + fx4_p2.clamp(0.0, 1.0) * 15.0; cr.set_bits(bits as u8); cr.set_factor(fac as u32); cr.set_mix(fx4_mix); let mut lbuf = [out]; let mut rbuf = [out]; cr.process(&mut lbuf, &mut rbuf); out = 0.5 * (lbuf[0] + rbuf[0]);
-        }
-      }
-      // EQ
-      let q = 1.0_f32; let mut any_nonzero = false;
-      for i in 0..8 {
-        let db = params.get_f32_h(self.paths.eq_bands[i], 0.0).clamp(-12.0, 12.0);
-        if (db - self.eq_last_db[i]).abs() > 1e-6 { self.eq_bands[i].set_peaking(self.sr, self.eq_centers[i], q, db); self.eq_last_db[i] = db; }
-        if db.abs() > 1e-3 { any_nonzero = true; }
-      }
-      if any_nonzero { for i in 0..8 { out = self.eq_bands[i].process(out); } }
-      // Mixer: PAN, VOLUME, HAAS, COMP
-      let mut l = out; let mut r = out;
-      let pan = params.get_f32_h(self.paths.mix_pan, 0.0).clamp(-1.0, 1.0);
-      let theta = (pan + 1.0) * std::f32::consts::FRAC_PI_4; let gl = theta.cos(); let gr = theta.sin(); l *= gl; r *= gr;
-      let vol = params.get_f32_h(self.paths.mix_volume, 1.0).clamp(0.0, 1.0); l *= vol; r *= vol;
-      let haas = params.get_f32_h(self.paths.mix_haas, 0.0).clamp(0.0, 1.0);
-      if haas > 0.0005 {
-        let rd = if self.haas_wr >= self.haas_d { self.haas_wr - self.haas_d } else { self.haas_wr + self.haas_len - self.haas_d };
-        let delayed_l = self.haas_buf[rd]; self.haas_buf[self.haas_wr] = l; self.haas_wr += 1; if self.haas_wr >= self.haas_len { self.haas_wr = 0; }
-        l = l * (1.0 - haas) + delayed_l * haas;
-      } else { self.haas_buf[self.haas_wr] = l; self.haas_wr += 1; if self.haas_wr >= self.haas_len { self.haas_wr = 0; } }
-      let comp = params.get_f32_h(self.paths.mix_comp, 0.0).clamp(0.0, 1.0);
-      if comp > 0.001 { let drive = 1.0 + 8.0 * comp; let id = 1.0 / drive.tanh(); l = (l * drive).tanh() * id; r = (r * drive).tanh() * id; }
-      return (l, r);
-    } else if module == 2 {
-      // Karplus-Strong mono voice sample
-      let s = self.karplus.render_one(params, &self.karplus_keys);
-      // Early-out if dry is silent and all FX mixes are ~zero (no tails needed)
-      let fx1_t_peek = params.get_i32_h(self.paths.fx1_type, 0);
-      let fx1_mix_peek = params.get_f32_h(self.paths.fx1_p3, 0.0).clamp(0.0, 1.0);
-      let fx2_t_peek = params.get_i32_h(self.paths.fx2_type, 0);
-      let fx2_mix_peek = params.get_f32_h(self.paths.fx2_p3, 0.0).clamp(0.0, 1.0);
-      let fx3_t_peek = params.get_i32_h(self.paths.fx3_type, 0);
-      let fx3_mix_peek = params.get_f32_h(self.paths.fx3_p3, 0.0).clamp(0.0, 1.0);
-      let fx4_t_peek = params.get_i32_h(self.paths.fx4_type, 0);
-      let fx4_mix_peek = params.get_f32_h(self.paths.fx4_p3, 0.0).clamp(0.0, 1.0);
-      if s.abs() < 1e-9 && (fx1_t_peek <= 0 || fx1_mix_peek <= 0.0005) && (fx2_t_peek <= 0 || fx2_mix_peek <= 0.0005) && (fx3_t_peek <= 0 || fx3_mix_peek <= 0.0005) && (fx4_t_peek <= 0 || fx4_mix_peek <= 0.0005) {
-        return (0.0, 0.0);
-      }
-      // FX chain (identical to Analog and Acid)
-      let mut out = s;
-      let fx1_t = params.get_i32_h(self.paths.fx1_type, 0);
-      let fx1_p1 = params.get_f32_h(self.paths.fx1_p1, 0.0);
-      let fx1_p2 = params.get_f32_h(self.paths.fx1_p2, 0.0);
-      let fx1_mix = params.get_f32_h(self.paths.fx1_p3, 0.0).clamp(0.0, 1.0);
-      if fx1_t <= 0 || fx1_mix <= 0.0005 {
-        if fx1_t <= 0 { self.fx1_reverb = None; self.fx1_crusher = None; }
-      } else if fx1_t == 2 {
-        let time_ms = 10.0 + fx1_p1.clamp(0.0, 1.0) * 990.0;
-        let fb = (fx1_p2.clamp(0.0, 1.0) * 0.95).min(0.95);
-        let mut lbuf = [out]; let mut rbuf = [out];
-        self.sdelay1.process_block(&mut lbuf, &mut rbuf, self.sr, time_ms, fb, fx1_mix, false);
-        out = 0.5 * (lbuf[0] + rbuf[0]);
-        self.fx1_reverb = None; self.fx1_crusher = None;
-      } else if fx1_t == 1 {
-        if self.fx1_reverb.is_none() {
-          let mut rv = Freeverb::new(self.sr as usize);
-          rv.set_room_size(0.35); rv.set_dampening(0.6); rv.set_wet(1.0); rv.set_dry(0.0); rv.set_width(0.9);
-          self.fx1_reverb = Some(rv);
-        }
-        if let Some(rv) = &mut self.fx1_reverb {
-          let room = 0.2 + fx1_p1.clamp(0.0, 1.0) * 0.8;
-          let damp = 0.2 + fx1_p2.clamp(0.0, 1.0) * 0.8;
-          let mix = fx1_mix;
-          rv.set_room_size(room as f64); rv.set_dampening(damp as f64); rv.set_wet(1.0); rv.set_dry(0.0); rv.set_width(0.9);
-          let dry = out; let (wl, wr) = rv.tick((dry as f64, dry as f64));
-          let lp_amt = 0.5 + 0.5 * (damp as f32);
-          self.fx1_wet_lp_l.set_hf_damp(lp_amt); self.fx1_wet_lp_r.set_hf_damp(lp_amt);
-          let wet_l = self.fx1_wet_lp_l.tick(wl as f32) as f32; let wet_r = self.fx1_wet_lp_r.tick(wr as f32) as f32;
-          let wet_m = 0.5 * (wet_l + wet_r);
-          out = dry * (1.0 - mix) + wet_m * mix;
-        }
-      } else if fx1_t == 3 || fx1_t == 4 || fx1_t == 5 {
-        let rate = 0.05 + fx1_p1 * (5.0 - 0.05);
-        let depth_ms = match fx1_t { 4 => 6.0 * fx1_p2, 5 => 12.0 * fx1_p2, _ => 4.0 * fx1_p2 };
-        if fx1_t == 3 {
-          let (wet, _) = self.phaser1.process_one(out, out, self.sr, rate, fx1_p2, 1.0);
-          out = out * (1.0 - fx1_mix) + wet * fx1_mix;
-        } else {
-          let base_ms = match fx1_t { 4 => 2.0, 5 => 15.0, _ => 3.0 };
-          let (wet, _) = self.delay1.process_one(out, out, self.sr, rate, base_ms, depth_ms, 1.0);
-          out = out * (1.0 - fx1_mix) + wet * fx1_mix;
-        }
-        self.fx1_reverb = None; self.fx1_crusher = None;
-      } else if fx1_t == 6 {
-        let dry = out; let drive_db = (fx1_p1.clamp(0.0, 1.0)) * 20.0; let g = (10.0_f32).powf(drive_db / 20.0); let x = (dry * g).tanh();
-        let tone = fx1_p2.clamp(0.0, 1.0); let lp_amt = 0.3 + 0.6 * (1.0 - tone); self.fx1_wet_lp_l.set_hf_damp(lp_amt);
-        let y_lp = self.fx1_wet_lp_l.tick(x); let y_hp = x - y_lp; let shaped = y_lp * (1.0 - tone) + y_hp * tone; out = dry * (1.0 - fx1_mix) + shaped * fx1_mix; self.fx1_reverb = None; self.fx1_crusher = None;
-      } else if fx1_t == 7 {
-        let dry = out; let drive = fx1_p2.clamp(0.0, 1.0) * 10.0; let g = 1.0 + drive; let cur = fx1_p1.clamp(0.0, 1.0);
-        let xin = dry * g; let shaped = if cur < 0.34 { xin.tanh() } else if cur < 0.67 { xin.clamp(-1.0, 1.0) } else { let m = (xin + 1.0).abs().rem_euclid(4.0); ((m - 2.0).abs() - 1.0).clamp(-1.0, 1.0) };
-        out = dry * (1.0 - fx1_mix) + shaped * fx1_mix; self.fx1_reverb = None; self.fx1_crusher = None;
-      } else if fx1_t == 8 {
-        if self.fx1_crusher.is_none() { self.fx1_crusher = Some(Bitcrusher::new()); }
-        if let Some(cr) = &mut self.fx1_crusher {
-          let bits = 4.0 + fx1_p1.clamp(0.0, 1.0) * 12.0; let fac = 1.0 + fx1_p2.clamp(0.0, 1.0) * 15.0; cr.set_bits(bits as u8); cr.set_factor(fac as u32); cr.set_mix(fx1_mix); let mut lbuf = [out]; let mut rbuf = [out]; cr.process(&mut lbuf, &mut rbuf); out = 0.5 * (lbuf[0] + rbuf[0]);
-        }
-      }
-      // FX2
-      let fx2_t = params.get_i32_h(self.paths.fx2_type, 0);
-      let fx2_p1 = params.get_f32_h(self.paths.fx2_p1, 0.0);
-      let fx2_p2 = params.get_f32_h(self.paths.fx2_p2, 0.0);
-      let fx2_mix = params.get_f32_h(self.paths.fx2_p3, 0.0).clamp(0.0, 1.0);
-      if fx2_t <= 0 || fx2_mix <= 0.0005 {
-        if fx2_t <= 0 { self.fx2_reverb = None; self.fx2_crusher = None; }
-      } else if fx2_t == 2 {
-        let time_ms = 10.0 + fx2_p1.clamp(0.0, 1.0) * 990.0; let fb = (fx2_p2.clamp(0.0, 1.0) * 0.95).min(0.95); let mut lbuf = [out]; let mut rbuf = [out]; self.sdelay2.process_block(&mut lbuf, &mut rbuf, self.sr, time_ms, fb, fx2_mix, false); out = 0.5 * (lbuf[0] + rbuf[0]); self.fx2_reverb = None; self.fx2_crusher = None;
-      } else if fx2_t == 1 {
-        if self.fx2_reverb.is_none() {
-          let mut rv = Freeverb::new(self.sr as usize); rv.set_room_size(0.35); rv.set_dampening(0.6); rv.set_wet(1.0); rv.set_dry(0.0); rv.set_width(0.9); self.fx2_reverb = Some(rv);
-        }
-        if let Some(rv) = &mut self.fx2_reverb {
-          let room = 0.2 + fx2_p1.clamp(0.0, 1.0) * 0.8; let damp = 0.2 + fx2_p2.clamp(0.0, 1.0) * 0.8; let mix = fx2_mix;
-          rv.set_room_size(room as f64); rv.set_dampening(damp as f64); rv.set_wet(1.0); rv.set_dry(0.0); rv.set_width(0.9);
-          let dry = out; let (wl, wr) = rv.tick((dry as f64, dry as f64)); let lp_amt = 0.5 + 0.5 * (damp as f32);
-          self.fx2_wet_lp_l.set_hf_damp(lp_amt); self.fx2_wet_lp_r.set_hf_damp(lp_amt);
-          let wet_l = self.fx2_wet_lp_l.tick(wl as f32) as f32; let wet_r = self.fx2_wet_lp_r.tick(wr as f32) as f32; let wet_m = 0.5 * (wet_l + wet_r);
-          out = dry * (1.0 - mix) + wet_m * mix;
-        }
-      } else if fx2_t == 3 || fx2_t == 4 || fx2_t == 5 {
-        let rate = 0.05 + fx2_p1 * (5.0 - 0.05); let depth_ms = match fx2_t { 4 => 6.0 * fx2_p2, 5 => 12.0 * fx2_p2, _ => 4.0 * fx2_p2 };
-        if fx2_t == 3 {
-          let (wet, _) = self.phaser2.process_one(out, out, self.sr, rate, fx2_p2, 1.0); out = out * (1.0 - fx2_mix) + wet * fx2_mix;
-        } else {
-          let base_ms = match fx2_t { 4 => 2.0, 5 => 15.0, _ => 3.0 }; let (wet, _) = self.delay2.process_one(out, out, self.sr, rate, base_ms, depth_ms, 1.0); out = out * (1.0 - fx2_mix) + wet * fx2_mix;
-        }
-        self.fx2_reverb = None; self.fx2_crusher = None;
-      } else if fx2_t == 6 {
-        let dry = out; let drive_db = (fx2_p1.clamp(0.0, 1.0)) * 20.0; let g = (10.0_f32).powf(drive_db / 20.0); let x = (dry * g).tanh();
-        let tone = fx2_p2.clamp(0.0, 1.0); let lp_amt = 0.3 + 0.6 * (1.0 - tone); self.fx2_wet_lp_l.set_hf_damp(lp_amt);
-        let y_lp = self.fx2_wet_lp_l.tick(x); let y_hp = x - y_lp; let shaped = y_lp * (1.0 - tone) + y_hp * tone; out = dry * (1.0 - fx2_mix) + shaped * fx2_mix; self.fx2_reverb = None; self.fx2_crusher = None;
-      } else if fx2_t == 7 {
-        let dry = out; let drive = fx2_p2.clamp(0.0, 1.0) * 10.0; let g = 1.0 + drive; let cur = fx2_p1.clamp(0.0, 1.0);
-        let xin = dry * g; let shaped = if cur < 0.34 { xin.tanh() } else if cur < 0.67 { xin.clamp(-1.0, 1.0) } else { let m = (xin + 1.0).abs().rem_euclid(4.0); ((m - 2.0).abs() - 1.0).clamp(-1.0, 1.0) };
-        out = dry * (1.0 - fx2_mix) + shaped * fx2_mix; self.fx2_reverb = None; self.fx2_crusher = None;
-      } else if fx2_t == 8 {
-        if self.fx2_crusher.is_none() { self.fx2_crusher = Some(Bitcrusher::new()); }
-        if let Some(cr) = &mut self.fx2_crusher {
-          let bits = 4.0 + fx2_p1.clamp(0.0, 1.0) * 12.0; let fac = 1.0 + fx2_p2.clamp(0.0, 1.0) * 15.0; cr.set_bits(bits as u8); cr.set_factor(fac as u32); cr.set_mix(fx2_mix); let mut lbuf = [out]; let mut rbuf = [out]; cr.process(&mut lbuf, &mut rbuf); out = 0.5 * (lbuf[0] + rbuf[0]);
-        }
-      }
-      // FX3 chain
-      let fx3_t = params.get_i32_h(self.paths.fx3_type, 0);
-      let fx3_p1 = params.get_f32_h(self.paths.fx3_p1, 0.0);
-      let fx3_p2 = params.get_f32_h(self.paths.fx3_p2, 0.0);
-      let fx3_mix = params.get_f32_h(self.paths.fx3_p3, 0.0).clamp(0.0, 1.0);
-      if fx3_t <= 0 || fx3_mix <= 0.0005 { if fx3_t <= 0 { self.fx3_reverb = None; self.fx3_crusher = None; } }
-      else if fx3_t == 2 {
-        let time_ms = 10.0 + fx3_p1.clamp(0.0, 1.0) * 990.0; let fb = (fx3_p2.clamp(0.0, 1.0) * 0.95).min(0.95);
-        let mut lbuf = [out]; let mut rbuf = [out]; self.sdelay3.process_block(&mut lbuf, &mut rbuf, self.sr, time_ms, fb, fx3_mix, false); out = 0.5 * (lbuf[0] + rbuf[0]);
-        self.fx3_reverb = None; self.fx3_crusher = None;
-      } else if fx3_t == 1 {
-        if self.fx3_reverb.is_none() {
-          let mut rv = Freeverb::new(self.sr as usize); rv.set_room_size(0.35); rv.set_dampening(0.6); rv.set_wet(1.0); rv.set_dry(0.0); rv.set_width(0.9); self.fx3_reverb = Some(rv);
-        }
-        if let Some(rv) = &mut self.fx3_reverb {
-          let room = 0.2 + fx3_p1.clamp(0.0, 1.0) * 0.8; let damp = 0.2 + fx3_p2.clamp(0.0, 1.0) * 0.8; let mix = fx3_mix;
-          rv.set_room_size(room as f64); rv.set_dampening(damp as f64); rv.set_wet(1.0); rv.set_dry(0.0); rv.set_width(0.9);
-          let dry = out; let (wl, wr) = rv.tick((dry as f64, dry as f64)); let lp_amt = 0.5 + 0.5 * (damp as f32);
-          self.fx3_wet_lp_l.set_hf_damp(lp_amt); self.fx3_wet_lp_r.set_hf_damp(lp_amt);
-          let wet_l = self.fx3_wet_lp_l.tick(wl as f32) as f32; let wet_r = self.fx3_wet_lp_r.tick(wr as f32) as f32; let wet_m = 0.5 * (wet_l + wet_r);
-          out = dry * (1.0 - mix) + wet_m * mix;
-        }
-      } else if fx3_t == 3 || fx3_t == 4 || fx3_t == 5 {
-        let rate = 0.05 + fx3_p1 * (5.0 - 0.05); let depth_ms = match fx3_t { 4 => 6.0 * fx3_p2, 5 => 12.0 * fx3_p2, _ => 4.0 * fx3_p2 };
-        if fx3_t == 3 {
-          let (wet, _) = self.phaser3.process_one(out, out, self.sr, rate, fx3_p2, 1.0); out = out * (1.0 - fx3_mix) + wet * fx3_mix;
-        } else {
-          let base_ms = match fx3_t { 4 => 2.0, 5 => 15.0, _ => 3.0 }; let (wet, _) = self.delay3.process_one(out, out, self.sr, rate, base_ms, depth_ms, 1.0); out = out * (1.0 - fx3_mix) + wet * fx3_mix;
-        }
-        self.fx3_reverb = None; self.fx3_crusher = None;
-      } else if fx3_t == 6 {
-        let dry = out; let drive_db = (fx3_p1.clamp(0.0, 1.0)) * 20.0; let g = (10.0_f32).powf(drive_db / 20.0); let x = (dry * g).tanh();
-        let tone = fx3_p2.clamp(0.0, 1.0); let lp_amt = 0.3 + 0.6 * (1.0 - tone); self.fx3_wet_lp_l.set_hf_damp(lp_amt);
-        let y_lp = self.fx3_wet_lp_l.tick(x); let y_hp = x - y_lp; let shaped = y_lp * (1.0 - tone) + y_hp * tone; out = dry * (1.0 - fx3_mix) + shaped * fx3_mix; self.fx3_reverb = None; self.fx3_crusher = None;
-      } else if fx3_t == 7 {
-        let dry = out; let drive = fx3_p2.clamp(0.0, 1.0) * 10.0; let g = 1.0 + drive; let cur = fx3_p1.clamp(0.0, 1.0);
-        let xin = dry * g; let shaped = if cur < 0.34 { xin.tanh() } else if cur < 0.67 { xin.clamp(-1.0, 1.0) } else { let m = (xin + 1.0).abs().rem_euclid(4.0); ((m - 2.0).abs() - 1.0).clamp(-1.0, 1.0) };
-        out = dry * (1.0 - fx3_mix) + shaped * fx3_mix; self.fx3_reverb = None; self.fx3_crusher = None;
-      } else if fx3_t == 8 {
-        if self.fx3_crusher.is_none() { self.fx3_crusher = Some(Bitcrusher::new()); }
-        if let Some(cr) = &mut self.fx3_crusher {
-          let bits = 4.0 + fx3_p1.clamp(0.0, 1.0) * 12.0; let fac = 1.0 + fx3_p2.clamp(0.0, 1.0) * 15.0; cr.set_bits(bits as u8); cr.set_factor(fac as u32); cr.set_mix(fx3_mix); let mut lbuf = [out]; let mut rbuf = [out]; cr.process(&mut lbuf, &mut rbuf); out = 0.5 * (lbuf[0] + rbuf[0]);
-        }
-      }
-      // FX4 chain
-      let fx4_t = params.get_i32_h(self.paths.fx4_type, 0);
-      let fx4_p1 = params.get_f32_h(self.paths.fx4_p1, 0.0);
-      let fx4_p2 = params.get_f32_h(self.paths.fx4_p2, 0.0);
-      let fx4_mix = params.get_f32_h(self.paths.fx4_p3, 0.0).clamp(0.0, 1.0);
-      if fx4_t <= 0 || fx4_mix <= 0.0005 { if fx4_t <= 0 { self.fx4_reverb = None; self.fx4_crusher = None; } }
-      else if fx4_t == 2 {
-        let time_ms = 10.0 + fx4_p1.clamp(0.0, 1.0) * 990.0; let fb = (fx4_p2.clamp(0.0, 1.0) * 0.95).min(0.95);
-        let mut lbuf = [out]; let mut rbuf = [out]; self.sdelay4.process_block(&mut lbuf, &mut rbuf, self.sr, time_ms, fb, fx4_mix, false); out = 0.5 * (lbuf[0] + rbuf[0]);
-        self.fx4_reverb = None; self.fx4_crusher = None;
-      } else if fx4_t == 1 {
-        if self.fx4_reverb.is_none() {
-          let mut rv = Freeverb::new(self.sr as usize); rv.set_room_size(0.35); rv.set_dampening(0.6); rv.set_wet(1.0); rv.set_dry(0.0); rv.set_width(0.9); self.fx4_reverb = Some(rv);
-        }
-        if let Some(rv) = &mut self.fx4_reverb {
-          let room = 0.2 + fx4_p1.clamp(0.0, 1.0) * 0.8; let damp = 0.2 + fx4_p2.clamp(0.0, 1.0) * 0.8; let mix = fx4_mix;
-          rv.set_room_size(room as f64); rv.set_dampening(damp as f64); rv.set_wet(1.0); rv.set_dry(0.0); rv.set_width(0.9);
-          let dry = out; let (wl, wr) = rv.tick((dry as f64, dry as f64)); let lp_amt = 0.5 + 0.5 * (damp as f32);
-          self.fx4_wet_lp_l.set_hf_damp(lp_amt); self.fx4_wet_lp_r.set_hf_damp(lp_amt);
-          let wet_l = self.fx4_wet_lp_l.tick(wl as f32) as f32; let wet_r = self.fx4_wet_lp_r.tick(wr as f32) as f32; let wet_m = 0.5 * (wet_l + wet_r);
-          out = dry * (1.0 - mix) + wet_m * mix;
-        }
-      } else if fx4_t == 3 || fx4_t == 4 || fx4_t == 5 {
-        let rate = 0.05 + fx4_p1 * (5.0 - 0.05); let depth_ms = match fx4_t { 4 => 6.0 * fx4_p2, 5 => 12.0 * fx4_p2, _ => 4.0 * fx4_p2 };
-        if fx4_t == 3 {
-          let (wet, _) = self.phaser4.process_one(out, out, self.sr, rate, fx4_p2, 1.0); out = out * (1.0 - fx4_mix) + wet * fx4_mix;
-        } else {
-          let base_ms = match fx4_t { 4 => 2.0, 5 => 15.0, _ => 3.0 }; let (wet, _) = self.delay4.process_one(out, out, self.sr, rate, base_ms, depth_ms, 1.0); out = out * (1.0 - fx4_mix) + wet * fx4_mix;
-        }
-        self.fx4_reverb = None; self.fx4_crusher = None;
-      } else if fx4_t == 6 {
-        let dry = out; let drive_db = (fx4_p1.clamp(0.0, 1.0)) * 20.0; let g = (10.0_f32).powf(drive_db / 20.0); let x = (dry * g).tanh();
-        let tone = fx4_p2.clamp(0.0, 1.0); let lp_amt = 0.3 + 0.6 * (1.0 - tone); self.fx4_wet_lp_l.set_hf_damp(lp_amt);
-        let y_lp = self.fx4_wet_lp_l.tick(x); let y_hp = x - y_lp; let shaped = y_lp * (1.0 - tone) + y_hp * tone; out = dry * (1.0 - fx4_mix) + shaped * fx4_mix; self.fx4_reverb = None; self.fx4_crusher = None;
-      } else if fx4_t == 7 {
-        let dry = out; let drive = fx4_p2.clamp(0.0, 1.0) * 10.0; let g = 1.0 + drive; let cur = fx4_p1.clamp(0.0, 1.0);
-        let xin = dry * g; let shaped = if cur < 0.34 { xin.tanh() } else if cur < 0.67 { xin.clamp(-1.0, 1.0) } else { let m = (xin + 1.0).abs().rem_euclid(4.0); ((m - 2.0).abs() - 1.0).clamp(-1.0, 1.0) };
-        out = dry * (1.0 - fx4_mix) + shaped * fx4_mix; self.fx4_reverb = None; self.fx4_crusher = None;
-      } else if fx4_t == 8 {
-        if self.fx4_crusher.is_none() { self.fx4_crusher = Some(Bitcrusher::new()); }
-        if let Some(cr) = &mut self.fx4_crusher {
-          let bits = 4.0 + fx4_p1.clamp(0.0, 1.0) * 12.0; let fac = 1.0 + fx4_p2.clamp(0.0, 1.0) * 15.0; cr.set_bits(bits as u8); cr.set_factor(fac as u32); cr.set_mix(fx4_mix); let mut lbuf = [out]; let mut rbuf = [out]; cr.process(&mut lbuf, &mut rbuf); out = 0.5 * (lbuf[0] + rbuf[0]);
-        }
-      }
-      // EQ
-      let q = 1.0_f32; let mut any_nonzero = false;
-      for i in 0..8 {
-        let db = params.get_f32_h(self.paths.eq_bands[i], 0.0).clamp(-12.0, 12.0);
-        if (db - self.eq_last_db[i]).abs() > 1e-6 { self.eq_bands[i].set_peaking(self.sr, self.eq_centers[i], q, db); self.eq_last_db[i] = db; }
-        if db.abs() > 1e-3 { any_nonzero = true; }
-      }
-      if any_nonzero { for i in 0..8 { out = self.eq_bands[i].process(out); } }
-      // Mixer: PAN, VOLUME, HAAS, COMP
-      let mut l = out; let mut r = out;
-      let pan = params.get_f32_h(self.paths.mix_pan, 0.0).clamp(-1.0, 1.0);
-      let theta = (pan + 1.0) * std::f32::consts::FRAC_PI_4; let gl = theta.cos(); let gr = theta.sin(); l *= gl; r *= gr;
-      let vol = params.get_f32_h(self.paths.mix_volume, 1.0).clamp(0.0, 1.0); l *= vol; r *= vol;
-      let haas = params.get_f32_h(self.paths.mix_haas, 0.0).clamp(0.0, 1.0);
-      if haas > 0.0005 {
-        let rd = if self.haas_wr >= self.haas_d { self.haas_wr - self.haas_d } else { self.haas_wr + self.haas_len - self.haas_d };
-        let delayed_l = self.haas_buf[rd]; self.haas_buf[self.haas_wr] = l; self.haas_wr += 1; if self.haas_wr >= self.haas_len { self.haas_wr = 0; }
-        l = l * (1.0 - haas) + delayed_l * haas;
-      } else { self.haas_buf[self.haas_wr] = l; self.haas_wr += 1; if self.haas_wr >= self.haas_len { self.haas_wr = 0; } }
-      let comp = params.get_f32_h(self.paths.mix_comp, 0.0).clamp(0.0, 1.0);
-      if comp > 0.001 { let drive = 1.0 + 8.0 * comp; let id = 1.0 / drive.tanh(); l = (l * drive).tanh() * id; r = (r * drive).tanh() * id; }
-      return (l, r);
-    } else if module == 3 {
-      // Resonator Bank mono voice sample
-      let s = self.resonator.render_one(params, &self.resonator_keys);
-      // Early-out if dry is silent and all FX mixes are ~zero (no tails needed)
-      let fx1_t_peek = params.get_i32_h(self.paths.fx1_type, 0);
-      let fx1_mix_peek = params.get_f32_h(self.paths.fx1_p3, 0.0).clamp(0.0, 1.0);
-      let fx2_t_peek = params.get_i32_h(self.paths.fx2_type, 0);
-      let fx2_mix_peek = params.get_f32_h(self.paths.fx2_p3, 0.0).clamp(0.0, 1.0);
-      let fx3_t_peek = params.get_i32_h(self.paths.fx3_type, 0);
-      let fx3_mix_peek = params.get_f32_h(self.paths.fx3_p3, 0.0).clamp(0.0, 1.0);
-      let fx4_t_peek = params.get_i32_h(self.paths.fx4_type, 0);
-      let fx4_mix_peek = params.get_f32_h(self.paths.fx4_p3, 0.0).clamp(0.0, 1.0);
-      if s.abs() < 1e-9 && (fx1_t_peek <= 0 || fx1_mix_peek <= 0.0005) && (fx2_t_peek <= 0 || fx2_mix_peek <= 0.0005) && (fx3_t_peek <= 0 || fx3_mix_peek <= 0.0005) && (fx4_t_peek <= 0 || fx4_mix_peek <= 0.0005) {
-        return (0.0, 0.0);
-      }
-      // FX chain (identical to other modules)
-      let mut out = s;
-      let fx1_t = params.get_i32_h(self.paths.fx1_type, 0);
-      let fx1_p1 = params.get_f32_h(self.paths.fx1_p1, 0.0);
-      let fx1_p2 = params.get_f32_h(self.paths.fx1_p2, 0.0);
-      let fx1_mix = params.get_f32_h(self.paths.fx1_p3, 0.0).clamp(0.0, 1.0);
-      if fx1_t <= 0 || fx1_mix <= 0.0005 { if fx1_t <= 0 { self.fx1_reverb = None; self.fx1_crusher = None; } }
-      else if fx1_t == 2 {
-        let time_ms = fx1_p1.clamp(0.0, 1.0) * 50.0 + 1.0; let fb = (fx1_p2.clamp(0.0, 1.0) * 0.95).min(0.95);
-        let mut lbuf = [out]; let mut rbuf = [out];
-        self.sdelay1.process_block(&mut lbuf, &mut rbuf, self.sr, time_ms, fb, fx1_mix, false);
-        out = 0.5 * (lbuf[0] + rbuf[0]); self.fx1_reverb = None; self.fx1_crusher = None;
-      } else if fx1_t == 1 {
-        if self.fx1_reverb.is_none() {
-          let mut rv = Freeverb::new(self.sr as usize);
-          rv.set_room_size(0.35); rv.set_dampening(0.6); rv.set_wet(1.0); rv.set_dry(0.0); rv.set_width(0.9);
-          self.fx1_reverb = Some(rv);
-        }
-        if let Some(rv) = &mut self.fx1_reverb {
-          let room = 0.2 + fx1_p1.clamp(0.0, 1.0) * 0.8; let damp = 0.2 + fx1_p2.clamp(0.0, 1.0) * 0.8; let mix = fx1_mix;
-          rv.set_room_size(room as f64); rv.set_dampening(damp as f64); rv.set_wet(1.0); rv.set_dry(0.0); rv.set_width(0.9);
-          let dry = out; let (wl, wr) = rv.tick((dry as f64, dry as f64));
-          let lp_amt = 0.5 + 0.5 * (damp as f32); self.fx1_wet_lp_l.set_hf_damp(lp_amt); self.fx1_wet_lp_r.set_hf_damp(lp_amt);
-          let wet_l = self.fx1_wet_lp_l.tick(wl as f32) as f32; let wet_r = self.fx1_wet_lp_r.tick(wr as f32) as f32;
-          let wet_m = 0.5 * (wet_l + wet_r); out = dry * (1.0 - mix) + wet_m * mix;
-        } self.fx1_crusher = None;
-      } else if fx1_t >= 3 && fx1_t <= 5 {
-        let rate = (fx1_p1.clamp(0.0, 1.0) * 10.0 + 0.1).min(20.0); let depth_ms = fx1_p2.clamp(0.0, 1.0) * 5.0;
-        let base_ms = match fx1_t { 4 => 2.0, 5 => 15.0, _ => 3.0 };
-        let (wet, _) = self.delay1.process_one(out, out, self.sr, rate, base_ms, depth_ms, 1.0);
-        out = out * (1.0 - fx1_mix) + wet * fx1_mix; self.fx1_reverb = None; self.fx1_crusher = None;
-      } else if fx1_t == 6 {
-        let dry = out; let drive_db = (fx1_p1.clamp(0.0, 1.0)) * 20.0; let g = (10.0_f32).powf(drive_db / 20.0);
-        let x = (dry * g).tanh(); let tone = fx1_p2.clamp(0.0, 1.0);
-        let lp_amt = 0.3 + 0.6 * (1.0 - tone); self.fx1_wet_lp_l.set_hf_damp(lp_amt);
-        let y_lp = self.fx1_wet_lp_l.tick(x); let y_hp = x - y_lp; let shaped = y_lp * (1.0 - tone) + y_hp * tone;
-        out = dry * (1.0 - fx1_mix) + shaped * fx1_mix; self.fx1_reverb = None;
-      } else if fx1_t == 7 {
-        let dry = out; let drive = fx1_p2.clamp(0.0, 1.0) * 10.0; let g = 1.0 + drive; let cur = fx1_p1.clamp(0.0, 1.0);
-        let xin = dry * g;
-        let shaped = if cur < 0.34 { xin.tanh() } else if cur < 0.67 { xin.clamp(-1.0, 1.0) } else { let m = (xin + 1.0).abs().rem_euclid(4.0); ((m - 2.0).abs() - 1.0).clamp(-1.0, 1.0) };
-        out = dry * (1.0 - fx1_mix) + shaped * fx1_mix; self.fx1_reverb = None;
-      } else if fx1_t == 8 {
-        if self.fx1_crusher.is_none() { self.fx1_crusher = Some(Bitcrusher::new()); }
-        if let Some(cr) = &mut self.fx1_crusher {
-          let bits = 4.0 + fx1_p1.clamp(0.0, 1.0) * 12.0; let fac = 1.0 + fx1_p2.clamp(0.0, 1.0) * 15.0;
-          cr.set_bits(bits as u8); cr.set_factor(fac as u32); cr.set_mix(fx1_mix);
-          let mut lbuf = [out]; let mut rbuf = [out]; cr.process(&mut lbuf, &mut rbuf); out = 0.5 * (lbuf[0] + rbuf[0]);
-        }
-      }
-      // FX2 chain
-      let fx2_t = params.get_i32_h(self.paths.fx2_type, 0);
-      let fx2_p1 = params.get_f32_h(self.paths.fx2_p1, 0.0);
-      let fx2_p2 = params.get_f32_h(self.paths.fx2_p2, 0.0);
-      let fx2_mix = params.get_f32_h(self.paths.fx2_p3, 0.0).clamp(0.0, 1.0);
-      if fx2_t <= 0 || fx2_mix <= 0.0005 { if fx2_t <= 0 { self.fx2_reverb = None; self.fx2_crusher = None; } }
-      else if fx2_t == 2 {
-        let time_ms = fx2_p1.clamp(0.0, 1.0) * 50.0 + 1.0; let fb = (fx2_p2.clamp(0.0, 1.0) * 0.95).min(0.95);
-        let mut lbuf = [out]; let mut rbuf = [out];
-        self.sdelay2.process_block(&mut lbuf, &mut rbuf, self.sr, time_ms, fb, fx2_mix, false);
-        out = 0.5 * (lbuf[0] + rbuf[0]); self.fx2_reverb = None; self.fx2_crusher = None;
-      } else if fx2_t == 1 {
-        if self.fx2_reverb.is_none() {
-          let mut rv = Freeverb::new(self.sr as usize);
-          rv.set_room_size(0.35); rv.set_dampening(0.6); rv.set_wet(1.0); rv.set_dry(0.0); rv.set_width(0.9);
-          self.fx2_reverb = Some(rv);
-        }
-        if let Some(rv) = &mut self.fx2_reverb {
-          let room = 0.2 + fx2_p1.clamp(0.0, 1.0) * 0.8; let damp = 0.2 + fx2_p2.clamp(0.0, 1.0) * 0.8; let mix = fx2_mix;
-          rv.set_room_size(room as f64); rv.set_dampening(damp as f64); rv.set_wet(1.0); rv.set_dry(0.0); rv.set_width(0.9);
-          let dry = out; let (wl, wr) = rv.tick((dry as f64, dry as f64));
-          let lp_amt = 0.5 + 0.5 * (damp as f32); self.fx2_wet_lp_l.set_hf_damp(lp_amt); self.fx2_wet_lp_r.set_hf_damp(lp_amt);
-          let wet_l = self.fx2_wet_lp_l.tick(wl as f32) as f32; let wet_r = self.fx2_wet_lp_r.tick(wr as f32) as f32;
-          let wet_m = 0.5 * (wet_l + wet_r); out = dry * (1.0 - mix) + wet_m * mix;
-        } self.fx2_crusher = None;
-      } else if fx2_t >= 3 && fx2_t <= 5 {
-        let rate = (fx2_p1.clamp(0.0, 1.0) * 10.0 + 0.1).min(20.0); let depth_ms = fx2_p2.clamp(0.0, 1.0) * 5.0;
-        let base_ms = match fx2_t { 4 => 2.0, 5 => 15.0, _ => 3.0 };
-        let (wet, _) = self.delay2.process_one(out, out, self.sr, rate, base_ms, depth_ms, 1.0);
-        out = out * (1.0 - fx2_mix) + wet * fx2_mix; self.fx2_reverb = None; self.fx2_crusher = None;
-      } else if fx2_t == 6 {
-        let dry = out; let drive_db = (fx2_p1.clamp(0.0, 1.0)) * 20.0; let g = (10.0_f32).powf(drive_db / 20.0);
-        let x = (dry * g).tanh(); let tone = fx2_p2.clamp(0.0, 1.0);
-        let lp_amt = 0.3 + 0.6 * (1.0 - tone); self.fx2_wet_lp_l.set_hf_damp(lp_amt);
-        let y_lp = self.fx2_wet_lp_l.tick(x); let y_hp = x - y_lp; let shaped = y_lp * (1.0 - tone) + y_hp * tone;
-        out = dry * (1.0 - fx2_mix) + shaped * fx2_mix; self.fx2_reverb = None;
-      } else if fx2_t == 7 {
-        let dry = out; let drive = fx2_p2.clamp(0.0, 1.0) * 10.0; let g = 1.0 + drive; let cur = fx2_p1.clamp(0.0, 1.0);
-        let xin = dry * g;
-        let shaped = if cur < 0.34 { xin.tanh() } else if cur < 0.67 { xin.clamp(-1.0, 1.0) } else { let m = (xin + 1.0).abs().rem_euclid(4.0); ((m - 2.0).abs() - 1.0).clamp(-1.0, 1.0) };
-        out = dry * (1.0 - fx2_mix) + shaped * fx2_mix; self.fx2_reverb = None;
-      } else if fx2_t == 8 {
-        if self.fx2_crusher.is_none() { self.fx2_crusher = Some(Bitcrusher::new()); }
-        if let Some(cr) = &mut self.fx2_crusher {
-          let bits = 4.0 + fx2_p1.clamp(0.0, 1.0) * 12.0; let fac = 1.0 + fx2_p2.clamp(0.0, 1.0) * 15.0;
-          cr.set_bits(bits as u8); cr.set_factor(fac as u32); cr.set_mix(fx2_mix);
-          let mut lbuf = [out]; let mut rbuf = [out]; cr.process(&mut lbuf, &mut rbuf); out = 0.5 * (lbuf[0] + rbuf[0]);
-        }
-      }
-      // FX3 chain
-      let fx3_t = params.get_i32_h(self.paths.fx3_type, 0);
-      let fx3_p1 = params.get_f32_h(self.paths.fx3_p1, 0.0);
-      let fx3_p2 = params.get_f32_h(self.paths.fx3_p2, 0.0);
-      let fx3_mix = params.get_f32_h(self.paths.fx3_p3, 0.0).clamp(0.0, 1.0);
-      if fx3_t <= 0 || fx3_mix <= 0.0005 { if fx3_t <= 0 { self.fx3_reverb = None; self.fx3_crusher = None; } }
-      else if fx3_t == 2 {
-        let time_ms = fx3_p1.clamp(0.0, 1.0) * 50.0 + 1.0; let fb = (fx3_p2.clamp(0.0, 1.0) * 0.95).min(0.95);
-        let mut lbuf = [out]; let mut rbuf = [out];
-        self.sdelay3.process_block(&mut lbuf, &mut rbuf, self.sr, time_ms, fb, fx3_mix, false);
-        out = 0.5 * (lbuf[0] + rbuf[0]); self.fx3_reverb = None; self.fx3_crusher = None;
-      } else if fx3_t == 1 {
-        if self.fx3_reverb.is_none() {
-          let mut rv = Freeverb::new(self.sr as usize);
-          rv.set_room_size(0.35); rv.set_dampening(0.6); rv.set_wet(1.0); rv.set_dry(0.0); rv.set_width(0.9);
-          self.fx3_reverb = Some(rv);
-        }
-        if let Some(rv) = &mut self.fx3_reverb {
-          let room = 0.2 + fx3_p1.clamp(0.0, 1.0) * 0.8; let damp = 0.2 + fx3_p2.clamp(0.0, 1.0) * 0.8; let mix = fx3_mix;
-          rv.set_room_size(room as f64); rv.set_dampening(damp as f64); rv.set_wet(1.0); rv.set_dry(0.0); rv.set_width(0.9);
-          let dry = out; let (wl, wr) = rv.tick((dry as f64, dry as f64));
-          let lp_amt = 0.5 + 0.5 * (damp as f32); self.fx3_wet_lp_l.set_hf_damp(lp_amt); self.fx3_wet_lp_r.set_hf_damp(lp_amt);
-          let wet_l = self.fx3_wet_lp_l.tick(wl as f32) as f32; let wet_r = self.fx3_wet_lp_r.tick(wr as f32) as f32;
-          let wet_m = 0.5 * (wet_l + wet_r); out = dry * (1.0 - mix) + wet_m * mix;
-        } self.fx3_crusher = None;
-      } else if fx3_t >= 3 && fx3_t <= 5 {
-        let rate = (fx3_p1.clamp(0.0, 1.0) * 10.0 + 0.1).min(20.0); let depth_ms = fx3_p2.clamp(0.0, 1.0) * 5.0;
-        let base_ms = match fx3_t { 4 => 2.0, 5 => 15.0, _ => 3.0 };
-        let (wet, _) = self.delay3.process_one(out, out, self.sr, rate, base_ms, depth_ms, 1.0);
-        out = out * (1.0 - fx3_mix) + wet * fx3_mix; self.fx3_reverb = None; self.fx3_crusher = None;
-      } else if fx3_t == 6 {
-        let dry = out; let drive_db = (fx3_p1.clamp(0.0, 1.0)) * 20.0; let g = (10.0_f32).powf(drive_db / 20.0);
-        let x = (dry * g).tanh(); let tone = fx3_p2.clamp(0.0, 1.0);
-        let lp_amt = 0.3 + 0.6 * (1.0 - tone); self.fx3_wet_lp_l.set_hf_damp(lp_amt);
-        let y_lp = self.fx3_wet_lp_l.tick(x); let y_hp = x - y_lp; let shaped = y_lp * (1.0 - tone) + y_hp * tone;
-        out = dry * (1.0 - fx3_mix) + shaped * fx3_mix; self.fx3_reverb = None;
-      } else if fx3_t == 7 {
-        let dry = out; let drive = fx3_p2.clamp(0.0, 1.0) * 10.0; let g = 1.0 + drive; let cur = fx3_p1.clamp(0.0, 1.0);
-        let xin = dry * g;
-        let shaped = if cur < 0.34 { xin.tanh() } else if cur < 0.67 { xin.clamp(-1.0, 1.0) } else { let m = (xin + 1.0).abs().rem_euclid(4.0); ((m - 2.0).abs() - 1.0).clamp(-1.0, 1.0) };
-        out = dry * (1.0 - fx3_mix) + shaped * fx3_mix; self.fx3_reverb = None;
-      } else if fx3_t == 8 {
-        if self.fx3_crusher.is_none() { self.fx3_crusher = Some(Bitcrusher::new()); }
-        if let Some(cr) = &mut self.fx3_crusher {
-          let bits = 4.0 + fx3_p1.clamp(0.0, 1.0) * 12.0; let fac = 1.0 + fx3_p2.clamp(0.0, 1.0) * 15.0;
-          cr.set_bits(bits as u8); cr.set_factor(fac as u32); cr.set_mix(fx3_mix);
-          let mut lbuf = [out]; let mut rbuf = [out]; cr.process(&mut lbuf, &mut rbuf); out = 0.5 * (lbuf[0] + rbuf[0]);
-        }
-      }
-      // FX4 chain
-      let fx4_t = params.get_i32_h(self.paths.fx4_type, 0);
-      let fx4_p1 = params.get_f32_h(self.paths.fx4_p1, 0.0);
-      let fx4_p2 = params.get_f32_h(self.paths.fx4_p2, 0.0);
-      let fx4_mix = params.get_f32_h(self.paths.fx4_p3, 0.0).clamp(0.0, 1.0);
-      if fx4_t <= 0 || fx4_mix <= 0.0005 { if fx4_t <= 0 { self.fx4_reverb = None; self.fx4_crusher = None; } }
-      else if fx4_t == 2 {
-        let time_ms = fx4_p1.clamp(0.0, 1.0) * 50.0 + 1.0; let fb = (fx4_p2.clamp(0.0, 1.0) * 0.95).min(0.95);
-        let mut lbuf = [out]; let mut rbuf = [out];
-        self.sdelay4.process_block(&mut lbuf, &mut rbuf, self.sr, time_ms, fb, fx4_mix, false);
-        out = 0.5 * (lbuf[0] + rbuf[0]); self.fx4_reverb = None; self.fx4_crusher = None;
-      } else if fx4_t == 1 {
-        if self.fx4_reverb.is_none() {
-          let mut rv = Freeverb::new(self.sr as usize);
-          rv.set_room_size(0.35); rv.set_dampening(0.6); rv.set_wet(1.0); rv.set_dry(0.0); rv.set_width(0.9);
-          self.fx4_reverb = Some(rv);
-        }
-        if let Some(rv) = &mut self.fx4_reverb {
-          let room = 0.2 + fx4_p1.clamp(0.0, 1.0) * 0.8; let damp = 0.2 + fx4_p2.clamp(0.0, 1.0) * 0.8; let mix = fx4_mix;
-          rv.set_room_size(room as f64); rv.set_dampening(damp as f64); rv.set_wet(1.0); rv.set_dry(0.0); rv.set_width(0.9);
-          let dry = out; let (wl, wr) = rv.tick((dry as f64, dry as f64));
-          let lp_amt = 0.5 + 0.5 * (damp as f32); self.fx4_wet_lp_l.set_hf_damp(lp_amt); self.fx4_wet_lp_r.set_hf_damp(lp_amt);
-          let wet_l = self.fx4_wet_lp_l.tick(wl as f32) as f32; let wet_r = self.fx4_wet_lp_r.tick(wr as f32) as f32;
-          let wet_m = 0.5 * (wet_l + wet_r); out = dry * (1.0 - mix) + wet_m * mix;
-        } self.fx4_crusher = None;
-      } else if fx4_t >= 3 && fx4_t <= 5 {
-        let rate = (fx4_p1.clamp(0.0, 1.0) * 10.0 + 0.1).min(20.0); let depth_ms = fx4_p2.clamp(0.0, 1.0) * 5.0;
-        let base_ms = match fx4_t { 4 => 2.0, 5 => 15.0, _ => 3.0 };
-        let (wet, _) = self.delay4.process_one(out, out, self.sr, rate, base_ms, depth_ms, 1.0);
-        out = out * (1.0 - fx4_mix) + wet * fx4_mix; self.fx4_reverb = None; self.fx4_crusher = None;
-      } else if fx4_t == 6 {
-        let dry = out; let drive_db = (fx4_p1.clamp(0.0, 1.0)) * 20.0; let g = (10.0_f32).powf(drive_db / 20.0);
-        let x = (dry * g).tanh(); let tone = fx4_p2.clamp(0.0, 1.0);
-        let lp_amt = 0.3 + 0.6 * (1.0 - tone); self.fx4_wet_lp_l.set_hf_damp(lp_amt);
-        let y_lp = self.fx4_wet_lp_l.tick(x); let y_hp = x - y_lp; let shaped = y_lp * (1.0 - tone) + y_hp * tone;
-        out = dry * (1.0 - fx4_mix) + shaped * fx4_mix; self.fx4_reverb = None;
-      } else if fx4_t == 7 {
-        let dry = out; let drive = fx4_p2.clamp(0.0, 1.0) * 10.0; let g = 1.0 + drive; let cur = fx4_p1.clamp(0.0, 1.0);
-        let xin = dry * g;
-        let shaped = if cur < 0.34 { xin.tanh() } else if cur < 0.67 { xin.clamp(-1.0, 1.0) } else { let m = (xin + 1.0).abs().rem_euclid(4.0); ((m - 2.0).abs() - 1.0).clamp(-1.0, 1.0) };
-        out = dry * (1.0 - fx4_mix) + shaped * fx4_mix; self.fx4_reverb = None;
-      } else if fx4_t == 8 {
-        if self.fx4_crusher.is_none() { self.fx4_crusher = Some(Bitcrusher::new()); }
-        if let Some(cr) = &mut self.fx4_crusher {
-          let bits = 4.0 + fx4_p1.clamp(0.0, 1.0) * 12.0; let fac = 1.0 + fx4_p2.clamp(0.0, 1.0) * 15.0;
-          cr.set_bits(bits as u8); cr.set_factor(fac as u32); cr.set_mix(fx4_mix);
-          let mut lbuf = [out]; let mut rbuf = [out]; cr.process(&mut lbuf, &mut rbuf); out = 0.5 * (lbuf[0] + rbuf[0]);
-        }
-      }
-      // Post-mix processing
-      let mut l = out; let mut r = out;
-      let pan = params.get_f32_h(self.paths.mix_pan, 0.0);
-      if pan.abs() > 0.001 {
-        let p = pan.clamp(-1.0, 1.0); let gl = ((1.0 - p) * 0.5).sqrt(); let gr = ((1.0 + p) * 0.5).sqrt();
-        l *= gl; r *= gr;
-      }
-      let width = params.get_f32_h(self.paths.mix_width, 0.0).clamp(0.0, 1.0);
-      if width > 0.001 {
-        let mid = 0.5 * (l + r); let side = 0.5 * (l - r);
-        let (low_s, _, _, _) = self.eq_lp.process(side); let (_, high_s, _, _) = self.eq_hp.process(side);
-        let enh_s = low_s * (1.0 - width * 0.3) + high_s * (1.0 + width * 0.7);
-        l = mid + enh_s; r = mid - enh_s;
-      }
-      let haas = params.get_f32_h(self.paths.mix_haas, 0.0).clamp(0.0, 1.0);
-      if haas > 0.001 && self.haas_d > 0 {
-        let rd = (self.haas_wr + self.haas_len - self.haas_d) % self.haas_len;
-        let delayed_l = self.haas_buf[rd]; self.haas_buf[self.haas_wr] = l; self.haas_wr += 1; if self.haas_wr >= self.haas_len { self.haas_wr = 0; }
-        l = l * (1.0 - haas) + delayed_l * haas;
-      } else { self.haas_buf[self.haas_wr] = l; self.haas_wr += 1; if self.haas_wr >= self.haas_len { self.haas_wr = 0; } }
-      let comp = params.get_f32_h(self.paths.mix_comp, 0.0).clamp(0.0, 1.0);
-      if comp > 0.001 { let drive = 1.0 + 8.0 * comp; let id = 1.0 / drive.tanh(); l = (l * drive).tanh() * id; r = (r * drive).tanh() * id; }
-      return (l, r);
-  } else if module == 4 {
-      // Sampler mono voice sample
-      let s = self.sampler.render_one(params, &self.sampler_keys, beat_phase);
-      // Early-out if dry is silent and all FX mixes are ~zero (no tails needed)
-      let fx1_t_peek = params.get_i32_h(self.paths.fx1_type, 0);
-      let fx1_mix_peek = params.get_f32_h(self.paths.fx1_p3, 0.0).clamp(0.0, 1.0);
-      let fx2_t_peek = params.get_i32_h(self.paths.fx2_type, 0);
-      let fx2_mix_peek = params.get_f32_h(self.paths.fx2_p3, 0.0).clamp(0.0, 1.0);
-      let fx3_t_peek = params.get_i32_h(self.paths.fx3_type, 0);
-      let fx3_mix_peek = params.get_f32_h(self.paths.fx3_p3, 0.0).clamp(0.0, 1.0);
-      let fx4_t_peek = params.get_i32_h(self.paths.fx4_type, 0);
-      let fx4_mix_peek = params.get_f32_h(self.paths.fx4_p3, 0.0).clamp(0.0, 1.0);
-      if s.abs() < 1e-9 && (fx1_t_peek <= 0 || fx1_mix_peek <= 0.0005) && (fx2_t_peek <= 0 || fx2_mix_peek <= 0.0005) && (fx3_t_peek <= 0 || fx3_mix_peek <= 0.0005) && (fx4_t_peek <= 0 || fx4_mix_peek <= 0.0005) {
-        return (0.0, 0.0);
-      }
-      // Full FX chain + EQ + Mixer, same as other modules
-      let mut out = s;
-      // FX1
-      let fx1_t = params.get_i32_h(self.paths.fx1_type, 0);
-      let fx1_p1 = params.get_f32_h(self.paths.fx1_p1, 0.0);
-      let fx1_p2 = params.get_f32_h(self.paths.fx1_p2, 0.0);
-      let fx1_mix = params.get_f32_h(self.paths.fx1_p3, 0.0).clamp(0.0, 1.0);
-      if fx1_t <= 0 || fx1_mix <= 0.0005 {
-        if fx1_t <= 0 { self.fx1_reverb = None; self.fx1_crusher = None; }
-      } else if fx1_t == 2 {
-        let time_ms = 10.0 + fx1_p1.clamp(0.0, 1.0) * 990.0;
-        let fb = (fx1_p2.clamp(0.0, 1.0) * 0.95).min(0.95);
-        let mut lbuf = [out]; let mut rbuf = [out];
-        self.sdelay1.process_block(&mut lbuf, &mut rbuf, self.sr, time_ms, fb, fx1_mix, false);
-        out = 0.5 * (lbuf[0] + rbuf[0]);
-        self.fx1_reverb = None; self.fx1_crusher = None;
-      } else if fx1_t == 1 {
-        if self.fx1_reverb.is_none() {
-          let mut rv = Freeverb::new(self.sr as usize);
-          rv.set_room_size(0.35); rv.set_dampening(0.6); rv.set_wet(1.0); rv.set_dry(0.0); rv.set_width(0.9);
-          self.fx1_reverb = Some(rv);
-        }
-        if let Some(rv) = &mut self.fx1_reverb {
-          let room = 0.2 + fx1_p1.clamp(0.0, 1.0) * 0.8;
-          let damp = 0.2 + fx1_p2.clamp(0.0, 1.0) * 0.8;
-          let mix = fx1_mix;
-          rv.set_room_size(room as f64); rv.set_dampening(damp as f64); rv.set_wet(1.0); rv.set_dry(0.0); rv.set_width(0.9);
-          let dry = out; let (wl, wr) = rv.tick((dry as f64, dry as f64));
-          let lp_amt = 0.5 + 0.5 * (damp as f32);
-          self.fx1_wet_lp_l.set_hf_damp(lp_amt); self.fx1_wet_lp_r.set_hf_damp(lp_amt);
-          let wet_l = self.fx1_wet_lp_l.tick(wl as f32) as f32; let wet_r = self.fx1_wet_lp_r.tick(wr as f32) as f32;
-          let wet_m = 0.5 * (wet_l + wet_r);
-          out = dry * (1.0 - mix) + wet_m * mix;
-        }
-      } else if fx1_t == 3 || fx1_t == 4 || fx1_t == 5 {
-        let rate = 0.05 + fx1_p1 * (5.0 - 0.05);
-        let depth_ms = match fx1_t { 4 => 6.0 * fx1_p2, 5 => 12.0 * fx1_p2, _ => 4.0 * fx1_p2 };
-        if fx1_t == 3 {
-          let (wet, _) = self.phaser1.process_one(out, out, self.sr, rate, fx1_p2, 1.0);
-          out = out * (1.0 - fx1_mix) + wet * fx1_mix;
-        } else {
-          let base_ms = match fx1_t { 4 => 2.0, 5 => 15.0, _ => 3.0 };
-          let (wet, _) = self.delay1.process_one(out, out, self.sr, rate, base_ms, depth_ms, 1.0);
-          out = out * (1.0 - fx1_mix) + wet * fx1_mix;
-        }
-        self.fx1_reverb = None; self.fx1_crusher = None;
-      } else if fx1_t == 6 {
-        let dry = out; let drive_db = (fx1_p1.clamp(0.0, 1.0)) * 20.0; let g = (10.0_f32).powf(drive_db / 20.0); let x = (dry * g).tanh();
-        let tone = fx1_p2.clamp(0.0, 1.0); let lp_amt = 0.3 + 0.6 * (1.0 - tone); self.fx1_wet_lp_l.set_hf_damp(lp_amt);
-        let y_lp = self.fx1_wet_lp_l.tick(x); let y_hp = x - y_lp; let shaped = y_lp * (1.0 - tone) + y_hp * tone; out = dry * (1.0 - fx1_mix) + shaped * fx1_mix; self.fx1_reverb = None;
-      } else if fx1_t == 7 {
-        let dry = out; let drive = fx1_p2.clamp(0.0, 1.0) * 10.0; let g = 1.0 + drive; let cur = fx1_p1.clamp(0.0, 1.0);
-        let xin = dry * g; let shaped = if cur < 0.34 { xin.tanh() } else if cur < 0.67 { xin.clamp(-1.0, 1.0) } else { let m = (xin + 1.0).abs().rem_euclid(4.0); ((m - 2.0).abs() - 1.0).clamp(-1.0, 1.0) };
-        out = dry * (1.0 - fx1_mix) + shaped * fx1_mix; self.fx1_reverb = None;
-      } else if fx1_t == 8 {
-        if self.fx1_crusher.is_none() { self.fx1_crusher = Some(Bitcrusher::new()); }
-        if let Some(cr) = &mut self.fx1_crusher {
-          let bits = 4.0 + fx1_p1.clamp(0.0, 1.0) * 12.0; let fac = 1.0 + fx1_p2.clamp(0.0, 1.0) * 15.0; cr.set_bits(bits as u8); cr.set_factor(fac as u32); cr.set_mix(fx1_mix); let mut lbuf = [out]; let mut rbuf = [out]; cr.process(&mut lbuf, &mut rbuf); out = 0.5 * (lbuf[0] + rbuf[0]);
-        }
-      }
-      // FX2
-      let fx2_t = params.get_i32_h(self.paths.fx2_type, 0);
-      let fx2_p1 = params.get_f32_h(self.paths.fx2_p1, 0.0);
-      let fx2_p2 = params.get_f32_h(self.paths.fx2_p2, 0.0);
-      let fx2_mix = params.get_f32_h(self.paths.fx2_p3, 0.0).clamp(0.0, 1.0);
-      if fx2_t <= 0 || fx2_mix <= 0.0005 { if fx2_t <= 0 { self.fx2_reverb = None; self.fx2_crusher = None; } }
-      else if fx2_t == 2 {
-        let time_ms = 10.0 + fx2_p1.clamp(0.0, 1.0) * 990.0; let fb = (fx2_p2.clamp(0.0, 1.0) * 0.95).min(0.95);
-        let mut lbuf = [out]; let mut rbuf = [out]; self.sdelay2.process_block(&mut lbuf, &mut rbuf, self.sr, time_ms, fb, fx2_mix, false); out = 0.5 * (lbuf[0] + rbuf[0]);
-        self.fx2_reverb = None; self.fx2_crusher = None;
-      } else if fx2_t == 1 {
-        if self.fx2_reverb.is_none() { let mut rv = Freeverb::new(self.sr as usize); rv.set_room_size(0.35); rv.set_dampening(0.6); rv.set_wet(1.0); rv.set_dry(0.0); rv.set_width(0.9); self.fx2_reverb = Some(rv); }
-        if let Some(rv) = &mut self.fx2_reverb {
-          let room = 0.2 + fx2_p1.clamp(0.0, 1.0) * 0.8; let damp = 0.2 + fx2_p2.clamp(0.0, 1.0) * 0.8; let mix = fx2_mix;
-          rv.set_room_size(room as f64); rv.set_dampening(damp as f64); rv.set_wet(1.0); rv.set_dry(0.0); rv.set_width(0.9);
-          let dry = out; let (wl, wr) = rv.tick((dry as f64, dry as f64)); let lp_amt = 0.5 + 0.5 * (damp as f32);
-          self.fx2_wet_lp_l.set_hf_damp(lp_amt); self.fx2_wet_lp_r.set_hf_damp(lp_amt);
-          let wet_l = self.fx2_wet_lp_l.tick(wl as f32) as f32; let wet_r = self.fx2_wet_lp_r.tick(wr as f32) as f32; let wet_m = 0.5 * (wet_l + wet_r);
-          out = dry * (1.0 - mix) + wet_m * mix;
-        }
-      } else if fx2_t == 3 || fx2_t == 4 || fx2_t == 5 {
-        let rate = 0.05 + fx2_p1 * (5.0 - 0.05); let depth_ms = match fx2_t { 4 => 6.0 * fx2_p2, 5 => 12.0 * fx2_p2, _ => 4.0 * fx2_p2 };
-        if fx2_t == 3 { let (wet, _) = self.phaser2.process_one(out, out, self.sr, rate, fx2_p2, 1.0); out = out * (1.0 - fx2_mix) + wet * fx2_mix; }
-        else { let base_ms = match fx2_t { 4 => 2.0, 5 => 15.0, _ => 3.0 }; let (wet, _) = self.delay2.process_one(out, out, self.sr, rate, base_ms, depth_ms, 1.0); out = out * (1.0 - fx2_mix) + wet * fx2_mix; }
-        self.fx2_reverb = None; self.fx2_crusher = None;
-      } else if fx2_t == 6 {
-        let dry = out; let drive_db = (fx2_p1.clamp(0.0, 1.0)) * 20.0; let g = (10.0_f32).powf(drive_db / 20.0); let x = (dry * g).tanh();
-        let tone = fx2_p2.clamp(0.0, 1.0); let lp_amt = 0.3 + 0.6 * (1.0 - tone); self.fx2_wet_lp_l.set_hf_damp(lp_amt);
-        let y_lp = self.fx2_wet_lp_l.tick(x); let y_hp = x - y_lp; let shaped = y_lp * (1.0 - tone) + y_hp * tone; out = dry * (1.0 - fx2_mix) + shaped * fx2_mix; self.fx2_reverb = None; self.fx2_crusher = None;
-      } else if fx2_t == 7 {
-        let dry = out; let drive = fx2_p2.clamp(0.0, 1.0) * 10.0; let g = 1.0 + drive; let cur = fx2_p1.clamp(0.0, 1.0);
-        let xin = dry * g; let shaped = if cur < 0.34 { xin.tanh() } else if cur < 0.67 { xin.clamp(-1.0, 1.0) } else { let m = (xin + 1.0).abs().rem_euclid(4.0); ((m - 2.0).abs() - 1.0).clamp(-1.0, 1.0) };
-        out = dry * (1.0 - fx2_mix) + shaped * fx2_mix; self.fx2_reverb = None; self.fx2_crusher = None;
-      } else if fx2_t == 8 {
-        if self.fx2_crusher.is_none() { self.fx2_crusher = Some(Bitcrusher::new()); }
-        if let Some(cr) = &mut self.fx2_crusher { let bits = 4.0 + fx2_p1.clamp(0.0, 1.0) * 12.0; let fac = 1.0 + fx2_p2.clamp(0.0, 1.0) * 15.0; cr.set_bits(bits as u8); cr.set_factor(fac as u32); cr.set_mix(fx2_mix); let mut lbuf = [out]; let mut rbuf = [out]; cr.process(&mut lbuf, &mut rbuf); out = 0.5 * (lbuf[0] + rbuf[0]); }
-      }
-      // FX3
-      let fx3_t = params.get_i32_h(self.paths.fx3_type, 0);
-      let fx3_p1 = params.get_f32_h(self.paths.fx3_p1, 0.0);
-      let fx3_p2 = params.get_f32_h(self.paths.fx3_p2, 0.0);
-      let fx3_mix = params.get_f32_h(self.paths.fx3_p3, 0.0).clamp(0.0, 1.0);
-      if fx3_t <= 0 || fx3_mix <= 0.0005 { if fx3_t <= 0 { self.fx3_reverb = None; self.fx3_crusher = None; } }
-      else if fx3_t == 2 {
-        let time_ms = 10.0 + fx3_p1.clamp(0.0, 1.0) * 990.0; let fb = (fx3_p2.clamp(0.0, 1.0) * 0.95).min(0.95);
-        let mut lbuf = [out]; let mut rbuf = [out]; self.sdelay3.process_block(&mut lbuf, &mut rbuf, self.sr, time_ms, fb, fx3_mix, false); out = 0.5 * (lbuf[0] + rbuf[0]);
-        self.fx3_reverb = None; self.fx3_crusher = None;
-      } else if fx3_t == 1 {
-        if self.fx3_reverb.is_none() { let mut rv = Freeverb::new(self.sr as usize); rv.set_room_size(0.35); rv.set_dampening(0.6); rv.set_wet(1.0); rv.set_dry(0.0); rv.set_width(0.9); self.fx3_reverb = Some(rv); }
-        if let Some(rv) = &mut self.fx3_reverb {
-          let room = 0.2 + fx3_p1.clamp(0.0, 1.0) * 0.8; let damp = 0.2 + fx3_p2.clamp(0.0, 1.0) * 0.8; let mix = fx3_mix;
-          rv.set_room_size(room as f64); rv.set_dampening(damp as f64); rv.set_wet(1.0); rv.set_dry(0.0); rv.set_width(0.9);
-          let dry = out; let (wl, wr) = rv.tick((dry as f64, dry as f64)); let lp_amt = 0.5 + 0.5 * (damp as f32);
-          self.fx3_wet_lp_l.set_hf_damp(lp_amt); self.fx3_wet_lp_r.set_hf_damp(lp_amt);
-          let wet_l = self.fx3_wet_lp_l.tick(wl as f32) as f32; let wet_r = self.fx3_wet_lp_r.tick(wr as f32) as f32; let wet_m = 0.5 * (wet_l + wet_r);
-          out = dry * (1.0 - mix) + wet_m * mix;
-        }
-      } else if fx3_t == 3 || fx3_t == 4 || fx3_t == 5 {
-        let rate = 0.05 + fx3_p1 * (5.0 - 0.05); let depth_ms = match fx3_t { 4 => 6.0 * fx3_p2, 5 => 12.0 * fx3_p2, _ => 4.0 * fx3_p2 };
-        if fx3_t == 3 { let (wet, _) = self.phaser3.process_one(out, out, self.sr, rate, fx3_p2, 1.0); out = out * (1.0 - fx3_mix) + wet * fx3_mix; }
-        else { let base_ms = match fx3_t { 4 => 2.0, 5 => 15.0, _ => 3.0 }; let (wet, _) = self.delay3.process_one(out, out, self.sr, rate, base_ms, depth_ms, 1.0); out = out * (1.0 - fx3_mix) + wet * fx3_mix; }
-        self.fx3_reverb = None; self.fx3_crusher = None;
-      } else if fx3_t == 6 {
-        let dry = out; let drive_db = (fx3_p1.clamp(0.0, 1.0)) * 20.0; let g = (10.0_f32).powf(drive_db / 20.0); let x = (dry * g).tanh();
-        let tone = fx3_p2.clamp(0.0, 1.0); let lp_amt = 0.3 + 0.6 * (1.0 - tone); self.fx3_wet_lp_l.set_hf_damp(lp_amt);
-        let y_lp = self.fx3_wet_lp_l.tick(x); let y_hp = x - y_lp; let shaped = y_lp * (1.0 - tone) + y_hp * tone; out = dry * (1.0 - fx3_mix) + shaped * fx3_mix; self.fx3_reverb = None; self.fx3_crusher = None;
-      } else if fx3_t == 7 {
-        let dry = out; let drive = fx3_p2.clamp(0.0, 1.0) * 10.0; let g = 1.0 + drive; let cur = fx3_p1.clamp(0.0, 1.0);
-        let xin = dry * g; let shaped = if cur < 0.34 { xin.tanh() } else if cur < 0.67 { xin.clamp(-1.0, 1.0) } else { let m = (xin + 1.0).abs().rem_euclid(4.0); ((m - 2.0).abs() - 1.0).clamp(-1.0, 1.0) };
-        out = dry * (1.0 - fx3_mix) + shaped * fx3_mix; self.fx3_reverb = None; self.fx3_crusher = None;
-      } else if fx3_t == 8 {
-        if self.fx3_crusher.is_none() { self.fx3_crusher = Some(Bitcrusher::new()); }
-        if let Some(cr) = &mut self.fx3_crusher { let bits = 4.0 + fx3_p1.clamp(0.0, 1.0) * 12.0; let fac = 1.0 + fx3_p2.clamp(0.0, 1.0) * 15.0; cr.set_bits(bits as u8); cr.set_factor(fac as u32); cr.set_mix(fx3_mix); let mut lbuf = [out]; let mut rbuf = [out]; cr.process(&mut lbuf, &mut rbuf); out = 0.5 * (lbuf[0] + rbuf[0]); }
-      }
-      // FX4
-      let fx4_t = params.get_i32_h(self.paths.fx4_type, 0);
-      let fx4_p1 = params.get_f32_h(self.paths.fx4_p1, 0.0);
-      let fx4_p2 = params.get_f32_h(self.paths.fx4_p2, 0.0);
-      let fx4_mix = params.get_f32_h(self.paths.fx4_p3, 0.0).clamp(0.0, 1.0);
-      if fx4_t <= 0 || fx4_mix <= 0.0005 { if fx4_t <= 0 { self.fx4_reverb = None; self.fx4_crusher = None; } }
-      else if fx4_t == 2 {
-        let time_ms = 10.0 + fx4_p1.clamp(0.0, 1.0) * 990.0; let fb = (fx4_p2.clamp(0.0, 1.0) * 0.95).min(0.95);
-        let mut lbuf = [out]; let mut rbuf = [out]; self.sdelay4.process_block(&mut lbuf, &mut rbuf, self.sr, time_ms, fb, fx4_mix, false); out = 0.5 * (lbuf[0] + rbuf[0]);
-        self.fx4_reverb = None; self.fx4_crusher = None;
-      } else if fx4_t == 1 {
-        if self.fx4_reverb.is_none() { let mut rv = Freeverb::new(self.sr as usize); rv.set_room_size(0.35); rv.set_dampening(0.6); rv.set_wet(1.0); rv.set_dry(0.0); rv.set_width(0.9); self.fx4_reverb = Some(rv); }
-        if let Some(rv) = &mut self.fx4_reverb {
-          let room = 0.2 + fx4_p1.clamp(0.0, 1.0) * 0.8; let damp = 0.2 + fx4_p2.clamp(0.0, 1.0) * 0.8; let mix = fx4_mix;
-          rv.set_room_size(room as f64); rv.set_dampening(damp as f64); rv.set_wet(1.0); rv.set_dry(0.0); rv.set_width(0.9);
-          let dry = out; let (wl, wr) = rv.tick((dry as f64, dry as f64)); let lp_amt = 0.5 + 0.5 * (damp as f32);
-          self.fx4_wet_lp_l.set_hf_damp(lp_amt); self.fx4_wet_lp_r.set_hf_damp(lp_amt);
-          let wet_l = self.fx4_wet_lp_l.tick(wl as f32) as f32; let wet_r = self.fx4_wet_lp_r.tick(wr as f32) as f32; let wet_m = 0.5 * (wet_l + wet_r);
-          out = dry * (1.0 - mix) + wet_m * mix;
-        }
-      } else if fx4_t == 3 || fx4_t == 4 || fx4_t == 5 {
-        let rate = 0.05 + fx4_p1 * (5.0 - 0.05); let depth_ms = match fx4_t { 4 => 6.0 * fx4_p2, 5 => 12.0 * fx4_p2, _ => 4.0 * fx4_p2 };
-        if fx4_t == 3 { let (wet, _) = self.phaser4.process_one(out, out, self.sr, rate, fx4_p2, 1.0); out = out * (1.0 - fx4_mix) + wet * fx4_mix; }
-        else { let base_ms = match fx4_t { 4 => 2.0, 5 => 15.0, _ => 3.0 }; let (wet, _) = self.delay4.process_one(out, out, self.sr, rate, base_ms, depth_ms, 1.0); out = out * (1.0 - fx4_mix) + wet * fx4_mix; }
-        self.fx4_reverb = None; self.fx4_crusher = None;
-      } else if fx4_t == 6 {
-        let dry = out; let drive_db = (fx4_p1.clamp(0.0, 1.0)) * 20.0; let g = (10.0_f32).powf(drive_db / 20.0); let x = (dry * g).tanh();
-        let tone = fx4_p2.clamp(0.0, 1.0); let lp_amt = 0.3 + 0.6 * (1.0 - tone); self.fx4_wet_lp_l.set_hf_damp(lp_amt);
-        let y_lp = self.fx4_wet_lp_l.tick(x); let y_hp = x - y_lp; let shaped = y_lp * (1.0 - tone) + y_hp * tone; out = dry * (1.0 - fx4_mix) + shaped * fx4_mix; self.fx4_reverb = None; self.fx4_crusher = None;
-      } else if fx4_t == 7 {
-        let dry = out; let drive = fx4_p2.clamp(0.0, 1.0) * 10.0; let g = 1.0 + drive; let cur = fx4_p1.clamp(0.0, 1.0);
-        let xin = dry * g; let shaped = if cur < 0.34 { xin.tanh() } else if cur < 0.67 { xin.clamp(-1.0, 1.0) } else { let m = (xin + 1.0).abs().rem_euclid(4.0); ((m - 2.0).abs() - 1.0).clamp(-1.0, 1.0) };
-        out = dry * (1.0 - fx4_mix) + shaped * fx4_mix; self.fx4_reverb = None; self.fx4_crusher = None;
-      } else if fx4_t == 8 {
-        if self.fx4_crusher.is_none() { self.fx4_crusher = Some(Bitcrusher::new()); }
-        if let Some(cr) = &mut self.fx4_crusher { let bits = 4.0 + fx4_p1.clamp(0.0, 1.0) * 12.0; let fac = 1.0 + fx4_p2.clamp(0.0, 1.0) * 15.0; cr.set_bits(bits as u8); cr.set_factor(fac as u32); cr.set_mix(fx4_mix); let mut lbuf = [out]; let mut rbuf = [out]; cr.process(&mut lbuf, &mut rbuf); out = 0.5 * (lbuf[0] + rbuf[0]); }
-      }
-      // EQ: 8-band peaking
-      let q = 1.0_f32; let mut any_nonzero = false;
-      for i in 0..8 {
-        let db = params.get_f32_h(self.paths.eq_bands[i], 0.0).clamp(-12.0, 12.0);
-        if (db - self.eq_last_db[i]).abs() > 1e-6 { self.eq_bands[i].set_peaking(self.sr, self.eq_centers[i], q, db); self.eq_last_db[i] = db; }
-        if db.abs() > 1e-3 { any_nonzero = true; }
-      }
-      if any_nonzero { for i in 0..8 { out = self.eq_bands[i].process(out); } }
-      // Mixer: PAN, VOLUME, HAAS, COMP
-      let mut l = out; let mut r = out;
-      let pan = params.get_f32_h(self.paths.mix_pan, 0.0).clamp(-1.0, 1.0);
-      let theta = (pan + 1.0) * std::f32::consts::FRAC_PI_4; let gl = theta.cos(); let gr = theta.sin(); l *= gl; r *= gr;
-      let vol = params.get_f32_h(self.paths.mix_volume, 1.0).clamp(0.0, 1.0); l *= vol; r *= vol;
-      let haas = params.get_f32_h(self.paths.mix_haas, 0.0).clamp(0.0, 1.0);
-      if haas > 0.0005 {
-        let rd = if self.haas_wr >= self.haas_d { self.haas_wr - self.haas_d } else { self.haas_wr + self.haas_len - self.haas_d };
-        let delayed_l = self.haas_buf[rd]; self.haas_buf[self.haas_wr] = l; self.haas_wr += 1; if self.haas_wr >= self.haas_len { self.haas_wr = 0; }
-        l = l * (1.0 - haas) + delayed_l * haas;
-      } else { self.haas_buf[self.haas_wr] = l; self.haas_wr += 1; if self.haas_wr >= self.haas_len { self.haas_wr = 0; } }
-      let comp = params.get_f32_h(self.paths.mix_comp, 0.0).clamp(0.0, 1.0);
-      if comp > 0.001 { let drive = 1.0 + 8.0 * comp; let id = 1.0 / drive.tanh(); l = (l * drive).tanh() * id; r = (r * drive).tanh() * id; }
-      return (l, r);
-  } else {
-      // Analog voices (module == 0)
-      // Compute LFO sample every sample; apply global depth with internal smoothing
-    let shape = params.get_i32_h(self.paths.lfo_shape, 0);
-    let rate_hz = params.get_f32_h(self.paths.lfo_rate_hz, 1.0).max(0.01);
-    self.lfo_phase = (self.lfo_phase + rate_hz / self.sr).fract();
-    let x = self.lfo_phase;
-    let mut lfo_val = match shape { 1 => 2.0*(x - (x+0.5).floor()).abs() - 1.0, 2 => if x < 0.5 { 1.0 } else { -1.0 }, 3 => 2.0*x - 1.0, _ => (2.0*PI*x).sin() };
-    let drive = params.get_f32_h(self.paths.lfo_drive, 0.0);
-    if drive > 0.001 { let k = 1.0 + 8.0*drive; lfo_val = (lfo_val*k).tanh() / k.tanh(); }
-    let target_amt = params.get_f32_h(self.paths.lfo_amount, 1.0).clamp(0.0, 1.0);
-    // simple de-zipper (~10ms): alpha = 1 - exp(-1/(ms*sr)) 
-  let alpha = 1.0 - (-1.0f32/(0.01*self.sr)).exp();
-    self.lfo_hold += (target_amt - self.lfo_hold) * alpha;
-    let modv = lfo_val * self.lfo_hold;
-    let mut modf = ModFrame { cents_a: 0.0, cents_b: 0.0, lvl_a: 0.0, lvl_b: 0.0, filt1: 0.0, filt2: 0.0 };
-    for i in 0..5 {
-      let dest = params.get_i32_h(self.paths.lfo_dest[i], 0) as u16;
-      if dest == 0 { continue; }
-      let row_amt = params.get_f32_h(self.paths.lfo_row_amount[i], 1.0).clamp(-1.0, 1.0);
-      let v = modv * row_amt;
-      match dest {
-        1 => modf.cents_a += 100.0 * v,
-        2 => modf.cents_b += 100.0 * v,
-        3 => modf.lvl_a += v,
-        4 => modf.lvl_b += v,
-        5 => modf.filt1 += v,
-        6 => modf.filt2 += v,
-        _ => {}
-      }
-    }
-    let mut s = 0.0f32;
-    for v in &mut self.voices { if v.is_active() { s += v.render(params, &self.paths, self.sr, &modf); } }
-    // Early-out if dry is silent and all FX mixes are ~zero (no tails needed)
-    let fx1_t_peek = params.get_i32_h(self.paths.fx1_type, 0);
-    let fx1_mix_peek = params.get_f32_h(self.paths.fx1_p3, 0.0).clamp(0.0, 1.0);
-    let fx2_t_peek = params.get_i32_h(self.paths.fx2_type, 0);
-    let fx2_mix_peek = params.get_f32_h(self.paths.fx2_p3, 0.0).clamp(0.0, 1.0);
-    let fx3_t_peek = params.get_i32_h(self.paths.fx3_type, 0);
-    let fx3_mix_peek = params.get_f32_h(self.paths.fx3_p3, 0.0).clamp(0.0, 1.0);
-    let fx4_t_peek = params.get_i32_h(self.paths.fx4_type, 0);
-    let fx4_mix_peek = params.get_f32_h(self.paths.fx4_p3, 0.0).clamp(0.0, 1.0);
-    if s.abs() < 1e-9 && (fx1_t_peek <= 0 || fx1_mix_peek <= 0.0005) && (fx2_t_peek <= 0 || fx2_mix_peek <= 0.0005) && (fx3_t_peek <= 0 || fx3_mix_peek <= 0.0005) && (fx4_t_peek <= 0 || fx4_mix_peek <= 0.0005) {
-      return (0.0, 0.0);
-    }
-    // FX1
-    let mut out = s;
-    let fx1_t = params.get_i32_h(self.paths.fx1_type, 0);
-    let fx1_p1 = params.get_f32_h(self.paths.fx1_p1, 0.0);
-    let fx1_p2 = params.get_f32_h(self.paths.fx1_p2, 0.0);
-    let fx1_mix = params.get_f32_h(self.paths.fx1_p3, 0.0).clamp(0.0, 1.0);
-    if fx1_t <= 0 || fx1_mix <= 0.0005 {
-      // No Effect
-      if fx1_t <= 0 { self.fx1_reverb = None; self.fx1_crusher = None; }
-    } else if fx1_t == 2 {
-      // Delay: p1->time_ms (10..1000), p2->feedback, p3->mix
-      let time_ms = 10.0 + fx1_p1.clamp(0.0, 1.0) * 990.0;
-      let fb = (fx1_p2.clamp(0.0, 1.0) * 0.95).min(0.95);
-      let mix = fx1_mix;
-      let mut lbuf = [out];
-      let mut rbuf = [out];
-      self.sdelay1.process_block(&mut lbuf, &mut rbuf, self.sr, time_ms, fb, mix, false);
-      out = 0.5 * (lbuf[0] + rbuf[0]);
-      self.fx1_reverb = None; self.fx1_crusher = None;
-    } else if fx1_t == 1 {
-      // Reverb (Freeverb): p1->room size, p2->damping, p3->mix
-      if self.fx1_reverb.is_none() {
-        let mut rv = Freeverb::new(self.sr as usize);
-        rv.set_room_size(0.35);
-        rv.set_dampening(0.6);
-        rv.set_wet(1.0);
-        rv.set_dry(0.0);
-        rv.set_width(0.9);
-        self.fx1_reverb = Some(rv);
-      }
-      if let Some(rv) = &mut self.fx1_reverb {
-        // Map for a smoother, less metallic character
-        let room = 0.2 + fx1_p1.clamp(0.0, 1.0) * 0.8;   // avoid tiny rooms
-        let damp = 0.2 + fx1_p2.clamp(0.0, 1.0) * 0.8;   // stronger HF damping baseline
-        let mix = fx1_mix;
-        rv.set_room_size(room as f64);
-        rv.set_dampening(damp as f64);
-        // Use pure-wet from Freeverb, mix externally (allows extra LPF on wet)
-        rv.set_wet(1.0);
-        rv.set_dry(0.0);
-        rv.set_width(0.9);
-        let dry = out;
-        let (wl, wr) = rv.tick((dry as f64, dry as f64));
-        // Gentle LPF on wet to reduce metallic sheen (tie amount to damping)
-        let lp_amt = 0.5 + 0.5 * (damp as f32);
-        self.fx1_wet_lp_l.set_hf_damp(lp_amt);
-        self.fx1_wet_lp_r.set_hf_damp(lp_amt);
-        let wet_l = self.fx1_wet_lp_l.tick(wl as f32) as f32;
-        let wet_r = self.fx1_wet_lp_r.tick(wr as f32) as f32;
-        let wet_m = 0.5 * (wet_l + wet_r);
-        out = dry * (1.0 - mix) + wet_m * mix;
+  // Stereo sources (Korus, Granular, Analog unison) keep their image through every stage
+  fn process_fx_eq_stereo(&mut self, params: &ParamStore, l: f32, r: f32) -> Option<(f32, f32)> {
+    // No input and no FX tails to ring out
+    let fx1_t_peek = params.get_i32_h(self.keys.fx1_type, 0);
+    let fx1_mix_peek = params.get_f32_h(self.keys.fx1_p3, 0.0).clamp(0.0, 1.0);
+    let fx2_t_peek = params.get_i32_h(self.keys.fx2_type, 0);
+    let fx2_mix_peek = params.get_f32_h(self.keys.fx2_p3, 0.0).clamp(0.0, 1.0);
+    let fx3_t_peek = params.get_i32_h(self.keys.fx3_type, 0);
+    let fx3_mix_peek = params.get_f32_h(self.keys.fx3_p3, 0.0).clamp(0.0, 1.0);
+    let fx4_t_peek = params.get_i32_h(self.keys.fx4_type, 0);
+    let fx4_mix_peek = params.get_f32_h(self.keys.fx4_p3, 0.0).clamp(0.0, 1.0);
+    if (l.abs() + r.abs()) < 1e-9 && (fx1_t_peek <= 0 || fx1_mix_peek <= 0.0005) && (fx2_t_peek <= 0 || fx2_mix_peek <= 0.0005) && (fx3_t_peek <= 0 || fx3_mix_peek <= 0.0005) && (fx4_t_peek <= 0 || fx4_mix_peek <= 0.0005) {
+      return None;
+    }
+    let mut l = l;
+    let mut r = r;
+    // --- FX1 (stereo) ---
+    let fx1_t = params.get_i32_h(self.keys.fx1_type, 0);
+    let fx1_p1 = params.get_f32_h(self.keys.fx1_p1, 0.0);
+    let fx1_p2 = params.get_f32_h(self.keys.fx1_p2, 0.0);
+    let fx1_mix = params.get_f32_h(self.keys.fx1_p3, 0.0).clamp(0.0, 1.0);
+    if fx1_t <= 0 || fx1_mix <= 0.0005 { if fx1_t <= 0 { self.fx1_reverb = None; self.fx1_crusher = None; } }
+    else if fx1_t == 2 {
+      let time_ms = 10.0 + fx1_p1.clamp(0.0, 1.0) * 990.0; let fb = (fx1_p2.clamp(0.0, 1.0) * 0.95).min(0.95);
+      let mut lbuf = [l]; let mut rbuf = [r]; self.sdelay1.process_block(&mut lbuf, &mut rbuf, self.sr, time_ms, fb, fx1_mix, false); l = lbuf[0]; r = rbuf[0];
+      self.fx1_reverb = None; self.fx1_crusher = None;
+    } else if fx1_t == 1 {
+      if self.fx1_reverb.is_none() { let mut rv = Freeverb::new(self.sr as usize); rv.set_room_size(0.35); rv.set_dampening(0.6); rv.set_wet(1.0); rv.set_dry(0.0); rv.set_width(0.9); self.fx1_reverb = Some(rv); }
+      if let Some(rv) = &mut self.fx1_reverb {
+        let room = 0.2 + fx1_p1.clamp(0.0, 1.0) * 0.8; let damp = 0.2 + fx1_p2.clamp(0.0, 1.0) * 0.8; let mix = fx1_mix;
+        rv.set_room_size(room as f64); rv.set_dampening(damp as f64); rv.set_wet(1.0); rv.set_dry(0.0); rv.set_width(0.9);
+        let (wl, wr) = rv.tick((l as f64, r as f64)); let lp_amt = 0.5 + 0.5 * (damp as f32); self.fx1_wet_lp_l.set_hf_damp(lp_amt); self.fx1_wet_lp_r.set_hf_damp(lp_amt);
+        let wet_l = self.fx1_wet_lp_l.tick(wl as f32); let wet_r = self.fx1_wet_lp_r.tick(wr as f32);
+        l = l * (1.0 - mix) + wet_l * mix; r = r * (1.0 - mix) + wet_r * mix;
       }
     } else if fx1_t == 3 || fx1_t == 4 || fx1_t == 5 {
-      // Chorus/Flanger/Phaser approximate: modulated short delay
-      let rate = 0.05 + fx1_p1 * (5.0 - 0.05);
-      let depth_ms = match fx1_t { 4 => 6.0 * fx1_p2, 5 => 12.0 * fx1_p2, _ => 4.0 * fx1_p2 };
-      if fx1_t == 3 {
-        let (wet, _) = self.phaser1.process_one(out, out, self.sr, rate, fx1_p2, 1.0);
-        out = out * (1.0 - fx1_mix) + wet * fx1_mix;
-      } else {
-        let base_ms = match fx1_t { 4 => 2.0, 5 => 15.0, _ => 3.0 };
-        let (wet, _) = self.delay1.process_one(out, out, self.sr, rate, base_ms, depth_ms, 1.0);
-        out = out * (1.0 - fx1_mix) + wet * fx1_mix;
-      }
+      let rate = 0.05 + fx1_p1 * (5.0 - 0.05); let depth_ms = match fx1_t { 4 => 6.0 * fx1_p2, 5 => 12.0 * fx1_p2, _ => 4.0 * fx1_p2 };
+      if fx1_t == 3 { let (wl, wr) = self.phaser1.process_one(l, r, self.sr, rate, fx1_p2, 1.0); l = l * (1.0 - fx1_mix) + wl * fx1_mix; r = r * (1.0 - fx1_mix) + wr * fx1_mix; }
+      else { let base_ms = match fx1_t { 4 => 2.0, 5 => 15.0, _ => 3.0 }; let (wl, wr) = self.delay1.process_one(l, r, self.sr, rate, base_ms, depth_ms, 1.0); l = l * (1.0 - fx1_mix) + wl * fx1_mix; r = r * (1.0 - fx1_mix) + wr * fx1_mix; }
       self.fx1_reverb = None; self.fx1_crusher = None;
     } else if fx1_t == 6 {
-      // Distortion: Drive (0..20 dB), Tone (LP<->HP), Mix
-      let dry = out;
-      let drive_db = (fx1_p1.clamp(0.0, 1.0)) * 20.0;
-      let g = (10.0_f32).powf(drive_db / 20.0);
-      let x = (dry * g).tanh();
-      let tone = fx1_p2.clamp(0.0, 1.0);
-      let lp_amt = 0.3 + 0.6 * (1.0 - tone);
-      self.fx1_wet_lp_l.set_hf_damp(lp_amt);
-      let y_lp = self.fx1_wet_lp_l.tick(x);
-      let y_hp = x - y_lp;
-      let shaped = y_lp * (1.0 - tone) + y_hp * tone;
-      out = dry * (1.0 - fx1_mix) + shaped * fx1_mix;
+      let drive_db = fx1_p1.clamp(0.0, 1.0) * 20.0; let g = (10.0_f32).powf(drive_db / 20.0); let tone = fx1_p2.clamp(0.0, 1.0);
+      let xl = (l * g).tanh(); let xr = (r * g).tanh();
+      let lp_amt = 0.3 + 0.6 * (1.0 - tone); self.fx1_wet_lp_l.set_hf_damp(lp_amt); self.fx1_wet_lp_r.set_hf_damp(lp_amt);
+      let yl_lp = self.fx1_wet_lp_l.tick(xl); let yr_lp = self.fx1_wet_lp_r.tick(xr);
+      let sl = yl_lp * (1.0 - tone) + (xl - yl_lp) * tone; let sr = yr_lp * (1.0 - tone) + (xr - yr_lp) * tone;
+      l = l * (1.0 - fx1_mix) + sl * fx1_mix; r = r * (1.0 - fx1_mix) + sr * fx1_mix;
       self.fx1_reverb = None;
     } else if fx1_t == 7 {
-      // Waveshaper: Curve (tanh/clip/fold), Drive (0..10), Mix
-      let dry = out;
-      let drive = fx1_p2.clamp(0.0, 1.0) * 10.0;
-      let g = 1.0 + drive;
-      let cur = fx1_p1.clamp(0.0, 1.0);
-      let xin = dry * g;
-      let shaped = if cur < 0.34 {
-        xin.tanh()
-      } else if cur < 0.67 {
-        xin.clamp(-1.0, 1.0)
-      } else {
-        // foldback
-        let m = (xin + 1.0).abs().rem_euclid(4.0);
-        ((m - 2.0).abs() - 1.0).clamp(-1.0, 1.0)
-      };
-      out = dry * (1.0 - fx1_mix) + shaped * fx1_mix;
-      self.fx1_reverb = None;
+      let drive = fx1_p2.clamp(0.0, 1.0) * 10.0; let g = 1.0 + drive; let cur = fx1_p1.clamp(0.0, 1.0);
+      let shape = |x: f32| if cur < 0.34 { (x * g).tanh() } else if cur < 0.67 { (x * g).clamp(-1.0, 1.0) } else { let m = (x * g + 1.0).abs().rem_euclid(4.0); ((m - 2.0).abs() - 1.0).clamp(-1.0, 1.0) };
+      l = l * (1.0 - fx1_mix) + shape(l) * fx1_mix; r = r * (1.0 - fx1_mix) + shape(r) * fx1_mix;
+      self.fx1_reverb = None; self.fx1_crusher = None;
     } else if fx1_t == 8 {
       if self.fx1_crusher.is_none() { self.fx1_crusher = Some(Bitcrusher::new()); }
-      if let Some(cr) = &mut self.fx1_crusher {
-        let bits = 4.0 + fx1_p1.clamp(0.0, 1.0) * 12.0;
-        let fac = 1.0 + fx1_p2.clamp(0.0, 1.0) * 15.0;
-        cr.set_bits(bits as u8);
-        cr.set_factor(fac as u32);
-        cr.set_mix(fx1_mix);
-        let mut lbuf = [out]; let mut rbuf = [out];
-        cr.process(&mut lbuf, &mut rbuf);
-        out = 0.5 * (lbuf[0] + rbuf[0]);
-      }
+      if let Some(cr) = &mut self.fx1_crusher { let bits = 4.0 + fx1_p1.clamp(0.0, 1.0) * 12.0; let fac = 1.0 + fx1_p2.clamp(0.0, 1.0) * 15.0; cr.set_bits(bits as u8); cr.set_factor(fac as u32); cr.set_mix(fx1_mix); let mut lbuf = [l]; let mut rbuf = [r]; cr.process(&mut lbuf, &mut rbuf); l = lbuf[0]; r = rbuf[0]; }
     }
-    // FX2
-    let fx2_t = params.get_i32_h(self.paths.fx2_type, 0);
-    let fx2_p1 = params.get_f32_h(self.paths.fx2_p1, 0.0);
-    let fx2_p2 = params.get_f32_h(self.paths.fx2_p2, 0.0);
-    let fx2_mix = params.get_f32_h(self.paths.fx2_p3, 0.0).clamp(0.0, 1.0);
-    if fx2_t <= 0 || fx2_mix <= 0.0005 {
-      // No Effect
-      if fx2_t <= 0 { self.fx2_reverb = None; self.fx2_crusher = None; }
-    } else if fx2_t == 2 {
-      let time_ms = 10.0 + fx2_p1.clamp(0.0, 1.0) * 990.0;
-      let fb = (fx2_p2.clamp(0.0, 1.0) * 0.95).min(0.95);
-      let mix = fx2_mix;
-      let mut lbuf = [out];
-      let mut rbuf = [out];
-      // Ping-pong optional; set true here if stereo pipeline is used earlier
-      self.sdelay2.process_block(&mut lbuf, &mut rbuf, self.sr, time_ms, fb, mix, false);
-      out = 0.5 * (lbuf[0] + rbuf[0]);
-      self.fx2_reverb = None; self.fx2_crusher = None;
-    } else if fx2_t == 1 {
-      if self.fx2_reverb.is_none() {
-        let mut rv = Freeverb::new(self.sr as usize);
-        rv.set_room_size(0.35);
-        rv.set_dampening(0.6);
-        rv.set_wet(1.0);
-        rv.set_dry(0.0);
-        rv.set_width(0.9);
-        self.fx2_reverb = Some(rv);
-      }
-      if let Some(rv) = &mut self.fx2_reverb {
-        let room = 0.2 + fx2_p1.clamp(0.0, 1.0) * 0.8;
-        let damp = 0.2 + fx2_p2.clamp(0.0, 1.0) * 0.8;
-        let mix = fx2_mix;
-        rv.set_room_size(room as f64);
-        rv.set_dampening(damp as f64);
-        rv.set_wet(1.0);
-        rv.set_dry(0.0);
-        rv.set_width(0.9);
-        let dry = out;
-        let (wl, wr) = rv.tick((dry as f64, dry as f64));
-        let lp_amt = 0.5 + 0.5 * (damp as f32);
-        self.fx2_wet_lp_l.set_hf_damp(lp_amt);
-        self.fx2_wet_lp_r.set_hf_damp(lp_amt);
-        let wet_l = self.fx2_wet_lp_l.tick(wl as f32) as f32;
-        let wet_r = self.fx2_wet_lp_r.tick(wr as f32) as f32;
-        let wet_m = 0.5 * (wet_l + wet_r);
-      out = dry * (1.0 - mix) + wet_m * mix;
-      }
-    } else if fx2_t == 3 || fx2_t == 4 || fx2_t == 5 {
-      let rate = 0.05 + fx2_p1 * (5.0 - 0.05);
-      let depth_ms = match fx2_t { 4 => 6.0 * fx2_p2, 5 => 12.0 * fx2_p2, _ => 4.0 * fx2_p2 };
-      if fx2_t == 3 {
-        let (wet, _) = self.phaser2.process_one(out, out, self.sr, rate, fx2_p2, 1.0);
-        out = out * (1.0 - fx2_mix) + wet * fx2_mix;
-      } else {
-        let base_ms = match fx2_t { 4 => 2.0, 5 => 15.0, _ => 3.0 };
-        let (wet, _) = self.delay2.process_one(out, out, self.sr, rate, base_ms, depth_ms, 1.0);
-        out = out * (1.0 - fx2_mix) + wet * fx2_mix;
-      }
-      self.fx2_reverb = None; self.fx2_crusher = None;
-    } else if fx2_t == 6 {
-      // Distortion
-      let dry = out;
-      let drive_db = (fx2_p1.clamp(0.0, 1.0)) * 20.0;
-      let g = (10.0_f32).powf(drive_db / 20.0);
-      let x = (dry * g).tanh();
-      let tone = fx2_p2.clamp(0.0, 1.0);
-      let lp_amt = 0.3 + 0.6 * (1.0 - tone);
-      self.fx2_wet_lp_l.set_hf_damp(lp_amt);
-      let y_lp = self.fx2_wet_lp_l.tick(x);
-      let y_hp = x - y_lp;
-      let shaped = y_lp * (1.0 - tone) + y_hp * tone;
-      out = dry * (1.0 - fx2_mix) + shaped * fx2_mix;
-      self.fx2_reverb = None; self.fx2_crusher = None;
-    } else if fx2_t == 7 {
-      // Waveshaper
-      let dry = out;
-      let drive = fx2_p2.clamp(0.0, 1.0) * 10.0;
-      let g = 1.0 + drive;
-      let cur = fx2_p1.clamp(0.0, 1.0);
-      let xin = dry * g;
-      let shaped = if cur < 0.34 {
-        xin.tanh()
-      } else if cur < 0.67 {
-        xin.clamp(-1.0, 1.0)
-      } else {
-        let m = (xin + 1.0).abs().rem_euclid(4.0);
-        ((m - 2.0).abs() - 1.0).clamp(-1.0, 1.0)
-      };
-      out = dry * (1.0 - fx2_mix) + shaped * fx2_mix;
-      self.fx2_reverb = None; self.fx2_crusher = None;
-    } else if fx2_t == 8 {
-      if self.fx2_crusher.is_none() { self.fx2_crusher = Some(Bitcrusher::new()); }
-      if let Some(cr) = &mut self.fx2_crusher {
-        let bits = 4.0 + fx2_p1.clamp(0.0, 1.0) * 12.0;
-        let fac = 1.0 + fx2_p2.clamp(0.0, 1.0) * 15.0;
-        cr.set_bits(bits as u8);
-        cr.set_factor(fac as u32);
-        cr.set_mix(fx2_mix);
-        let mut lbuf = [out]; let mut rbuf = [out];
-        cr.process(&mut lbuf, &mut rbuf);
-        out = 0.5 * (lbuf[0] + rbuf[0]);
-      }
-    }
-    // FX3
-    let fx3_t = params.get_i32_h(self.paths.fx3_type, 0);
-    let fx3_p1 = params.get_f32_h(self.paths.fx3_p1, 0.0);
-    let fx3_p2 = params.get_f32_h(self.paths.fx3_p2, 0.0);
-    let fx3_mix = params.get_f32_h(self.paths.fx3_p3, 0.0).clamp(0.0, 1.0);
-    if fx3_t <= 0 || fx3_mix <= 0.0005 {
-      // No Effect
-      if fx3_t <= 0 { self.fx3_reverb = None; self.fx3_crusher = None; }
-    } else if fx3_t == 2 {
-      // Simple delay
-      let time_ms = 10.0 + fx3_p1.clamp(0.0, 1.0) * 990.0;
-      let fb = (fx3_p2.clamp(0.0, 1.0) * 0.95).min(0.95);
-      let mut lbuf = [out]; let mut rbuf = [out];
-      self.sdelay3.process_block(&mut lbuf, &mut rbuf, self.sr, time_ms, fb, fx3_mix, false);
-      out = 0.5 * (lbuf[0] + rbuf[0]);
-      self.fx3_reverb = None; self.fx3_crusher = None;
-    } else if fx3_t == 1 {
-      // Reverb: Room, Damp, Mix
-      if self.fx3_reverb.is_none() {
-        let mut rv = Freeverb::new(self.sr as usize);
-        rv.set_room_size(0.35);
-        rv.set_dampening(0.6);
-        rv.set_wet(1.0);
-        rv.set_dry(0.0);
-        rv.set_width(0.9);
-        self.fx3_reverb = Some(rv);
-      }
-      if let Some(rv) = &mut self.fx3_reverb {
-        let room = 0.2 + fx3_p1.clamp(0.0, 1.0) * 0.8;
-        let damp = 0.2 + fx3_p2.clamp(0.0, 1.0) * 0.8;
-        let mix = fx3_mix;
-        rv.set_room_size(room as f64);
-        rv.set_dampening(damp as f64);
-        rv.set_wet(1.0);
-        rv.set_dry(0.0);
-        rv.set_width(0.9);
-        let dry = out;
-        let (wl, wr) = rv.tick((dry as f64, dry as f64));
-        let lp_amt = 0.5 + 0.5 * (damp as f32);
-        self.fx3_wet_lp_l.set_hf_damp(lp_amt);
-        self.fx3_wet_lp_r.set_hf_damp(lp_amt);
-        let wet_l = self.fx3_wet_lp_l.tick(wl as f32) as f32;
-        let wet_r = self.fx3_wet_lp_r.tick(wr as f32) as f32;
-        let wet_m = 0.5 * (wet_l + wet_r);
-        out = dry * (1.0 - mix) + wet_m * mix;
-      }
-    } else if fx3_t == 3 || fx3_t == 4 || fx3_t == 5 {
-      // Phaser (3), Chorus (4), Flanger (5)
-      let rate = 0.05 + fx3_p1 * (5.0 - 0.05);
-      let depth_ms = match fx3_t {
-        4 => 6.0 * fx3_p2,
-        5 => 12.0 * fx3_p2,
-        _ => 4.0 * fx3_p2
-      };
-      if fx3_t == 3 {
-        let (wet, _) = self.phaser3.process_one(out, out, self.sr, rate, fx3_p2, 1.0);
-        out = out * (1.0 - fx3_mix) + wet * fx3_mix;
-      } else {
-        let base_ms = match fx3_t { 4 => 2.0, 5 => 15.0, _ => 3.0 };
-        let (wet, _) = self.delay1.process_one(out, out, self.sr, rate, base_ms, depth_ms, 1.0);
-        out = out * (1.0 - fx3_mix) + wet * fx3_mix;
+    // --- FX2 (stereo) ---
+    let fx2_t = params.get_i32_h(self.keys.fx2_type, 0);
+    let fx2_p1 = params.get_f32_h(self.keys.fx2_p1, 0.0);
+    let fx2_p2 = params.get_f32_h(self.keys.fx2_p2, 0.0);
+    let fx2_mix = params.get_f32_h(self.keys.fx2_p3, 0.0).clamp(0.0, 1.0);
+    if fx2_t <= 0 || fx2_mix <= 0.0005 { if fx2_t <= 0 { self.fx2_reverb = None; self.fx2_crusher = None; } }
+    else if fx2_t == 2 { let time_ms = 10.0 + fx2_p1.clamp(0.0, 1.0) * 990.0; let fb = (fx2_p2.clamp(0.0, 1.0) * 0.95).min(0.95); let mut lbuf = [l]; let mut rbuf = [r]; self.sdelay2.process_block(&mut lbuf, &mut rbuf, self.sr, time_ms, fb, fx2_mix, false); l = lbuf[0]; r = rbuf[0]; self.fx2_reverb = None; self.fx2_crusher = None; }
+    else if fx2_t == 1 { if self.fx2_reverb.is_none() { let mut rv = Freeverb::new(self.sr as usize); rv.set_room_size(0.35); rv.set_dampening(0.6); rv.set_wet(1.0); rv.set_dry(0.0); rv.set_width(0.9); self.fx2_reverb = Some(rv); } if let Some(rv) = &mut self.fx2_reverb { let room = 0.2 + fx2_p1.clamp(0.0, 1.0) * 0.8; let damp = 0.2 + fx2_p2.clamp(0.0, 1.0) * 0.8; let mix = fx2_mix; rv.set_room_size(room as f64); rv.set_dampening(damp as f64); rv.set_wet(1.0); rv.set_dry(0.0); rv.set_width(0.9); let (wl, wr) = rv.tick((l as f64, r as f64)); let lp_amt = 0.5 + 0.5 * (damp as f32); self.fx2_wet_lp_l.set_hf_damp(lp_amt); self.fx2_wet_lp_r.set_hf_damp(lp_amt); let wet_l = self.fx2_wet_lp_l.tick(wl as f32); let wet_r = self.fx2_wet_lp_r.tick(wr as f32); l = l * (1.0 - mix) + wet_l * mix; r = r * (1.0 - mix) + wet_r * mix; } }
+    else if fx2_t == 3 || fx2_t == 4 || fx2_t == 5 { let rate = 0.05 + fx2_p1 * (5.0 - 0.05); let depth_ms = match fx2_t { 4 => 6.0 * fx2_p2, 5 => 12.0 * fx2_p2, _ => 4.0 * fx2_p2 }; if fx2_t == 3 { let (wl, wr) = self.phaser2.process_one(l, r, self.sr, rate, fx2_p2, 1.0); l = l * (1.0 - fx2_mix) + wl * fx2_mix; r = r * (1.0 - fx2_mix) + wr * fx2_mix; } else { let base_ms = match fx2_t { 4 => 2.0, 5 => 15.0, _ => 3.0 }; let (wl, wr) = self.delay2.process_one(l, r, self.sr, rate, base_ms, depth_ms, 1.0); l = l * (1.0 - fx2_mix) + wl * fx2_mix; r = r * (1.0 - fx2_mix) + wr * fx2_mix; } self.fx2_reverb = None; self.fx2_crusher = None; }
+    else if fx2_t == 6 { let drive_db = fx2_p1.clamp(0.0, 1.0) * 20.0; let g = (10.0_f32).powf(drive_db / 20.0); let tone = fx2_p2.clamp(0.0, 1.0); let xl = (l * g).tanh(); let xr = (r * g).tanh(); let lp_amt = 0.3 + 0.6 * (1.0 - tone); self.fx2_wet_lp_l.set_hf_damp(lp_amt); self.fx2_wet_lp_r.set_hf_damp(lp_amt); let yl_lp = self.fx2_wet_lp_l.tick(xl); let yr_lp = self.fx2_wet_lp_r.tick(xr); let sl = yl_lp * (1.0 - tone) + (xl - yl_lp) * tone; let sr = yr_lp * (1.0 - tone) + (xr - yr_lp) * tone; l = l * (1.0 - fx2_mix) + sl * fx2_mix; r = r * (1.0 - fx2_mix) + sr * fx2_mix; self.fx2_reverb = None; }
+    else if fx2_t == 7 { let drive = fx2_p2.clamp(0.0, 1.0) * 10.0; let g = 1.0 + drive; let cur = fx2_p1.clamp(0.0, 1.0); let shape = |x: f32| if cur < 0.34 { (x * g).tanh() } else if cur < 0.67 { (x * g).clamp(-1.0, 1.0) } else { let m = (x * g + 1.0).abs().rem_euclid(4.0); ((m - 2.0).abs() - 1.0).clamp(-1.0, 1.0) }; l = l * (1.0 - fx2_mix) + shape(l) * fx2_mix; r = r * (1.0 - fx2_mix) + shape(r) * fx2_mix; self.fx2_reverb = None; self.fx2_crusher = None; }
+    else if fx2_t == 8 { if self.fx2_crusher.is_none() { self.fx2_crusher = Some(Bitcrusher::new()); } if let Some(cr) = &mut self.fx2_crusher { let bits = 4.0 + fx2_p1.clamp(0.0, 1.0) * 12.0; let fac = 1.0 + fx2_p2.clamp(0.0, 1.0) * 15.0; cr.set_bits(bits as u8); cr.set_factor(fac as u32); cr.set_mix(fx2_mix); let mut lbuf = [l]; let mut rbuf = [r]; cr.process(&mut lbuf, &mut rbuf); l = lbuf[0]; r = rbuf[0]; } }
+    // --- FX3 (stereo) ---
+    let fx3_t = params.get_i32_h(self.keys.fx3_type, 0);
+    let fx3_p1 = params.get_f32_h(self.keys.fx3_p1, 0.0);
+    let fx3_p2 = params.get_f32_h(self.keys.fx3_p2, 0.0);
+    let fx3_mix = params.get_f32_h(self.keys.fx3_p3, 0.0).clamp(0.0, 1.0);
+    if fx3_t <= 0 || fx3_mix <= 0.0005 { if fx3_t <= 0 { self.fx3_reverb = None; self.fx3_crusher = None; } }
+    else if fx3_t == 2 { let time_ms = 10.0 + fx3_p1.clamp(0.0, 1.0) * 990.0; let fb = (fx3_p2.clamp(0.0, 1.0) * 0.95).min(0.95); let mut lbuf = [l]; let mut rbuf = [r]; self.sdelay3.process_block(&mut lbuf, &mut rbuf, self.sr, time_ms, fb, fx3_mix, false); l = lbuf[0]; r = rbuf[0]; self.fx3_reverb = None; self.fx3_crusher = None; }
+    else if fx3_t == 1 { if self.fx3_reverb.is_none() { let mut rv = Freeverb::new(self.sr as usize); rv.set_room_size(0.35); rv.set_dampening(0.6); rv.set_wet(1.0); rv.set_dry(0.0); rv.set_width(0.9); self.fx3_reverb = Some(rv); } if let Some(rv) = &mut self.fx3_reverb { let room = 0.2 + fx3_p1.clamp(0.0, 1.0) * 0.8; let damp = 0.2 + fx3_p2.clamp(0.0, 1.0) * 0.8; let mix = fx3_mix; rv.set_room_size(room as f64); rv.set_dampening(damp as f64); rv.set_wet(1.0); rv.set_dry(0.0); rv.set_width(0.9); let (wl, wr) = rv.tick((l as f64, r as f64)); let lp_amt = 0.5 + 0.5 * (damp as f32); self.fx3_wet_lp_l.set_hf_damp(lp_amt); self.fx3_wet_lp_r.set_hf_damp(lp_amt); let wet_l = self.fx3_wet_lp_l.tick(wl as f32); let wet_r = self.fx3_wet_lp_r.tick(wr as f32); l = l * (1.0 - mix) + wet_l * mix; r = r * (1.0 - mix) + wet_r * mix; } }
+    else if fx3_t == 3 || fx3_t == 4 || fx3_t == 5 { let rate = 0.05 + fx3_p1 * (5.0 - 0.05); let depth_ms = match fx3_t { 4 => 6.0 * fx3_p2, 5 => 12.0 * fx3_p2, _ => 4.0 * fx3_p2 }; if fx3_t == 3 { let (wl, wr) = self.phaser3.process_one(l, r, self.sr, rate, fx3_p2, 1.0); l = l * (1.0 - fx3_mix) + wl * fx3_mix; r = r * (1.0 - fx3_mix) + wr * fx3_mix; } else { let base_ms = match fx3_t { 4 => 2.0, 5 => 15.0, _ => 3.0 }; let (wl, wr) = self.delay3.process_one(l, r, self.sr, rate, base_ms, depth_ms, 1.0); l = l * (1.0 - fx3_mix) + wl * fx3_mix; r = r * (1.0 - fx3_mix) + wr * fx3_mix; } self.fx3_reverb = None; self.fx3_crusher = None; }
+    else if fx3_t == 6 { let drive_db = fx3_p1.clamp(0.0, 1.0) * 20.0; let g = (10.0_f32).powf(drive_db / 20.0); let tone = fx3_p2.clamp(0.0, 1.0); let xl = (l * g).tanh(); let xr = (r * g).tanh(); let lp_amt = 0.3 + 0.6 * (1.0 - tone); self.fx3_wet_lp_l.set_hf_damp(lp_amt); self.fx3_wet_lp_r.set_hf_damp(lp_amt); let yl_lp = self.fx3_wet_lp_l.tick(xl); let yr_lp = self.fx3_wet_lp_r.tick(xr); let sl = yl_lp * (1.0 - tone) + (xl - yl_lp) * tone; let sr = yr_lp * (1.0 - tone) + (xr - yr_lp) * tone; l = l * (1.0 - fx3_mix) + sl * fx3_mix; r = r * (1.0 - fx3_mix) + sr * fx3_mix; self.fx3_reverb = None; }
+    else if fx3_t == 7 { let drive = fx3_p2.clamp(0.0, 1.0) * 10.0; let g = 1.0 + drive; let cur = fx3_p1.clamp(0.0, 1.0); let shape = |x: f32| if cur < 0.34 { (x * g).tanh() } else if cur < 0.67 { (x * g).clamp(-1.0, 1.0) } else { let m = (x * g + 1.0).abs().rem_euclid(4.0); ((m - 2.0).abs() - 1.0).clamp(-1.0, 1.0) }; l = l * (1.0 - fx3_mix) + shape(l) * fx3_mix; r = r * (1.0 - fx3_mix) + shape(r) * fx3_mix; self.fx3_reverb = None; self.fx3_crusher = None; }
+    else if fx3_t == 8 { if self.fx3_crusher.is_none() { self.fx3_crusher = Some(Bitcrusher::new()); } if let Some(cr) = &mut self.fx3_crusher { let bits = 4.0 + fx3_p1.clamp(0.0, 1.0) * 12.0; let fac = 1.0 + fx3_p2.clamp(0.0, 1.0) * 15.0; cr.set_bits(bits as u8); cr.set_factor(fac as u32); cr.set_mix(fx3_mix); let mut lbuf = [l]; let mut rbuf = [r]; cr.process(&mut lbuf, &mut rbuf); l = lbuf[0]; r = rbuf[0]; } }
+    // --- FX4 (stereo) ---
+    let fx4_t = params.get_i32_h(self.keys.fx4_type, 0);
+    let fx4_p1 = params.get_f32_h(self.keys.fx4_p1, 0.0);
+    let fx4_p2 = params.get_f32_h(self.keys.fx4_p2, 0.0);
+    let fx4_mix = params.get_f32_h(self.keys.fx4_p3, 0.0).clamp(0.0, 1.0);
+    if fx4_t <= 0 || fx4_mix <= 0.0005 { if fx4_t <= 0 { self.fx4_reverb = None; self.fx4_crusher = None; } }
+    else if fx4_t == 2 { let time_ms = 10.0 + fx4_p1.clamp(0.0, 1.0) * 990.0; let fb = (fx4_p2.clamp(0.0, 1.0) * 0.95).min(0.95); let mut lbuf = [l]; let mut rbuf = [r]; self.sdelay4.process_block(&mut lbuf, &mut rbuf, self.sr, time_ms, fb, fx4_mix, false); l = lbuf[0]; r = rbuf[0]; self.fx4_reverb = None; self.fx4_crusher = None; }
+    else if fx4_t == 1 { if self.fx4_reverb.is_none() { let mut rv = Freeverb::new(self.sr as usize); rv.set_room_size(0.35); rv.set_dampening(0.6); rv.set_wet(1.0); rv.set_dry(0.0); rv.set_width(0.9); self.fx4_reverb = Some(rv); } if let Some(rv) = &mut self.fx4_reverb { let room = 0.2 + fx4_p1.clamp(0.0, 1.0) * 0.8; let damp = 0.2 + fx4_p2.clamp(0.0, 1.0) * 0.8; let mix = fx4_mix; rv.set_room_size(room as f64); rv.set_dampening(damp as f64); rv.set_wet(1.0); rv.set_dry(0.0); rv.set_width(0.9); let (wl, wr) = rv.tick((l as f64, r as f64)); let lp_amt = 0.5 + 0.5 * (damp as f32); self.fx4_wet_lp_l.set_hf_damp(lp_amt); self.fx4_wet_lp_r.set_hf_damp(lp_amt); let wet_l = self.fx4_wet_lp_l.tick(wl as f32); let wet_r = self.fx4_wet_lp_r.tick(wr as f32); l = l * (1.0 - mix) + wet_l * mix; r = r * (1.0 - mix) + wet_r * mix; } }
+    else if fx4_t == 3 || fx4_t == 4 || fx4_t == 5 { let rate = 0.05 + fx4_p1 * (5.0 - 0.05); let depth_ms = match fx4_t { 4 => 6.0 * fx4_p2, 5 => 12.0 * fx4_p2, _ => 4.0 * fx4_p2 }; if fx4_t == 3 { let (wl, wr) = self.phaser4.process_one(l, r, self.sr, rate, fx4_p2, 1.0); l = l * (1.0 - fx4_mix) + wl * fx4_mix; r = r * (1.0 - fx4_mix) + wr * fx4_mix; } else { let base_ms = match fx4_t { 4 => 2.0, 5 => 15.0, _ => 3.0 }; let (wl, wr) = self.delay4.process_one(l, r, self.sr, rate, base_ms, depth_ms, 1.0); l = l * (1.0 - fx4_mix) + wl * fx4_mix; r = r * (1.0 - fx4_mix) + wr * fx4_mix; } self.fx4_reverb = None; self.fx4_crusher = None; }
+    else if fx4_t == 6 { let drive_db = fx4_p1.clamp(0.0, 1.0) * 20.0; let g = (10.0_f32).powf(drive_db / 20.0); let tone = fx4_p2.clamp(0.0, 1.0); let xl = (l * g).tanh(); let xr = (r * g).tanh(); let lp_amt = 0.3 + 0.6 * (1.0 - tone); self.fx4_wet_lp_l.set_hf_damp(lp_amt); self.fx4_wet_lp_r.set_hf_damp(lp_amt); let yl_lp = self.fx4_wet_lp_l.tick(xl); let yr_lp = self.fx4_wet_lp_r.tick(xr); let sl = yl_lp * (1.0 - tone) + (xl - yl_lp) * tone; let sr = yr_lp * (1.0 - tone) + (xr - yr_lp) * tone; l = l * (1.0 - fx4_mix) + sl * fx4_mix; r = r * (1.0 - fx4_mix) + sr * fx4_mix; self.fx4_reverb = None; }
+    else if fx4_t == 7 { let drive = fx4_p2.clamp(0.0, 1.0) * 10.0; let g = 1.0 + drive; let cur = fx4_p1.clamp(0.0, 1.0); let shape = |x: f32| if cur < 0.34 { (x * g).tanh() } else if cur < 0.67 { (x * g).clamp(-1.0, 1.0) } else { let m = (x * g + 1.0).abs().rem_euclid(4.0); ((m - 2.0).abs() - 1.0).clamp(-1.0, 1.0) }; l = l * (1.0 - fx4_mix) + shape(l) * fx4_mix; r = r * (1.0 - fx4_mix) + shape(r) * fx4_mix; self.fx4_reverb = None; self.fx4_crusher = None; }
+    else if fx4_t == 8 { if self.fx4_crusher.is_none() { self.fx4_crusher = Some(Bitcrusher::new()); } if let Some(cr) = &mut self.fx4_crusher { let bits = 4.0 + fx4_p1.clamp(0.0, 1.0) * 12.0; let fac = 1.0 + fx4_p2.clamp(0.0, 1.0) * 15.0; cr.set_bits(bits as u8); cr.set_factor(fac as u32); cr.set_mix(fx4_mix); let mut lbuf = [l]; let mut rbuf = [r]; cr.process(&mut lbuf, &mut rbuf); l = lbuf[0]; r = rbuf[0]; } }
+    // --- EQ (stereo) ---
+    let q = 1.0_f32; let mut any_nonzero = false;
+    for i in 0..8 { let db = params.get_f32_h(self.keys.eq_bands[i], 0.0).clamp(-12.0, 12.0); if (db - self.eq_last_db[i]).abs() > 1e-6 { self.eq_bands[i].set_peaking(self.sr, self.eq_centers[i], q, db); self.eq_bands_r[i].set_peaking(self.sr, self.eq_centers[i], q, db); self.eq_last_db[i] = db; } if db.abs() > 1e-3 { any_nonzero = true; } }
+    if any_nonzero { for i in 0..8 { l = self.eq_bands[i].process(l); r = self.eq_bands_r[i].process(r); } }
+    Some((l, r))
+  }
+
+  fn apply_stereo_mixer(&mut self, params: &ParamStore, l: f32, r: f32) -> (f32, f32) {
+    let mut l = l;
+    let mut r = r;
+    let pan = params.get_f32_h(self.keys.mix_pan, 0.0).clamp(-1.0, 1.0);
+    let theta = (pan + 1.0) * std::f32::consts::FRAC_PI_4; let gl = theta.cos(); let gr = theta.sin(); l *= gl; r *= gr;
+    let vol = params.get_f32_h(self.keys.mix_volume, 1.0).clamp(0.0, 1.0); l *= vol; r *= vol;
+    let haas = params.get_f32_h(self.keys.mix_haas, 0.0).clamp(0.0, 1.0);
+    if haas > 0.0005 { let rd = if self.haas_wr >= self.haas_d { self.haas_wr - self.haas_d } else { self.haas_wr + self.haas_len - self.haas_d }; let delayed_l = self.haas_buf[rd]; self.haas_buf[self.haas_wr] = l; self.haas_wr += 1; if self.haas_wr >= self.haas_len { self.haas_wr = 0; } l = l * (1.0 - haas) + delayed_l * haas; } else { self.haas_buf[self.haas_wr] = l; self.haas_wr += 1; if self.haas_wr >= self.haas_len { self.haas_wr = 0; } }
+    let comp = params.get_f32_h(self.keys.mix_comp, 0.0).clamp(0.0, 1.0);
+    if comp > 0.001 { let drive = 1.0 + 8.0 * comp; let id = 1.0 / drive.tanh(); l = (l * drive).tanh() * id; r = (r * drive).tanh() * id; }
+    (l, r)
+  }
+}
+
+pub struct Part {
+  voices: Vec<Voice>,
+  sr: f32,
+  next_voice: usize,
+  acid: Acid303,
+  acid_keys: AcidParamKeys,
+  karplus: KarplusStrong,
+  karplus_keys: KSParamKeys,
+  korus: Korus,
+  korus_keys: KorusParamKeys,
+  resonator: ResonatorBank,
+  resonator_keys: ResonatorParamKeys,
+  sampler: Sampler,
+  sampler_keys: SamplerParamKeys,
+  drum: DrumPlayer,
+  drum_keys: DrumParamKeys,
+  // Drum slots routed away from the part output this frame (aux + sub-channels), read by Mixer
+  drum_buses: [DrumBusFrame; DRUM_BUSES],
+  granular: Granular,
+  granular_keys: GranularParamKeys,
+  wavetable: Option<WavetableBank>,
+  fm: FmSynth,
+  fm_keys: FmParamKeys,
+  drum_synth: DrumSynth,
+  drum_synth_keys: DrumSynthParamKeys,
+  strip: ChannelStrip,
+  paths: ParamPaths,
+  perf: Performance,
+  mods: MatrixState,
+  mono: MonoState,
+  unison: Unison,
+}
+
+impl Part {
+  pub fn new(sr: f32, poly: usize, idx: usize) -> Self {
+    let mut voices = Vec::with_capacity(poly);
+    for _ in 0..poly { voices.push(Voice::new(sr)); }
+    // Granular plays whatever sample is loaded into the Sampler
+    let sampler = Sampler::new(sr);
+    let granular = Granular::new(sr, sampler.buffer_handle());
+    Self { voices, sr, next_voice: 0,
+      acid: Acid303::new(sr),
+      acid_keys: AcidParamKeys {
+        module_kind: hash_path(&format!("part/{}/module_kind", idx)),
+        wave: hash_path(&format!("part/{}/acid/wave", idx)),
+        cutoff: hash_path(&format!("part/{}/acid/cutoff", idx)),
+        reso: hash_path(&format!("part/{}/acid/reso", idx)),
+        envmod: hash_path(&format!("part/{}/acid/envmod", idx)),
+        decay: hash_path(&format!("part/{}/acid/decay", idx)),
+        accent: hash_path(&format!("part/{}/acid/accent", idx)),
+        slide: hash_path(&format!("part/{}/acid/slide", idx)),
+        drive: hash_path(&format!("part/{}/acid/drive", idx)),
+        vel_amp: hash_path(&format!("part/{}/acid/vel_amp", idx)),
+        vel_filter: hash_path(&format!("part/{}/acid/vel_filter", idx)),
+      },
+      karplus: KarplusStrong::new(sr),
+      karplus_keys: KSParamKeys {
+        module_kind: hash_path(&format!("part/{}/module_kind", idx)),
+        decay: hash_path(&format!("part/{}/ks/decay", idx)),
+        damp: hash_path(&format!("part/{}/ks/damp", idx)),
+        excite: hash_path(&format!("part/{}/ks/excite", idx)),
+        tune: hash_path(&format!("part/{}/ks/tune", idx)),
+        pick: hash_path(&format!("part/{}/ks/pick", idx)),
+        body: hash_path(&format!("part/{}/ks/body", idx)),
+        sympathetic: hash_path(&format!("part/{}/ks/sympathetic", idx)),
+        vel_amp: hash_path(&format!("part/{}/ks/vel_amp", idx)),
+        vel_filter: hash_path(&format!("part/{}/ks/vel_filter", idx)),
+      },
+      korus: Korus::new(sr),
+      korus_keys: KorusParamKeys::new(idx),
+      resonator: ResonatorBank::new(sr),
+      resonator_keys: ResonatorParamKeys {
+        module_kind: hash_path(&format!("part/{}/module_kind", idx)),
+        pitch: hash_path(&format!("part/{}/resonator/pitch", idx)),
+        decay: hash_path(&format!("part/{}/resonator/decay", idx)),
+        brightness: hash_path(&format!("part/{}/resonator/brightness", idx)),
+        bank_size: hash_path(&format!("part/{}/resonator/bank_size", idx)),
+        mode: hash_path(&format!("part/{}/resonator/mode", idx)),
+        inharmonicity: hash_path(&format!("part/{}/resonator/inharmonicity", idx)),
+        feedback: hash_path(&format!("part/{}/resonator/feedback", idx)),
+        drive: hash_path(&format!("part/{}/resonator/drive", idx)),
+        exciter_type: hash_path(&format!("part/{}/resonator/exciter_type", idx)),
+        exciter_amount: hash_path(&format!("part/{}/resonator/exciter_amount", idx)),
+        noise_color: hash_path(&format!("part/{}/resonator/noise_color", idx)),
+        strike_rate: hash_path(&format!("part/{}/resonator/strike_rate", idx)),
+        stereo_width: hash_path(&format!("part/{}/resonator/stereo_width", idx)),
+        randomize: hash_path(&format!("part/{}/resonator/randomize", idx)),
+        body_blend: hash_path(&format!("part/{}/resonator/body_blend", idx)),
+        output_gain: hash_path(&format!("part/{}/resonator/output_gain", idx)),
+        pressure_amp: hash_path(&format!("part/{}/resonator/pressure_amp", idx)),
+        slide_bright: hash_path(&format!("part/{}/resonator/slide_bright", idx)),
+      },
+  sampler,
+      sampler_keys: SamplerParamKeys {
+        module_kind: hash_path(&format!("part/{}/module_kind", idx)),
+        sample_start: hash_path(&format!("part/{}/sampler/sample_start", idx)),
+        sample_end: hash_path(&format!("part/{}/sampler/sample_end", idx)),
+        pitch_semitones: hash_path(&format!("part/{}/sampler/pitch_semitones", idx)),
+        pitch_cents: hash_path(&format!("part/{}/sampler/pitch_cents", idx)),
+        playback_mode: hash_path(&format!("part/{}/sampler/playback_mode", idx)),
+        loop_start: hash_path(&format!("part/{}/sampler/loop_start", idx)),
+        loop_end: hash_path(&format!("part/{}/sampler/loop_end", idx)),
+        loop_mode: hash_path(&format!("part/{}/sampler/loop_mode", idx)),
+        smoothness: hash_path(&format!("part/{}/sampler/smoothness", idx)),
+  retrig_mode: hash_path(&format!("part/{}/sampler/retrig_mode", idx)),
+        warp: hash_path(&format!("part/{}/sampler/warp", idx)),
+        warp_bars: hash_path(&format!("part/{}/sampler/warp_bars", idx)),
+        warp_grain: hash_path(&format!("part/{}/sampler/warp_grain", idx)),
+        quality: hash_path(&format!("part/{}/sampler/quality", idx)),
+        attack: hash_path(&format!("part/{}/sampler/attack", idx)),
+        decay: hash_path(&format!("part/{}/sampler/decay", idx)),
+        sustain: hash_path(&format!("part/{}/sampler/sustain", idx)),
+        release: hash_path(&format!("part/{}/sampler/release", idx)),
+        pressure_amp: hash_path(&format!("part/{}/sampler/pressure_amp", idx)),
+        slide_filter: hash_path(&format!("part/{}/sampler/slide_filter", idx)),
+      },
+  drum: DrumPlayer::new(sr),
+  drum_keys: DrumParamKeys::new(idx),
+  drum_buses: [DrumBusFrame::default(); DRUM_BUSES],
+  granular,
+  granular_keys: GranularParamKeys::new(idx),
+  wavetable: None,
+  fm: FmSynth::new(sr),
+  fm_keys: FmParamKeys::new(idx),
+  drum_synth: DrumSynth::new(sr),
+  drum_synth_keys: DrumSynthParamKeys::new(idx),
+  strip: ChannelStrip::new(sr, &format!("part/{}/", idx)),
+  paths: ParamPaths::new(idx), perf: Performance::new(), mods: MatrixState::new(sr, idx), mono: MonoState::new(), unison: Unison::new() }
+  }
+  // accent/slide are per-step flags from the sequencer; only the Acid303 uses them. The voice
+  // keeps `channel` so per-channel expression reaches only the notes started on it
+  pub fn note_on(&mut self, params: &ParamStore, note: u8, vel: f32, accent: bool, slide: bool, channel: u8) {
+    let mk = params.get_i32_h(self.paths.module_kind, 0);
+    let vel = velocity::apply_curve(vel, params.get_f32_h(self.paths.vel_curve, 0.0));
+    self.perf.held[note as usize & 127] = true;
+    self.perf.sustained[note as usize & 127] = false;
+    self.perf.poly_pressure[note as usize & 127] = 0.0;
+    self.mods.note = note;
+    self.mods.vel = vel;
+    self.mods.rng ^= self.mods.rng << 13; self.mods.rng ^= self.mods.rng >> 17; self.mods.rng ^= self.mods.rng << 5;
+    self.mods.random = (self.mods.rng as f32 / u32::MAX as f32) * 2.0 - 1.0;
+    self.mods.env.gate_on();
+    for (lfo, keys) in self.mods.lfos.iter_mut().zip(self.mods.lfo_keys.iter()) { lfo.note_on(params, keys); }
+    match mk {
+      0 | 9 if params.get_i32_h(self.paths.voice_mode, 0) != 0 => { self.voices[0].channel = channel; self.mono_note_on(params, note, vel) }
+      0 | 9 => { // Analog / wavetable poly
+        if self.mono.mode != 0 { self.mono.mode = 0; self.mono.keys.clear(); }
+        // Retrigger a key on its own channel; MPE members may hold the same key on different channels
+        for v in &mut self.voices { if v.note == note && v.channel == channel && v.is_active() { v.note_off(); } }
+        let mut idx = None; for (i, v) in self.voices.iter().enumerate() { if !v.is_active() { idx = Some(i); break; } }
+        let i = idx.unwrap_or_else(|| { let i = self.next_voice; self.next_voice = (self.next_voice + 1) % self.voices.len(); i });
+        self.voices[i].note_on(params, note, vel);
+        self.voices[i].channel = channel;
+        let phase = params.get_f32_h(self.paths.unison_phase, 0.0);
+        if phase > 0.0 { self.voices[i].scatter_phases(phase, &mut self.mods.rng); }
       }
-      self.fx3_reverb = None; self.fx3_crusher = None;
-    } else if fx3_t == 6 {
-      // Distortion: Drive (0..20db), Tone (0..1), Mix
-      let dry = out;
-      let drive_db = (fx3_p1.clamp(0.0, 1.0)) * 20.0;
-      let g = (10.0_f32).powf(drive_db / 20.0);
-      let x = (dry * g).tanh();
-      let tone = fx3_p2.clamp(0.0, 1.0);
-      let lp_amt = 0.3 + 0.6 * (1.0 - tone);
-      self.fx3_wet_lp_l.set_hf_damp(lp_amt);
-      let y_lp = self.fx3_wet_lp_l.tick(x);
-      let y_hp = x - y_lp;
-      let shaped = y_lp * (1.0 - tone) + y_hp * tone;
-      out = dry * (1.0 - fx3_mix) + shaped * fx3_mix;
-      self.fx3_reverb = None; self.fx3_crusher = None;
-    } else if fx3_t == 7 {
-      // Waveshaper
-      let dry = out;
-      let drive = fx3_p2.clamp(0.0, 1.0) * 10.0;
-      let g = 1.0 + drive;
-      let cur = fx3_p1.clamp(0.0, 1.0);
-      let xin = dry * g;
-      let shaped = if cur < 0.34 {
-        xin.tanh()
-      } else if cur < 0.67 {
-        xin.clamp(-1.0, 1.0)
-      } else {
-        let m = (xin + 1.0).abs().rem_euclid(4.0);
-        ((m - 2.0).abs() - 1.0).clamp(-1.0, 1.0)
-      };
-      out = dry * (1.0 - fx3_mix) + shaped * fx3_mix;
-      self.fx3_reverb = None; self.fx3_crusher = None;
-    } else if fx3_t == 8 {
-      if self.fx3_crusher.is_none() { self.fx3_crusher = Some(Bitcrusher::new()); }
-      if let Some(cr) = &mut self.fx3_crusher {
-        let bits = 4.0 + fx3_p1.clamp(0.0, 1.0) * 12.0;
-        let fac = 1.0 + fx3_p2.clamp(0.0, 1.0) * 15.0;
-        cr.set_bits(bits as u8);
-        cr.set_factor(fac as u32);
-        cr.set_mix(fx3_mix);
-        let mut lbuf = [out]; let mut rbuf = [out];
-        cr.process(&mut lbuf, &mut rbuf);
-        out = 0.5 * (lbuf[0] + rbuf[0]);
+      1 => { self.acid.note_on(note, vel, accent, slide); }
+      2 => { self.karplus.note_on(note, vel); }
+      3 => { self.resonator.note_on(note, vel, channel); }
+      4 => { // Sampler
+        let retrig_i = params.get_i32_h(self.sampler_keys.retrig_mode, 0);
+        let retrig_mode = crate::engine::modules::sampler::RetrigMode::from_index(retrig_i);
+        self.sampler.note_on(note, vel, retrig_mode, channel);
       }
+      5 => { self.drum.note_on(params, &self.drum_keys, note, vel); }
+      6 => { self.korus.note_on(params, &self.korus_keys, note, vel, channel); }
+      7 => { self.granular.note_on(note, vel); }
+      8 => { self.drum_synth.note_on(note, vel); }
+      10 => { self.fm.note_on(note, vel, channel); }
+      _ => {}
     }
-    // FX4
-    let fx4_t = params.get_i32_h(self.paths.fx4_type, 0);
-    let fx4_p1 = params.get_f32_h(self.paths.fx4_p1, 0.0);
-    let fx4_p2 = params.get_f32_h(self.paths.fx4_p2, 0.0);
-    let fx4_mix = params.get_f32_h(self.paths.fx4_p3, 0.0).clamp(0.0, 1.0);
-    if fx4_t <= 0 || fx4_mix <= 0.0005 {
-      // No Effect
-      if fx4_t <= 0 { self.fx4_reverb = None; self.fx4_crusher = None; }
-    } else if fx4_t == 2 {
-      // Simple delay
-      let time_ms = 10.0 + fx4_p1.clamp(0.0, 1.0) * 990.0;
-      let fb = (fx4_p2.clamp(0.0, 1.0) * 0.95).min(0.95);
-      let mut lbuf = [out]; let mut rbuf = [out];
-      self.sdelay4.process_block(&mut lbuf, &mut rbuf, self.sr, time_ms, fb, fx4_mix, false);
-      out = 0.5 * (lbuf[0] + rbuf[0]);
-      self.fx4_reverb = None; self.fx4_crusher = None;
-    } else if fx4_t == 1 {
-      // Reverb: Room, Damp, Mix
-      if self.fx4_reverb.is_none() {
-        let mut rv = Freeverb::new(self.sr as usize);
-        rv.set_room_size(0.35);
-        rv.set_dampening(0.6);
-        rv.set_wet(1.0);
-        rv.set_dry(0.0);
-        rv.set_width(0.9);
-        self.fx4_reverb = Some(rv);
+  }
+  // Key up; held by the sustain pedal until it is released
+  pub fn note_off(&mut self, note: u8) {
+    let n = note as usize & 127;
+    self.perf.held[n] = false;
+    self.mono.keys.retain(|&k| k != note);
+    if self.perf.sustain {
+      self.perf.sustained[n] = true;
+      return;
+    }
+    self.release_note(note);
+  }
+
+  fn release_note(&mut self, note: u8) {
+    if !self.perf.held.iter().any(|&h| h) && !self.perf.sustained.iter().any(|&s| s) { self.mods.env.gate_off(); }
+    if self.mono.mode != 0 {
+      self.mono_release(note);
+    } else {
+      // Stop all voices with this note to guarantee preview stops fully
+      for v in &mut self.voices { if v.note == note && v.is_active() { v.note_off(); } }
+    }
+    self.acid.note_off(note);
+    self.karplus.note_off(note);
+    self.resonator.note_off(note);
+  self.sampler.note_off(note);
+  // Drum voices may have been triggered; attempt to stop matching slot
+  self.drum.note_off(note);
+  self.korus.note_off(note);
+  self.granular.note_off(note);
+  self.drum_synth.note_off(note);
+  self.fm.note_off(note);
+  }
+
+  // Mono/legato: one voice follows the priority note among the held keys
+  fn mono_note_on(&mut self, params: &ParamStore, note: u8, vel: f32) {
+    let was_poly = self.mono.mode == 0;
+    self.mono.mode = params.get_i32_h(self.paths.voice_mode, 0);
+    self.mono.priority = params.get_i32_h(self.paths.voice_priority, 0);
+    self.mono.glide_s = params.get_f32_h(self.paths.voice_glide, 0.0).clamp(0.0, 10.0);
+    self.mono.per_octave = params.get_i32_h(self.paths.voice_glide_mode, 0) != 0;
+    if was_poly {
+      for v in self.voices.iter_mut().skip(1) { v.note_off(); }
+    }
+    let overlapping = !self.mono.keys.is_empty();
+    self.mono.keys.retain(|&k| k != note);
+    self.mono.keys.push(note);
+    let Some(target) = self.mono.pick() else { return };
+    let v = &mut self.voices[0];
+    let sounding = v.active;
+    if sounding && target == v.note && overlapping { return; } // a lower-priority key was added
+    if !sounding && !v.is_active() {
+      v.note_on(params, target, vel);
+      let phase = params.get_f32_h(self.paths.unison_phase, 0.0);
+      if phase > 0.0 { v.scatter_phases(phase, &mut self.mods.rng); }
+      return;
+    }
+    // Legato only glides between overlapping keys; mono glides from wherever the voice is
+    let glide = if self.mono.mode == 2 && !overlapping { 0.0 } else { self.mono.glide_s };
+    v.glide_to(target, glide, self.mono.per_octave, self.sr);
+    if self.mono.mode != 2 || !overlapping { v.retrigger(vel); }
+  }
+
+  fn mono_release(&mut self, note: u8) {
+    let target = self.mono.pick();
+    let (glide_s, per_octave, legato) = (self.mono.glide_s, self.mono.per_octave, self.mono.mode == 2);
+    let v = &mut self.voices[0];
+    match target {
+      None => { if v.note == note { v.note_off(); } }
+      // Back to a held key: glide there, retriggering unless legato
+      Some(t) if t != v.note && v.active => {
+        let vel = v.vel;
+        v.glide_to(t, glide_s, per_octave, self.sr);
+        if !legato { v.retrigger(vel); }
       }
-      if let Some(rv) = &mut self.fx4_reverb {
-        let room = 0.2 + fx4_p1.clamp(0.0, 1.0) * 0.8;
-        let damp = 0.2 + fx4_p2.clamp(0.0, 1.0) * 0.8;
-        let mix = fx4_mix;
-        rv.set_room_size(room as f64);
-        rv.set_dampening(damp as f64);
-        rv.set_wet(1.0);
-        rv.set_dry(0.0);
-        rv.set_width(0.9);
-        let dry = out;
-        let (wl, wr) = rv.tick((dry as f64, dry as f64));
-        let lp_amt = 0.5 + 0.5 * (damp as f32);
-        self.fx4_wet_lp_l.set_hf_damp(lp_amt);
-        self.fx4_wet_lp_r.set_hf_damp(lp_amt);
-        let wet_l = self.fx4_wet_lp_l.tick(wl as f32) as f32;
-        let wet_r = self.fx4_wet_lp_r.tick(wr as f32) as f32;
-        let wet_m = 0.5 * (wet_l + wet_r);
-        out = dry * (1.0 - mix) + wet_m * mix;
+      _ => {}
+    }
+  }
+
+  pub fn set_sustain(&mut self, on: bool) {
+    self.perf.sustain = on;
+    if on { return; }
+    for n in 0..128 {
+      if self.perf.sustained[n] {
+        self.perf.sustained[n] = false;
+        if !self.perf.held[n] { self.release_note(n as u8); }
       }
-    } else if fx4_t == 3 || fx4_t == 4 || fx4_t == 5 {
-      // Phaser (3), Chorus (4), Flanger (5)
-      let rate = 0.05 + fx4_p1 * (5.0 - 0.05);
-      let depth_ms = match fx4_t {
-        4 => 6.0 * fx4_p2,
-        5 => 12.0 * fx4_p2,
-        _ => 4.0 * fx4_p2
+    }
+  }
+
+  pub fn set_pitch_bend(&mut self, value: f32) { self.perf.bend = value.clamp(-1.0, 1.0); }
+
+  pub fn set_mod_wheel(&mut self, value: f32) { self.perf.wheel = value.clamp(0.0, 1.0); }
+
+  // note = None is channel pressure, Some(note) is poly pressure for that key
+  pub fn set_aftertouch(&mut self, note: Option<u8>, value: f32) {
+    let value = value.clamp(0.0, 1.0);
+    match note {
+      Some(n) => self.perf.poly_pressure[n as usize & 127] = value,
+      None => self.perf.pressure = value,
+    }
+  }
+
+  fn channel_role(&self, params: &ParamStore, channel: u8) -> ChannelRole {
+    channel_role(params.get_i32_h(self.paths.mpe_zone, 0), params.get_i32_h(self.paths.mpe_members, MPE_MEMBERS), channel)
+  }
+
+  // Channel pitch bend, -1..1. The master channel bends the whole part (scaled by bend_range),
+  // a member channel its own notes (scaled by mpe/bend_range). Member state is held until the next
+  // message on the channel, so controllers sent ahead of a note-on apply to that note
+  pub fn set_note_bend(&mut self, params: &ParamStore, channel: u8, value: f32) {
+    let value = value.clamp(-1.0, 1.0);
+    match self.channel_role(params, channel) {
+      ChannelRole::Master => self.perf.bend = value,
+      ChannelRole::Member => {
+        let range = params.get_f32_h(self.paths.mpe_bend_range, MPE_MEMBER_BEND_RANGE).clamp(0.0, 96.0);
+        self.perf.channels[channel as usize & 15].bend_cents = value * range * 100.0;
+      }
+      ChannelRole::Outside => {}
+    }
+  }
+
+  // Channel pressure, 0..1; the master channel presses every note of the part
+  pub fn set_note_pressure(&mut self, params: &ParamStore, channel: u8, value: f32) {
+    let value = value.clamp(0.0, 1.0);
+    match self.channel_role(params, channel) {
+      ChannelRole::Master => self.perf.pressure = value,
+      ChannelRole::Member => self.perf.channels[channel as usize & 15].pressure = value,
+      ChannelRole::Outside => {}
+    }
+  }
+
+  // Slide / CC74, 0..1; a per-note control, so only member channels carry it
+  pub fn set_note_slide(&mut self, params: &ParamStore, channel: u8, value: f32) {
+    if self.channel_role(params, channel) == ChannelRole::Member {
+      self.perf.channels[channel as usize & 15].slide = value.clamp(0.0, 1.0);
+    }
+  }
+
+  // Evaluate this part's matrix rows into the ParamStore overlay (control rate, see MOD_BLOCK)
+  pub fn update_mod_matrix(&mut self, params: &mut ParamStore, followers: &[f32]) {
+    let a = params.get_f32_h(self.paths.mod_attack, 0.01);
+    let d = params.get_f32_h(self.paths.mod_decay, 0.1);
+    let s = params.get_f32_h(self.paths.mod_sustain, 0.8);
+    let r = params.get_f32_h(self.paths.mod_release, 0.2);
+    self.mods.env.set(a, d, s, r);
+    let src = ModSources {
+      lfos: self.mods.lfo_out,
+      env: self.mods.env.env,
+      velocity: self.mods.vel,
+      note: ((self.mods.note as f32 - 60.0) / 64.0).clamp(-1.0, 1.0),
+      wheel: self.perf.wheel,
+      pressure: self.perf.pressure,
+      random: self.mods.random,
+      followers,
+    };
+    self.mods.matrix.evaluate(params, &src);
+  }
+
+  pub fn follower(&self) -> f32 { self.mods.follower }
+
+  // Envelope follower on the part output (fast attack, ~150ms release)
+  fn track_output(&mut self, l: f32, r: f32) {
+    let x = l.abs().max(r.abs()).min(1.0);
+    let coef = if x > self.mods.follower { 0.01 } else { 1.0 / (0.15 * self.sr) };
+    self.mods.follower += (x - self.mods.follower) * coef;
+  }
+
+  // Push part bend and per-note expression to the voices of the module being rendered
+  fn apply_expression(&mut self, params: &ParamStore, module: i32) {
+    let range = params.get_f32_h(self.paths.bend_range, 2.0).clamp(0.0, 48.0);
+    let cents = self.perf.bend * range * 100.0;
+    let perf = &self.perf;
+    match module {
+      0 | 9 => { for v in &mut self.voices { v.expr = perf.voice_expr(v.note, v.channel, cents); } }
+      1 => self.acid.set_pitch_bend(cents),
+      3 => self.resonator.set_expression(|n, ch| perf.voice_expr(n, ch, cents)),
+      4 => self.sampler.set_expression(|n, ch| perf.voice_expr(n, ch, cents)),
+      6 => self.korus.set_expression(|n, ch| perf.voice_expr(n, ch, cents)),
+      10 => self.fm.set_expression(|n, ch| perf.voice_expr(n, ch, cents)),
+      _ => {}
+    }
+  }
+
+  pub fn load_sample(&mut self, path: &str) -> Result<(), String> {
+    self.sampler.load_sample(path);
+    Ok(())
+  }
+
+  pub fn clear_sample(&mut self) {
+    self.sampler.clear_sample();
+  }
+
+  pub fn load_wavetable(&mut self, path: &str) -> Result<(), String> {
+    self.wavetable = Some(WavetableBank::load(path)?);
+    Ok(())
+  }
+
+  // Resonator profile mode (resonator/mode 2): tune the bank to the strongest partials of a recording
+  pub fn load_resonator_profile(&mut self, path: &str) -> Result<(), String> {
+    let buf = Sampler::decode_file(path).map_err(|e| format!("{path}: {e}"))?;
+    let profile = SpectralProfile::analyse(&buf.data, buf.sample_rate, 16)?;
+    self.resonator.set_profile(Some(profile));
+    Ok(())
+  }
+
+  pub fn load_drum_pack(&mut self, slots: &[Vec<DrumZoneSpec>], notes: &[u8]) {
+    self.drum.load_pack(slots, notes);
+  }
+
+  // Slot remaps apply to both drum kinds so patterns keep working when switching between them
+  pub fn set_drum_slot_note(&mut self, slot: usize, note: u8) {
+    self.drum.set_slot_note(slot, note);
+    self.drum_synth.set_slot_note(slot, note);
+  }
+
+  pub fn swap_drum_slots(&mut self, a: usize, b: usize) {
+    self.drum.swap_slots(a, b);
+    self.drum_synth.swap_slots(a, b);
+  }
+
+  // beats: absolute transport position, which tempo-synced LFOs lock to
+  pub fn render(&mut self, params: &ParamStore, _part_idx: usize, beat_phase: f32, beats: f64) -> (f32, f32) {
+    // Module dispatch (0 = Analog, 1 = Acid303, 2 = KarplusStrong, 3 = ResonatorBank, 4 = Sampler,
+    // 5 = Drum, 6 = Korus, 7 = Granular, 8 = DrumSynth, 9 = Wavetable, 10 = FM)
+    let module = params.get_i32_h(self.paths.module_kind, 0);
+    self.drum_buses = [DrumBusFrame::default(); DRUM_BUSES];
+    self.apply_expression(params, module);
+    for i in 0..LFOS_PER_PART { self.mods.lfo_out[i] = self.mods.lfos[i].tick(params, &self.mods.lfo_keys[i], beats); }
+    self.mods.env.next();
+    
+    if module == 7 {
+      // Granular: LFO rows with dest 7 sweep the grain position
+      let modv = self.mods.lfo_out[0];
+      let mut pos_mod = 0.0;
+      for i in 0..5 {
+        if params.get_i32_h(self.paths.lfo_dest[i], 0) != 7 { continue; }
+        pos_mod += modv * params.get_f32_h(self.paths.lfo_row_amount[i], 1.0).clamp(-1.0, 1.0);
+      }
+      let gframe = self.granular.render(params, &self.granular_keys, pos_mod);
+      return match self.strip.process_fx_eq_stereo(params, gframe.left, gframe.right) {
+        Some((l, r)) => self.strip.apply_stereo_mixer(params, l, r),
+        None => (0.0, 0.0),
       };
-      if fx4_t == 3 {
-        let (wet, _) = self.phaser4.process_one(out, out, self.sr, rate, fx4_p2, 1.0);
-        out = out * (1.0 - fx4_mix) + wet * fx4_mix;
-      } else {
-        let base_ms = match fx4_t { 4 => 2.0, 5 => 15.0, _ => 3.0 };
-        let (wet, _) = self.delay2.process_one(out, out, self.sr, rate, base_ms, depth_ms, 1.0);
-        out = out * (1.0 - fx4_mix) + wet * fx4_mix;
-      }
-      self.fx4_reverb = None; self.fx4_crusher = None;
-    } else if fx4_t == 6 {
-      // Distortion: Drive (0..20db), Tone (0..1), Mix
-      let dry = out;
-      let drive_db = (fx4_p1.clamp(0.0, 1.0)) * 20.0;
-      let g = (10.0_f32).powf(drive_db / 20.0);
-      let x = (dry * g).tanh();
-      let tone = fx4_p2.clamp(0.0, 1.0);
-      let lp_amt = 0.3 + 0.6 * (1.0 - tone);
-      self.fx4_wet_lp_l.set_hf_damp(lp_amt);
-      let y_lp = self.fx4_wet_lp_l.tick(x);
-      let y_hp = x - y_lp;
-      let shaped = y_lp * (1.0 - tone) + y_hp * tone;
-      out = dry * (1.0 - fx4_mix) + shaped * fx4_mix;
-      self.fx4_reverb = None; self.fx4_crusher = None;
-    } else if fx4_t == 7 {
-      // Waveshaper
-      let dry = out;
-      let drive = fx4_p2.clamp(0.0, 1.0) * 10.0;
-      let g = 1.0 + drive;
-      let cur = fx4_p1.clamp(0.0, 1.0);
-      let xin = dry * g;
-      let shaped = if cur < 0.34 {
-        xin.tanh()
-      } else if cur < 0.67 {
-        xin.clamp(-1.0, 1.0)
-      } else {
-        let m = (xin + 1.0).abs().rem_euclid(4.0);
-        ((m - 2.0).abs() - 1.0).clamp(-1.0, 1.0)
+    }
+
+    if module == 8 {
+      let dframe = self.drum_synth.render(params, &self.drum_synth_keys);
+      let out = match self.strip.process_fx_eq_mono(params, dframe.mono) {
+        Some(v) => v,
+        None => return (0.0, 0.0),
       };
-      out = dry * (1.0 - fx4_mix) + shaped * fx4_mix;
-      self.fx4_reverb = None; self.fx4_crusher = None;
-    } else if fx4_t == 8 {
-      if self.fx4_crusher.is_none() {
-        self.fx4_crusher = Some(Bitcrusher::new());
-      }
-      if let Some(cr) = &mut self.fx4_crusher {
-        let bits = 4.0 + fx4_p1.clamp(0.0, 1.0) * 12.0;
-        let fac = 1.0 + fx4_p2.clamp(0.0, 1.0) * 15.0;
-        cr.set_bits(bits as u8);
-        cr.set_factor(fac as u32);
-        cr.set_mix(fx4_mix);
-        let mut lbuf = [out];
-        let mut rbuf = [out];
-        cr.process(&mut lbuf, &mut rbuf);
-        out = 0.5 * (lbuf[0] + rbuf[0]);
-      }
+      let pan_local = if dframe.energy > 1e-9 { (dframe.pan_accum / dframe.energy).clamp(-1.0, 1.0) } else { 0.0 };
+      return self.strip.apply_standard_mixer(params, out, pan_local);
+    }
+
+    if module == 10 {
+      let dry = self.fm.render(params, &self.fm_keys);
+      let out = match self.strip.process_fx_eq_mono(params, dry) {
+        Some(v) => v,
+        None => return (0.0, 0.0),
+      };
+      return self.strip.apply_standard_mixer(params, out, 0.0);
     }
-      // EQ for Analog branch
-      let q = 1.0_f32; let mut any_nonzero = false;
-      for i in 0..8 {
-        let db = params.get_f32_h(self.paths.eq_bands[i], 0.0).clamp(-12.0, 12.0);
-        if (db - self.eq_last_db[i]).abs() > 1e-6 { self.eq_bands[i].set_peaking(self.sr, self.eq_centers[i], q, db); self.eq_last_db[i] = db; }
-        if db.abs() > 1e-3 { any_nonzero = true; }
+    
+    // Debug: Log module kind for part 0 when it changes
+  // (debug logging removed for stability)
+    
+    if module == 5 {
+      // Drum Sampler render path (mono aggregation -> FX -> EQ -> Mixer)
+      let dframe = self.drum.render(params, &self.drum_keys);
+      self.drum_buses = dframe.buses;
+      let out = match self.strip.process_fx_eq_mono(params, dframe.mono) {
+        Some(v) => v,
+        None => return (0.0, 0.0),
+      };
+      // Per-voice pan blending
+      let pan_local = if dframe.mono.abs() > 1e-9 { (dframe.pan_accum / dframe.mono).clamp(-1.0, 1.0) } else { 0.0 };
+      return self.strip.apply_standard_mixer(params, out, pan_local);
+  } else if module == 6 {
+      // Korus (6-voice Juno clone) stereo render
+      let (kl, kr) = self.korus.render_one(params, &self.korus_keys);
+      // Process stereo through FX chain -> EQ -> Mixer (stereo-aware)
+      return match self.strip.process_fx_eq_stereo(params, kl, kr) {
+        Some((l, r)) => self.strip.apply_stereo_mixer(params, l, r),
+        None => (0.0, 0.0),
+      };
+  } else if module == 1 {
+      // Acid303 mono voice sample
+      let s = self.acid.render_one(params, &self.acid_keys);
+      return self.strip.render_input(params, s, 0.0);
+    } else if module == 2 {
+      // Karplus-Strong string pool (summed mono)
+      let s = self.karplus.render_one(params, &self.karplus_keys);
+      return self.strip.render_input(params, s, 0.0);
+    } else if module == 3 {
+      // Resonator Bank mono voice sample
+      let s = self.resonator.render_one(params, &self.resonator_keys);
+      return self.strip.render_input(params, s, 0.0);
+  } else if module == 4 {
+      // Sampler mono voice sample
+      let s = self.sampler.render_one(params, &self.sampler_keys, beat_phase);
+      return self.strip.render_input(params, s, 0.0);
+  } else {
+      // Analog voices (module == 0; the wavetable module 9 is the same voice reading a table)
+    let modv = self.mods.lfo_out[0];
+    let mut modf = ModFrame { cents_a: 0.0, cents_b: 0.0, lvl_a: 0.0, lvl_b: 0.0, filt1: 0.0, filt2: 0.0, wt_pos: 0.0 };
+    // LFO rows, then mod wheel rows (both part-wide)
+    for i in 0..10 {
+      let (dest_p, amt_p, src) = if i < 5 { (self.paths.lfo_dest[i], self.paths.lfo_row_amount[i], modv) } else { (self.paths.wheel_dest[i - 5], self.paths.wheel_row_amount[i - 5], self.perf.wheel) };
+      let dest = params.get_i32_h(dest_p, 0) as u16;
+      if dest == 0 { continue; }
+      let row_amt = params.get_f32_h(amt_p, 1.0).clamp(-1.0, 1.0);
+      let v = src * row_amt;
+      match dest {
+        1 => modf.cents_a += 100.0 * v,
+        2 => modf.cents_b += 100.0 * v,
+        3 => modf.lvl_a += v,
+        4 => modf.lvl_b += v,
+        5 => modf.filt1 += v,
+        6 => modf.filt2 += v,
+        7 => modf.wt_pos += v,
+        _ => {}
       }
-      if any_nonzero { for i in 0..8 { out = self.eq_bands[i].process(out); } }
-      // Mixer: PAN, VOLUME, HAAS, COMP
-      let mut l = out; let mut r = out;
-      let pan = params.get_f32_h(self.paths.mix_pan, 0.0).clamp(-1.0, 1.0);
-      let theta = (pan + 1.0) * std::f32::consts::FRAC_PI_4; let gl = theta.cos(); let gr = theta.sin(); l *= gl; r *= gr;
-      let vol = params.get_f32_h(self.paths.mix_volume, 1.0).clamp(0.0, 1.0); l *= vol; r *= vol;
-      let haas = params.get_f32_h(self.paths.mix_haas, 0.0).clamp(0.0, 1.0);
-      if haas > 0.0005 {
-        let rd = if self.haas_wr >= self.haas_d { self.haas_wr - self.haas_d } else { self.haas_wr + self.haas_len - self.haas_d };
-        let delayed_l = self.haas_buf[rd]; self.haas_buf[self.haas_wr] = l; self.haas_wr += 1; if self.haas_wr >= self.haas_len { self.haas_wr = 0; }
-        l = l * (1.0 - haas) + delayed_l * haas;
-      } else { self.haas_buf[self.haas_wr] = l; self.haas_wr += 1; if self.haas_wr >= self.haas_len { self.haas_wr = 0; } }
-      let comp = params.get_f32_h(self.paths.mix_comp, 0.0).clamp(0.0, 1.0);
-      if comp > 0.001 { let drive = 1.0 + 8.0 * comp; let id = 1.0 / drive.tanh(); l = (l * drive).tanh() * id; r = (r * drive).tanh() * id; }
-      return (l, r);
+    }
+    self.unison.update(params.get_i32_h(self.paths.unison_voices, 1), params.get_f32_h(self.paths.unison_detune, 0.0), params.get_f32_h(self.paths.unison_spread, 0.0));
+    let wt = if module == 9 { self.wavetable.as_ref() } else { None };
+    let (mut s, mut side) = (0.0f32, 0.0f32);
+    for v in &mut self.voices {
+      if v.is_active() { let (l, r) = v.render(params, &self.paths, self.sr, &modf, &self.unison, wt); s += 0.5 * (l + r); side += 0.5 * (l - r); }
+    }
+    // Without spread the side is silent, so only a spread stack pays for the stereo strip
+    if !self.unison.is_stereo() { return self.strip.render_input(params, s, 0.0); }
+    match self.strip.process_fx_eq_stereo(params, s + side, s - side) {
+      Some((l, r)) => self.strip.apply_stereo_mixer(params, l, r),
+      None => (0.0, 0.0),
+    }
     } // end Analog branch
   } // end render
 } // end impl Part
 
+// Mixer buses fed by routed drum slots: 0 = aux, 1..=4 = drum sub-channels.
+// Each is a channel strip addressed as `mixer/aux/...` or `mixer/subK/...`.
+struct MixerBus {
+  strip: ChannelStrip,
+  gain_db: u64,
+  duck: u64,
+}
+
+impl MixerBus {
+  fn new(sr: f32, base: &str) -> Self {
+    Self {
+      strip: ChannelStrip::new(sr, base),
+      gain_db: hash_path(&format!("{}gain_db", base)),
+      duck: hash_path(&format!("{}mixer/duck", base)),
+    }
+  }
+}
+
 pub struct Mixer {
   sr: f32,
   part_gains: [f32; 6],
+  buses: Vec<MixerBus>,
+  // Sidechain follower: `mixer/sidechain/source` picks a bus (0 = off, 1 = aux, 2..=5 = sub 1..4)
+  sc_env: f32,
+  sc_source: u64,
+  sc_release: u64,
 }
 
 impl Mixer {
-  pub fn new(sr: f32) -> Self { Self { sr, part_gains: [1.0; 6] } }
+  pub fn new(sr: f32) -> Self {
+    let mut buses = Vec::with_capacity(DRUM_BUSES);
+    buses.push(MixerBus::new(sr, "mixer/aux/"));
+    for k in 1..DRUM_BUSES { buses.push(MixerBus::new(sr, &format!("mixer/sub{}/", k))); }
+    Self {
+      sr, part_gains: [1.0; 6], buses,
+      sc_env: 0.0,
+      sc_source: hash_path("mixer/sidechain/source"),
+      sc_release: hash_path("mixer/sidechain/release"),
+    }
+  }
   pub fn set_gain_db(&mut self, idx: usize, db: f32) { if idx < 6 { self.part_gains[idx] = db_to_gain(db.clamp(-12.0, 12.0)); } }
-  pub fn mix(&self, parts: &mut [Part], params: &ParamStore, beat_phase: f32) -> (f32, f32) {
-    let mut l = 0.0f32; let mut r = 0.0f32;
+  pub fn mix(&mut self, parts: &mut [Part], params: &ParamStore, beat_phase: f32, beats: f64) -> (f32, f32) {
+    let mut part_out = [(0.0f32, 0.0f32); 6];
+    let mut bus_in = [DrumBusFrame::default(); DRUM_BUSES];
     for i in 0..parts.len().min(6) {
-      let (pl, pr) = parts[i].render(params, i, beat_phase);
+      let (pl, pr) = parts[i].render(params, i, beat_phase, beats);
       // Robust gain composition: multiplicative with identity defaults; clamp to sensible range
       let pg = self.part_gains[i].clamp(0.0, 2.0);
       let param_g = db_to_gain(params.get_f32_h(parts[i].paths.mixer_gain_db, 0.0)).clamp(0.0, 2.0);
       let g = pg * param_g;
       let (pl, pr) = if pl.is_finite() && pr.is_finite() { (pl, pr) } else { (0.0, 0.0) };
-      l += pl * g; r += pr * g;
+      part_out[i] = (pl * g, pr * g);
+      parts[i].track_output(pl * g, pr * g);
+      for (acc, f) in bus_in.iter_mut().zip(parts[i].drum_buses.iter()) {
+        acc.mono += f.mono; acc.pan_accum += f.pan_accum; acc.energy += f.energy;
+      }
+    }
+
+    let mut bus_out = [(0.0f32, 0.0f32); DRUM_BUSES];
+    for (k, bus) in self.buses.iter_mut().enumerate() {
+      let f = bus_in[k];
+      let pan_local = if f.energy > 1e-9 { (f.pan_accum / f.energy).clamp(-1.0, 1.0) } else { 0.0 };
+      let (bl, br) = bus.strip.render_input(params, f.mono, pan_local);
+      let g = db_to_gain(params.get_f32_h(bus.gain_db, 0.0)).clamp(0.0, 2.0);
+      bus_out[k] = if bl.is_finite() && br.is_finite() { (bl * g, br * g) } else { (0.0, 0.0) };
+    }
+
+    // Sidechain: follow the dry input of the source bus (e.g. the kick alone) and duck every
+    // channel by its own `mixer/duck` depth
+    let source = params.get_i32_h(self.sc_source, 0);
+    let duck = if source >= 1 && (source as usize) <= DRUM_BUSES {
+      let x = bus_in[source as usize - 1].mono.abs();
+      let release_ms = params.get_f32_h(self.sc_release, 120.0).clamp(10.0, 1000.0);
+      let coef = if x > self.sc_env { 1.0 - (-1.0 / (0.001 * self.sr)).exp() } else { 1.0 - (-1.0 / (release_ms * 0.001 * self.sr)).exp() };
+      self.sc_env += (x - self.sc_env) * coef;
+      (self.sc_env * 2.0).min(1.0) // full depth from -6 dBFS
+    } else { self.sc_env = 0.0; 0.0 };
+    let duck_gain = |key: u64| 1.0 - params.get_f32_h(key, 0.0).clamp(0.0, 1.0) * duck;
+
+    let mut l = 0.0f32; let mut r = 0.0f32;
+    for i in 0..parts.len().min(6) {
+      let g = duck_gain(parts[i].paths.mix_duck);
+      l += part_out[i].0 * g; r += part_out[i].1 * g;
+    }
+    for (k, bus) in self.buses.iter().enumerate() {
+      let g = duck_gain(bus.duck);
+      l += bus_out[k].0 * g; r += bus_out[k].1 * g;
     }
     let l = soft_clip(l);
     let r = soft_clip(r);
@@ -2908,6 +1976,7 @@ pub struct EngineGraph {
   // tempo/transport
   bpm: f32,
   beat_phase: f32,
+  mod_counter: u32,
 }
 
 impl EngineGraph {
@@ -2924,13 +1993,14 @@ impl EngineGraph {
       preview_playing: false,
       bpm: 120.0,
       beat_phase: 0.0,
+      mod_counter: 0,
     }
   }
   
   pub fn load_preview_sample(&mut self, path: &str) -> Result<(), String> {
     self.preview_sampler.load_sample(path);
   // Use normalized velocity (0..1) now that sampler clamps internally; 0.85 gives headroom
-  self.preview_sampler.note_on(60, 0.85, crate::engine::modules::sampler::RetrigMode::Immediate); // Trigger preview playback at moderate level
+  self.preview_sampler.note_on(60, 0.85, crate::engine::modules::sampler::RetrigMode::Immediate, 0); // Trigger preview playback at moderate level
     self.preview_playing = true;
     Ok(())
   }
@@ -2942,7 +2012,18 @@ impl EngineGraph {
   
   pub fn tempo(&self) -> f32 { self.bpm }
 
-  pub fn render_frame(&mut self, params: &ParamStore, external_beat_phase: f32) -> (f32, f32) {
+  // transport_beats: the transport's absolute position in beats (external_beat_phase is its fraction)
+  pub fn render_frame(&mut self, params: &mut ParamStore, external_beat_phase: f32, transport_beats: f64) -> (f32, f32) {
+    // Mod matrix runs at control rate; every part can read every other part's follower
+    if self.mod_counter == 0 {
+      params.clear_mods();
+      let mut followers = [0.0f32; 6];
+      for (f, part) in followers.iter_mut().zip(self.parts.iter()) { *f = part.follower(); }
+      for part in &mut self.parts { part.update_mod_matrix(params, &followers); }
+    }
+    self.mod_counter = (self.mod_counter + 1) % MOD_BLOCK;
+    let params: &ParamStore = params;
+
     // Advance local transport to keep tempo stable even if external phase jitters
     let spb = 60.0_f32 / self.bpm.max(1.0);
     let bps = (1.0 / self.sr) / spb; // beats-per-sample
@@ -2960,7 +2041,7 @@ impl EngineGraph {
 
     self.beat_phase = internal_phase;
 
-  let mut result = self.mixer.mix(&mut self.parts, params, self.beat_phase);
+  let mut result = self.mixer.mix(&mut self.parts, params, self.beat_phase, transport_beats);
 
     // Update playhead states for any parts using sampler module (kind == 4)
     for (i, part) in self.parts.iter().enumerate() {
@@ -2971,7 +2052,7 @@ impl EngineGraph {
         } else {
           set_playhead_state(i, None);
         }
-      } else if module == 5 { // Drum
+      } else if module == 5 || module == 8 { // Drum / DrumSynth
         // No playhead; clear any previous
         set_playhead_state(i, None);
       }
@@ -2991,10 +2072,16 @@ impl EngineGraph {
         loop_mode: 0,
         smoothness: 0,
         retrig_mode: 0,
+        warp: 0,
+        warp_bars: 0,
+        warp_grain: 0,
+        quality: 0,
         attack: 0,
         decay: 0,
         sustain: 0,
         release: 0,
+        pressure_amp: 0,
+        slide_filter: 0,
       };
   let preview_out = self.preview_sampler.render_one(params, &preview_keys, self.beat_phase);
       result.0 += preview_out * 0.3; // Lower volume for preview
@@ -3014,3 +2101,44 @@ impl EngineGraph {
     self.bpm = clamped;
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::engine::messages::ParamValue;
+
+  #[test]
+  fn mpe_zone_channel_roles() {
+    assert_eq!(channel_role(1, 15, 0), ChannelRole::Master);
+    assert_eq!(channel_role(1, 15, 15), ChannelRole::Member);
+    assert_eq!(channel_role(1, 3, 4), ChannelRole::Outside);
+    assert_eq!(channel_role(2, 3, 15), ChannelRole::Master);
+    assert_eq!(channel_role(2, 3, 12), ChannelRole::Member);
+    assert_eq!(channel_role(2, 3, 11), ChannelRole::Outside);
+    assert_eq!(channel_role(0, 15, 0), ChannelRole::Member);
+  }
+
+  #[test]
+  fn master_bend_and_pressure_add_to_member_voices() {
+    let mut params = ParamStore::new();
+    params.set("part/0/mpe/zone".into(), ParamValue::I32(1));
+    params.set("part/0/mpe/members".into(), ParamValue::I32(4));
+    params.set("part/0/mpe/bend_range".into(), ParamValue::F32(24.0));
+    let mut part = Part::new(48000.0, 6, 0);
+    // Two member channels on the same key
+    part.note_on(&params, 60, 1.0, false, false, 1);
+    part.note_on(&params, 60, 1.0, false, false, 2);
+    part.set_note_bend(&params, 1, 0.5);
+    part.set_note_pressure(&params, 2, 0.25);
+    part.set_note_bend(&params, 0, 1.0); // master: bend_range defaults to 2 semitones
+    part.set_note_pressure(&params, 0, 0.5);
+    part.set_note_bend(&params, 7, 1.0); // outside the zone
+    part.apply_expression(&params, 0);
+    let expr = |ch: u8| part.voices.iter().find(|v| v.is_active() && v.channel == ch).unwrap().expr;
+    assert_eq!(expr(1).bend_cents, 1200.0 + 200.0);
+    assert_eq!(expr(2).bend_cents, 200.0);
+    assert_eq!(expr(1).pressure, 0.5);
+    assert_eq!(expr(2).pressure, 0.75);
+    assert_eq!(part.perf.channels[7].bend_cents, 0.0);
+  }
+}
@@ -12,19 +12,58 @@ pub enum ParamValue {
     Str(String),
 }
 
+// One sample of a drum slot and the velocity range (0..1) it answers to
+#[derive(Clone, Debug, Deserialize)]
+pub struct DrumZoneSpec {
+    pub path: String,
+    pub vel_lo: f32,
+    pub vel_hi: f32,
+}
+
 #[derive(Clone, Debug, Deserialize)]
 pub enum EngineMsg {
     SetParam { path: String, value: ParamValue },
-    NoteOn { part: usize, note: u8, vel: f32 },
+    // accent/slide: per-step 303 flags (slide ties into the note still held on the part)
+    // channel: MIDI channel (0-based) the note came in on; the voice follows that channel's expression
+    NoteOn {
+        part: usize,
+        note: u8,
+        vel: f32,
+        #[serde(default)]
+        accent: bool,
+        #[serde(default)]
+        slide: bool,
+        #[serde(default)]
+        channel: u8,
+    },
     NoteOff { part: usize, note: u8 },
+    // Performance controllers; bend is -1..1, the rest 0..1
+    PitchBend { part: usize, value: f32 },
+    ModWheel { part: usize, value: f32 },
+    // note = None is channel pressure, Some(note) is polyphonic aftertouch
+    Aftertouch { part: usize, note: Option<u8>, value: f32 },
+    Sustain { part: usize, on: bool },
+    // Channel-tagged expression, routed by the part's MPE zone: the master channel moves the whole
+    // part, a member channel only the voices started on it. Bend is -1..1, the rest 0..1
+    NoteBend { part: usize, channel: u8, value: f32 },
+    NotePressure { part: usize, channel: u8, value: f32 },
+    NoteSlide { part: usize, channel: u8, value: f32 },
     SetTempo { bpm: f32 },
     Transport { playing: bool },
     StartRecording,
     StopRecording,
     LoadSample { part: usize, path: String },
     ClearSample { part: usize },
-    // Drum pack load (paths are absolute file paths resolved on the command side)
-    LoadDrumPack { part: usize, paths: Vec<String> },
+    // Wavetable: a WAV of 2048-sample frames, a single-cycle file, or a folder of single cycles
+    LoadWavetable { part: usize, path: String },
+    // Analyse a recording's partials and use them as the resonator bank's profile-mode tuning
+    LoadResonatorProfile { part: usize, path: String },
+    // Drum pack load (zone paths are absolute file paths resolved on the command side;
+    // notes[i] is the MIDI note that triggers slot i)
+    LoadDrumPack { part: usize, slots: Vec<Vec<DrumZoneSpec>>, notes: Vec<u8> },
+    // Move a drum slot to another note (the slot holding that note takes the old one)
+    SetDrumSlotNote { part: usize, slot: usize, note: u8 },
+    SwapDrumSlots { part: usize, a: usize, b: usize },
     PreviewSample { path: String },
     StopPreview,
     Quit,
@@ -0,0 +1,354 @@
+// Per-part modulation matrix
+//
+// Each row routes one source to a parameter path relative to the part
+// (`part/N/matrix/rowK/{source,dest,amount,polarity}`, dest e.g. "korus/cutoff").
+// Rows are evaluated at control rate and land in the ParamStore modulation overlay, so every
+// module sees the modulated value through its usual `get_f32_h` read. Only paths listed in
+// MOD_TARGETS can be modulated; the amount is a fraction of that target's range.
+
+use crate::engine::dsp::lfo::LFOS_PER_PART;
+use crate::engine::params::{hash_path, hash_path_suffix, ParamStore};
+
+pub const MOD_MATRIX_ROWS: usize = 8;
+// Samples between matrix evaluations
+pub const MOD_BLOCK: u32 = 32;
+
+// Source codes for `matrix/rowK/source`
+pub const MOD_SRC_LFO: i32 = 1;
+pub const MOD_SRC_ENV: i32 = 2;
+pub const MOD_SRC_VELOCITY: i32 = 3;
+pub const MOD_SRC_NOTE: i32 = 4;
+pub const MOD_SRC_WHEEL: i32 = 5;
+pub const MOD_SRC_PRESSURE: i32 = 6;
+pub const MOD_SRC_RANDOM: i32 = 7;
+pub const MOD_SRC_LFO2: i32 = 8;
+pub const MOD_SRC_LFO3: i32 = 9;
+// Envelope follower of part k is MOD_SRC_FOLLOWER + k
+pub const MOD_SRC_FOLLOWER: i32 = 16;
+
+// Modulatable paths (relative to `part/N/`) and their ranges; `*` matches a slot index
+pub const MOD_TARGETS: &[(&str, f32, f32)] = &[
+    // Analog
+    ("oscA/detune_cents", -1200.0, 1200.0),
+    ("oscB/detune_cents", -1200.0, 1200.0),
+    ("oscA/level", 0.0, 1.0),
+    ("oscB/level", 0.0, 1.0),
+    ("oscA/pulse_width", 0.02, 0.98),
+    ("oscA/fm_to_B", 0.0, 1000.0),
+    ("oscB/fm_to_A", 0.0, 1000.0),
+    ("filter1/cutoff_hz", 20.0, 20000.0),
+    ("filter1/q", 0.1, 20.0),
+    ("filter2/cutoff_hz", 20.0, 20000.0),
+    ("filter2/q", 0.1, 20.0),
+    ("filter1/drive", 0.0, 1.0),
+    ("filter2/drive", 0.0, 1.0),
+    ("unison/detune", 0.0, 100.0),
+    ("unison/spread", 0.0, 1.0),
+    // Wavetable (analog voice with a table on oscillator A)
+    ("wavetable/position", 0.0, 1.0),
+    // Acid303
+    ("acid/cutoff", 0.0, 1.0),
+    ("acid/reso", 0.0, 1.0),
+    ("acid/envmod", 0.0, 1.0),
+    ("acid/decay", 0.0, 1.0),
+    ("acid/accent", 0.0, 1.0),
+    ("acid/drive", 0.0, 1.0),
+    // Karplus-Strong
+    ("ks/decay", 0.0, 1.0),
+    ("ks/damp", 0.0, 1.0),
+    ("ks/excite", 0.0, 1.0),
+    ("ks/tune", 0.0, 1.0),
+    ("ks/pick", 0.0, 1.0),
+    ("ks/body", 0.0, 1.0),
+    // Resonator
+    ("resonator/pitch", -1.0, 1.0),
+    ("resonator/decay", 0.0, 1.0),
+    ("resonator/brightness", 0.0, 1.0),
+    ("resonator/inharmonicity", 0.0, 1.0),
+    ("resonator/feedback", 0.0, 1.0),
+    ("resonator/drive", 0.0, 1.0),
+    ("resonator/exciter_amount", 0.0, 1.0),
+    ("resonator/body_blend", 0.0, 1.0),
+    // Sampler
+    ("sampler/sample_start", 0.0, 1.0),
+    ("sampler/sample_end", 0.0, 1.0),
+    ("sampler/pitch_semitones", -48.0, 48.0),
+    ("sampler/pitch_cents", -100.0, 100.0),
+    ("sampler/loop_start", 0.0, 1.0),
+    ("sampler/loop_end", 0.0, 1.0),
+    // Drum slots (applied on the next hit)
+    ("drum/slot/*/volume", 0.0, 1.0),
+    ("drum/slot/*/pan", 0.0, 1.0),
+    ("drum/slot/*/pitch_semitones", -24.0, 24.0),
+    ("drum/slot/*/start", 0.0, 1.0),
+    ("drum/slot/*/cutoff", 20.0, 18000.0),
+    ("drum/slot/*/decay", 0.0, 1.0),
+    ("drumsynth/slot/*/tune", -24.0, 24.0),
+    ("drumsynth/slot/*/decay", 0.0, 1.0),
+    ("drumsynth/slot/*/tone", 0.0, 1.0),
+    ("drumsynth/slot/*/character", 0.0, 1.0),
+    ("drumsynth/slot/*/level", 0.0, 1.5),
+    // Korus
+    ("korus/cutoff", 0.0, 1.0),
+    ("korus/reso", 0.0, 1.0),
+    ("korus/pwm", 0.0, 1.0),
+    ("korus/sub", 0.0, 1.0),
+    ("korus/noise", 0.0, 1.0),
+    ("korus/env_amt", 0.0, 1.0),
+    ("korus/chorus", 0.0, 1.0),
+    ("korus/detune", 0.0, 100.0),
+    ("korus/spread", 0.0, 1.0),
+    // FM
+    ("fm/op*/ratio", 0.0, 32.0),
+    ("fm/op*/level", 0.0, 1.0),
+    ("fm/op*/feedback", 0.0, 1.0),
+    // Granular
+    ("granular/position", 0.0, 1.0),
+    ("granular/size", 5.0, 500.0),
+    ("granular/density", 1.0, 100.0),
+    ("granular/spray", 0.0, 1000.0),
+    ("granular/pitch", -48.0, 48.0),
+    // LFOs
+    ("lfo/rate_hz", 0.01, 20.0),
+    ("lfo/amount", 0.0, 1.0),
+    ("lfo2/rate_hz", 0.01, 20.0),
+    ("lfo2/amount", 0.0, 1.0),
+    ("lfo3/rate_hz", 0.01, 20.0),
+    ("lfo3/amount", 0.0, 1.0),
+    // Part FX and mixer
+    ("fx1/p1", 0.0, 1.0),
+    ("fx1/p2", 0.0, 1.0),
+    ("fx1/p3", 0.0, 1.0),
+    ("fx2/p1", 0.0, 1.0),
+    ("fx2/p2", 0.0, 1.0),
+    ("fx2/p3", 0.0, 1.0),
+    ("fx3/p1", 0.0, 1.0),
+    ("fx3/p2", 0.0, 1.0),
+    ("fx3/p3", 0.0, 1.0),
+    ("fx4/p1", 0.0, 1.0),
+    ("fx4/p2", 0.0, 1.0),
+    ("fx4/p3", 0.0, 1.0),
+    ("mixer/pan", -1.0, 1.0),
+    ("mixer/volume", 0.0, 1.0),
+    ("mixer/width", 0.0, 1.0),
+];
+
+// Range of a registered target. A trailing `*` in a pattern segment stands for a number, so
+// `*` matches `3` and `op*` matches `op2`.
+pub fn target_range(suffix: &str) -> Option<(f32, f32)> {
+    let numbered = |pattern: &str, seg: &str| match pattern.strip_suffix('*') {
+        Some(prefix) => seg
+            .strip_prefix(prefix)
+            .is_some_and(|n| !n.is_empty() && n.bytes().all(|c| c.is_ascii_digit())),
+        None => pattern == seg,
+    };
+    MOD_TARGETS.iter().find_map(|&(pattern, lo, hi)| {
+        let mut a = pattern.split('/');
+        let mut b = suffix.split('/');
+        loop {
+            match (a.next(), b.next()) {
+                (None, None) => return Some((lo, hi)),
+                (Some(x), Some(y)) if numbered(x, y) => {}
+                _ => return None,
+            }
+        }
+    })
+}
+
+// Source values for one evaluation; unipolar sources are 0..1, bipolar -1..1
+pub struct ModSources<'a> {
+    pub lfos: [f32; LFOS_PER_PART],
+    pub env: f32,
+    pub velocity: f32,
+    pub note: f32,
+    pub wheel: f32,
+    pub pressure: f32,
+    pub random: f32,
+    pub followers: &'a [f32],
+}
+
+impl ModSources<'_> {
+    // (value, natively bipolar)
+    fn get(&self, source: i32) -> Option<(f32, bool)> {
+        match source {
+            MOD_SRC_LFO => Some((self.lfos[0], true)),
+            MOD_SRC_LFO2 => Some((self.lfos[1], true)),
+            MOD_SRC_LFO3 => Some((self.lfos[2], true)),
+            MOD_SRC_ENV => Some((self.env, false)),
+            MOD_SRC_VELOCITY => Some((self.velocity, false)),
+            MOD_SRC_NOTE => Some((self.note, true)),
+            MOD_SRC_WHEEL => Some((self.wheel, false)),
+            MOD_SRC_PRESSURE => Some((self.pressure, false)),
+            MOD_SRC_RANDOM => Some((self.random, true)),
+            s if s >= MOD_SRC_FOLLOWER => self
+                .followers
+                .get((s - MOD_SRC_FOLLOWER) as usize)
+                .map(|&v| (v, false)),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+struct RowKeys {
+    source: u64,
+    dest: u64,
+    amount: u64,
+    polarity: u64, // 0 = unipolar, 1 = bipolar; defaults to the source's own polarity
+}
+
+// Resolved destination of a row, refreshed when its dest string changes
+#[derive(Clone, Copy, Default)]
+struct RowTarget {
+    dest_hash: u64, // hash of the dest string it was resolved from
+    key: u64,       // param key of the target, 0 if unregistered
+    lo: f32,
+    hi: f32,
+}
+
+pub struct ModMatrix {
+    part_prefix: u64,
+    rows: [RowKeys; MOD_MATRIX_ROWS],
+    targets: [RowTarget; MOD_MATRIX_ROWS],
+}
+
+impl ModMatrix {
+    pub fn new(part: usize) -> Self {
+        let key = |row: usize, name: &str| {
+            hash_path(&format!("part/{}/matrix/row{}/{}", part, row, name))
+        };
+        Self {
+            part_prefix: hash_path(&format!("part/{}/", part)),
+            rows: std::array::from_fn(|i| RowKeys {
+                source: key(i, "source"),
+                dest: key(i, "dest"),
+                amount: key(i, "amount"),
+                polarity: key(i, "polarity"),
+            }),
+            targets: [RowTarget::default(); MOD_MATRIX_ROWS],
+        }
+    }
+
+    // Add this part's row offsets to the overlay (the caller clears it once per block)
+    pub fn evaluate(&mut self, params: &mut ParamStore, src: &ModSources) {
+        for i in 0..MOD_MATRIX_ROWS {
+            let row = self.rows[i];
+            let Some((v, bipolar)) = src.get(params.get_i32_h(row.source, 0)) else {
+                continue;
+            };
+            let amount = params.get_f32_h(row.amount, 0.0).clamp(-1.0, 1.0);
+            if amount == 0.0 {
+                continue;
+            }
+            let dest = params.get_str_h(row.dest, "");
+            let dest_hash = hash_path(dest);
+            if self.targets[i].dest_hash != dest_hash {
+                let (key, (lo, hi)) = match target_range(dest) {
+                    Some(range) => (hash_path_suffix(self.part_prefix, dest), range),
+                    None => (0, (0.0, 0.0)),
+                };
+                self.targets[i] = RowTarget {
+                    dest_hash,
+                    key,
+                    lo,
+                    hi,
+                };
+            }
+            let t = self.targets[i];
+            if t.key == 0 {
+                continue;
+            }
+            let want_bipolar = params.get_i32_h(row.polarity, bipolar as i32) != 0;
+            let v = match (bipolar, want_bipolar) {
+                (true, false) => 0.5 * (v + 1.0),
+                (false, true) => 2.0 * v - 1.0,
+                _ => v,
+            };
+            params.add_mod(t.key, v * amount * (t.hi - t.lo), t.lo, t.hi);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::messages::ParamValue;
+
+    fn sources(followers: &[f32]) -> ModSources<'_> {
+        ModSources {
+            lfos: [0.5, 0.0, 0.0],
+            env: 1.0,
+            velocity: 0.8,
+            note: 0.0,
+            wheel: 0.25,
+            pressure: 0.0,
+            random: 0.0,
+            followers,
+        }
+    }
+
+    #[test]
+    fn targets_match_slot_wildcards_only_on_numbers() {
+        assert_eq!(target_range("korus/cutoff"), Some((0.0, 1.0)));
+        assert_eq!(target_range("drum/slot/3/pan"), Some((0.0, 1.0)));
+        assert_eq!(target_range("drum/slot/x/pan"), None);
+        assert_eq!(target_range("fm/op2/level"), Some((0.0, 1.0)));
+        assert_eq!(target_range("fm/op4/ratio"), Some((0.0, 32.0)));
+        assert_eq!(target_range("fm/op/level"), None);
+        assert_eq!(target_range("fm/opx/level"), None);
+        assert_eq!(target_range("korus/unknown"), None);
+    }
+
+    #[test]
+    fn rows_offset_their_target_within_range() {
+        let mut params = ParamStore::new();
+        params.set("part/1/korus/cutoff".into(), ParamValue::F32(0.5));
+        params.set(
+            "part/1/matrix/row0/source".into(),
+            ParamValue::I32(MOD_SRC_WHEEL),
+        );
+        params.set(
+            "part/1/matrix/row0/dest".into(),
+            ParamValue::Str("korus/cutoff".into()),
+        );
+        params.set("part/1/matrix/row0/amount".into(), ParamValue::F32(0.4));
+        params.set(
+            "part/1/matrix/row1/source".into(),
+            ParamValue::I32(MOD_SRC_FOLLOWER + 2),
+        );
+        params.set(
+            "part/1/matrix/row1/dest".into(),
+            ParamValue::Str("mixer/volume".into()),
+        );
+        params.set("part/1/matrix/row1/amount".into(), ParamValue::F32(-1.0));
+        let mut m = ModMatrix::new(1);
+        params.clear_mods();
+        m.evaluate(&mut params, &sources(&[0.0, 0.0, 0.75]));
+        let cutoff = params.get_f32_h(hash_path("part/1/korus/cutoff"), 0.0);
+        assert!((cutoff - 0.6).abs() < 1e-6);
+        // The volume target falls back to the default and clamps at the bottom of its range
+        let vol = params.get_f32_h(hash_path("part/1/mixer/volume"), 0.5);
+        assert_eq!(vol, 0.0);
+    }
+
+    #[test]
+    fn polarity_converts_the_source() {
+        let mut params = ParamStore::new();
+        params.set(
+            "part/0/matrix/row0/source".into(),
+            ParamValue::I32(MOD_SRC_LFO),
+        );
+        params.set(
+            "part/0/matrix/row0/dest".into(),
+            ParamValue::Str("mixer/pan".into()),
+        );
+        params.set("part/0/matrix/row0/amount".into(), ParamValue::F32(0.5));
+        params.set("part/0/matrix/row0/polarity".into(), ParamValue::I32(0));
+        let mut m = ModMatrix::new(0);
+        params.clear_mods();
+        m.evaluate(&mut params, &sources(&[]));
+        // LFO 0.5 as unipolar is 0.75, times 0.5 of the 2.0 wide pan range
+        let pan = params.get_f32_h(hash_path("part/0/mixer/pan"), 0.0);
+        assert!((pan - 0.75).abs() < 1e-6);
+    }
+}
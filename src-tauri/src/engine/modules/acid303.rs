@@ -1,5 +1,6 @@
 use std::f32::consts::PI;
 
+use crate::engine::dsp::velocity;
 use crate::engine::params::ParamStore;
 
 #[inline]
@@ -119,10 +120,8 @@ pub struct AcidParamKeys {
     pub accent: u64,
     pub slide: u64,
     pub drive: u64,
-    #[allow(dead_code)]
-    pub step_accent: u64,
-    #[allow(dead_code)]
-    pub step_slide: u64,
+    pub vel_amp: u64,
+    pub vel_filter: u64,
 }
 
 #[derive(Clone)]
@@ -140,8 +139,12 @@ pub struct Acid303 {
     gate: bool,
     just_triggered: bool,
     current_note: Option<u8>, // Track current note for proper legato detection
+    sliding: bool,            // current note was tied in with slide: glide toward it
+    note_accent: f32,         // 1.0 while an accented note plays, else 0.0
+    vel: f32,
+    bend_ratio: f32,
     filt: BiquadLP,
-    // Accent smoothing for TB-303 style global accent behavior
+    // Smoothed accent boost (accent amount on accented notes, 0 otherwise)
     accent_smooth: f32,
     accent_smooth_alpha: f32,
 }
@@ -169,32 +172,51 @@ impl Acid303 {
             gate: false,
             just_triggered: false,
             current_note: None, // Initialize to no note
+            sliding: false,
+            note_accent: 0.0,
+            vel: 1.0,
+            bend_ratio: 1.0,
             filt: BiquadLP::new(),
             accent_smooth: 0.0,
             accent_smooth_alpha,
         }
     }
 
-    pub fn note_on(&mut self, note: u8, _vel: f32) {
+    // Like the TB-303, a slide is a tie: the new note only glides (without retriggering the
+    // envelope) when the previous note is still gated. The sequencer sends the new NoteOn before
+    // the old NoteOff on slide steps; overlapping keyboard notes behave the same way.
+    pub fn note_on(&mut self, note: u8, vel: f32, accent: bool, slide: bool) {
         self.target_freq = midi_to_freq(note);
 
-        // Legato detection: only treat as legato if:
-        // 1. A note is already gated AND
-        // 2. It's a DIFFERENT note (same note = retrigger)
-        let is_legato = self.gate && self.current_note.is_some() && self.current_note != Some(note);
+        // Tie if a note is held and this step slides (a same-note slide just sustains), or if a
+        // different note overlaps the held one
+        let tied =
+            self.gate && self.current_note.is_some() && (slide || self.current_note != Some(note));
 
         self.gate = true;
         self.current_note = Some(note);
         self.just_triggered = true;
+        self.sliding = tied;
+        self.note_accent = if accent { 1.0 } else { 0.0 };
+        self.vel = vel.clamp(0.0, 1.0);
 
-        // Only retrigger envelope if this is NOT legato
-        if !is_legato {
+        // Only retrigger envelope (and jump straight to pitch) if this is NOT tied
+        if !tied {
             self.env = 0.0; // Start from 0 for attack phase
             self.in_attack = true;
+            self.freq = self.target_freq;
         }
     }
 
-    pub fn note_off(&mut self, _note: u8) {
+    pub fn set_pitch_bend(&mut self, cents: f32) {
+        self.bend_ratio = (2.0_f32).powf(cents / 1200.0);
+    }
+
+    pub fn note_off(&mut self, note: u8) {
+        // A tie already moved on to the next note; the old note's release must not cut it
+        if self.current_note != Some(note) {
+            return;
+        }
         self.gate = false;
         self.current_note = None; // Clear current note
         self.in_attack = false; // Exit attack if in progress
@@ -246,9 +268,13 @@ impl Acid303 {
         let accent_amt = params.get_f32_h(keys.accent, 0.7).clamp(0.0, 1.0);
         let slide_n = params.get_f32_h(keys.slide, 0.4).clamp(0.0, 1.0);
         let drive = params.get_f32_h(keys.drive, 0.3).clamp(0.0, 1.0);
+        let vel_amp = params.get_f32_h(keys.vel_amp, velocity::DEFAULT_AMOUNT);
+        let vel_filter = params.get_f32_h(keys.vel_filter, velocity::DEFAULT_AMOUNT);
 
-        // Smooth accent parameter (TB-303 style global accent behavior)
-        self.accent_smooth += (accent_amt - self.accent_smooth) * self.accent_smooth_alpha;
+        // Accent only boosts accented notes; smoothed so ties between accented and plain notes
+        // do not click
+        let accent_target = accent_amt * self.note_accent;
+        self.accent_smooth += (accent_target - self.accent_smooth) * self.accent_smooth_alpha;
         let a_s = self.accent_smooth;
 
         // Apply TB-303 accent boosts to all parameters
@@ -279,20 +305,19 @@ impl Acid303 {
             self.glide_alpha = (-1.0 / ((glide_ms / 1000.0) * self.sr)).exp();
         }
 
-        // Handle note trigger logic - simplified without step_slide complexity
+        // Handle note trigger logic
         if self.just_triggered {
             self.just_triggered = false;
             // Note: envelope and legato logic is now handled in note_on()
         }
 
-        // Update frequency with glide if slide parameter > 0 and gate is active
-        // This creates smooth pitch transitions between notes when slide is enabled
-        if self.gate && self.glide_alpha > 0.0 {
+        // Glide toward tied notes only; the Slide parameter sets the glide time
+        if self.sliding && self.glide_alpha > 0.0 {
             self.freq = self.freq * self.glide_alpha + self.target_freq * (1.0 - self.glide_alpha);
         } else {
             self.freq = self.target_freq;
         }
-        let ph_inc = (self.freq / self.sr).clamp(0.0, 0.5);
+        let ph_inc = (self.freq * self.bend_ratio / self.sr).clamp(0.0, 0.5);
         self.phase = (self.phase + ph_inc) % 1.0;
         let mut osc = self.wt.sample(self.phase, wave);
 
@@ -303,7 +328,8 @@ impl Acid303 {
         let env = self.update_envelope();
 
         // Filter cutoff: use accent-boosted cutoff and envmod
-        let mut cutoff_hz = map_cutoff_norm(cutoff_eff.clamp(0.0, 1.0));
+        let mut cutoff_hz = map_cutoff_norm(cutoff_eff.clamp(0.0, 1.0))
+            * velocity::filter_scale(self.vel, vel_filter);
         let env_hz = cutoff_hz * (2.0_f32).powf(envmod_eff * env * 3.0);
         cutoff_hz = cutoff_hz.max(20.0).min(10000.0);
         cutoff_hz = (cutoff_hz + env_hz).min(12000.0);
@@ -320,7 +346,7 @@ impl Acid303 {
         y = Self::soft_clip_drive(y, drive);
 
         // Amp from env (303 short decay)
-        y *= env;
+        y *= env * velocity::amp_gain(self.vel, vel_amp);
 
         // Denormal protection
         if !y.is_finite() || y.abs() < 1e-24 {
@@ -0,0 +1,456 @@
+// Disk streaming for long samples
+//
+// Files above STREAM_THRESHOLD_BYTES are not decoded into RAM. The first HEAD_SECS are
+// preloaded (so triggers from the start are instant) and a background thread decodes
+// into a fixed-size ring, keeping it ahead of the slowest voice still playing. Each voice
+// reports its playhead in its own slot, so several voices don't drag the ring back and
+// forth; a voice more than a ring length ahead of the slowest one hears silence. The audio
+// thread never blocks: it reads whatever the ring holds and gets silence on an underrun.
+//
+// Frame indices are in the file's native sample rate (mono mixdown).
+
+use std::fs::File;
+use std::path::Path;
+use std::sync::atomic::{fence, AtomicU32, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Weak};
+use std::thread;
+use std::time::Duration;
+
+use symphonia::core::audio::{AudioBufferRef, SampleBuffer as DecodeBuffer};
+use symphonia::core::codecs::{Decoder, DecoderOptions, CODEC_TYPE_NULL};
+use symphonia::core::errors::Error;
+use symphonia::core::formats::{FormatOptions, FormatReader, SeekMode, SeekTo};
+use symphonia::core::io::MediaSourceStream;
+use symphonia::core::meta::MetadataOptions;
+use symphonia::core::probe::Hint;
+
+pub const STREAM_THRESHOLD_BYTES: u64 = 32 * 1024 * 1024;
+const HEAD_SECS: f32 = 8.0;
+const RING_SECS: f32 = 16.0;
+// Frames kept behind the playhead so small backwards jumps (ping-pong, WSOLA) stay cached
+const RING_LOOKBACK_SECS: f32 = 2.0;
+// Playhead slots, one per voice that can read from the stream
+pub const STREAM_PLAYHEADS: usize = 16;
+const IDLE: usize = usize::MAX;
+
+// The valid window travels as one word so a reader never sees the start of one window
+// with the end of another
+#[inline]
+fn pack(start: usize, end: usize) -> u64 {
+    ((start as u64) << 32) | end as u64
+}
+
+#[inline]
+fn unpack(range: u64) -> (usize, usize) {
+    ((range >> 32) as usize, (range & 0xffff_ffff) as usize)
+}
+
+pub struct DiskStream {
+    ring: Box<[AtomicU32]>,        // f32 bits
+    range: AtomicU64,              // [start, end) of the valid frames, see `pack`
+    playheads: Box<[AtomicUsize]>, // per voice, IDLE when not playing
+    total_frames: usize,
+}
+
+impl DiskStream {
+    // Preload the head and spawn the fill thread. Returns (head, native_rate, total_frames, stream).
+    pub fn open(path: &str) -> Result<(Vec<f32>, f32, usize, Arc<DiskStream>), String> {
+        let mut src = DecodeSource::open(path)?;
+        let total_frames = src.total_frames.ok_or("unknown length; cannot stream")?;
+        if total_frames > u32::MAX as usize {
+            return Err("file too long to stream".into());
+        }
+        let rate = src.sample_rate;
+        let head_frames = ((HEAD_SECS * rate) as usize).min(total_frames);
+        let mut head = Vec::with_capacity(head_frames);
+        while head.len() < head_frames {
+            if !src.next_chunk(&mut head)? {
+                break;
+            }
+        }
+        // Anything decoded past the head seeds the ring
+        let carry: Vec<f32> = if head.len() > head_frames {
+            head.split_off(head_frames)
+        } else {
+            Vec::new()
+        };
+
+        let ring_len = ((RING_SECS * rate) as usize).max(4096);
+        let stream = Arc::new(DiskStream::with_ring(ring_len, head.len(), total_frames));
+        stream.push(&carry);
+
+        let weak = Arc::downgrade(&stream);
+        let head_len = head.len();
+        thread::Builder::new()
+            .name("sampler-disk".into())
+            .spawn(move || fill_loop(weak, src, head_len, rate))
+            .map_err(|e| format!("spawn disk thread: {e}"))?;
+        Ok((head, rate, total_frames, stream))
+    }
+
+    fn with_ring(ring_len: usize, first_frame: usize, total_frames: usize) -> Self {
+        Self {
+            ring: (0..ring_len).map(|_| AtomicU32::new(0)).collect(),
+            range: AtomicU64::new(pack(first_frame, first_frame)),
+            playheads: (0..STREAM_PLAYHEADS)
+                .map(|_| AtomicUsize::new(IDLE))
+                .collect(),
+            total_frames,
+        }
+    }
+
+    // Audio thread: note where voice `slot` is playing so the fill thread can keep ahead of it
+    #[inline]
+    pub fn request(&self, slot: usize, frame: usize) {
+        if let Some(p) = self.playheads.get(slot) {
+            p.store(frame.min(IDLE - 1), Ordering::Relaxed);
+        }
+    }
+
+    // Audio thread: voice `slot` stopped and no longer holds the ring back
+    #[inline]
+    pub fn release(&self, slot: usize) {
+        if let Some(p) = self.playheads.get(slot) {
+            p.store(IDLE, Ordering::Relaxed);
+        }
+    }
+
+    // Slowest playing voice, if any
+    fn min_playhead(&self) -> Option<usize> {
+        self.playheads
+            .iter()
+            .map(|p| p.load(Ordering::Relaxed))
+            .filter(|&f| f != IDLE)
+            .min()
+    }
+
+    // Audio thread: frame from the ring, or silence if it has not been decoded yet
+    #[inline]
+    pub fn sample(&self, frame: usize) -> f32 {
+        let (start, end) = unpack(self.range.load(Ordering::Acquire));
+        if frame < start || frame >= end {
+            return 0.0;
+        }
+        let s = self.ring[frame % self.ring.len()].load(Ordering::Relaxed);
+        // The fill thread drops frames from the window before overwriting their slots: if this
+        // one left the window meanwhile, the value may belong to a newer frame
+        fence(Ordering::Acquire);
+        let (start, end) = unpack(self.range.load(Ordering::Relaxed));
+        if frame < start || frame >= end {
+            return 0.0;
+        }
+        f32::from_bits(s)
+    }
+
+    // Fill thread: append decoded frames, dropping the oldest when the ring is full
+    fn push(&self, frames: &[f32]) {
+        let len = self.ring.len();
+        let (start, end) = unpack(self.range.load(Ordering::Relaxed));
+        let new_end = end + frames.len();
+        let new_start = start.max(new_end.saturating_sub(len));
+        if new_start != start {
+            self.range
+                .store(pack(new_start, end.max(new_start)), Ordering::Relaxed);
+            fence(Ordering::Release);
+        }
+        for (i, &s) in frames
+            .iter()
+            .enumerate()
+            .skip(frames.len().saturating_sub(len))
+        {
+            self.ring[(end + i) % len].store(s.to_bits(), Ordering::Relaxed);
+        }
+        self.range
+            .store(pack(new_start, new_end), Ordering::Release);
+    }
+
+    // Fill thread: empty the ring and restart it at `frame`
+    fn reset_at(&self, frame: usize) {
+        self.range.store(pack(frame, frame), Ordering::Relaxed);
+        fence(Ordering::Release);
+    }
+}
+
+// What the fill thread does next
+#[derive(Debug, PartialEq)]
+enum Fill {
+    Seek(usize),
+    Decode,
+    Idle,
+}
+
+// `want` is the slowest playhead; the ring holds [start, end) out of `total` frames
+fn plan(
+    want: usize,
+    start: usize,
+    end: usize,
+    total: usize,
+    ring_len: usize,
+    lookback: usize,
+    rate: usize,
+) -> Fill {
+    // Playhead left the cached window (loop wrap, new start point): seek and refill
+    if want < start || want > end + lookback + rate {
+        return Fill::Seek(want.saturating_sub(lookback));
+    }
+    // Keep most of the ring ahead of the playhead
+    if end < total && end.saturating_sub(want) < ring_len - lookback {
+        Fill::Decode
+    } else {
+        Fill::Idle
+    }
+}
+
+fn fill_loop(weak: Weak<DiskStream>, mut src: DecodeSource, head_len: usize, rate: f32) {
+    let lookback = (RING_LOOKBACK_SECS * rate) as usize;
+    let mut scratch = Vec::new();
+    // Exits once the owning SampleBuffer (and its clones) are gone
+    while let Some(stream) = weak.upgrade() {
+        // With nothing playing, stay parked just past the head for the next trigger
+        let want = stream.min_playhead().unwrap_or(0).max(head_len);
+        let (start, end) = unpack(stream.range.load(Ordering::Relaxed));
+        let step = plan(
+            want,
+            start,
+            end,
+            stream.total_frames,
+            stream.ring.len(),
+            lookback,
+            rate as usize,
+        );
+        if let Fill::Seek(target) = step {
+            let target = target.max(head_len);
+            match src.seek(target) {
+                Ok(()) => stream.reset_at(target),
+                Err(e) => {
+                    eprintln!("[stream] seek failed: {}", e);
+                    thread::sleep(Duration::from_millis(50));
+                }
+            }
+            continue;
+        }
+
+        if step == Fill::Decode {
+            scratch.clear();
+            match src.next_chunk(&mut scratch) {
+                Ok(true) => stream.push(&scratch),
+                Ok(false) => thread::sleep(Duration::from_millis(10)),
+                Err(e) => {
+                    eprintln!("[stream] decode failed: {}", e);
+                    thread::sleep(Duration::from_millis(50));
+                }
+            }
+        } else {
+            drop(stream);
+            thread::sleep(Duration::from_millis(5));
+        }
+    }
+}
+
+// Format reader + decoder for one track, producing mono f32 frames
+struct DecodeSource {
+    format: Box<dyn FormatReader>,
+    decoder: Box<dyn Decoder>,
+    track_id: u32,
+    sample_rate: f32,
+    total_frames: Option<usize>,
+    skip: usize, // frames to drop after an inexact seek
+}
+
+impl DecodeSource {
+    fn open(path: &str) -> Result<Self, String> {
+        let file = File::open(path).map_err(|e| e.to_string())?;
+        let mss = MediaSourceStream::new(Box::new(file), Default::default());
+        let mut hint = Hint::new();
+        if let Some(ext) = Path::new(path).extension().and_then(|e| e.to_str()) {
+            hint.with_extension(ext);
+        }
+        let meta_opts: MetadataOptions = Default::default();
+        let fmt_opts: FormatOptions = Default::default();
+        let probed = symphonia::default::get_probe()
+            .format(&hint, mss, &fmt_opts, &meta_opts)
+            .map_err(|e| e.to_string())?;
+        let format = probed.format;
+        let track = format
+            .tracks()
+            .iter()
+            .find(|t| t.codec_params.codec != CODEC_TYPE_NULL)
+            .ok_or_else(|| "no supported audio tracks".to_string())?;
+        let dec_opts: DecoderOptions = Default::default();
+        let decoder = symphonia::default::get_codecs()
+            .make(&track.codec_params, &dec_opts)
+            .map_err(|e| e.to_string())?;
+        Ok(Self {
+            track_id: track.id,
+            sample_rate: track.codec_params.sample_rate.unwrap_or(44100) as f32,
+            total_frames: track.codec_params.n_frames.map(|n| n as usize),
+            format,
+            decoder,
+            skip: 0,
+        })
+    }
+
+    // Decode the next packet of this track onto `out`. Ok(false) at end of stream.
+    fn next_chunk(&mut self, out: &mut Vec<f32>) -> Result<bool, String> {
+        loop {
+            let packet = match self.format.next_packet() {
+                Ok(packet) => packet,
+                Err(Error::ResetRequired) | Err(Error::IoError(_)) => return Ok(false),
+                Err(err) => return Err(err.to_string()),
+            };
+            if packet.track_id() != self.track_id {
+                continue;
+            }
+            let decoded = match self.decoder.decode(&packet) {
+                Ok(d) => d,
+                Err(Error::DecodeError(_)) => continue,
+                Err(err) => return Err(err.to_string()),
+            };
+            let before = out.len();
+            mix_to_mono(decoded, out);
+            if self.skip > 0 {
+                let drop_n = self.skip.min(out.len() - before);
+                out.drain(before..before + drop_n);
+                self.skip -= drop_n;
+            }
+            return Ok(true);
+        }
+    }
+
+    fn seek(&mut self, frame: usize) -> Result<(), String> {
+        let seeked = self
+            .format
+            .seek(
+                SeekMode::Accurate,
+                SeekTo::TimeStamp {
+                    ts: frame as u64,
+                    track_id: self.track_id,
+                },
+            )
+            .map_err(|e| e.to_string())?;
+        self.decoder.reset();
+        self.skip = seeked.required_ts.saturating_sub(seeked.actual_ts) as usize;
+        Ok(())
+    }
+}
+
+// Same mixdown as the in-memory loader: average stereo, take the first channel otherwise
+fn mix_to_mono(decoded: AudioBufferRef, out: &mut Vec<f32>) {
+    let spec = *decoded.spec();
+    let channels = spec.channels.count().max(1);
+    let mut buf = DecodeBuffer::<f32>::new(decoded.capacity() as u64, spec);
+    buf.copy_interleaved_ref(decoded);
+    for frame in buf.samples().chunks(channels) {
+        let s = if channels == 2 {
+            (frame[0] + frame[1]) * 0.5
+        } else {
+            frame[0]
+        };
+        out.push(s);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ring_keeps_the_newest_frames() {
+        let ds = DiskStream::with_ring(8, 100, 1000);
+        assert_eq!(ds.sample(100), 0.0);
+        let frames: Vec<f32> = (0..12).map(|i| (100 + i) as f32).collect();
+        ds.push(&frames[..5]);
+        assert_eq!(ds.sample(104), 104.0);
+        assert_eq!(ds.sample(105), 0.0);
+        // Wrapping drops the oldest frames from the window
+        ds.push(&frames[5..]);
+        assert_eq!(unpack(ds.range.load(Ordering::Relaxed)), (104, 112));
+        assert_eq!(ds.sample(103), 0.0);
+        for f in 104..112 {
+            assert_eq!(ds.sample(f), f as f32);
+        }
+        ds.reset_at(500);
+        assert_eq!(ds.sample(110), 0.0);
+        ds.push(&[7.0]);
+        assert_eq!(ds.sample(500), 7.0);
+        assert_eq!(ds.sample(501), 0.0);
+    }
+
+    #[test]
+    fn slowest_voice_sets_the_playhead() {
+        let ds = DiskStream::with_ring(8, 0, 1000);
+        assert_eq!(ds.min_playhead(), None);
+        ds.request(0, 900);
+        ds.request(3, 200);
+        assert_eq!(ds.min_playhead(), Some(200));
+        ds.release(3);
+        assert_eq!(ds.min_playhead(), Some(900));
+        // Out-of-range slots are ignored rather than panicking on the audio thread
+        ds.request(STREAM_PLAYHEADS, 5);
+        assert_eq!(ds.min_playhead(), Some(900));
+    }
+
+    #[test]
+    fn plan_seeks_only_when_the_playhead_leaves_the_window() {
+        let (ring, look, rate, total) = (1600, 200, 100, 10_000);
+        // Inside the window with room ahead: keep decoding
+        assert_eq!(plan(1000, 800, 1500, total, ring, look, rate), Fill::Decode);
+        // Ring full ahead of the playhead, or the file is done: idle
+        assert_eq!(plan(1000, 800, 2400, total, ring, look, rate), Fill::Idle);
+        assert_eq!(plan(9900, 8500, total, total, ring, look, rate), Fill::Idle);
+        // Small jumps ahead are decoded through, not seeked
+        assert_eq!(plan(1700, 800, 1500, total, ring, look, rate), Fill::Decode);
+        // Backwards past the window (loop wrap) or far ahead: seek with lookback
+        assert_eq!(
+            plan(500, 800, 1500, total, ring, look, rate),
+            Fill::Seek(300)
+        );
+        assert_eq!(
+            plan(5000, 800, 1500, total, ring, look, rate),
+            Fill::Seek(4800)
+        );
+    }
+
+    #[test]
+    fn streams_a_file_past_the_head_and_seeks_back() {
+        let rate = 1000;
+        let total = 60_000; // 60 s: head is 8 s, ring 16 s
+        let path =
+            std::env::temp_dir().join(format!("disk_stream_test_{}.wav", std::process::id()));
+        let spec = hound::WavSpec {
+            channels: 1,
+            sample_rate: rate,
+            bits_per_sample: 32,
+            sample_format: hound::SampleFormat::Float,
+        };
+        let value = |i: usize| (i % 977) as f32 / 977.0;
+        let mut w = hound::WavWriter::create(&path, spec).unwrap();
+        for i in 0..total {
+            w.write_sample(value(i)).unwrap();
+        }
+        w.finalize().unwrap();
+
+        let (head, sr, frames, ds) = DiskStream::open(path.to_str().unwrap()).unwrap();
+        assert_eq!((head.len(), sr as u32, frames), (8000, rate, total));
+        let wait_for = |frame: usize| {
+            for _ in 0..400 {
+                if ds.sample(frame) != 0.0 || frame % 977 == 0 {
+                    return ds.sample(frame);
+                }
+                thread::sleep(Duration::from_millis(5));
+            }
+            panic!("frame {frame} never arrived");
+        };
+        // Two voices far apart: the ring follows the slower one instead of thrashing
+        ds.request(0, 45_000);
+        ds.request(1, 9_000);
+        assert_eq!(wait_for(12_000), value(12_000));
+        ds.release(1);
+        assert_eq!(wait_for(50_000), value(50_000));
+        // Loop wrap back towards the head
+        ds.request(0, 10_000);
+        assert_eq!(wait_for(10_500), value(10_500));
+        drop(ds);
+        let _ = std::fs::remove_file(&path);
+    }
+}
@@ -1,4 +1,5 @@
 use std::fs::File;
+use std::ops::Range;
 use std::path::Path;
 
 use symphonia::core::audio::{AudioBufferRef, Signal};
@@ -9,10 +10,17 @@ use symphonia::core::io::MediaSourceStream;
 use symphonia::core::meta::MetadataOptions;
 use symphonia::core::probe::Hint;
 
+use crate::engine::dsp::resample::{resample, sinc_read};
+use crate::engine::dsp::svf::Svf;
+use crate::engine::messages::DrumZoneSpec;
 use crate::engine::params::{hash_path, ParamStore};
 
 pub const MAX_DRUM_SLOTS: usize = 32;
+// Note for slot 0 when a pack gives no explicit mapping (C2, typical drum root)
+pub const DRUM_BASE_NOTE: u8 = 36;
 const MAX_DRUM_VOICES: usize = 64;
+// Fade applied when a voice is choked or released, instead of cutting it (which clicks)
+const CHOKE_RELEASE_MS: f32 = 5.0;
 
 #[derive(Clone)]
 struct DrumSample {
@@ -43,23 +51,208 @@ impl DrumSample {
         let b = self.data[next];
         a + (b - a) * frac
     }
+    // Pitched-up reads go through the band-limited kernel to avoid aliasing
+    fn sample_at_step(&self, pos: f32, step: f32) -> f32 {
+        if step > 1.001 {
+            sinc_read(&self.data, pos, step)
+        } else {
+            self.sample_at(pos)
+        }
+    }
+    // Convert to the engine rate once so playback at unity pitch needs no interpolation
+    fn conform_rate(&mut self, sr: f32) {
+        if (self.sample_rate - sr).abs() >= 0.5 {
+            self.data = resample(&self.data, self.sample_rate, sr);
+            self.len = self.data.len();
+            self.sample_rate = sr;
+        }
+    }
+}
+
+// One sample of a slot, played for velocities in [vel_lo, vel_hi)
+struct DrumZone {
+    sample: DrumSample,
+    vel_lo: f32,
+    vel_hi: f32,
+}
+
+// Zones sharing a velocity range: the round-robin variants of one layer
+struct DrumLayer {
+    zones: Range<usize>, // contiguous in DrumSlot::zones
+    vel_lo: f32,
+    vel_hi: f32,
+    rr_next: usize, // cyclic round-robin position
+}
+
+struct DrumSlot {
+    zones: Vec<DrumZone>,
+    layers: Vec<DrumLayer>,
+    last_zone: usize,
+}
+
+impl DrumSlot {
+    // Groups the zones into layers; variants keep their load order within a layer
+    fn new(mut zones: Vec<DrumZone>) -> Self {
+        zones.sort_by(|a, b| {
+            a.vel_lo
+                .total_cmp(&b.vel_lo)
+                .then(a.vel_hi.total_cmp(&b.vel_hi))
+        });
+        let mut layers: Vec<DrumLayer> = Vec::new();
+        for (i, z) in zones.iter().enumerate() {
+            match layers.last_mut() {
+                Some(l) if l.vel_lo == z.vel_lo && l.vel_hi == z.vel_hi => l.zones.end = i + 1,
+                _ => layers.push(DrumLayer {
+                    zones: i..i + 1,
+                    vel_lo: z.vel_lo,
+                    vel_hi: z.vel_hi,
+                    rr_next: 0,
+                }),
+            }
+        }
+        Self {
+            zones,
+            layers,
+            last_zone: usize::MAX,
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.zones.iter().all(|z| z.sample.len == 0)
+    }
+
+    // Layer whose velocity range holds `vel`; falls back to the nearest layer so a gap in the
+    // ranges never silences a hit
+    fn layer_for(&self, vel: f32) -> Option<usize> {
+        let holds =
+            |l: &DrumLayer| vel >= l.vel_lo && (vel < l.vel_hi || (l.vel_hi >= 1.0 && vel <= 1.0));
+        if let Some(i) = self.layers.iter().position(holds) {
+            return Some(i);
+        }
+        let dist = |l: &DrumLayer| (l.vel_lo - vel).max(vel - l.vel_hi).max(0.0);
+        (0..self.layers.len())
+            .min_by(|&a, &b| dist(&self.layers[a]).total_cmp(&dist(&self.layers[b])))
+    }
+}
+
+// Per-slot insert applied to the sum of that slot's voices: SVF, then tanh drive
+struct SlotTone {
+    svf: Svf,
+    last_cutoff: f32,
+    last_q: f32,
+    ringing: bool, // output still above silence, keep processing the tail
+}
+
+impl SlotTone {
+    fn new() -> Self {
+        Self {
+            svf: Svf::new(),
+            last_cutoff: -1.0,
+            last_q: -1.0,
+            ringing: false,
+        }
+    }
+
+    fn process(
+        &mut self,
+        params: &ParamStore,
+        keys: &DrumParamKeys,
+        slot: usize,
+        sr: f32,
+        x: f32,
+    ) -> f32 {
+        let mut y = x;
+        // 0 = off, 1 = LP, 2 = HP, 3 = BP
+        let mode = params.get_i32_h(keys.slot_filter_type[slot], 0);
+        if mode > 0 {
+            let cutoff = params
+                .get_f32_h(keys.slot_cutoff[slot], 18000.0)
+                .clamp(20.0, sr * 0.45);
+            let res = params
+                .get_f32_h(keys.slot_resonance[slot], 0.0)
+                .clamp(0.0, 1.0);
+            let q = 0.707 + res * res * 15.0;
+            if (cutoff - self.last_cutoff).abs() > 0.5 || (q - self.last_q).abs() > 1e-4 {
+                self.svf.set_params(cutoff, q, sr);
+                self.last_cutoff = cutoff;
+                self.last_q = q;
+            }
+            let (lp, hp, bp, _) = self.svf.process(x);
+            y = match mode {
+                2 => hp,
+                3 => bp,
+                _ => lp,
+            };
+        }
+        let drive = params.get_f32_h(keys.slot_drive[slot], 0.0).clamp(0.0, 1.0);
+        if drive > 0.0005 {
+            // Normalised so a full-scale input stays at full scale
+            let g = 1.0 + drive * 9.0;
+            y = (y * g).tanh() / g.tanh();
+        }
+        self.ringing = mode > 0 && y.abs() > 1e-6;
+        y
+    }
 }
 
 #[derive(Clone, Copy)]
 struct DrumVoice {
     slot: usize,
-    position: f32,
+    zone: usize,
+    position: f32, // frames played, counted from the start offset in the playback direction
     velocity: f32,
     active: bool,
+    reverse: bool,
+    start: f32,   // start offset in frames, latched at trigger
+    age: f32,     // seconds since trigger, drives the AHD envelope
+    release: f32, // 1.0 until choked/released, then fades to 0
+    releasing: bool,
 }
 
 impl DrumVoice {
     fn new() -> Self {
         Self {
             slot: 0,
+            zone: 0,
             position: 0.0,
             velocity: 0.0,
             active: false,
+            reverse: false,
+            start: 0.0,
+            age: 0.0,
+            release: 1.0,
+            releasing: false,
+        }
+    }
+
+    fn trigger(slot: usize, zone: usize, velocity: f32, reverse: bool, start: f32) -> Self {
+        Self {
+            slot,
+            zone,
+            velocity,
+            active: true,
+            reverse,
+            start,
+            ..Self::new()
+        }
+    }
+
+    // Attack / hold / decay gain at the voice's age. A decay of 0 disables the decay stage so
+    // the sample plays to its end (the original one-shot behaviour). None once decay finishes.
+    fn envelope(&self, attack: f32, hold: f32, decay: f32) -> Option<f32> {
+        if self.age < attack {
+            return Some(self.age / attack);
+        }
+        if decay <= 0.0 {
+            return Some(1.0);
+        }
+        let t = (self.age - attack - hold) / decay;
+        if t <= 0.0 {
+            Some(1.0)
+        } else if t >= 1.0 {
+            None
+        } else {
+            Some((1.0 - t) * (1.0 - t))
         }
     }
 }
@@ -71,6 +264,19 @@ pub struct DrumParamKeys {
     pub slot_pan: [u64; MAX_DRUM_SLOTS],
     pub slot_semitones: [u64; MAX_DRUM_SLOTS],
     pub slot_fine: [u64; MAX_DRUM_SLOTS],
+    pub slot_choke: [u64; MAX_DRUM_SLOTS], // 0 = none, 1..=8 = choke group
+    pub slot_attack: [u64; MAX_DRUM_SLOTS],
+    pub slot_hold: [u64; MAX_DRUM_SLOTS],
+    pub slot_decay: [u64; MAX_DRUM_SLOTS],
+    pub slot_reverse: [u64; MAX_DRUM_SLOTS],
+    pub slot_start: [u64; MAX_DRUM_SLOTS],
+    pub slot_rr_mode: [u64; MAX_DRUM_SLOTS], // 0 = cycle, 1 = random
+    pub slot_filter_type: [u64; MAX_DRUM_SLOTS],
+    pub slot_cutoff: [u64; MAX_DRUM_SLOTS],
+    pub slot_resonance: [u64; MAX_DRUM_SLOTS],
+    pub slot_drive: [u64; MAX_DRUM_SLOTS],
+    pub slot_output: [u64; MAX_DRUM_SLOTS], // 0 = part, 1 = aux, 2..=5 = sub-channel 1..4
+    pub slot_aux_send: [u64; MAX_DRUM_SLOTS],
 }
 
 impl DrumParamKeys {
@@ -79,12 +285,38 @@ impl DrumParamKeys {
         let mut slot_pan = [0u64; MAX_DRUM_SLOTS];
         let mut slot_semitones = [0u64; MAX_DRUM_SLOTS];
         let mut slot_fine = [0u64; MAX_DRUM_SLOTS];
+        let mut slot_choke = [0u64; MAX_DRUM_SLOTS];
+        let mut slot_attack = [0u64; MAX_DRUM_SLOTS];
+        let mut slot_hold = [0u64; MAX_DRUM_SLOTS];
+        let mut slot_decay = [0u64; MAX_DRUM_SLOTS];
+        let mut slot_reverse = [0u64; MAX_DRUM_SLOTS];
+        let mut slot_start = [0u64; MAX_DRUM_SLOTS];
+        let mut slot_rr_mode = [0u64; MAX_DRUM_SLOTS];
+        let mut slot_filter_type = [0u64; MAX_DRUM_SLOTS];
+        let mut slot_cutoff = [0u64; MAX_DRUM_SLOTS];
+        let mut slot_resonance = [0u64; MAX_DRUM_SLOTS];
+        let mut slot_drive = [0u64; MAX_DRUM_SLOTS];
+        let mut slot_output = [0u64; MAX_DRUM_SLOTS];
+        let mut slot_aux_send = [0u64; MAX_DRUM_SLOTS];
         for i in 0..MAX_DRUM_SLOTS {
             let base = format!("part/{}/drum/slot/{}", part_idx, i);
             slot_volume[i] = hash_path(&format!("{}/volume", base));
             slot_pan[i] = hash_path(&format!("{}/pan", base));
             slot_semitones[i] = hash_path(&format!("{}/pitch_semitones", base));
             slot_fine[i] = hash_path(&format!("{}/pitch_fine", base));
+            slot_choke[i] = hash_path(&format!("{}/choke_group", base));
+            slot_attack[i] = hash_path(&format!("{}/attack", base));
+            slot_hold[i] = hash_path(&format!("{}/hold", base));
+            slot_decay[i] = hash_path(&format!("{}/decay", base));
+            slot_reverse[i] = hash_path(&format!("{}/reverse", base));
+            slot_start[i] = hash_path(&format!("{}/start", base));
+            slot_rr_mode[i] = hash_path(&format!("{}/rr_mode", base));
+            slot_filter_type[i] = hash_path(&format!("{}/filter_type", base));
+            slot_cutoff[i] = hash_path(&format!("{}/cutoff", base));
+            slot_resonance[i] = hash_path(&format!("{}/resonance", base));
+            slot_drive[i] = hash_path(&format!("{}/drive", base));
+            slot_output[i] = hash_path(&format!("{}/output", base));
+            slot_aux_send[i] = hash_path(&format!("{}/aux_send", base));
         }
         Self {
             module_kind: hash_path(&format!("part/{}/module_kind", part_idx)),
@@ -92,63 +324,127 @@ impl DrumParamKeys {
             slot_pan,
             slot_semitones,
             slot_fine,
+            slot_choke,
+            slot_attack,
+            slot_hold,
+            slot_decay,
+            slot_reverse,
+            slot_start,
+            slot_rr_mode,
+            slot_filter_type,
+            slot_cutoff,
+            slot_resonance,
+            slot_drive,
+            slot_output,
+            slot_aux_send,
         }
     }
 }
 
+// Mixer buses a slot can be routed to: 0 = aux, 1..=4 = sub-channels
+pub const DRUM_BUSES: usize = 5;
+
+#[derive(Default, Clone, Copy)]
+pub struct DrumBusFrame {
+    pub mono: f32,
+    pub pan_accum: f32,
+    pub energy: f32,
+}
+
+impl DrumBusFrame {
+    fn add(&mut self, y: f32, pan: f32) {
+        self.mono += y;
+        self.pan_accum += y * pan;
+        self.energy += y.abs();
+    }
+}
+
 #[derive(Default)]
 pub struct DrumRenderFrame {
     pub mono: f32,
     pub pan_accum: f32,
     pub energy: f32,
+    pub buses: [DrumBusFrame; DRUM_BUSES], // slots routed away from the part output
 }
 
 pub struct DrumPlayer {
     sr: f32,
-    samples: Vec<DrumSample>,
+    slots: Vec<DrumSlot>,
     sample_names: Vec<String>,
+    slot_notes: Vec<u8>, // trigger note per slot
+    tone: Vec<SlotTone>,
+    slot_mix: [f32; MAX_DRUM_SLOTS], // this frame's voice sum per slot, before the slot insert
     voices: Vec<DrumVoice>,
     next_voice: usize,
+    rng: u32,
 }
 
 impl DrumPlayer {
     pub fn new(sr: f32) -> Self {
         Self {
             sr,
-            samples: Vec::new(),
+            slots: Vec::new(),
             sample_names: Vec::new(),
+            slot_notes: Vec::new(),
+            tone: (0..MAX_DRUM_SLOTS).map(|_| SlotTone::new()).collect(),
+            slot_mix: [0.0; MAX_DRUM_SLOTS],
             voices: (0..MAX_DRUM_VOICES).map(|_| DrumVoice::new()).collect(),
             next_voice: 0,
+            rng: 0x1234_5678,
         }
     }
 
     pub fn clear(&mut self) {
-        self.samples.clear();
+        self.slots.clear();
         self.sample_names.clear();
+        self.slot_notes.clear();
+        for t in &mut self.tone {
+            *t = SlotTone::new();
+        }
         for v in &mut self.voices {
             *v = DrumVoice::new();
         }
     }
 
-    pub fn load_pack(&mut self, paths: &[String]) {
+    // Each slot is a list of zones (velocity layers, and round-robin variants within a layer)
+    pub fn load_pack(&mut self, slots: &[Vec<DrumZoneSpec>], notes: &[u8]) {
         self.clear();
-        for path in paths.iter().take(MAX_DRUM_SLOTS) {
-            match Self::decode_sample(path) {
-                Ok(sample) => {
-                    let name = Path::new(path)
+        for (i, specs) in slots.iter().take(MAX_DRUM_SLOTS).enumerate() {
+            let note = notes
+                .get(i)
+                .copied()
+                .unwrap_or(DRUM_BASE_NOTE.saturating_add(i as u8));
+            self.slot_notes.push(note);
+            let mut zones = Vec::with_capacity(specs.len());
+            for spec in specs {
+                let sample = match Self::decode_sample(&spec.path) {
+                    Ok(mut sample) => {
+                        sample.conform_rate(self.sr);
+                        sample
+                    }
+                    Err(err) => {
+                        eprintln!("[drum] failed to load {}: {}", spec.path, err);
+                        DrumSample::empty()
+                    }
+                };
+                zones.push(DrumZone {
+                    sample,
+                    vel_lo: spec.vel_lo.clamp(0.0, 1.0),
+                    vel_hi: spec.vel_hi.clamp(0.0, 1.0),
+                });
+            }
+            let name = specs
+                .first()
+                .map(|z| {
+                    Path::new(&z.path)
                         .file_name()
                         .and_then(|n| n.to_str())
                         .map(|s| s.to_string())
-                        .unwrap_or_else(|| path.clone());
-                    self.samples.push(sample);
-                    self.sample_names.push(name);
-                }
-                Err(err) => {
-                    eprintln!("[drum] failed to load {}: {}", path, err);
-                    self.samples.push(DrumSample::empty());
-                    self.sample_names.push(path.clone());
-                }
-            }
+                        .unwrap_or_else(|| z.path.clone())
+                })
+                .unwrap_or_default();
+            self.slots.push(DrumSlot::new(zones));
+            self.sample_names.push(name);
         }
     }
 
@@ -157,50 +453,93 @@ impl DrumPlayer {
         &self.sample_names
     }
 
-    pub fn note_on(&mut self, note: u8, vel: f32) {
-        if self.samples.is_empty() {
+    // Give `slot` a new trigger note; a slot already on that note takes the old one
+    pub fn set_slot_note(&mut self, slot: usize, note: u8) {
+        if slot >= self.slot_notes.len() {
             return;
         }
-        let slot = self.slot_for_note(note);
-        if slot >= self.samples.len() {
-            return;
+        let old = self.slot_notes[slot];
+        if let Some(other) = self.slot_notes.iter().position(|&n| n == note) {
+            self.slot_notes[other] = old;
+        }
+        self.slot_notes[slot] = note;
+    }
+
+    pub fn swap_slots(&mut self, a: usize, b: usize) {
+        if a < self.slot_notes.len() && b < self.slot_notes.len() {
+            self.slot_notes.swap(a, b);
+        }
+    }
+
+    pub fn note_on(&mut self, params: &ParamStore, keys: &DrumParamKeys, note: u8, vel: f32) {
+        let slot = match self.slot_for_note(note) {
+            Some(slot) => slot,
+            None => return,
+        };
+        // Choke: a hit releases every voice in the same group (open hat cut by closed hat)
+        let group = params.get_i32_h(keys.slot_choke[slot], 0);
+        if group > 0 {
+            for v in &mut self.voices {
+                if v.active && params.get_i32_h(keys.slot_choke[v.slot], 0) == group {
+                    v.releasing = true;
+                }
+            }
         }
         let velocity = vel.clamp(0.0, 1.0);
+        let li = match self.slots[slot].layer_for(velocity) {
+            Some(li) => li,
+            None => return,
+        };
+        let random = params.get_i32_h(keys.slot_rr_mode[slot], 0) == 1;
+        if random {
+            self.rng = self.rng.wrapping_mul(1664525).wrapping_add(1013904223);
+        }
+        let s = &mut self.slots[slot];
+        let layer = &mut s.layers[li];
+        let n = layer.zones.len();
+        let pick = if random {
+            let mut pick = (self.rng >> 8) as usize % n;
+            // Never the same variant twice in a row
+            if n > 1 && layer.zones.start + pick == s.last_zone {
+                pick = (pick + 1) % n;
+            }
+            pick
+        } else {
+            let pick = layer.rr_next % n;
+            layer.rr_next = layer.rr_next.wrapping_add(1);
+            pick
+        };
+        let zone = layer.zones.start + pick;
+        s.last_zone = zone;
+        let len = self.slots[slot].zones[zone].sample.len as f32;
+        let start = params.get_f32_h(keys.slot_start[slot], 0.0).clamp(0.0, 1.0) * len;
+        let reverse = params.get_i32_h(keys.slot_reverse[slot], 0) != 0;
+        let voice = DrumVoice::trigger(slot, zone, velocity, reverse, start);
         // find free voice
         if let Some(v) = self.voices.iter_mut().find(|v| !v.active) {
-            *v = DrumVoice {
-                slot,
-                position: 0.0,
-                velocity,
-                active: true,
-            };
+            *v = voice;
             return;
         }
         // steal next voice (simple round robin)
         let idx = self.next_voice;
         self.next_voice = (self.next_voice + 1) % self.voices.len();
-        self.voices[idx] = DrumVoice {
-            slot,
-            position: 0.0,
-            velocity,
-            active: true,
-        };
+        self.voices[idx] = voice;
     }
 
     pub fn note_off(&mut self, note: u8) {
-        if self.samples.is_empty() {
-            return;
-        }
-        let slot = self.slot_for_note(note);
+        let slot = match self.slot_for_note(note) {
+            Some(slot) => slot,
+            None => return,
+        };
         for v in &mut self.voices {
             if v.active && v.slot == slot {
-                v.active = false;
+                v.releasing = true;
             }
         }
     }
 
     pub fn render(&mut self, params: &ParamStore, keys: &DrumParamKeys) -> DrumRenderFrame {
-        if self.samples.is_empty() {
+        if self.slots.is_empty() {
             return DrumRenderFrame::default();
         }
 
@@ -210,7 +549,8 @@ impl DrumPlayer {
                 continue;
             }
             let slot = voice.slot;
-            let sample = match self.samples.get(slot) {
+            let zone = self.slots.get(slot).and_then(|s| s.zones.get(voice.zone));
+            let sample = match zone.map(|z| &z.sample) {
                 Some(s) if s.len > 0 => s,
                 _ => {
                     voice.active = false;
@@ -218,12 +558,9 @@ impl DrumPlayer {
                 }
             };
 
-            let amp = sample.sample_at(voice.position);
-
             let volume = params
                 .get_f32_h(keys.slot_volume[slot], 0.85)
                 .clamp(0.0, 1.5);
-            let pan_norm = params.get_f32_h(keys.slot_pan[slot], 0.5).clamp(0.0, 1.0);
             let semis = params.get_f32_h(keys.slot_semitones[slot], 0.0);
             let fine = params.get_f32_h(keys.slot_fine[slot], 0.0);
             let total_semi = semis + fine / 100.0;
@@ -231,32 +568,81 @@ impl DrumPlayer {
             let base_step = (sample.sample_rate / self.sr).max(0.01);
             let step = (base_step * ratio).clamp(0.01, 64.0);
 
-            let amp_scaled = amp * volume * voice.velocity;
-            let pan = (pan_norm * 2.0) - 1.0; // -1..1
-            frame.mono += amp_scaled;
-            frame.pan_accum += amp_scaled * pan;
-            frame.energy += amp_scaled.abs();
+            let attack = params
+                .get_f32_h(keys.slot_attack[slot], 0.0)
+                .clamp(0.0, 2000.0)
+                * 0.001;
+            let hold = params
+                .get_f32_h(keys.slot_hold[slot], 0.0)
+                .clamp(0.0, 5000.0)
+                * 0.001;
+            let decay = params
+                .get_f32_h(keys.slot_decay[slot], 0.0)
+                .clamp(0.0, 10000.0)
+                * 0.001;
+            let env = match voice.envelope(attack, hold, decay) {
+                Some(g) => g,
+                None => {
+                    voice.active = false;
+                    continue;
+                }
+            };
+            if voice.releasing {
+                voice.release -= 1.0 / (CHOKE_RELEASE_MS * 0.001 * self.sr);
+                if voice.release <= 0.0 {
+                    voice.active = false;
+                    continue;
+                }
+            }
+
+            let offset = voice.start + voice.position;
+            let read_pos = if voice.reverse {
+                sample.len as f32 - 1.0 - offset
+            } else {
+                offset
+            };
+            let amp = sample.sample_at_step(read_pos, step);
+
+            self.slot_mix[slot] += amp * volume * voice.velocity * env * voice.release;
 
             voice.position += step;
-            if voice.position >= sample.len as f32 {
+            voice.age += 1.0 / self.sr;
+            if offset + step >= sample.len as f32 {
                 voice.active = false;
             }
         }
+
+        for slot in 0..self.slots.len() {
+            let x = std::mem::take(&mut self.slot_mix[slot]);
+            if x == 0.0 && !self.tone[slot].ringing {
+                continue;
+            }
+            let y = self.tone[slot].process(params, keys, slot, self.sr, x);
+            let pan_norm = params.get_f32_h(keys.slot_pan[slot], 0.5).clamp(0.0, 1.0);
+            let pan = (pan_norm * 2.0) - 1.0; // -1..1
+            let output = params.get_i32_h(keys.slot_output[slot], 0);
+            if output >= 1 && (output as usize) <= DRUM_BUSES {
+                frame.buses[output as usize - 1].add(y, pan);
+            } else {
+                frame.mono += y;
+                frame.pan_accum += y * pan;
+                frame.energy += y.abs();
+            }
+            let send = params
+                .get_f32_h(keys.slot_aux_send[slot], 0.0)
+                .clamp(0.0, 1.0);
+            if send > 0.0005 {
+                frame.buses[0].add(y * send, pan);
+            }
+        }
         frame
     }
 
-    fn slot_for_note(&self, note: u8) -> usize {
-        if self.samples.is_empty() {
-            return 0;
-        }
-        let count = self.samples.len();
-        let base = 36u8; // C2 typical drum root
-        if note >= base {
-            let idx = (note - base) as usize;
-            idx % count
-        } else {
-            (note as usize) % count
-        }
+    fn slot_for_note(&self, note: u8) -> Option<usize> {
+        self.slot_notes
+            .iter()
+            .position(|&n| n == note)
+            .filter(|&slot| slot < self.slots.len() && !self.slots[slot].is_empty())
     }
 
     fn decode_sample(path: &str) -> Result<DrumSample, String> {
@@ -463,3 +849,34 @@ impl DrumPlayer {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn zone(vel_lo: f32, vel_hi: f32) -> DrumZone {
+        DrumZone {
+            sample: DrumSample::empty(),
+            vel_lo,
+            vel_hi,
+        }
+    }
+
+    #[test]
+    fn zones_group_into_velocity_layers() {
+        let slot = DrumSlot::new(vec![
+            zone(0.5, 1.0),
+            zone(0.0, 0.3),
+            zone(0.5, 1.0),
+            zone(0.0, 0.3),
+        ]);
+        assert_eq!(slot.layers.len(), 2);
+        assert_eq!(slot.layers[0].zones, 0..2);
+        assert_eq!(slot.layers[1].zones, 2..4);
+        assert_eq!(slot.layer_for(0.1), Some(0));
+        assert_eq!(slot.layer_for(1.0), Some(1));
+        // 0.45 falls in the gap; the nearer layer wins
+        assert_eq!(slot.layer_for(0.45), Some(1));
+        assert_eq!(DrumSlot::new(Vec::new()).layer_for(0.5), None);
+    }
+}
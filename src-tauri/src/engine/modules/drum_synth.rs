@@ -0,0 +1,338 @@
+// Drum synth - analog-modelled drum voices, no samples needed
+//
+// Uses the same slot/note layout as DrumPlayer (slot i on note 36 + i unless remapped), so a
+// pattern written for a sample kit plays the matching synth voices:
+//   0 kick, 1 snare, 2 closed hat, 3 open hat, 4 clap, 5 low tom, 6 mid tom, 7 high tom
+//
+// Every slot reads the same generic keys under part/N/drumsynth/slot/M/...:
+//   level, pan, tune (semitones), decay (0..1), tone (0..1), character (0..1), drive (0..1)
+// `character` is the model's main colour control: kick click, snare noise amount, hat
+// metal/noise balance, clap burst spread, tom pitch sweep.
+
+use std::f32::consts::PI;
+
+use crate::engine::dsp::svf::Svf;
+use crate::engine::modules::drum::{DrumRenderFrame, DRUM_BASE_NOTE};
+use crate::engine::params::{hash_path, ParamStore};
+
+pub const DRUM_SYNTH_SLOTS: usize = 8;
+
+const KICK: usize = 0;
+const SNARE: usize = 1;
+const CLOSED_HAT: usize = 2;
+const OPEN_HAT: usize = 3;
+const CLAP: usize = 4;
+
+// TR-808 style metallic oscillator bank (Hz at tune = 0)
+const HAT_FREQS: [f32; 6] = [205.3, 304.4, 369.6, 522.7, 540.0, 800.0];
+// Clap: bursts before the tail, in seconds after the trigger (scaled by `character`)
+const CLAP_BURSTS: [f32; 3] = [0.0, 0.011, 0.023];
+
+pub struct DrumSynthParamKeys {
+    pub level: [u64; DRUM_SYNTH_SLOTS],
+    pub pan: [u64; DRUM_SYNTH_SLOTS],
+    pub tune: [u64; DRUM_SYNTH_SLOTS],
+    pub decay: [u64; DRUM_SYNTH_SLOTS],
+    pub tone: [u64; DRUM_SYNTH_SLOTS],
+    pub character: [u64; DRUM_SYNTH_SLOTS],
+    pub drive: [u64; DRUM_SYNTH_SLOTS],
+}
+
+impl DrumSynthParamKeys {
+    pub fn new(part_idx: usize) -> Self {
+        let key = |slot: usize, name: &str| {
+            hash_path(&format!(
+                "part/{}/drumsynth/slot/{}/{}",
+                part_idx, slot, name
+            ))
+        };
+        Self {
+            level: std::array::from_fn(|i| key(i, "level")),
+            pan: std::array::from_fn(|i| key(i, "pan")),
+            tune: std::array::from_fn(|i| key(i, "tune")),
+            decay: std::array::from_fn(|i| key(i, "decay")),
+            tone: std::array::from_fn(|i| key(i, "tone")),
+            character: std::array::from_fn(|i| key(i, "character")),
+            drive: std::array::from_fn(|i| key(i, "drive")),
+        }
+    }
+}
+
+// Per-slot parameters read once per sample
+struct VoiceParams {
+    tune: f32, // pitch ratio
+    decay: f32,
+    tone: f32,
+    character: f32,
+}
+
+#[derive(Clone)]
+struct SynthVoice {
+    active: bool,
+    t: f32, // seconds since trigger
+    velocity: f32,
+    phase: [f32; 6],
+    choke: f32, // 1.0, fading to 0 after a choke
+    choked: bool,
+    filt: Svf,
+    body: Svf,
+}
+
+impl SynthVoice {
+    fn new() -> Self {
+        Self {
+            active: false,
+            t: 0.0,
+            velocity: 0.0,
+            phase: [0.0; 6],
+            choke: 1.0,
+            choked: false,
+            filt: Svf::new(),
+            body: Svf::new(),
+        }
+    }
+
+    fn trigger(&mut self, velocity: f32) {
+        self.active = true;
+        self.t = 0.0;
+        self.velocity = velocity;
+        self.phase = [0.0; 6];
+        self.choke = 1.0;
+        self.choked = false;
+    }
+
+    #[inline]
+    fn sine(&mut self, i: usize, freq: f32, sr: f32) -> f32 {
+        let y = (2.0 * PI * self.phase[i]).sin();
+        self.phase[i] = (self.phase[i] + freq / sr).fract();
+        y
+    }
+
+    #[inline]
+    fn square(&mut self, i: usize, freq: f32, sr: f32) -> f32 {
+        let y = if self.phase[i] < 0.5 { 1.0 } else { -1.0 };
+        self.phase[i] = (self.phase[i] + freq / sr).fract();
+        y
+    }
+}
+
+fn exp_env(t: f32, time: f32) -> f32 {
+    (-t / time.max(1e-4)).exp()
+}
+
+pub struct DrumSynth {
+    sr: f32,
+    voices: Vec<SynthVoice>,
+    slot_notes: [u8; DRUM_SYNTH_SLOTS],
+    rng: u32,
+}
+
+impl DrumSynth {
+    pub fn new(sr: f32) -> Self {
+        Self {
+            sr,
+            voices: (0..DRUM_SYNTH_SLOTS).map(|_| SynthVoice::new()).collect(),
+            slot_notes: std::array::from_fn(|i| DRUM_BASE_NOTE + i as u8),
+            rng: 0x2468_ace1,
+        }
+    }
+
+    // Same semantics as DrumPlayer::set_slot_note / swap_slots
+    pub fn set_slot_note(&mut self, slot: usize, note: u8) {
+        if slot >= DRUM_SYNTH_SLOTS {
+            return;
+        }
+        let old = self.slot_notes[slot];
+        if let Some(other) = self.slot_notes.iter().position(|&n| n == note) {
+            self.slot_notes[other] = old;
+        }
+        self.slot_notes[slot] = note;
+    }
+
+    pub fn swap_slots(&mut self, a: usize, b: usize) {
+        if a < DRUM_SYNTH_SLOTS && b < DRUM_SYNTH_SLOTS {
+            self.slot_notes.swap(a, b);
+        }
+    }
+
+    pub fn note_on(&mut self, note: u8, vel: f32) {
+        let slot = match self.slot_notes.iter().position(|&n| n == note) {
+            Some(slot) => slot,
+            None => return,
+        };
+        // Closed hat cuts the open hat
+        if slot == CLOSED_HAT && self.voices[OPEN_HAT].active {
+            self.voices[OPEN_HAT].choked = true;
+        }
+        self.voices[slot].trigger(vel.clamp(0.0, 1.0));
+    }
+
+    // One-shots: note off is ignored, like a drum machine
+    pub fn note_off(&mut self, _note: u8) {}
+
+    #[inline]
+    fn noise(&mut self) -> f32 {
+        self.rng = self.rng.wrapping_mul(1664525).wrapping_add(1013904223);
+        (self.rng >> 8) as f32 / (1u32 << 23) as f32 - 1.0
+    }
+
+    pub fn render(&mut self, params: &ParamStore, keys: &DrumSynthParamKeys) -> DrumRenderFrame {
+        let mut frame = DrumRenderFrame::default();
+        let sr = self.sr;
+        let dt = 1.0 / sr;
+        for slot in 0..DRUM_SYNTH_SLOTS {
+            if !self.voices[slot].active {
+                continue;
+            }
+            let p = VoiceParams {
+                tune: 2f32.powf(params.get_f32_h(keys.tune[slot], 0.0).clamp(-24.0, 24.0) / 12.0),
+                decay: params.get_f32_h(keys.decay[slot], 0.5).clamp(0.0, 1.0),
+                tone: params.get_f32_h(keys.tone[slot], 0.5).clamp(0.0, 1.0),
+                character: params.get_f32_h(keys.character[slot], 0.5).clamp(0.0, 1.0),
+            };
+            let n = self.noise();
+            let mut v = std::mem::replace(&mut self.voices[slot], SynthVoice::new());
+            let raw = match slot {
+                KICK => Self::kick(&mut v, &p, n, sr),
+                SNARE => Self::snare(&mut v, &p, n, sr),
+                CLOSED_HAT => Self::hat(&mut v, &p, n, sr, false),
+                OPEN_HAT => Self::hat(&mut v, &p, n, sr, true),
+                CLAP => Self::clap(&mut v, &p, n, sr),
+                _ => Self::tom(&mut v, &p, n, sr, slot - 5),
+            };
+            let mut y = match raw {
+                Some(y) => y,
+                None => {
+                    v.active = false;
+                    self.voices[slot] = v;
+                    continue;
+                }
+            };
+
+            let drive = params.get_f32_h(keys.drive[slot], 0.0).clamp(0.0, 1.0);
+            if drive > 0.0005 {
+                let g = 1.0 + drive * 9.0;
+                y = (y * g).tanh() / g.tanh();
+            }
+            if v.choked {
+                v.choke -= 1.0 / (0.005 * sr);
+                if v.choke <= 0.0 {
+                    v.active = false;
+                }
+            }
+            let level = params.get_f32_h(keys.level[slot], 0.8).clamp(0.0, 1.5);
+            let out = y * level * v.velocity * v.choke.max(0.0);
+            let pan = params.get_f32_h(keys.pan[slot], 0.5).clamp(0.0, 1.0) * 2.0 - 1.0;
+            frame.mono += out;
+            frame.pan_accum += out * pan;
+            frame.energy += out.abs();
+
+            v.t += dt;
+            self.voices[slot] = v;
+        }
+        frame
+    }
+
+    // Sine with a fast downward pitch sweep, a noise click on the attack, long exponential body
+    fn kick(v: &mut SynthVoice, p: &VoiceParams, noise: f32, sr: f32) -> Option<f32> {
+        let decay = 0.08 + p.decay * 1.2;
+        let amp = exp_env(v.t, decay);
+        if amp < 1e-4 {
+            return None;
+        }
+        let base = 48.0 * p.tune;
+        let sweep = 1.0 + (2.0 + 6.0 * p.tone) * exp_env(v.t, 0.012 + 0.03 * p.tone);
+        let body = v.sine(0, base * sweep, sr) * amp;
+        let click_env = exp_env(v.t, 0.0015);
+        v.filt.set_params(3000.0, 0.7, sr);
+        let (_, click_hp, _, _) = v.filt.process(noise);
+        Some(body + click_hp * click_env * p.character * 0.8)
+    }
+
+    // Two detuned body sines plus filtered noise; `character` sets the noise ("snappy") level
+    fn snare(v: &mut SynthVoice, p: &VoiceParams, noise: f32, sr: f32) -> Option<f32> {
+        let body_decay = 0.04 + p.decay * 0.2;
+        let noise_decay = 0.06 + p.decay * 0.35;
+        let body_amp = exp_env(v.t, body_decay);
+        let noise_amp = exp_env(v.t, noise_decay);
+        if body_amp.max(noise_amp) < 1e-4 {
+            return None;
+        }
+        let sweep = 1.0 + 0.5 * exp_env(v.t, 0.005);
+        let f = 180.0 * p.tune * sweep;
+        let body = (v.sine(0, f, sr) + 0.6 * v.sine(1, f * 1.83, sr)) * 0.6 * body_amp;
+        let cutoff = 1500.0 + p.tone * 8000.0;
+        v.filt.set_params(cutoff.min(sr * 0.45), 0.8, sr);
+        let (_, hp, bp, _) = v.filt.process(noise);
+        let snap = (0.6 * hp + 0.4 * bp) * noise_amp;
+        Some(body * (1.0 - 0.5 * p.character) + snap * (0.3 + 0.9 * p.character))
+    }
+
+    // Six square oscillators at inharmonic ratios through a band-pass and high-pass
+    fn hat(v: &mut SynthVoice, p: &VoiceParams, noise: f32, sr: f32, open: bool) -> Option<f32> {
+        let decay = if open {
+            0.12 + p.decay * 1.2
+        } else {
+            0.02 + p.decay * 0.12
+        };
+        let amp = exp_env(v.t, decay);
+        if amp < 1e-4 {
+            return None;
+        }
+        let mut metal = 0.0;
+        for (i, f) in HAT_FREQS.iter().enumerate() {
+            metal += v.square(i, f * p.tune, sr);
+        }
+        metal /= HAT_FREQS.len() as f32;
+        let src = metal * (0.4 + 0.6 * (1.0 - p.character)) + noise * 0.6 * p.character;
+        let bp_f = (6000.0 + p.tone * 6000.0).min(sr * 0.45);
+        v.body.set_params(bp_f, 1.2, sr);
+        let (_, _, bp, _) = v.body.process(src);
+        v.filt
+            .set_params((5000.0 + p.tone * 4000.0).min(sr * 0.45), 0.7, sr);
+        let (_, hp, _, _) = v.filt.process(bp);
+        Some(hp * amp * 1.6)
+    }
+
+    // Band-passed noise: a few short bursts followed by a longer reverberant tail
+    fn clap(v: &mut SynthVoice, p: &VoiceParams, noise: f32, sr: f32) -> Option<f32> {
+        let spread = 0.5 + p.character;
+        let tail_decay = 0.08 + p.decay * 0.5;
+        let last_burst = CLAP_BURSTS[CLAP_BURSTS.len() - 1] * spread;
+        let tail = if v.t >= last_burst {
+            exp_env(v.t - last_burst, tail_decay)
+        } else {
+            0.0
+        };
+        if v.t > last_burst && tail < 1e-4 {
+            return None;
+        }
+        let mut bursts = 0.0f32;
+        for b in CLAP_BURSTS {
+            let start = b * spread;
+            if v.t >= start {
+                bursts = bursts.max(exp_env(v.t - start, 0.004));
+            }
+        }
+        let cutoff = (900.0 + p.tone * 2000.0) * p.tune;
+        v.filt.set_params(cutoff.min(sr * 0.45), 1.5, sr);
+        let (_, _, bp, _) = v.filt.process(noise);
+        Some(bp * bursts.max(tail) * 2.0)
+    }
+
+    // Sine with a slower pitch drop than the kick and a touch of noise; size 0 = low .. 2 = high
+    fn tom(v: &mut SynthVoice, p: &VoiceParams, noise: f32, sr: f32, size: usize) -> Option<f32> {
+        let decay = 0.08 + p.decay * 0.7;
+        let amp = exp_env(v.t, decay);
+        if amp < 1e-4 {
+            return None;
+        }
+        let base = [90.0, 130.0, 185.0][size.min(2)] * p.tune;
+        let sweep = 1.0 + p.character * 0.8 * exp_env(v.t, 0.05);
+        let body = v.sine(0, base * sweep, sr);
+        v.filt
+            .set_params((400.0 + p.tone * 3000.0).min(sr * 0.45), 0.7, sr);
+        let (lp, _, _, _) = v.filt.process(noise);
+        Some((body + lp * 0.25 * exp_env(v.t, 0.02)) * amp)
+    }
+}
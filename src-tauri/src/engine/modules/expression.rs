@@ -0,0 +1,12 @@
+// Per-note expression handed to the polyphonic voices
+//
+// The part combines its own controllers (bend, channel pressure) with poly aftertouch for the
+// voice's key and the bend, pressure and slide of the MIDI channel the voice was started on
+// (an MPE member channel), and pushes the result to each sounding voice.
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct NoteExpression {
+    pub bend_cents: f32, // part bend plus the channel's bend
+    pub pressure: f32,   // 0..1, the highest of part, key and channel pressure
+    pub slide: f32,      // 0..1, MPE timbre (CC74)
+}
@@ -0,0 +1,381 @@
+// FM - 4-operator phase-modulation synth (module_kind 10)
+//
+// Operators are sines numbered 1..4; op 4 sits at the top of every stack and op 1 is always a
+// carrier. Algorithms (`part/N/fm/algorithm`):
+//   0: 4 > 3 > 2 > 1             4: 4 > 3, 2 > 1          (two pairs)
+//   1: (3 + 4) > 2 > 1           5: 4 > 1, 2 and 3        (one modulator, three carriers)
+//   2: 4 > 3 > 1, 2 > 1          6: 4 > 3, plus 2 and 1   (one pair, two sines)
+//   3: 4 > (2 + 3) > 1           7: 1 + 2 + 3 + 4         (additive)
+//
+// Per-operator keys live under `part/N/fm/opK/`: ratio, fixed (0 ratio, 1 fixed), freq_hz
+// (fixed mode), detune (cents), level (0..1), feedback (0..1), attack, decay, sustain, release
+// and vel (velocity sensitivity 0..1, applied to the operator's level).
+
+use std::f32::consts::PI;
+
+use crate::engine::modules::expression::NoteExpression;
+use crate::engine::params::{hash_path, ParamStore};
+
+pub const FM_OPS: usize = 4;
+pub const FM_ALGORITHMS: usize = 8;
+const NUM_VOICES: usize = 8;
+// Phase deviation in radians of a modulator at full level
+const MAX_INDEX: f32 = 8.0;
+
+// mods[dst][src]: src modulates dst (sources always sit above their destinations)
+struct Algorithm {
+    mods: [[bool; FM_OPS]; FM_OPS],
+    carriers: [bool; FM_OPS],
+}
+
+const fn algo(links: &[(usize, usize)], carriers: [bool; FM_OPS]) -> Algorithm {
+    let mut mods = [[false; FM_OPS]; FM_OPS];
+    let mut i = 0;
+    while i < links.len() {
+        // (src, dst), 1-based like the diagrams above
+        mods[links[i].1 - 1][links[i].0 - 1] = true;
+        i += 1;
+    }
+    Algorithm { mods, carriers }
+}
+
+const ALGORITHMS: [Algorithm; FM_ALGORITHMS] = [
+    algo(&[(4, 3), (3, 2), (2, 1)], [true, false, false, false]),
+    algo(&[(3, 2), (4, 2), (2, 1)], [true, false, false, false]),
+    algo(&[(4, 3), (3, 1), (2, 1)], [true, false, false, false]),
+    algo(
+        &[(4, 2), (4, 3), (2, 1), (3, 1)],
+        [true, false, false, false],
+    ),
+    algo(&[(4, 3), (2, 1)], [true, false, true, false]),
+    algo(&[(4, 1), (4, 2), (4, 3)], [true, true, true, false]),
+    algo(&[(4, 3)], [true, true, true, false]),
+    algo(&[], [true, true, true, true]),
+];
+
+pub struct FmParamKeys {
+    pub algorithm: u64,
+    pub ratio: [u64; FM_OPS],
+    pub fixed: [u64; FM_OPS],
+    pub freq_hz: [u64; FM_OPS],
+    pub detune: [u64; FM_OPS],
+    pub level: [u64; FM_OPS],
+    pub feedback: [u64; FM_OPS],
+    pub attack: [u64; FM_OPS],
+    pub decay: [u64; FM_OPS],
+    pub sustain: [u64; FM_OPS],
+    pub release: [u64; FM_OPS],
+    pub vel: [u64; FM_OPS],
+}
+
+impl FmParamKeys {
+    pub fn new(part: usize) -> Self {
+        let op =
+            |i: usize, name: &str| hash_path(&format!("part/{}/fm/op{}/{}", part, i + 1, name));
+        Self {
+            algorithm: hash_path(&format!("part/{}/fm/algorithm", part)),
+            ratio: std::array::from_fn(|i| op(i, "ratio")),
+            fixed: std::array::from_fn(|i| op(i, "fixed")),
+            freq_hz: std::array::from_fn(|i| op(i, "freq_hz")),
+            detune: std::array::from_fn(|i| op(i, "detune")),
+            level: std::array::from_fn(|i| op(i, "level")),
+            feedback: std::array::from_fn(|i| op(i, "feedback")),
+            attack: std::array::from_fn(|i| op(i, "attack")),
+            decay: std::array::from_fn(|i| op(i, "decay")),
+            sustain: std::array::from_fn(|i| op(i, "sustain")),
+            release: std::array::from_fn(|i| op(i, "release")),
+            vel: std::array::from_fn(|i| op(i, "vel")),
+        }
+    }
+}
+
+// One sample's worth of operator settings, read once and shared by all voices
+#[derive(Clone, Copy, Default)]
+struct OpParams {
+    ratio: f32,
+    fixed_hz: Option<f32>,
+    detune: f32, // frequency multiplier
+    level: f32,
+    feedback: f32,
+    attack: f32,
+    decay: f32,
+    sustain: f32,
+    release: f32,
+    vel: f32,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum EnvStage {
+    Idle,
+    Attack,
+    Decay,
+    Release,
+}
+
+// Linear attack, exponential decay and release
+#[derive(Clone, Copy)]
+struct OpEnv {
+    value: f32,
+    stage: EnvStage,
+}
+
+impl OpEnv {
+    fn new() -> Self {
+        Self {
+            value: 0.0,
+            stage: EnvStage::Idle,
+        }
+    }
+
+    #[inline]
+    fn next(&mut self, p: &OpParams, sr: f32) -> f32 {
+        match self.stage {
+            EnvStage::Idle => {}
+            EnvStage::Attack => {
+                self.value += 1.0 / (p.attack.max(0.0005) * sr);
+                if self.value >= 1.0 {
+                    self.value = 1.0;
+                    self.stage = EnvStage::Decay;
+                }
+            }
+            EnvStage::Decay => {
+                // ~5 time constants over the decay time
+                let k = 1.0 - (-5.0 / (p.decay.max(0.001) * sr)).exp();
+                self.value += (p.sustain - self.value) * k;
+                if p.sustain <= 0.0 && self.value < 1e-5 {
+                    self.value = 0.0;
+                    self.stage = EnvStage::Idle;
+                }
+            }
+            EnvStage::Release => {
+                let k = 1.0 - (-5.0 / (p.release.max(0.001) * sr)).exp();
+                self.value -= self.value * k;
+                if self.value < 1e-5 {
+                    self.value = 0.0;
+                    self.stage = EnvStage::Idle;
+                }
+            }
+        }
+        self.value
+    }
+}
+
+#[derive(Clone, Copy)]
+struct Operator {
+    phase: f32,
+    env: OpEnv,
+    fb: [f32; 2], // last two outputs, averaged for feedback (DX style)
+}
+
+struct FmVoice {
+    active: bool,
+    note: u8,
+    age: u64,
+    vel: f32,
+    channel: u8, // MIDI channel of the note; selects its per-channel expression
+    expr: NoteExpression,
+    ops: [Operator; FM_OPS],
+}
+
+impl FmVoice {
+    fn new() -> Self {
+        Self {
+            active: false,
+            note: 0,
+            age: 0,
+            vel: 1.0,
+            channel: 0,
+            expr: NoteExpression::default(),
+            ops: [Operator {
+                phase: 0.0,
+                env: OpEnv::new(),
+                fb: [0.0; 2],
+            }; FM_OPS],
+        }
+    }
+
+    fn note_on(&mut self, note: u8, vel: f32) {
+        self.active = true;
+        self.note = note;
+        self.age = 0;
+        self.vel = vel;
+        // Phases restart so every note has the same attack transient
+        for op in &mut self.ops {
+            op.phase = 0.0;
+            op.fb = [0.0; 2];
+            op.env.stage = EnvStage::Attack;
+        }
+    }
+
+    fn note_off(&mut self) {
+        self.active = false;
+        for op in &mut self.ops {
+            if op.env.stage != EnvStage::Idle {
+                op.env.stage = EnvStage::Release;
+            }
+        }
+    }
+
+    fn is_active(&self) -> bool {
+        self.ops.iter().any(|op| op.env.stage != EnvStage::Idle)
+    }
+
+    #[inline]
+    fn render(&mut self, ops: &[OpParams; FM_OPS], algo: &Algorithm, sr: f32) -> f32 {
+        self.age += 1;
+        let base =
+            440.0 * (2.0_f32).powf((self.note as f32 - 69.0 + self.expr.bend_cents / 100.0) / 12.0);
+        let mut out = [0.0f32; FM_OPS];
+        let mut mix = 0.0;
+        // Top of the stack first so each modulator's output is ready for the ops below it
+        for i in (0..FM_OPS).rev() {
+            let p = &ops[i];
+            let op = &mut self.ops[i];
+            let env = op.env.next(p, sr);
+            let freq = p.fixed_hz.unwrap_or(base * p.ratio) * p.detune;
+            let mut pm = 0.0;
+            for (src, &m) in out.iter().enumerate().skip(i + 1) {
+                if algo.mods[i][src] {
+                    pm += m * MAX_INDEX;
+                }
+            }
+            pm += p.feedback * 0.5 * (op.fb[0] + op.fb[1]) * PI;
+            let level = p.level * (1.0 - p.vel * (1.0 - self.vel));
+            let y = (2.0 * PI * op.phase + pm).sin() * env * level;
+            op.fb = [y, op.fb[0]];
+            op.phase = (op.phase + freq / sr).fract();
+            out[i] = y;
+            if algo.carriers[i] {
+                mix += y;
+            }
+        }
+        mix
+    }
+}
+
+pub struct FmSynth {
+    sr: f32,
+    voices: Vec<FmVoice>,
+}
+
+impl FmSynth {
+    pub fn new(sr: f32) -> Self {
+        Self {
+            sr,
+            voices: (0..NUM_VOICES).map(|_| FmVoice::new()).collect(),
+        }
+    }
+
+    pub fn note_on(&mut self, note: u8, vel: f32, channel: u8) {
+        let vel = vel.clamp(0.0, 1.0);
+        // Same note retriggers its voice; otherwise a free voice, else the oldest
+        let i = self
+            .voices
+            .iter()
+            .position(|v| v.note == note && v.is_active())
+            .or_else(|| self.voices.iter().position(|v| !v.is_active()))
+            .unwrap_or_else(|| {
+                let mut oldest = 0;
+                for (i, v) in self.voices.iter().enumerate() {
+                    if v.age > self.voices[oldest].age {
+                        oldest = i;
+                    }
+                }
+                oldest
+            });
+        self.voices[i].note_on(note, vel);
+        self.voices[i].channel = channel;
+    }
+
+    pub fn note_off(&mut self, note: u8) {
+        for v in &mut self.voices {
+            if v.note == note && v.active {
+                v.note_off();
+            }
+        }
+    }
+
+    // `expr` maps a voice's note and MIDI channel to its expression
+    pub fn set_expression(&mut self, expr: impl Fn(u8, u8) -> NoteExpression) {
+        for v in &mut self.voices {
+            v.expr = expr(v.note, v.channel);
+        }
+    }
+
+    pub fn render(&mut self, params: &ParamStore, keys: &FmParamKeys) -> f32 {
+        if !self.voices.iter().any(|v| v.is_active()) {
+            return 0.0;
+        }
+        let algo_idx = params
+            .get_i32_h(keys.algorithm, 0)
+            .clamp(0, FM_ALGORITHMS as i32 - 1) as usize;
+        let algo = &ALGORITHMS[algo_idx];
+        let ops: [OpParams; FM_OPS] = std::array::from_fn(|i| OpParams {
+            ratio: params.get_f32_h(keys.ratio[i], 1.0).clamp(0.0, 32.0),
+            fixed_hz: (params.get_i32_h(keys.fixed[i], 0) != 0)
+                .then(|| params.get_f32_h(keys.freq_hz[i], 440.0).clamp(0.0, 20000.0)),
+            detune: (2.0_f32)
+                .powf(params.get_f32_h(keys.detune[i], 0.0).clamp(-100.0, 100.0) / 1200.0),
+            // Only op 1 sounds by default: a plain sine until modulators are turned up
+            level: params
+                .get_f32_h(keys.level[i], if i == 0 { 1.0 } else { 0.0 })
+                .clamp(0.0, 1.0),
+            feedback: params.get_f32_h(keys.feedback[i], 0.0).clamp(0.0, 1.0),
+            attack: params.get_f32_h(keys.attack[i], 0.005).clamp(0.0, 10.0),
+            decay: params.get_f32_h(keys.decay[i], 1.0).clamp(0.001, 20.0),
+            sustain: params.get_f32_h(keys.sustain[i], 0.8).clamp(0.0, 1.0),
+            release: params.get_f32_h(keys.release[i], 0.3).clamp(0.001, 20.0),
+            vel: params.get_f32_h(keys.vel[i], 0.5).clamp(0.0, 1.0),
+        });
+        let carriers = algo.carriers.iter().filter(|&&c| c).count() as f32;
+        let mut mix = 0.0;
+        for v in &mut self.voices {
+            if v.is_active() {
+                mix += v.render(&ops, algo, self.sr);
+            }
+        }
+        // Headroom for stacked voices, and equal loudness across algorithms
+        mix * 0.3 / carriers.sqrt()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn modulators_sit_above_their_carriers() {
+        for a in &ALGORITHMS {
+            assert!(a.carriers[0]);
+            for dst in 0..FM_OPS {
+                for src in 0..=dst {
+                    assert!(!a.mods[dst][src]);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn modulator_adds_harmonics() {
+        let mut params = ParamStore::new();
+        let keys = FmParamKeys::new(0);
+        let brightness = |params: &ParamStore| {
+            let mut fm = FmSynth::new(48000.0);
+            fm.note_on(57, 1.0, 0);
+            let mut prev = 0.0;
+            let mut diff = 0.0;
+            for _ in 0..4800 {
+                let x = fm.render(params, &keys);
+                diff += (x - prev).abs();
+                prev = x;
+            }
+            diff
+        };
+        let sine = brightness(&params);
+        params.set(
+            "part/0/fm/op2/level".into(),
+            crate::engine::messages::ParamValue::F32(0.8),
+        );
+        assert!(brightness(&params) > 1.5 * sine);
+    }
+}
@@ -0,0 +1,280 @@
+// Granular - grain cloud player over the part's sample buffer
+//
+// Features:
+// - Shares the Sampler's SampleBuffer, so a loaded sample plays in either module
+// - Grain position, size, density and spray (random position offset)
+// - Per-grain pitch and pan randomisation (each grain is panned into a stereo frame)
+// - Freeze: hold the read head at `position` instead of scanning through the sample
+// - Position offset input for the part LFO
+
+use std::sync::{Arc, Mutex};
+
+use crate::engine::modules::disk_stream::STREAM_PLAYHEADS;
+use crate::engine::modules::sampler::{SampleBuffer, MAX_VOICES};
+use crate::engine::params::ParamStore;
+
+const NUM_STREAMS: usize = 4;
+const MAX_GRAINS: usize = 64;
+// Disk-stream playhead slots sit after the Sampler voices sharing the buffer
+const _: () = assert!(MAX_VOICES + NUM_STREAMS <= STREAM_PLAYHEADS);
+
+#[derive(Clone, Copy)]
+struct Grain {
+    pos: f32,  // read position in buffer samples
+    step: f32, // buffer samples per output sample
+    age: usize,
+    len: usize,
+    amp: f32,
+    pan: f32, // -1..1
+    stream: usize,
+    active: bool,
+}
+
+impl Grain {
+    fn idle() -> Self {
+        Self {
+            pos: 0.0,
+            step: 1.0,
+            age: 0,
+            len: 0,
+            amp: 0.0,
+            pan: 0.0,
+            stream: 0,
+            active: false,
+        }
+    }
+}
+
+// One held note: owns a read head and spawns grains into the shared pool
+#[derive(Clone, Copy)]
+struct GrainStream {
+    note: u8,
+    velocity: f32,
+    gate: bool,
+    env: f32,
+    head: f32,        // scan offset from `position`, in buffer samples
+    spawn_timer: f32, // samples until the next grain
+}
+
+impl GrainStream {
+    fn idle() -> Self {
+        Self {
+            note: 60,
+            velocity: 0.0,
+            gate: false,
+            env: 0.0,
+            head: 0.0,
+            spawn_timer: 0.0,
+        }
+    }
+
+    fn is_active(&self) -> bool {
+        self.gate || self.env > 1e-5
+    }
+}
+
+#[derive(Default)]
+pub struct GranularRenderFrame {
+    pub left: f32,
+    pub right: f32,
+}
+
+pub struct GranularParamKeys {
+    pub position: u64,   // 0..1 of the sample
+    pub size: u64,       // grain length (ms)
+    pub density: u64,    // grains per second per note
+    pub spray: u64,      // random position offset (ms)
+    pub pitch: u64,      // semitones
+    pub pitch_rand: u64, // 0..1 -> up to +-12 semitones per grain
+    pub pan_rand: u64,   // 0..1 stereo scatter
+    pub freeze: u64,     // 0 = scan, 1 = hold position
+    pub attack: u64,     // ms
+    pub release: u64,    // ms
+}
+
+impl GranularParamKeys {
+    pub fn new(part: usize) -> Self {
+        use crate::engine::params::path_hash;
+        let p = |name: &str| path_hash(&format!("part/{}/granular/{}", part, name));
+        Self {
+            position: p("position"),
+            size: p("size"),
+            density: p("density"),
+            spray: p("spray"),
+            pitch: p("pitch"),
+            pitch_rand: p("pitch_rand"),
+            pan_rand: p("pan_rand"),
+            freeze: p("freeze"),
+            attack: p("attack"),
+            release: p("release"),
+        }
+    }
+}
+
+pub struct Granular {
+    sr: f32,
+    buffer: Arc<Mutex<SampleBuffer>>,
+    streams: [GrainStream; NUM_STREAMS],
+    grains: Vec<Grain>,
+    next_stream: usize,
+    rng: u32,
+}
+
+impl Granular {
+    pub fn new(sr: f32, buffer: Arc<Mutex<SampleBuffer>>) -> Self {
+        Self {
+            sr,
+            buffer,
+            streams: [GrainStream::idle(); NUM_STREAMS],
+            grains: vec![Grain::idle(); MAX_GRAINS],
+            next_stream: 0,
+            rng: 0x1234_5678,
+        }
+    }
+
+    pub fn note_on(&mut self, note: u8, vel: f32) {
+        let idx = match self.streams.iter().position(|s| !s.is_active()) {
+            Some(i) => i,
+            None => {
+                let i = self.next_stream;
+                self.next_stream = (self.next_stream + 1) % NUM_STREAMS;
+                i
+            }
+        };
+        self.streams[idx] = GrainStream {
+            note,
+            velocity: vel.clamp(0.0, 1.0),
+            gate: true,
+            env: self.streams[idx].env,
+            head: 0.0,
+            spawn_timer: 0.0,
+        };
+    }
+
+    pub fn note_off(&mut self, note: u8) {
+        for s in &mut self.streams {
+            if s.gate && s.note == note {
+                s.gate = false;
+            }
+        }
+    }
+
+    /// Render one sample. `position_mod` is added to the position parameter (LFO input).
+    pub fn render(
+        &mut self,
+        params: &ParamStore,
+        keys: &GranularParamKeys,
+        position_mod: f32,
+    ) -> GranularRenderFrame {
+        let mut frame = GranularRenderFrame::default();
+        let idle = !self.streams.iter().any(|s| s.is_active());
+        if idle && !self.grains.iter().any(|g| g.active) {
+            return frame;
+        }
+        let handle = Arc::clone(&self.buffer);
+        let buffer = match handle.lock() {
+            Ok(b) => b,
+            Err(_) => return frame,
+        };
+        if buffer.is_empty() {
+            return frame;
+        }
+
+        let len = buffer.length_samples as f32;
+        let position = (params.get_f32_h(keys.position, 0.0) + position_mod).clamp(0.0, 1.0);
+        let size_ms = params.get_f32_h(keys.size, 80.0).clamp(5.0, 500.0);
+        let density = params.get_f32_h(keys.density, 20.0).clamp(1.0, 100.0);
+        let spray_ms = params.get_f32_h(keys.spray, 0.0).clamp(0.0, 1000.0);
+        let pitch = params.get_f32_h(keys.pitch, 0.0).clamp(-48.0, 48.0);
+        let pitch_rand = params.get_f32_h(keys.pitch_rand, 0.0).clamp(0.0, 1.0);
+        let pan_rand = params.get_f32_h(keys.pan_rand, 0.0).clamp(0.0, 1.0);
+        let freeze = params.get_i32_h(keys.freeze, 0) != 0;
+        let attack_ms = params.get_f32_h(keys.attack, 10.0).max(1.0);
+        let release_ms = params.get_f32_h(keys.release, 300.0).max(1.0);
+
+        let rate_ratio = buffer.sample_rate / self.sr;
+        let grain_len = ((size_ms * 0.001 * self.sr) as usize).max(16);
+        let spawn_interval = self.sr / density;
+        let spray_samps = spray_ms * 0.001 * buffer.sample_rate;
+        let attack_step = 1.0 / (attack_ms * 0.001 * self.sr);
+        let release_step = 1.0 / (release_ms * 0.001 * self.sr);
+
+        for si in 0..NUM_STREAMS {
+            let mut s = self.streams[si];
+            if !s.is_active() {
+                buffer.release_playhead(MAX_VOICES + si);
+                continue;
+            }
+            if s.gate {
+                s.env = (s.env + attack_step).min(1.0);
+            } else {
+                s.env = (s.env - release_step).max(0.0);
+            }
+            let ratio = 2f32.powf((pitch + s.note as f32 - 60.0) / 12.0);
+
+            s.spawn_timer -= 1.0;
+            if s.gate && s.spawn_timer <= 0.0 {
+                s.spawn_timer += spawn_interval;
+                let offset = (self.rand01() * 2.0 - 1.0) * spray_samps;
+                let start = (position * len + s.head + offset).rem_euclid(len);
+                let detune = (self.rand01() * 2.0 - 1.0) * 12.0 * pitch_rand;
+                let pan = (self.rand01() * 2.0 - 1.0) * pan_rand;
+                let step = rate_ratio * ratio * 2f32.powf(detune / 12.0);
+                let slot = match self.grains.iter().position(|g| !g.active) {
+                    Some(i) => i,
+                    // Pool exhausted: replace the oldest grain
+                    None => (0..MAX_GRAINS)
+                        .max_by_key(|&i| self.grains[i].age)
+                        .unwrap_or(0),
+                };
+                self.grains[slot] = Grain {
+                    pos: start,
+                    step,
+                    age: 0,
+                    len: grain_len,
+                    amp: s.velocity,
+                    pan,
+                    stream: si,
+                    active: true,
+                };
+            }
+            // Scan through the sample in real time unless frozen
+            if !freeze {
+                s.head = (s.head + rate_ratio).rem_euclid(len);
+            }
+            // Grains scatter around the read head, which keeps a streamed file decoding ahead
+            buffer.note_playhead(MAX_VOICES + si, (position * len + s.head).rem_euclid(len));
+            self.streams[si] = s;
+        }
+
+        // Overlapping Hann grains; normalise so density/size changes keep a steady level
+        let overlap = (grain_len as f32 / spawn_interval).max(1.0);
+        let norm = 1.0 / overlap.sqrt();
+        let envs = self.streams.map(|s| s.env);
+        for g in &mut self.grains {
+            if !g.active {
+                continue;
+            }
+            if g.age >= g.len {
+                g.active = false;
+                continue;
+            }
+            let t = g.age as f32 / g.len as f32;
+            let w = 0.5 * (1.0 - (2.0 * std::f32::consts::PI * t).cos());
+            let x = buffer.get_sample_interpolated(g.pos, 0) * w * g.amp * norm * envs[g.stream];
+            // Equal-power pan, unity at the centre like the mono path it replaced
+            let theta = (g.pan + 1.0) * std::f32::consts::FRAC_PI_4;
+            frame.left += x * theta.cos() * std::f32::consts::SQRT_2;
+            frame.right += x * theta.sin() * std::f32::consts::SQRT_2;
+            g.pos = (g.pos + g.step).rem_euclid(len);
+            g.age += 1;
+        }
+        frame
+    }
+
+    fn rand01(&mut self) -> f32 {
+        // Simple linear congruential generator
+        self.rng = self.rng.wrapping_mul(1103515245).wrapping_add(12345);
+        ((self.rng >> 8) & 0xffffff) as f32 / 16777216.0
+    }
+}
@@ -1,5 +1,13 @@
+use crate::engine::dsp::svf::Svf;
+use crate::engine::dsp::velocity;
 use crate::engine::params::ParamStore;
 
+const KS_VOICES: usize = 8;
+// Longest excitation burst (see `excite` mapping) before the pick-position comb
+const MAX_EXCITE: usize = 120;
+// Body resonance: two band-passes roughly at a small acoustic body's air and top modes
+const BODY_MODES: [(f32, f32); 2] = [(110.0, 2.5), (240.0, 3.0)];
+
 #[derive(Clone)]
 struct DelayLine {
     buffer: Vec<f32>,
@@ -67,20 +75,6 @@ impl OnePoleLP {
     }
 }
 
-#[derive(Clone)]
-pub struct KarplusStrong {
-    sr: f32,
-    delay_line: DelayLine,
-    filter: OnePoleLP,
-    excite_counter: u32,
-    excite_length: u32,
-    gate: bool,
-    just_triggered: bool,
-    rng: u32,
-    base_note: u8,
-    last_tune: f32,
-}
-
 #[derive(Clone)]
 pub struct KSParamKeys {
     #[allow(dead_code)]
@@ -89,6 +83,75 @@ pub struct KSParamKeys {
     pub damp: u64,
     pub excite: u64,
     pub tune: u64,
+    pub pick: u64,        // pick position along the string, 0..1 -> bridge..middle
+    pub body: u64,        // body resonance mix, 0..1
+    pub sympathetic: u64, // coupling between ringing strings, 0..1
+    pub vel_amp: u64,     // velocity -> burst level
+    pub vel_filter: u64,  // velocity -> burst brightness
+}
+
+// One string: a tuned delay loop with its own excitation burst
+#[derive(Clone)]
+struct KsString {
+    delay_line: DelayLine,
+    filter: OnePoleLP,
+    excite: Vec<f32>, // pre-rendered burst (filtered noise through the pick comb)
+    excite_pos: usize,
+    excite_len: usize,
+    note: u8,
+    vel: f32,
+    gate: bool,
+    active: bool,
+    age: u64,   // trigger order, for stealing the oldest string
+    level: f32, // output envelope follower, used to free silent strings
+    out: f32,   // last output sample (for sympathetic coupling)
+}
+
+impl KsString {
+    fn new(max_delay_samples: usize) -> Self {
+        Self {
+            delay_line: DelayLine::new(max_delay_samples),
+            filter: OnePoleLP::new(),
+            excite: vec![0.0; MAX_EXCITE + max_delay_samples / 2 + 1],
+            excite_pos: 0,
+            excite_len: 0,
+            note: 0,
+            vel: 0.0,
+            gate: false,
+            active: false,
+            age: 0,
+            level: 0.0,
+            out: 0.0,
+        }
+    }
+
+    fn set_pitch(&mut self, sr: f32, tune_param: f32) {
+        // tune_param is 0..1, map to ±50 cents
+        let tune_cents = (tune_param - 0.5) * 100.0; // ±50 cents
+
+        // Calculate frequency with tuning offset
+        let base_freq = 440.0 * (2.0_f32).powf((self.note as f32 - 69.0) / 12.0);
+        let tuned_freq = base_freq * (2.0_f32).powf(tune_cents / 1200.0);
+
+        // Calculate delay length
+        let delay_samples = (sr / tuned_freq) as usize;
+        self.delay_line.set_length(delay_samples.max(1));
+    }
+}
+
+#[derive(Clone)]
+pub struct KarplusStrong {
+    sr: f32,
+    strings: Vec<KsString>,
+    body: [Svf; 2],
+    rng: u32,
+    last_tune: f32,
+    trigger_count: u64,
+    // Excitation settings latched from the last render for the next note_on
+    excite_samples: usize,
+    pick: f32,
+    vel_amp: f32,
+    vel_filter: f32,
 }
 
 impl KarplusStrong {
@@ -98,52 +161,94 @@ impl KarplusStrong {
 
         Self {
             sr,
-            delay_line: DelayLine::new(max_delay_samples),
-            filter: OnePoleLP::new(),
-            excite_counter: 0,
-            excite_length: 0,
-            gate: false,
-            just_triggered: false,
+            strings: (0..KS_VOICES)
+                .map(|_| KsString::new(max_delay_samples))
+                .collect(),
+            body: [Svf::new(), Svf::new()],
             rng: 0x12345678,
-            base_note: 60,  // Default to middle C
             last_tune: 0.5, // Default to no detune
+            trigger_count: 0,
+            excite_samples: 70,
+            pick: 0.25,
+            vel_amp: velocity::DEFAULT_AMOUNT,
+            vel_filter: velocity::DEFAULT_AMOUNT,
         }
     }
 
-    pub fn note_on(&mut self, note: u8, _vel: f32) {
-        self.gate = true;
-        self.just_triggered = true;
+    pub fn note_on(&mut self, note: u8, vel: f32) {
+        // Same note restrikes its string; otherwise a free string, else steal the oldest
+        let idx = self
+            .strings
+            .iter()
+            .position(|s| s.active && s.note == note)
+            .or_else(|| self.strings.iter().position(|s| !s.active))
+            .unwrap_or_else(|| {
+                let mut oldest = 0;
+                for (i, s) in self.strings.iter().enumerate() {
+                    if s.age < self.strings[oldest].age {
+                        oldest = i;
+                    }
+                }
+                oldest
+            });
+
+        self.trigger_count += 1;
+        let vel = vel.clamp(0.0, 1.0);
+        let sr = self.sr;
+        let tune = self.last_tune;
+        let excite_samples = self.excite_samples;
+        let pick = self.pick;
+        let gain = velocity::amp_gain(vel, self.vel_amp);
+
+        // Velocity opens the burst's lowpass (vel_filter) and sets its level (vel_amp)
+        let bright_a = {
+            let bright = 1.0 - self.vel_filter * (1.0 - vel);
+            let cutoff = 800.0 + bright * bright * 11000.0;
+            (2.0 * std::f32::consts::PI * (cutoff / sr).clamp(0.0001, 0.4)).clamp(0.0001, 0.9)
+        };
+        let mut lp = 0.0;
+        let mut noise = [0.0f32; MAX_EXCITE];
+        for n in noise.iter_mut().take(excite_samples) {
+            lp += bright_a * ((self.rand01() * 2.0 - 1.0) * 0.3 - lp);
+            *n = lp;
+        }
 
-        // Store the base note for tuning calculations
-        self.base_note = note;
+        let string = &mut self.strings[idx];
+        string.note = note;
+        string.vel = vel;
+        string.gate = true;
+        string.active = true;
+        string.age = self.trigger_count;
+        string.level = 1.0;
 
         // Calculate delay length for pitch with current tune setting
-        // Don't reset tune to 0.5, keep the current tune value
-        let current_tune = self.last_tune;
-        self.update_pitch(current_tune);
+        string.set_pitch(sr, tune);
 
         // Clear the delay buffer for a clean start
-        self.delay_line.clear();
-
-        // Reset excitation
-        self.excite_counter = 0;
-    }
-
-    fn update_pitch(&mut self, tune_param: f32) {
-        // tune_param is 0..1, map to ±50 cents
-        let tune_cents = (tune_param - 0.5) * 100.0; // ±50 cents
-
-        // Calculate frequency with tuning offset
-        let base_freq = 440.0 * (2.0_f32).powf((self.base_note as f32 - 69.0) / 12.0);
-        let tuned_freq = base_freq * (2.0_f32).powf(tune_cents / 1200.0);
-
-        // Calculate delay length
-        let delay_samples = (self.sr / tuned_freq) as usize;
-        self.delay_line.set_length(delay_samples.max(1));
+        string.delay_line.clear();
+
+        // Pick position comb: the burst minus itself delayed by the pick distance from the bridge
+        let pick_delay = ((0.02 + pick * 0.48) * string.delay_line.length as f32) as usize;
+        let len = (excite_samples + pick_delay).min(string.excite.len());
+        for i in 0..len {
+            let direct = if i < excite_samples { noise[i] } else { 0.0 };
+            let reflected = if i >= pick_delay && i - pick_delay < excite_samples {
+                noise[i - pick_delay]
+            } else {
+                0.0
+            };
+            string.excite[i] = (direct - reflected) * gain;
+        }
+        string.excite_len = len;
+        string.excite_pos = 0;
     }
 
-    pub fn note_off(&mut self) {
-        self.gate = false;
+    pub fn note_off(&mut self, note: u8) {
+        for s in self.strings.iter_mut() {
+            if s.active && s.note == note {
+                s.gate = false;
+            }
+        }
     }
 
     pub fn render_one(&mut self, params: &ParamStore, keys: &KSParamKeys) -> f32 {
@@ -152,51 +257,89 @@ impl KarplusStrong {
         let damp = params.get_f32_h(keys.damp, 0.5).clamp(0.0, 1.0);
         let excite = params.get_f32_h(keys.excite, 0.5).clamp(0.0, 1.0);
         let tune = params.get_f32_h(keys.tune, 0.5).clamp(0.0, 1.0);
+        let body_mix = params.get_f32_h(keys.body, 0.0).clamp(0.0, 1.0);
+        let sympathetic = params.get_f32_h(keys.sympathetic, 0.0).clamp(0.0, 1.0);
+        self.pick = params.get_f32_h(keys.pick, 0.25).clamp(0.0, 1.0);
+        self.vel_amp = params
+            .get_f32_h(keys.vel_amp, velocity::DEFAULT_AMOUNT)
+            .clamp(0.0, 1.0);
+        self.vel_filter = params
+            .get_f32_h(keys.vel_filter, velocity::DEFAULT_AMOUNT)
+            .clamp(0.0, 1.0);
 
         // Map parameters
         let feedback = 0.85 + decay * 0.14; // 0.85 to 0.99 (increased minimum for better sustain)
         let cutoff_hz = 1000.0 + damp * 10000.0; // 1kHz to 11kHz (higher range for less aggressive filtering)
-        let excite_samples = (20.0 + excite * 100.0) as u32; // 20 to 120 samples (more reasonable range)
+        self.excite_samples = (20.0 + excite * 100.0) as usize; // 20 to 120 samples (more reasonable range)
 
-        // Update pitch only when note is triggered or tune changes significantly
+        // Retune ringing strings only when tune changes significantly
         // This avoids constant delay line adjustments during playback which causes instability
-        if self.just_triggered || (tune - self.last_tune).abs() > 0.005 {
-            self.update_pitch(tune);
+        let retune = (tune - self.last_tune).abs() > 0.005;
+        if retune {
             self.last_tune = tune;
         }
 
-        // Set filter cutoff
-        self.filter.set_cutoff(cutoff_hz, self.sr);
-
-        // Handle excitation on trigger
-        if self.just_triggered {
-            self.excite_length = excite_samples;
-            self.just_triggered = false;
+        // Sympathetic coupling: each string hears a little of the others' previous output.
+        // A string peaks at 1 / (1 - feedback) on its harmonics, so the coupling is kept below
+        // (1 - feedback) / (strings - 1) to keep the coupled loop gain under one.
+        let total_out: f32 = self.strings.iter().map(|s| s.out).sum();
+        let couple = sympathetic * 0.5 * (1.0 - feedback) / (KS_VOICES - 1) as f32;
+        let release_floor = 1.0 - 0.2 * (1.0 - decay); // released strings ring out a bit faster
+
+        let sr = self.sr;
+        let mut mix = 0.0;
+        for s in self.strings.iter_mut() {
+            if !s.active {
+                continue;
+            }
+            if retune {
+                s.set_pitch(sr, tune);
+            }
+            s.filter.set_cutoff(cutoff_hz, sr);
+
+            // Read from delay line (this is our output)
+            let delayed = s.delay_line.read();
+
+            let excitation = if s.excite_pos < s.excite_len {
+                let e = s.excite[s.excite_pos];
+                s.excite_pos += 1;
+                e
+            } else {
+                0.0
+            };
+
+            let fb = if s.gate {
+                feedback
+            } else {
+                feedback * release_floor
+            };
+            // Create feedback signal: delayed output * feedback + excitation (+ coupling)
+            let feedback_signal = delayed * fb + excitation + couple * (total_out - s.out);
+
+            // Apply lowpass filter to the feedback signal and write it back
+            let filtered = s.filter.process(feedback_signal);
+            s.delay_line.write(filtered);
+
+            s.out = delayed;
+            mix += delayed;
+
+            // Free strings that have rung out (after their burst has been played in)
+            s.level = s.level * 0.9995 + delayed.abs() * 0.0005;
+            if s.excite_pos >= s.excite_len && s.level < 1e-6 {
+                s.active = false;
+                s.out = 0.0;
+            }
         }
 
-        // Read from delay line (this is our output)
-        let delayed = self.delay_line.read();
-
-        // Generate excitation noise if still in excitation phase
-        let excitation = if self.excite_counter < self.excite_length {
-            let noise = (self.rand01() * 2.0 - 1.0) * 0.3; // White noise ±0.3 (reduced amplitude)
-            self.excite_counter += 1;
-            noise
-        } else {
-            0.0
-        };
-
-        // Create feedback signal: delayed output * feedback + excitation
-        let feedback_signal = delayed * feedback + excitation;
-
-        // Apply lowpass filter to the feedback signal
-        let filtered = self.filter.process(feedback_signal);
-
-        // Write filtered signal back to delay line
-        self.delay_line.write(filtered);
-
-        // Return the delayed signal as our output
-        delayed
+        if body_mix > 0.0005 {
+            let mut resonance = 0.0;
+            for (f, (freq, q)) in self.body.iter_mut().zip(BODY_MODES) {
+                f.set_params(freq, q, sr);
+                resonance += f.process(mix).2;
+            }
+            mix = mix * (1.0 - 0.5 * body_mix) + resonance * body_mix * 1.5;
+        }
+        mix
     }
 
     fn rand01(&mut self) -> f32 {
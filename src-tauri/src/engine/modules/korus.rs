@@ -2,12 +2,16 @@
 //
 // Features:
 // - DCO with Saw/Pulse crossfade, PWM, Sub oscillator, Noise
+// - Unison (up to 8 detuned DCOs per voice) with stereo spread
 // - Juno-style 4-pole (24dB) lowpass filter with resonance
 // - Single ADSR envelope (shared amp/filter)
 // - LFO for PWM and filter modulation
 // - BBD-style stereo chorus
 
 use std::f32::consts::PI;
+use crate::engine::dsp::unison::{self, Unison, MAX_UNISON};
+use crate::engine::dsp::velocity;
+use crate::engine::modules::expression::NoteExpression;
 use crate::engine::params::ParamStore;
 
 const TAU: f32 = 2.0 * PI;
@@ -236,11 +240,15 @@ struct KorusVoice {
     active: bool,
     note: u8,
     age: u64,
+    vel: f32,
+    channel: u8, // MIDI channel of the note; selects its per-channel expression
+    pub expr: NoteExpression,
     freq: f32,
-    phase: f32,      // Main oscillator
+    phases: [f32; MAX_UNISON], // Main oscillator, one per unison copy
     sub_phase: f32,  // Sub oscillator (1 octave down)
     env: KorusEnv,
     filter: Juno4Pole,
+    filter_r: Juno4Pole, // Right channel, used while the unison stack is spread
     sr: f32,
 }
 
@@ -250,27 +258,39 @@ impl KorusVoice {
             active: false,
             note: 0,
             age: 0,
+            vel: 1.0,
+            channel: 0,
+            expr: NoteExpression::default(),
             freq: 440.0,
-            phase: 0.0,
+            phases: [0.0; MAX_UNISON],
             sub_phase: 0.0,
             env: KorusEnv::new(sr),
             filter: Juno4Pole::new(sr),
+            filter_r: Juno4Pole::new(sr),
             sr,
         }
     }
 
-    fn note_on(&mut self, note: u8) {
+    fn note_on(&mut self, note: u8, vel: f32) {
         self.active = true;
         self.note = note;
         self.age = 0;
+        self.vel = vel;
         self.freq = 440.0 * (2.0_f32).powf((note as f32 - 69.0) / 12.0);
         // Don't reset phase for warmer sound (free-running oscillators)
         self.env.gate_on();
     }
 
-    fn retrigger(&mut self) {
+    fn scatter_phases(&mut self, amount: f32, rng: &mut u32) {
+        for ph in &mut self.phases {
+            *ph = unison::random_phase(rng, amount);
+        }
+    }
+
+    fn retrigger(&mut self, vel: f32) {
         self.active = true;
         self.age = 0;
+        self.vel = vel;
         self.env.retrigger();
     }
 
@@ -295,10 +315,15 @@ impl KorusVoice {
         lfo_filter: f32,
         lfo_value: f32,  // current LFO value (-1 to 1)
         a: f32, d: f32, s: f32, r: f32,
+        vel_amp: f32,
+        vel_filter: f32,
+        pressure_amp: f32,
+        slide_filter: f32,
+        uni: &Unison,
         rng: &mut u32,
-    ) -> f32 {
+    ) -> (f32, f32) {
         if !self.is_active() {
-            return 0.0;
+            return (0.0, 0.0);
         }
 
         self.age += 1;
@@ -308,28 +333,35 @@ impl KorusVoice {
         
         if env < 1e-6 {
             self.active = false;
-            return 0.0;
+            return (0.0, 0.0);
         }
 
         // ─── Oscillator ───
         
         // Advance phases
-        let phase_inc = self.freq / self.sr;
-        self.phase += phase_inc;
-        if self.phase >= 1.0 { self.phase -= 1.0; }
-        
+        let phase_inc = self.freq * (2.0_f32).powf(self.expr.bend_cents / 1200.0) / self.sr;
         self.sub_phase += phase_inc * 0.5; // Sub is 1 octave down
         if self.sub_phase >= 1.0 { self.sub_phase -= 1.0; }
 
-        // Saw wave (naive but sounds fine with filter)
-        let saw = self.phase * 2.0 - 1.0;
-        
-        // Pulse wave with PWM
         let pw = 0.05 + pwm * 0.9; // 5% to 95% duty cycle
-        let pulse = if self.phase < pw { 1.0 } else { -1.0 };
-        
-        // Crossfade saw/pulse
-        let main_osc = saw * (1.0 - wave) + pulse * wave;
+        let mut main_l = 0.0;
+        let mut main_r = 0.0;
+        for k in 0..uni.count {
+            let ph = &mut self.phases[k];
+            *ph += phase_inc * uni.ratio[k];
+            if *ph >= 1.0 { *ph -= 1.0; }
+
+            // Saw wave (naive but sounds fine with filter)
+            let saw = *ph * 2.0 - 1.0;
+
+            // Pulse wave with PWM
+            let pulse = if *ph < pw { 1.0 } else { -1.0 };
+
+            // Crossfade saw/pulse
+            let x = saw * (1.0 - wave) + pulse * wave;
+            main_l += x * uni.gain_l[k];
+            main_r += x * uni.gain_r[k];
+        }
         
         // Sub oscillator (square, 1 octave down)
         let sub = if self.sub_phase < 0.5 { 1.0 } else { -1.0 };
@@ -340,14 +372,15 @@ impl KorusVoice {
         *rng ^= *rng << 5;
         let noise = (*rng as f32 / u32::MAX as f32) * 2.0 - 1.0;
         
-        // Mix oscillators
-        let osc_out = main_osc + sub * sub_level + noise * noise_level;
+        // Mix oscillators (sub and noise stay centred)
+        let centre = sub * sub_level + noise * noise_level;
 
         // ─── Filter ───
         
         // Calculate filter cutoff with envelope and LFO modulation
         // Map normalized cutoff (0-1) to Hz (20 - 20000, logarithmic)
-        let base_hz = 20.0 * (1000.0_f32).powf(cutoff);
+        let base_hz = 20.0 * (1000.0_f32).powf(cutoff) * velocity::filter_scale(self.vel, vel_filter)
+            * velocity::filter_scale(self.expr.slide, slide_filter);
         
         // Envelope modulation (positive only, like Juno)
         let env_mod = env * env_amt * base_hz * 2.0;
@@ -357,10 +390,16 @@ impl KorusVoice {
         
         let final_cutoff = (base_hz + env_mod + lfo_mod).clamp(20.0, 20000.0);
         
-        let filtered = self.filter.process(osc_out, final_cutoff, reso);
+        let out_l = self.filter.process(main_l + centre, final_cutoff, reso);
+        let out_r = if uni.is_stereo() {
+            self.filter_r.process(main_r + centre, final_cutoff, reso)
+        } else {
+            out_l
+        };
 
         // Apply envelope as VCA
-        filtered * env
+        let gain = env * velocity::amp_gain(self.vel, vel_amp) * velocity::amp_gain(self.expr.pressure, pressure_amp);
+        (out_l * gain, out_r * gain)
     }
 }
 
@@ -387,6 +426,17 @@ pub struct KorusParamKeys {
     pub lfo_pwm: u64,
     pub chorus: u64,
     pub chorus_rate: u64,
+    // Velocity
+    pub vel_amp: u64,
+    pub vel_filter: u64,
+    // Per-note expression (pressure to level, slide to cutoff)
+    pub pressure_amp: u64,
+    pub slide_filter: u64,
+    // Unison: voices 1..8, detune (cents, ±), spread 0..1, phase_rand 0..1
+    pub unison: u64,
+    pub detune: u64,
+    pub spread: u64,
+    pub phase_rand: u64,
 }
 
 impl KorusParamKeys {
@@ -410,6 +460,14 @@ impl KorusParamKeys {
             lfo_pwm: p("lfo_pwm"),
             chorus: p("chorus"),
             chorus_rate: p("chorus_rate"),
+            vel_amp: p("vel_amp"),
+            vel_filter: p("vel_filter"),
+            pressure_amp: p("pressure_amp"),
+            slide_filter: p("slide_filter"),
+            unison: p("unison"),
+            detune: p("detune"),
+            spread: p("spread"),
+            phase_rand: p("phase_rand"),
         }
     }
 }
@@ -422,6 +480,7 @@ pub struct Korus {
     next_voice: usize,
     lfo_phase: f32,
     chorus: BbdChorus,
+    unison: Unison,
     rng: u32,
 }
 
@@ -433,15 +492,17 @@ impl Korus {
             next_voice: 0,
             lfo_phase: 0.0,
             chorus: BbdChorus::new(sr),
+            unison: Unison::new(),
             rng: 0x12345678,
         }
     }
 
-    pub fn note_on(&mut self, note: u8, _vel: f32) {
+    pub fn note_on(&mut self, params: &ParamStore, keys: &KorusParamKeys, note: u8, vel: f32, channel: u8) {
+        let vel = vel.clamp(0.0, 1.0);
         // Check if same note is already playing - retrigger it
         for voice in &mut self.voices {
             if voice.note == note && voice.is_active() {
-                voice.retrigger();
+                voice.retrigger(vel);
                 return;
             }
         }
@@ -468,7 +529,19 @@ impl Korus {
             oldest_idx
         });
 
-        self.voices[i].note_on(note);
+        self.voices[i].note_on(note, vel);
+        self.voices[i].channel = channel;
+        let phase_rand = params.get_f32_h(keys.phase_rand, 0.0).clamp(0.0, 1.0);
+        if phase_rand > 0.0 {
+            self.voices[i].scatter_phases(phase_rand, &mut self.rng);
+        }
+    }
+
+    // `expr` maps a voice's note and MIDI channel to its expression
+    pub fn set_expression(&mut self, expr: impl Fn(u8, u8) -> NoteExpression) {
+        for voice in &mut self.voices {
+            voice.expr = expr(voice.note, voice.channel);
+        }
     }
 
     pub fn note_off(&mut self, note: u8) {
@@ -502,6 +575,15 @@ impl Korus {
         let lfo_pwm = params.get_f32_h(keys.lfo_pwm, 0.0).clamp(0.0, 1.0);
         let chorus_depth = params.get_f32_h(keys.chorus, 0.5).clamp(0.0, 1.0);
         let chorus_rate = params.get_f32_h(keys.chorus_rate, 0.3).clamp(0.0, 1.0);
+        let vel_amp = params.get_f32_h(keys.vel_amp, velocity::DEFAULT_AMOUNT);
+        let vel_filter = params.get_f32_h(keys.vel_filter, velocity::DEFAULT_AMOUNT);
+        let pressure_amp = params.get_f32_h(keys.pressure_amp, 0.0);
+        let slide_filter = params.get_f32_h(keys.slide_filter, 0.0);
+        self.unison.update(
+            params.get_i32_h(keys.unison, 1),
+            params.get_f32_h(keys.detune, 0.0),
+            params.get_f32_h(keys.spread, 0.0),
+        );
 
         // Update LFO (0.1 - 10 Hz)
         let lfo_hz = 0.1 + lfo_rate * 9.9;
@@ -513,21 +595,28 @@ impl Korus {
         let pwm = (pwm_base + lfo_value * lfo_pwm * 0.4).clamp(0.05, 0.95);
 
         // Render all voices
-        let mut mix = 0.0;
+        let mut mix_l = 0.0;
+        let mut mix_r = 0.0;
         for voice in &mut self.voices {
-            mix += voice.render(
+            let (l, r) = voice.render(
                 wave, pwm, sub, noise,
                 cutoff, reso, env_amt, lfo_filter, lfo_value,
                 attack, decay, sustain, release,
+                vel_amp, vel_filter,
+                pressure_amp, slide_filter,
+                &self.unison,
                 &mut self.rng,
             );
+            mix_l += l;
+            mix_r += r;
         }
 
         // Scale down for headroom
-        mix *= 0.25;
+        mix_l *= 0.25;
+        mix_r *= 0.25;
 
-        // Process through chorus (mono in, stereo out)
-        let (out_l, out_r) = self.chorus.process(mix, mix, chorus_depth, chorus_rate);
+        // Process through chorus (the dry path keeps the unison spread, the wet path is mono-fed)
+        let (out_l, out_r) = self.chorus.process(mix_l, mix_r, chorus_depth, chorus_rate);
 
         // Soft clip for warmth
         (out_l.tanh(), out_r.tanh())
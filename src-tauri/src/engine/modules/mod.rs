@@ -1,8 +1,14 @@
 pub mod acid303;
+pub mod disk_stream;
 pub mod drum;
+pub mod drum_synth;
+pub mod expression;
+pub mod fm;
+pub mod granular;
 pub mod karplus_strong;
 pub mod korus;
 pub mod resonator_bank;
 pub mod sampler;
+pub mod wavetable;
 
 // Intentionally do not re-export modules here; import concrete types where needed
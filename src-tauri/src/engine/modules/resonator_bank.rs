@@ -1,6 +1,12 @@
+use crate::engine::dsp::velocity;
+use crate::engine::modules::expression::NoteExpression;
 use crate::engine::params::ParamStore;
+use rustfft::{num_complex::Complex32, FftPlanner};
 use std::f32::consts::PI;
 
+// Up to 8 resonators for the modal and comb modes; an analysed profile may use 16
+const MAX_RESONATORS: usize = 16;
+
 // Helper functions
 #[inline]
 fn midi_to_freq(m: u8) -> f32 {
@@ -190,6 +196,84 @@ impl Biquad {
     }
 }
 
+// Partials measured from a recording, used as the resonator tuning in profile mode (mode 2).
+// Ratios are relative to the lowest partial, which the played note lands on; amplitudes peak at 1.
+#[derive(Clone, Default)]
+pub struct SpectralProfile {
+    pub ratios: Vec<f32>,
+    pub amps: Vec<f32>,
+}
+
+impl SpectralProfile {
+    // FFT peak analysis of a mono recording: a Hann-windowed block with the loudest point a
+    // quarter of the way in, picking the strongest spectral peaks (parabolic-interpolated) within
+    // 40 dB of the strongest bin
+    pub fn analyse(samples: &[f32], sr: f32, max_partials: usize) -> Result<Self, String> {
+        if samples.len() < 1024 {
+            return Err("sample too short to analyse".to_string());
+        }
+        let n = (1usize << (usize::BITS - 1 - samples.len().leading_zeros())).min(65536);
+        let peak = samples
+            .iter()
+            .enumerate()
+            .fold((0, 0.0f32), |m, (i, &x)| if x.abs() > m.1 { (i, x.abs()) } else { m })
+            .0;
+        // The window is 0 at its edges, so the attack must not sit on the first sample
+        let start = peak.saturating_sub(n / 4).min(samples.len() - n);
+        let mut buf: Vec<Complex32> = samples[start..start + n]
+            .iter()
+            .enumerate()
+            .map(|(i, &x)| {
+                let w = 0.5 - 0.5 * (2.0 * PI * i as f32 / n as f32).cos();
+                Complex32::new(x * w, 0.0)
+            })
+            .collect();
+        FftPlanner::<f32>::new().plan_fft_forward(n).process(&mut buf);
+        let mag: Vec<f32> = buf[..n / 2].iter().map(|c| c.norm()).collect();
+
+        let bin_hz = sr / n as f32;
+        let lo = ((30.0 / bin_hz) as usize).max(1);
+        let hi = ((sr * 0.45 / bin_hz) as usize).min(n / 2 - 2);
+        let top = mag.iter().fold(0.0f32, |m, &x| m.max(x));
+        if top <= 0.0 {
+            return Err("sample is silent".to_string());
+        }
+        let floor = top * 0.01; // -40 dB
+        let mut peaks: Vec<(f32, f32)> = Vec::new(); // (Hz, magnitude)
+        for k in lo..=hi {
+            let (a, b, c) = (mag[k - 1], mag[k], mag[k + 1]);
+            if b > floor && b > a && b >= c {
+                // Parabolic interpolation on log magnitude
+                let (la, lb, lc) = (a.max(1e-12).ln(), b.ln(), c.max(1e-12).ln());
+                let d = 0.5 * (la - lc) / (la - 2.0 * lb + lc);
+                let d = if d.is_finite() { d.clamp(-0.5, 0.5) } else { 0.0 };
+                peaks.push(((k as f32 + d) * bin_hz, (lb - 0.25 * (la - lc) * d).exp()));
+            }
+        }
+        peaks.sort_by(|x, y| y.1.total_cmp(&x.1));
+        // Strongest first; skip peaks within a quarter tone of one already taken (window sidelobes)
+        let mut chosen: Vec<(f32, f32)> = Vec::new();
+        for p in peaks {
+            if chosen.len() >= max_partials {
+                break;
+            }
+            if chosen.iter().all(|c| (p.0 / c.0).log2().abs() > 1.0 / 24.0) {
+                chosen.push(p);
+            }
+        }
+        if chosen.is_empty() {
+            return Err("no spectral peaks found".to_string());
+        }
+        chosen.sort_by(|x, y| x.0.total_cmp(&y.0));
+        let f0 = chosen[0].0;
+        let amp_max = chosen.iter().fold(0.0f32, |m, c| m.max(c.1));
+        Ok(Self {
+            ratios: chosen.iter().map(|c| c.0 / f0).collect(),
+            amps: chosen.iter().map(|c| c.1 / amp_max).collect(),
+        })
+    }
+}
+
 // Single voice for polyphonic resonator bank
 #[derive(Clone)]
 pub struct ResonatorVoice {
@@ -198,6 +282,8 @@ pub struct ResonatorVoice {
     velocity: f32,
     gate: bool,
     just_triggered: bool,
+    channel: u8, // MIDI channel of the note; selects its per-channel expression
+    pub expr: NoteExpression,
 
     // Resonator bank
     resonators: Vec<Biquad>,
@@ -225,7 +311,7 @@ pub struct ResonatorVoice {
 
 impl ResonatorVoice {
     pub fn new(sr: f32) -> Self {
-        let max_resonators = 8;
+        let max_resonators = MAX_RESONATORS;
 
         Self {
             sr,
@@ -233,6 +319,8 @@ impl ResonatorVoice {
             velocity: 0.0,
             gate: false,
             just_triggered: false,
+            channel: 0,
+            expr: NoteExpression::default(),
             resonators: vec![Biquad::new(); max_resonators],
             resonator_gains: vec![0.0; max_resonators],
             exciter: Exciter::new(),
@@ -298,13 +386,26 @@ impl ResonatorVoice {
         }
     }
 
-    pub fn render(&mut self, params: &ParamStore, param_keys: &ResonatorParamKeys) -> f32 {
+    // Force the tuning to be recomputed on the next sample (e.g. a new profile was loaded)
+    fn invalidate(&mut self) {
+        self.last_mode = -999;
+    }
+
+    pub fn render(
+        &mut self,
+        params: &ParamStore,
+        param_keys: &ResonatorParamKeys,
+        profile: Option<&SpectralProfile>,
+    ) -> f32 {
         // Get parameters
-        let pitch_offset = params.get_f32_h(param_keys.pitch, 0.0); // ±1 for ±48 semitones
+        // ±1 for ±48 semitones, plus pitch bend (48 semitones = 4800 cents)
+        let pitch_offset = params.get_f32_h(param_keys.pitch, 0.0) + self.expr.bend_cents / 4800.0;
         let decay = params.get_f32_h(param_keys.decay, 0.5);
-        let brightness = params.get_f32_h(param_keys.brightness, 0.5);
-        let bank_size = params.get_i32_h(param_keys.bank_size, 8).max(1).min(8) as usize;
+        let brightness = params.get_f32_h(param_keys.brightness, 0.5)
+            * velocity::amp_gain(self.expr.slide, params.get_f32_h(param_keys.slide_bright, 0.0));
         let mode = params.get_i32_h(param_keys.mode, 0);
+        let max_bank = if mode == 2 { MAX_RESONATORS } else { 8 } as i32;
+        let bank_size = params.get_i32_h(param_keys.bank_size, 8).max(1).min(max_bank) as usize;
         let inharmonicity = params.get_f32_h(param_keys.inharmonicity, 0.1);
         let feedback = params.get_f32_h(param_keys.feedback, 0.3);
         let drive = params.get_f32_h(param_keys.drive, 0.0);
@@ -346,8 +447,38 @@ impl ResonatorVoice {
             self.compute_partial_weights(body_blend, bank_size);
 
             // Update resonator bank based on mode
-            match mode {
-                0 => {
+            match (mode, profile) {
+                (2, Some(profile)) => {
+                    // Profile mode - resonators tuned to an analysed recording; its own amplitudes
+                    // replace the body blend curve and brightness tilts them
+                    let tone = brightness.clamp(0.0, 1.0);
+                    let decay_e = decay.clamp(0.0, 1.0).powf(1.35);
+                    let q = 3.0 + decay_e * 22.0;
+                    for i in 0..bank_size {
+                        self.partial_weights[i] = 1.0;
+                        let (Some(&ratio), Some(&amp)) = (profile.ratios.get(i), profile.amps.get(i))
+                        else {
+                            self.resonator_gains[i] = 0.0;
+                            continue;
+                        };
+                        let detune_cents = inharmonicity * (ratio - 1.0) * 5.0;
+                        let random_detune = if randomize > 0.01 {
+                            let seed = (self.note as f32 * 17.0 + i as f32 * 23.0) % 1000.0;
+                            (seed.sin() * 2.0 - 1.0) * randomize * 50.0
+                        } else {
+                            0.0
+                        };
+                        let freq = base_freq * ratio * cents_to_ratio(detune_cents + random_detune);
+                        // Partials above the band are dropped rather than piled up at the limit
+                        if freq >= self.sr * 0.45 {
+                            self.resonator_gains[i] = 0.0;
+                            continue;
+                        }
+                        self.resonators[i].set_bandpass(freq, q, self.sr);
+                        self.resonator_gains[i] = amp * ratio.powf((tone - 0.5) * 1.5);
+                    }
+                }
+                (0, _) => {
                     // Modal mode - harmonic resonators (Brightness = tonal tilt; dark adds undertones)
                     // Tone shaping
                     let tone = brightness.clamp(0.0, 1.0);
@@ -391,7 +522,7 @@ impl ResonatorVoice {
                         self.resonator_gains[i] = gain;
                     }
                 }
-                1 => {
+                (1, _) => {
                     // Comb mode - tone shaping and optional undertone; no brightness->pitch coupling
                     if bank_size > 0 {
                         let tone = brightness.clamp(0.0, 1.0);
@@ -498,6 +629,7 @@ impl ResonatorVoice {
 
         // Apply output gain (±1 range for ±24dB)
         output *= db_to_gain(output_gain_db * 24.0);
+        output *= velocity::amp_gain(self.expr.pressure, params.get_f32_h(param_keys.pressure_amp, 0.0));
 
         // Update limiter state for voice activity detection
         self.limiter_state = output;
@@ -527,6 +659,9 @@ pub struct ResonatorParamKeys {
     pub randomize: u64,
     pub body_blend: u64,
     pub output_gain: u64,
+    // Per-note expression (pressure to level, slide to brightness)
+    pub pressure_amp: u64,
+    pub slide_bright: u64,
 }
 
 // Main ResonatorBank structure with polyphonic voices
@@ -536,6 +671,7 @@ pub struct ResonatorBank {
     sr: f32,
     voices: [ResonatorVoice; 3], // 3-voice polyphony
     voice_allocator: usize,
+    profile: Option<SpectralProfile>,
 }
 
 impl ResonatorBank {
@@ -548,10 +684,11 @@ impl ResonatorBank {
                 ResonatorVoice::new(sr),
             ],
             voice_allocator: 0,
+            profile: None,
         }
     }
 
-    pub fn note_on(&mut self, note: u8, velocity: f32) {
+    pub fn note_on(&mut self, note: u8, velocity: f32, channel: u8) {
         // Find a free voice or steal the oldest
         let voice_idx = self.find_free_voice().unwrap_or_else(|| {
             let idx = self.voice_allocator;
@@ -560,6 +697,14 @@ impl ResonatorBank {
         });
 
         self.voices[voice_idx].note_on(note, velocity);
+        self.voices[voice_idx].channel = channel;
+    }
+
+    // `expr` maps a voice's note and MIDI channel to its expression
+    pub fn set_expression(&mut self, expr: impl Fn(u8, u8) -> NoteExpression) {
+        for voice in &mut self.voices {
+            voice.expr = expr(voice.note, voice.channel);
+        }
     }
 
     pub fn note_off(&mut self, note: u8) {
@@ -586,7 +731,7 @@ impl ResonatorBank {
 
         for voice in &mut self.voices {
             if voice.is_active() {
-                output += voice.render(params, param_keys);
+                output += voice.render(params, param_keys, self.profile.as_ref());
             }
         }
 
@@ -595,4 +740,68 @@ impl ResonatorBank {
 
         output
     }
+
+    // Tune profile mode (mode 2) to an analysed recording; None reverts it to plain modal
+    pub fn set_profile(&mut self, profile: Option<SpectralProfile>) {
+        self.profile = profile;
+        for voice in &mut self.voices {
+            voice.invalidate();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn analysis_finds_partial_ratios() {
+        let sr = 48000.0;
+        let partials = [(220.0, 1.0), (563.0, 0.5), (1001.0, 0.25)];
+        let samples: Vec<f32> = (0..16384)
+            .map(|i| {
+                let t = i as f32 / sr;
+                partials.iter().map(|&(f, a)| a * (2.0 * PI * f * t).sin()).sum()
+            })
+            .collect();
+        let profile = SpectralProfile::analyse(&samples, sr, 8).unwrap();
+        assert_eq!(profile.ratios.len(), 3);
+        for (i, &(f, a)) in partials.iter().enumerate() {
+            assert!((profile.ratios[i] - f / 220.0).abs() < 0.01, "ratio {}", profile.ratios[i]);
+            assert!((profile.amps[i] - a).abs() < 0.05, "amp {}", profile.amps[i]);
+        }
+    }
+
+    #[test]
+    fn analysis_without_peaks_is_an_error() {
+        // A decaying sub thump: all its energy sits below the analysed band
+        let sr = 48000.0;
+        let samples: Vec<f32> = (0..16384)
+            .map(|i| {
+                let t = i as f32 / sr;
+                (-t / 0.1).exp() * (2.0 * PI * 12.0 * t).sin()
+            })
+            .collect();
+        assert!(SpectralProfile::analyse(&samples, sr, 8).is_err());
+        assert!(SpectralProfile::analyse(&[0.0; 4096], sr, 8).is_err());
+    }
+
+    #[test]
+    fn expression_follows_the_voice_channel() {
+        let mut bank = ResonatorBank::new(48000.0);
+        bank.note_on(60, 1.0, 1);
+        bank.note_on(60, 1.0, 2);
+        bank.set_expression(|_, ch| NoteExpression {
+            bend_cents: ch as f32 * 100.0,
+            ..Default::default()
+        });
+        let mut bends: Vec<f32> = bank
+            .voices
+            .iter()
+            .filter(|v| v.gate)
+            .map(|v| v.expr.bend_cents)
+            .collect();
+        bends.sort_by(f32::total_cmp);
+        assert_eq!(bends, vec![100.0, 200.0]);
+    }
 }
@@ -1,3 +1,7 @@
+use crate::engine::dsp::resample::{resample, sinc_read};
+use crate::engine::dsp::velocity;
+use crate::engine::modules::disk_stream::{DiskStream, STREAM_THRESHOLD_BYTES};
+use crate::engine::modules::expression::NoteExpression;
 use crate::engine::params::ParamStore;
 use std::f32::consts::PI;
 use std::fs::File;
@@ -36,7 +40,6 @@ fn lerp(a: f32, b: f32, t: f32) -> f32 {
 }
 
 #[inline]
-#[allow(dead_code)]
 fn hann_window(t: f32) -> f32 {
     0.5 * (1.0 - (2.0 * PI * t).cos())
 }
@@ -108,6 +111,8 @@ pub struct SampleBuffer {
     pub channels: usize,
     pub sample_rate: f32,
     pub length_samples: usize,
+    // Long files: `data` holds only the preloaded head, the rest comes from disk
+    pub stream: Option<Arc<DiskStream>>,
 }
 
 impl SampleBuffer {
@@ -117,6 +122,7 @@ impl SampleBuffer {
             channels: 1,
             sample_rate: 44100.0,
             length_samples: 0,
+            stream: None,
         }
     }
 
@@ -124,6 +130,23 @@ impl SampleBuffer {
     pub fn clear(&mut self) {
         self.data.clear();
         self.length_samples = 0;
+        self.stream = None;
+    }
+
+    // Tell the disk thread where voice `slot` is playing (no-op for in-memory samples)
+    #[inline]
+    pub fn note_playhead(&self, slot: usize, position: f32) {
+        if let Some(stream) = &self.stream {
+            stream.request(slot, position.max(0.0) as usize);
+        }
+    }
+
+    // Voice `slot` went idle
+    #[inline]
+    pub fn release_playhead(&self, slot: usize) {
+        if let Some(stream) = &self.stream {
+            stream.release(slot);
+        }
     }
 
     pub fn is_empty(&self) -> bool {
@@ -150,6 +173,8 @@ impl SampleBuffer {
 
         if index < self.data.len() {
             self.data[index]
+        } else if let Some(stream) = &self.stream {
+            stream.sample(pos_samples)
         } else {
             0.0
         }
@@ -168,6 +193,13 @@ impl SampleBuffer {
             return self.get_sample(position, channel);
         }
 
+        if (pos_int + 3) * self.channels >= self.data.len() {
+            // Past the resident head of a streamed file
+            let at = |i: usize| self.get_sample(i as f32, channel);
+            let y0 = at(pos_int.saturating_sub(1));
+            return cubic_interpolate(y0, at(pos_int), at(pos_int + 1), at(pos_int + 2), frac);
+        }
+
         let channel_offset = if self.channels == 1 {
             0
         } else {
@@ -186,6 +218,42 @@ impl SampleBuffer {
 
         cubic_interpolate(y0, y1, y2, y3, frac)
     }
+
+    // Read for playback at `step` source samples per output sample. `hq` selects the
+    // band-limited sinc kernel (anti-aliased when pitching up) over cubic interpolation.
+    pub fn read(&self, position: f32, step: f32, hq: bool) -> f32 {
+        let resident = self.stream.is_none() || position + 64.0 < self.data.len() as f32;
+        if hq && self.channels == 1 && resident {
+            sinc_read(
+                &self.data[..self.length_samples.min(self.data.len())],
+                position,
+                step,
+            )
+        } else {
+            self.get_sample_interpolated(position, 0)
+        }
+    }
+
+    // Peak-sampled overview for waveform display
+    pub fn overview(&self, samples: usize) -> Vec<f32> {
+        if self.is_empty() {
+            return vec![0.0; samples];
+        }
+
+        let mut overview = Vec::with_capacity(samples);
+        let step = self.length_samples as f32 / samples as f32;
+
+        for i in 0..samples {
+            let pos = (i as f32 * step) as usize;
+            if pos < self.data.len() {
+                overview.push(self.data[pos]);
+            } else {
+                overview.push(0.0);
+            }
+        }
+
+        overview
+    }
 }
 
 // ADSR envelope
@@ -291,6 +359,60 @@ impl Envelope {
     }
 }
 
+// Wrap a read position back into [loop_start, loop_start + loop_len)
+#[inline]
+fn wrap_in_loop(pos: f32, loop_start: f32, loop_len: f32) -> f32 {
+    loop_start + (pos - loop_start).rem_euclid(loop_len)
+}
+
+// One overlap-add grain used by warp playback
+#[derive(Clone, Copy)]
+struct WarpGrain {
+    pos: f32,   // read position in samples (wraps inside the loop region)
+    age: usize, // samples rendered since grain start
+    active: bool,
+}
+
+impl WarpGrain {
+    fn idle() -> Self {
+        Self {
+            pos: 0.0,
+            age: 0,
+            active: false,
+        }
+    }
+}
+
+// Two-grain WSOLA state for tempo-synced warp playback in Loop mode
+#[derive(Clone)]
+struct WarpState {
+    grains: [WarpGrain; 2],
+    next: usize,        // grain slot to restart on the next hop
+    hop_counter: usize, // samples left until the next grain starts
+    anchor_beats: f32,  // local beat time the loop phase is measured from
+    engaged: bool,      // false until the first warped sample after a mode change
+}
+
+impl WarpState {
+    fn new() -> Self {
+        Self {
+            grains: [WarpGrain::idle(); 2],
+            next: 0,
+            hop_counter: 0,
+            anchor_beats: 0.0,
+            engaged: false,
+        }
+    }
+
+    fn reset(&mut self, anchor_beats: f32) {
+        self.grains = [WarpGrain::idle(); 2];
+        self.next = 0;
+        self.hop_counter = 0;
+        self.anchor_beats = anchor_beats;
+        self.engaged = true;
+    }
+}
+
 // Single voice for polyphonic sampler
 #[derive(Clone)]
 pub struct SamplerVoice {
@@ -301,9 +423,12 @@ pub struct SamplerVoice {
     just_triggered: bool,
 
     // Playback state
-    position: f32,    // Current position in samples
-    pitch_ratio: f32, // Playback speed ratio for pitch shifting
-    direction: f32,   // 1.0 for forward, -1.0 for reverse (ping-pong)
+    position: f32,            // Current position in samples
+    pitch_ratio: f32,         // Playback speed ratio for pitch shifting
+    channel: u8,              // MIDI channel of the note; selects its per-channel expression
+    pub expr: NoteExpression, // bend on top of the tuning params, pressure, slide
+    direction: f32,           // 1.0 for forward, -1.0 for reverse (ping-pong)
+    tone_lp: f32,             // one-pole lowpass state for slide -> tone
 
     // Envelope
     envelope: Envelope,
@@ -335,6 +460,10 @@ pub struct SamplerVoice {
     stall_until_retrig: bool,
     // Monotonic serial so UI can pick newest active voice for playhead
     trigger_serial: u64,
+    // Time-stretch state for warped loops
+    warp: WarpState,
+    // Playhead slot on a disk-streamed buffer (the voice index)
+    stream_slot: usize,
 }
 
 impl SamplerVoice {
@@ -347,7 +476,10 @@ impl SamplerVoice {
             just_triggered: false,
             position: 0.0,
             pitch_ratio: 1.0,
+            channel: 0,
+            expr: NoteExpression::default(),
             direction: 1.0,
+            tone_lp: 0.0,
             envelope: Envelope::new(sr),
             declick_ramp: 1.0,
             declick_target: 1.0,
@@ -364,6 +496,8 @@ impl SamplerVoice {
             last_interval_beats: 0.0,
             stall_until_retrig: false,
             trigger_serial: 0,
+            warp: WarpState::new(),
+            stream_slot: 0,
         }
     }
 
@@ -450,6 +584,14 @@ impl SamplerVoice {
         let smoothness_ms = params
             .get_f32_h(param_keys.smoothness, 0.0)
             .clamp(0.0, 50.0);
+        // Warp: stretch the loop region to `warp_bars` bars at the current tempo
+        let warp_on = params.get_i32_h(param_keys.warp, 0) != 0;
+        let warp_bars = params
+            .get_f32_h(param_keys.warp_bars, 1.0)
+            .clamp(0.25, 64.0);
+        let warp_grain_ms = params
+            .get_f32_h(param_keys.warp_grain, 40.0)
+            .clamp(10.0, 200.0);
 
         let attack_ms = params.get_f32_h(param_keys.attack, 10.0);
         let decay_ms = params.get_f32_h(param_keys.decay, 100.0);
@@ -466,7 +608,7 @@ impl SamplerVoice {
 
         // Calculate pitch ratio
         let total_pitch = pitch_semitones + pitch_cents / 100.0;
-        let mut pitch_ratio = cents_to_ratio(total_pitch * 100.0);
+        let mut pitch_ratio = cents_to_ratio(total_pitch * 100.0 + self.expr.bend_cents);
 
         // Apply keytrack in Keytrack and Loop modes
         if matches!(playback_mode, PlaybackMode::Keytrack | PlaybackMode::Loop) {
@@ -475,7 +617,12 @@ impl SamplerVoice {
             pitch_ratio *= cents_to_ratio(note_offset * 100.0);
         }
 
+        // Streamed buffers stay at the file rate; conformed ones make this 1.0
+        pitch_ratio *= buffer.sample_rate / self.sr;
+
         self.pitch_ratio = pitch_ratio;
+        // Interpolation quality: 0 = cubic, 1 = band-limited sinc (default)
+        let hq = params.get_i32_h(param_keys.quality, 1) != 0;
 
         // Reset position if just triggered
         if self.just_triggered {
@@ -487,6 +634,7 @@ impl SamplerVoice {
             self.last_interval_beats = 0.0;
             self.stall_until_retrig = false;
             self.last_beat_phase = beat_phase;
+            self.warp.reset(0.0);
             // Start envelope on trigger only for Loop/Keytrack; One-Shot uses full-level gate
             if matches!(playback_mode, PlaybackMode::Loop | PlaybackMode::Keytrack) {
                 self.envelope.note_on();
@@ -569,6 +717,7 @@ impl SamplerVoice {
             && !self.gate
             && !matches!(playback_mode, PlaybackMode::OneShot)
         {
+            buffer.release_playhead(self.stream_slot);
             return 0.0;
         }
 
@@ -583,7 +732,7 @@ impl SamplerVoice {
                     self.direction = 1.0;
                 }
                 if self.position < end_pos {
-                    output = buffer.get_sample_interpolated(self.position, 0);
+                    output = buffer.read(self.position, pitch_ratio, hq);
                     self.position += self.pitch_ratio;
                 } else {
                     // One-Shot: mark envelope idle only once the sample region finished
@@ -591,7 +740,31 @@ impl SamplerVoice {
                     self.envelope.level = 0.0;
                 }
             }
+            PlaybackMode::Loop if warp_on => {
+                let loop_start_pos = start_pos + (loop_start * (end_pos - start_pos));
+                let loop_end_pos = start_pos + (loop_end * (end_pos - start_pos));
+                let loop_len = (loop_end_pos - loop_start_pos).max(1.0);
+                let loop_beats = warp_bars * 4.0;
+                if !self.warp.engaged {
+                    // Switched into warp mid-note: keep the current loop phase
+                    let phase = ((self.position - loop_start_pos) / loop_len).clamp(0.0, 1.0);
+                    self.warp.reset(self.local_beats - phase * loop_beats);
+                }
+                if retrig_now {
+                    // Tempo retrig restarts the warped loop from its start
+                    self.warp.reset(self.local_beats);
+                }
+                output = self.render_warp(
+                    buffer,
+                    loop_start_pos,
+                    loop_len,
+                    loop_beats,
+                    warp_grain_ms,
+                    hq,
+                );
+            }
             PlaybackMode::Loop => {
+                self.warp.engaged = false;
                 let loop_start_pos = start_pos + (loop_start * (end_pos - start_pos));
                 let loop_end_pos = start_pos + (loop_end * (end_pos - start_pos));
                 let loop_len = (loop_end_pos - loop_start_pos).max(1.0);
@@ -627,7 +800,7 @@ impl SamplerVoice {
                     output = 0.0;
                 } else if self.position >= loop_start_pos && self.position <= loop_end_pos {
                     // Base sample at current position
-                    let base = buffer.get_sample_interpolated(self.position, 0);
+                    let base = buffer.read(self.position, pitch_ratio, hq);
                     output = base;
 
                     match loop_mode {
@@ -640,7 +813,7 @@ impl SamplerVoice {
                                         .clamp(0.0, 1.0);
                                     // Align start window to loop start with same offset
                                     let start_pos = loop_start_pos + (self.position - window_start);
-                                    let s_start = buffer.get_sample_interpolated(start_pos, 0);
+                                    let s_start = buffer.read(start_pos, pitch_ratio, hq);
                                     let s_end = base;
                                     output = s_end * (1.0 - t) + s_start * t;
                                 }
@@ -688,7 +861,7 @@ impl SamplerVoice {
                         self.stall_until_retrig = false;
                     }
                     if self.position < end_pos {
-                        output = buffer.get_sample_interpolated(self.position, 0);
+                        output = buffer.read(self.position, pitch_ratio, hq);
                         self.position += self.pitch_ratio;
                     } else {
                         self.envelope.note_off();
@@ -701,7 +874,7 @@ impl SamplerVoice {
                     self.direction = 1.0;
                 }
                 if self.position < end_pos {
-                    output = buffer.get_sample_interpolated(self.position, 0);
+                    output = buffer.read(self.position, pitch_ratio, hq);
                     self.position += self.pitch_ratio;
                 } else {
                     self.envelope.note_off();
@@ -720,6 +893,21 @@ impl SamplerVoice {
         };
         output *= env_level * self.velocity;
 
+        // Per-note expression: pressure to level, slide opens a lowpass (bypassed at 0)
+        let pressure_amp = params.get_f32_h(param_keys.pressure_amp, 0.0);
+        let slide_filter = params.get_f32_h(param_keys.slide_filter, 0.0);
+        output *= velocity::amp_gain(self.expr.pressure, pressure_amp);
+        if slide_filter > 0.0 {
+            let fc = 18000.0 * velocity::filter_scale(self.expr.slide, slide_filter);
+            let a = 1.0 - (-2.0 * PI * fc / self.sr).exp();
+            self.tone_lp += a * (output - self.tone_lp);
+            output = self.tone_lp;
+        } else {
+            self.tone_lp = output;
+        }
+
+        buffer.note_playhead(self.stream_slot, self.position);
+
         // Apply de-click ramp if parameters changed
         self.declick_ramp += (self.declick_target - self.declick_ramp) * self.declick_rate;
         output *= self.declick_ramp;
@@ -727,6 +915,106 @@ impl SamplerVoice {
         output
     }
 
+    // WSOLA time-stretch. The analysis position follows the note-anchored beat clock so the
+    // loop always spans `loop_beats` at the current tempo; grains read at `pitch_ratio`, so
+    // pitch is independent of the stretch. Two Hann grains overlap by half a grain.
+    fn render_warp(
+        &mut self,
+        buffer: &SampleBuffer,
+        loop_start_pos: f32,
+        loop_len: f32,
+        loop_beats: f32,
+        grain_ms: f32,
+        hq: bool,
+    ) -> f32 {
+        let phase = ((self.local_beats - self.warp.anchor_beats) / loop_beats).rem_euclid(1.0);
+        let src_pos = loop_start_pos + phase * loop_len;
+        let grain_len = ((grain_ms * 0.001 * self.sr) as usize)
+            .min(loop_len as usize)
+            .max(64);
+        let hop = grain_len / 2;
+
+        if self.warp.hop_counter == 0 {
+            let slot = self.warp.next;
+            let outgoing = self.warp.grains[1 - slot];
+            let start = if outgoing.active {
+                self.wsola_align(buffer, src_pos, outgoing.pos, loop_start_pos, loop_len, hop)
+            } else {
+                src_pos
+            };
+            self.warp.grains[slot] = WarpGrain {
+                pos: start,
+                age: 0,
+                active: true,
+            };
+            self.warp.next = 1 - slot;
+            self.warp.hop_counter = hop;
+        }
+        self.warp.hop_counter -= 1;
+
+        let mut out = 0.0;
+        for g in &mut self.warp.grains {
+            if !g.active {
+                continue;
+            }
+            if g.age >= grain_len {
+                g.active = false;
+                continue;
+            }
+            let w = hann_window(g.age as f32 / grain_len as f32);
+            out += w * buffer.read(g.pos, self.pitch_ratio, hq);
+            g.pos = wrap_in_loop(g.pos + self.pitch_ratio, loop_start_pos, loop_len);
+            g.age += 1;
+        }
+
+        // Playhead reports the tempo-locked analysis position
+        self.position = src_pos;
+        out
+    }
+
+    // Pick a start near `target` whose waveform best continues the outgoing grain at
+    // `outgoing_pos`, searching +-10ms with a decimated normalised cross-correlation.
+    fn wsola_align(
+        &self,
+        buffer: &SampleBuffer,
+        target: f32,
+        outgoing_pos: f32,
+        loop_start_pos: f32,
+        loop_len: f32,
+        overlap: usize,
+    ) -> f32 {
+        const POINTS: usize = 64;
+        let stride = (overlap as f32 / POINTS as f32).max(1.0) * self.pitch_ratio;
+        let mut reference = [0.0f32; POINTS];
+        for (k, r) in reference.iter_mut().enumerate() {
+            let p = wrap_in_loop(outgoing_pos + k as f32 * stride, loop_start_pos, loop_len);
+            *r = buffer.get_sample(p, 0);
+        }
+
+        let tolerance = (0.010 * self.sr).min(loop_len * 0.25) as i32;
+        let mut best = target;
+        let mut best_score = f32::MIN;
+        let mut delta = -tolerance;
+        while delta <= tolerance {
+            let cand = target + delta as f32;
+            let mut dot = 0.0;
+            let mut energy = 1e-9;
+            for (k, r) in reference.iter().enumerate() {
+                let p = wrap_in_loop(cand + k as f32 * stride, loop_start_pos, loop_len);
+                let s = buffer.get_sample(p, 0);
+                dot += r * s;
+                energy += s * s;
+            }
+            let score = dot / energy.sqrt();
+            if score > best_score {
+                best_score = score;
+                best = cand;
+            }
+            delta += 4;
+        }
+        wrap_in_loop(best, loop_start_pos, loop_len)
+    }
+
     pub fn is_active(&self) -> bool {
         self.envelope.is_active()
     }
@@ -756,13 +1044,25 @@ pub struct SamplerParamKeys {
     pub loop_mode: u64,
     pub smoothness: u64,  // still used for loop wrap crossfade (ms)
     pub retrig_mode: u64, // 0=Immediate; 1..7 = tempo-synced: 1/1,1/2,1/4,1/8,1/16,1/32,1/64
+    // Warp (time-stretch) parameters
+    pub warp: u64,       // 0=off (repitch), 1=on (loop stretched to tempo)
+    pub warp_bars: u64,  // loop length in bars when warped
+    pub warp_grain: u64, // grain size (ms)
+    // Playback interpolation: 0=cubic, 1=band-limited sinc
+    pub quality: u64,
     // Envelope parameters
     pub attack: u64,
     pub decay: u64,
     pub sustain: u64,
     pub release: u64,
+    // Per-note expression (pressure to level, slide to tone)
+    pub pressure_amp: u64,
+    pub slide_filter: u64,
 }
 
+// Voice i reports its disk-stream playhead in slot i
+pub const MAX_VOICES: usize = 6;
+
 // Main Sampler structure with polyphonic voices
 #[derive(Clone)]
 pub struct Sampler {
@@ -789,10 +1089,14 @@ pub struct PlayheadState {
 
 impl Sampler {
     pub fn new(sr: f32) -> Self {
-        let max_voices = 6;
         Self {
             sr,
-            voices: (0..max_voices).map(|_| SamplerVoice::new(sr)).collect(),
+            voices: (0..MAX_VOICES)
+                .map(|i| SamplerVoice {
+                    stream_slot: i,
+                    ..SamplerVoice::new(sr)
+                })
+                .collect(),
             voice_allocator: 0,
             sample_buffer: Arc::new(Mutex::new(SampleBuffer::new())),
             recording: false,
@@ -801,15 +1105,23 @@ impl Sampler {
         }
     }
 
-    pub fn note_on(&mut self, note: u8, velocity: f32, _retrig_mode: RetrigMode) {
+    pub fn note_on(&mut self, note: u8, velocity: f32, _retrig_mode: RetrigMode, channel: u8) {
         // Allocate a voice (polyphonic). If all are active, steal one via round-robin.
         let voice_idx = self.find_available_voice();
         self.voices[voice_idx].note_on(note, velocity);
+        self.voices[voice_idx].channel = channel;
         self.voices[voice_idx].trigger_serial = self.trigger_counter;
         // Avoid zero so default-initialized voices are always older
         self.trigger_counter = self.trigger_counter.wrapping_add(1).max(1);
     }
 
+    // `expr` maps a voice's note and MIDI channel to its expression
+    pub fn set_expression(&mut self, expr: impl Fn(u8, u8) -> NoteExpression) {
+        for voice in &mut self.voices {
+            voice.expr = expr(voice.note, voice.channel);
+        }
+    }
+
     pub fn note_off(&mut self, note: u8) {
         // Release all voices with matching note
         for voice in &mut self.voices {
@@ -858,6 +1170,11 @@ impl Sampler {
         self.voices.iter().any(|voice| voice.is_active())
     }
 
+    // Shared handle to the loaded sample (used by the granular module)
+    pub fn buffer_handle(&self) -> Arc<Mutex<SampleBuffer>> {
+        Arc::clone(&self.sample_buffer)
+    }
+
     #[allow(dead_code)]
     pub fn start_recording(&mut self) {
         self.recording = true;
@@ -874,6 +1191,7 @@ impl Sampler {
             buffer.length_samples = self.record_buffer.len();
             buffer.channels = 1; // Mono recording for now
             buffer.sample_rate = self.sr;
+            buffer.stream = None;
         }
     }
 
@@ -886,28 +1204,8 @@ impl Sampler {
 
     pub fn load_sample(&mut self, file_path: &str) {
         match self.load_audio_file(file_path) {
-            Ok(_) => {
-                // Normalize peak to ~0.9 to avoid clipping and keep consistent preview loudness
-                if let Ok(mut buffer) = self.sample_buffer.lock() {
-                    let mut peak = 0.0f32;
-                    for &s in &buffer.data {
-                        let a = s.abs();
-                        if a > peak {
-                            peak = a;
-                        }
-                    }
-                    if peak > 0.0001 {
-                        let norm = 0.9 / peak;
-                        if norm < 1.5 {
-                            // avoid over-amplifying very quiet samples drastically here
-                            for s in &mut buffer.data {
-                                *s *= norm;
-                            }
-                        }
-                    }
-                }
-                println!("Successfully loaded sample: {}", file_path)
-            }
+            // Played at file level so normalisation done in the sample editor is kept
+            Ok(_) => println!("Successfully loaded sample: {}", file_path),
             Err(e) => eprintln!("Failed to load sample {}: {}", file_path, e),
         }
     }
@@ -917,6 +1215,7 @@ impl Sampler {
             buffer.data.clear();
             buffer.length_samples = 0;
             buffer.channels = 1;
+            buffer.stream = None;
         }
         // Force all voices idle
         for v in &mut self.voices {
@@ -926,6 +1225,38 @@ impl Sampler {
     }
 
     fn load_audio_file(&mut self, file_path: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let size = std::fs::metadata(file_path).map(|m| m.len()).unwrap_or(0);
+        if size > STREAM_THRESHOLD_BYTES {
+            match DiskStream::open(file_path) {
+                Ok((head, rate, total_frames, stream)) => {
+                    let mut buffer = self.sample_buffer.lock().unwrap();
+                    buffer.data = head;
+                    buffer.length_samples = total_frames;
+                    buffer.sample_rate = rate;
+                    buffer.channels = 1;
+                    buffer.stream = Some(stream);
+                    return Ok(());
+                }
+                // Fall back to decoding into memory
+                Err(e) => eprintln!("Streaming unavailable for {}: {}", file_path, e),
+            }
+        }
+        let decoded = Self::decode_file(file_path)?;
+        if !decoded.is_empty() {
+            // Conform to the engine rate once so voices play at a true 1:1 step
+            let data = resample(&decoded.data, decoded.sample_rate, self.sr);
+            let mut buffer = self.sample_buffer.lock().unwrap();
+            buffer.length_samples = data.len();
+            buffer.data = data;
+            buffer.sample_rate = self.sr;
+            buffer.channels = 1; // We convert everything to mono
+            buffer.stream = None;
+        }
+        Ok(())
+    }
+
+    // Decode a file to a mono buffer at its native sample rate
+    pub fn decode_file(file_path: &str) -> Result<SampleBuffer, Box<dyn std::error::Error>> {
         // Open the file
         let file = File::open(file_path)?;
         let mss = MediaSourceStream::new(Box::new(file), Default::default());
@@ -1229,37 +1560,17 @@ impl Sampler {
             }
         }
 
-        // Update the sample buffer
-        if !sample_buf.is_empty() {
-            let mut buffer = self.sample_buffer.lock().unwrap();
-            buffer.data = sample_buf;
-            buffer.length_samples = buffer.data.len();
-            buffer.sample_rate = sample_rate;
-            buffer.channels = 1; // We convert everything to mono
-        }
-
-        Ok(())
+        Ok(SampleBuffer {
+            length_samples: sample_buf.len(),
+            data: sample_buf,
+            channels: 1, // We convert everything to mono
+            sample_rate,
+            stream: None,
+        })
     }
 
     pub fn get_waveform_overview(&self, samples: usize) -> Vec<f32> {
-        let buffer = self.sample_buffer.lock().unwrap();
-        if buffer.is_empty() {
-            return vec![0.0; samples];
-        }
-
-        let mut overview = Vec::with_capacity(samples);
-        let step = buffer.length_samples as f32 / samples as f32;
-
-        for i in 0..samples {
-            let pos = (i as f32 * step) as usize;
-            if pos < buffer.length_samples {
-                overview.push(buffer.data[pos]);
-            } else {
-                overview.push(0.0);
-            }
-        }
-
-        overview
+        self.sample_buffer.lock().unwrap().overview(samples)
     }
 
     pub fn get_sample_info(&self) -> (usize, f32, usize) {
@@ -1315,3 +1626,109 @@ impl Sampler {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::messages::ParamValue;
+    use crate::engine::params::hash_path;
+
+    const SR: f32 = 16000.0;
+
+    fn keys() -> SamplerParamKeys {
+        let p = |name: &str| hash_path(&format!("part/0/sampler/{}", name));
+        SamplerParamKeys {
+            module_kind: p("module_kind"),
+            sample_start: p("sample_start"),
+            sample_end: p("sample_end"),
+            pitch_semitones: p("pitch_semitones"),
+            pitch_cents: p("pitch_cents"),
+            playback_mode: p("playback_mode"),
+            loop_start: p("loop_start"),
+            loop_end: p("loop_end"),
+            loop_mode: p("loop_mode"),
+            smoothness: p("smoothness"),
+            retrig_mode: p("retrig_mode"),
+            warp: p("warp"),
+            warp_bars: p("warp_bars"),
+            warp_grain: p("warp_grain"),
+            quality: p("quality"),
+            attack: p("attack"),
+            decay: p("decay"),
+            sustain: p("sustain"),
+            release: p("release"),
+            pressure_amp: p("pressure_amp"),
+            slide_filter: p("slide_filter"),
+        }
+    }
+
+    fn tone(freq: f32, seconds: f32) -> SampleBuffer {
+        let len = (seconds * SR) as usize;
+        SampleBuffer {
+            data: (0..len)
+                .map(|i| (2.0 * PI * freq * i as f32 / SR).sin())
+                .collect(),
+            channels: 1,
+            sample_rate: SR,
+            length_samples: len,
+            stream: None,
+        }
+    }
+
+    // Warped Loop playback of `buffer` at `bpm`; returns the audio and the analysis playhead
+    fn render_warped(
+        buffer: &SampleBuffer,
+        bars: f32,
+        bpm: f32,
+        seconds: f32,
+    ) -> (Vec<f32>, Vec<f32>) {
+        let mut params = ParamStore::new();
+        params.set("part/0/sampler/playback_mode".into(), ParamValue::I32(1));
+        params.set("part/0/sampler/warp".into(), ParamValue::I32(1));
+        params.set("part/0/sampler/warp_bars".into(), ParamValue::F32(bars));
+        params.set("part/0/sampler/sustain".into(), ParamValue::F32(1.0));
+        let keys = keys();
+        let mut voice = SamplerVoice::new(SR);
+        voice.note_on(60, 1.0);
+        let beats_per_sample = bpm as f64 / 60.0 / SR as f64;
+        let (mut out, mut playhead) = (Vec::new(), Vec::new());
+        for i in 0..(seconds * SR) as usize {
+            let beat_phase = (i as f64 * beats_per_sample).fract() as f32;
+            out.push(voice.render(buffer, &params, &keys, beat_phase));
+            playhead.push(voice.position());
+        }
+        (out, playhead)
+    }
+
+    #[test]
+    fn warp_cycle_lasts_the_warped_bars_at_tempo() {
+        let buffer = tone(200.0, 1.5);
+        for (bars, bpm) in [(2.0, 120.0), (1.0, 90.0), (0.5, 140.0)] {
+            let expected = bars * 4.0 * 60.0 / bpm * SR;
+            let (_, playhead) = render_warped(&buffer, bars, bpm, 2.5 * expected / SR);
+            // The playhead jumps back to the loop start once per cycle
+            let wraps: Vec<usize> = (1..playhead.len())
+                .filter(|&i| playhead[i] < playhead[i - 1] - 0.5 * buffer.length_samples as f32)
+                .collect();
+            assert_eq!(wraps.len(), 2, "{} bars at {} BPM", bars, bpm);
+            assert!((wraps[0] as f32 - expected).abs() < 0.002 * expected);
+            assert!(((wraps[1] - wraps[0]) as f32 - expected).abs() < 0.002 * expected);
+        }
+    }
+
+    #[test]
+    fn warp_keeps_the_pitch_of_a_pure_tone() {
+        // A 1 s loop stretched to 2 s and squeezed to 0.5 s
+        let buffer = tone(400.0, 1.0);
+        for bars in [1.0, 0.25] {
+            let (out, _) = render_warped(&buffer, bars, 120.0, 2.0);
+            let steady = &out[(0.1 * SR) as usize..];
+            let rising = steady
+                .windows(2)
+                .filter(|w| w[0] <= 0.0 && w[1] > 0.0)
+                .count() as f32;
+            let hz = rising / (steady.len() as f32 / SR);
+            assert!((hz - 400.0).abs() < 4.0, "{} bars: {} Hz", bars, hz);
+        }
+    }
+}
@@ -0,0 +1,239 @@
+// Wavetable oscillator source (module_kind 9)
+//
+// A table is a run of single-cycle frames, FRAME_LEN samples each, loaded from a Serum-style
+// WAV of back-to-back 2048-sample frames, a single-cycle file of any length, or a folder of
+// single cycles (one frame per file, in name order). Every frame is stored as a mip-map of
+// band-limited copies built with an FFT; playback picks the copy whose top harmonic stays below
+// Nyquist and morphs between neighbouring frames by `wavetable/position`.
+//
+// The module is the analog voice with oscillator A reading the table: oscillator B, the filters,
+// envelopes, mod rows (dest 7 = position) and the part FX chain all work as in the analog engine.
+
+use crate::engine::modules::sampler::Sampler;
+use rustfft::{num_complex::Complex32, FftPlanner};
+use std::path::Path;
+
+pub const FRAME_LEN: usize = 2048;
+pub const MAX_FRAMES: usize = 256;
+// Level l keeps harmonics up to (FRAME_LEN / 2) >> l, so the last level is a sine
+const MIP_LEVELS: usize = 11;
+const AUDIO_EXTENSIONS: [&str; 5] = ["wav", "flac", "aif", "aiff", "mp3"];
+
+pub struct WavetableBank {
+    frames: usize,
+    // levels[l] holds every frame's copy at level l back to back, level_len[l] samples each
+    levels: Vec<Vec<f32>>,
+    level_len: [usize; MIP_LEVELS],
+}
+
+impl WavetableBank {
+    // Loads a table from a file or a folder of single-cycle files
+    pub fn load(path: &str) -> Result<Self, String> {
+        let p = Path::new(path);
+        let frames = if p.is_dir() {
+            let mut files: Vec<_> = std::fs::read_dir(p)
+                .map_err(|e| format!("read_dir: {e}"))?
+                .filter_map(|e| e.ok().map(|e| e.path()))
+                .filter(|f| {
+                    f.extension()
+                        .and_then(|x| x.to_str())
+                        .map(|x| AUDIO_EXTENSIONS.contains(&x.to_ascii_lowercase().as_str()))
+                        .unwrap_or(false)
+                })
+                .collect();
+            files.sort();
+            let mut frames = Vec::new();
+            for f in files.iter().take(MAX_FRAMES) {
+                let data = decode(&f.to_string_lossy())?;
+                if !data.is_empty() {
+                    frames.push(resample_cycle(&data));
+                }
+            }
+            frames
+        } else {
+            let data = decode(path)?;
+            if data.len() >= FRAME_LEN && data.len() % FRAME_LEN == 0 {
+                data.chunks(FRAME_LEN)
+                    .take(MAX_FRAMES)
+                    .map(|c| c.to_vec())
+                    .collect()
+            } else if data.is_empty() {
+                Vec::new()
+            } else {
+                vec![resample_cycle(&data)]
+            }
+        };
+        Self::from_frames(&frames)
+    }
+
+    // Builds the mip-maps; every frame must be FRAME_LEN samples long
+    pub fn from_frames(frames: &[Vec<f32>]) -> Result<Self, String> {
+        if frames.is_empty() {
+            return Err("wavetable has no frames".to_string());
+        }
+        if frames.iter().any(|f| f.len() != FRAME_LEN) {
+            return Err(format!("wavetable frames must be {FRAME_LEN} samples"));
+        }
+        let mut planner = FftPlanner::<f32>::new();
+        let forward = planner.plan_fft_forward(FRAME_LEN);
+        let mut level_len = [0usize; MIP_LEVELS];
+        let mut inverse = Vec::with_capacity(MIP_LEVELS);
+        for (l, len) in level_len.iter_mut().enumerate() {
+            let harmonics = (FRAME_LEN / 2) >> l;
+            *len = (4 * harmonics).clamp(64, FRAME_LEN);
+            inverse.push(planner.plan_fft_inverse(*len));
+        }
+        let mut levels: Vec<Vec<f32>> = level_len
+            .iter()
+            .map(|&len| Vec::with_capacity(len * frames.len()))
+            .collect();
+
+        let mut spectrum = vec![Complex32::new(0.0, 0.0); FRAME_LEN];
+        let mut buf = Vec::with_capacity(FRAME_LEN);
+        for frame in frames {
+            for (s, &x) in spectrum.iter_mut().zip(frame.iter()) {
+                *s = Complex32::new(x, 0.0);
+            }
+            forward.process(&mut spectrum);
+            for l in 0..MIP_LEVELS {
+                let len = level_len[l];
+                // DC is dropped; the Nyquist bin of the full-size copy too
+                let top = ((FRAME_LEN / 2) >> l).min(len / 2 - 1);
+                buf.clear();
+                buf.resize(len, Complex32::new(0.0, 0.0));
+                for k in 1..=top {
+                    buf[k] = spectrum[k];
+                    buf[len - k] = spectrum[k].conj();
+                }
+                inverse[l].process(&mut buf);
+                let scale = 1.0 / FRAME_LEN as f32;
+                levels[l].extend(buf.iter().map(|c| c.re * scale));
+            }
+        }
+
+        // One gain for the whole table so frames keep their relative levels
+        let peak = levels[0].iter().fold(0.0f32, |m, &x| m.max(x.abs()));
+        if peak > 1e-6 {
+            for level in &mut levels {
+                for x in level.iter_mut() {
+                    *x /= peak;
+                }
+            }
+        }
+        Ok(Self {
+            frames: frames.len(),
+            levels,
+            level_len,
+        })
+    }
+
+    pub fn frame_count(&self) -> usize {
+        self.frames
+    }
+
+    // phase: 0..1 within the cycle, position: 0..1 across the frames, dt: frequency / sample rate
+    #[inline]
+    pub fn sample(&self, phase: f32, position: f32, dt: f32) -> f32 {
+        // Smallest level whose top harmonic (1024 >> l) fits under Nyquist
+        let level = (FRAME_LEN as f32 * dt.abs()).log2().ceil();
+        let l = if level > 0.0 {
+            (level as usize).min(MIP_LEVELS - 1)
+        } else {
+            0
+        };
+        let len = self.level_len[l];
+        let table = &self.levels[l];
+
+        let fpos = position.clamp(0.0, 1.0) * (self.frames - 1) as f32;
+        let f0 = fpos as usize;
+        let f1 = (f0 + 1).min(self.frames - 1);
+        let ft = fpos - f0 as f32;
+
+        let idx = phase.rem_euclid(1.0) * len as f32;
+        let i0 = (idx as usize).min(len - 1);
+        let i1 = (i0 + 1) % len;
+        let t = idx - i0 as f32;
+        let read = |f: usize| {
+            let base = f * len;
+            table[base + i0] + (table[base + i1] - table[base + i0]) * t
+        };
+        let a = read(f0);
+        if ft > 0.0 && f1 != f0 {
+            a + (read(f1) - a) * ft
+        } else {
+            a
+        }
+    }
+}
+
+fn decode(path: &str) -> Result<Vec<f32>, String> {
+    Sampler::decode_file(path)
+        .map(|b| b.data)
+        .map_err(|e| format!("{path}: {e}"))
+}
+
+// Stretch one cycle of any length to FRAME_LEN samples (linear, wrapping at the end)
+fn resample_cycle(data: &[f32]) -> Vec<f32> {
+    let n = data.len();
+    (0..FRAME_LEN)
+        .map(|i| {
+            let x = i as f32 * n as f32 / FRAME_LEN as f32;
+            let i0 = (x as usize).min(n - 1);
+            let i1 = (i0 + 1) % n;
+            data[i0] + (data[i1] - data[i0]) * (x - i0 as f32)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::PI;
+
+    fn saw_frames(count: usize) -> Vec<Vec<f32>> {
+        (0..count)
+            .map(|f| {
+                (0..FRAME_LEN)
+                    .map(|i| {
+                        let p = i as f32 / FRAME_LEN as f32;
+                        // Frame 0 is a sine, later frames a saw
+                        if f == 0 {
+                            (2.0 * PI * p).sin()
+                        } else {
+                            2.0 * p - 1.0
+                        }
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn high_notes_read_band_limited_levels() {
+        let bank = WavetableBank::from_frames(&saw_frames(2)).unwrap();
+        // At 5 kHz / 48 kHz only four harmonics fit, so the saw's sharp edge must be gone
+        let dt = 5000.0 / 48000.0;
+        let step = (0..256)
+            .map(|i| {
+                let a = bank.sample(i as f32 / 256.0, 1.0, dt);
+                let b = bank.sample((i + 1) as f32 / 256.0, 1.0, dt);
+                (b - a).abs()
+            })
+            .fold(0.0f32, f32::max);
+        assert!(step < 0.3, "edge step {step}");
+        // A low note keeps the edge
+        let low = (bank.sample(0.999, 1.0, 0.0005) - bank.sample(0.001, 1.0, 0.0005)).abs();
+        assert!(low > 1.0, "low edge {low}");
+    }
+
+    #[test]
+    fn position_morphs_between_frames() {
+        let bank = WavetableBank::from_frames(&saw_frames(2)).unwrap();
+        let dt = 100.0 / 48000.0;
+        let sine = bank.sample(0.25, 0.0, dt);
+        let saw = bank.sample(0.25, 1.0, dt);
+        let mid = bank.sample(0.25, 0.5, dt);
+        assert!((mid - 0.5 * (sine + saw)).abs() < 1e-4);
+        assert!(WavetableBank::from_frames(&[vec![0.0; 10]]).is_err());
+    }
+}
@@ -2,10 +2,19 @@ use std::collections::HashMap;
 
 use super::messages::ParamValue;
 
+// Mod matrix offset on a parameter, clamped to the target's range
+#[derive(Clone, Copy)]
+struct ModOffset {
+    amount: f32,
+    lo: f32,
+    hi: f32,
+}
+
 #[derive(Clone)]
 pub struct ParamStore {
     pub map: HashMap<String, ParamValue>,
     map_h: HashMap<u64, ParamValue>,
+    mods: HashMap<u64, ModOffset>,
 }
 
 impl ParamStore {
@@ -13,6 +22,7 @@ impl ParamStore {
         Self {
             map: HashMap::new(),
             map_h: HashMap::new(),
+            mods: HashMap::new(),
         }
     }
     pub fn set(&mut self, path: String, v: ParamValue) {
@@ -35,11 +45,34 @@ impl ParamStore {
         }
     }
     pub fn get_f32_h(&self, key: u64, default: f32) -> f32 {
-        match self.map_h.get(&key) {
+        let v = match self.map_h.get(&key) {
             Some(ParamValue::F32(v)) => *v,
             _ => default,
+        };
+        if self.mods.is_empty() {
+            return v;
+        }
+        match self.mods.get(&key) {
+            Some(m) => (v + m.amount).clamp(m.lo, m.hi),
+            None => v,
+        }
+    }
+    pub fn get_str_h<'a>(&'a self, key: u64, default: &'a str) -> &'a str {
+        match self.map_h.get(&key) {
+            Some(ParamValue::Str(v)) => v.as_str(),
+            _ => default,
         }
     }
+    // Modulation overlay: offsets summed per key, rebuilt by the mod matrix every block
+    pub fn clear_mods(&mut self) {
+        self.mods.clear();
+    }
+    pub fn add_mod(&mut self, key: u64, amount: f32, lo: f32, hi: f32) {
+        self.mods
+            .entry(key)
+            .and_modify(|m| m.amount += amount)
+            .or_insert(ModOffset { amount, lo, hi });
+    }
     pub fn get_i32_h(&self, key: u64, default: i32) -> i32 {
         match self.map_h.get(&key) {
             Some(ParamValue::I32(v)) => *v,
@@ -65,7 +98,11 @@ impl ParamStore {
 #[inline]
 fn fast_hash(s: &str) -> u64 {
     // FNV-1a 64-bit
-    let mut hash: u64 = 0xcbf29ce484222325; // offset basis
+    fnv_continue(0xcbf29ce484222325, s) // offset basis
+}
+
+#[inline]
+fn fnv_continue(mut hash: u64, s: &str) -> u64 {
     for b in s.as_bytes() {
         hash ^= *b as u64;
         hash = hash.wrapping_mul(0x100000001b3);
@@ -78,6 +115,11 @@ pub fn hash_path(path: &str) -> u64 {
     fast_hash(path)
 }
 
+// Hash of `prefix + suffix` given the hash of `prefix` (FNV is streaming), without allocating
+pub fn hash_path_suffix(prefix_hash: u64, suffix: &str) -> u64 {
+    fnv_continue(prefix_hash, suffix)
+}
+
 // Alias for modules that use path_hash naming
 pub fn path_hash(path: &str) -> u64 {
     fast_hash(path)
@@ -3,6 +3,7 @@ mod engine {
     pub mod dsp;
     pub mod graph;
     pub mod messages;
+    pub mod mod_matrix;
     pub mod modules;
     pub mod params;
     pub mod state;
@@ -11,6 +12,8 @@ mod commands;
 use commands::*;
 mod fs_api;
 use fs_api::*;
+mod sample_edit;
+use sample_edit::*;
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
@@ -22,6 +25,13 @@ pub fn run() {
             set_param,
             note_on,
             note_off,
+            pitch_bend,
+            mod_wheel,
+            aftertouch,
+            sustain,
+            note_bend,
+            note_pressure,
+            note_slide,
             set_tempo,
             set_transport,
             debug_ping,
@@ -50,14 +60,20 @@ pub fn run() {
             list_subsamples,
             load_sample,
             clear_sample,
+            load_wavetable,
+            load_resonator_profile,
             preview_sample,
             stop_preview,
             get_sample_waveform,
             get_sampler_playhead,
             get_sample_info,
+            edit_sample,
+            undo_sample_edit,
             list_drum_packs,
             list_drum_samples,
             load_drum_pack,
+            set_drum_slot_note,
+            swap_drum_slots,
         ])
         .setup(|app| {
             if cfg!(debug_assertions) {
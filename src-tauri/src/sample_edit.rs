@@ -0,0 +1,522 @@
+// Destructive sample edits for files in Documents/subsamples.
+//
+// Every edit decodes the source, applies one operation and writes the result as a new
+// 32-bit float WAV next to it; the source file is never modified. A hidden sidecar
+// (`.<new file>.undo.json`) records which file an edit came from so `undo_sample_edit`
+// can step back through a chain of edits.
+
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use symphonia::core::audio::SampleBuffer;
+use symphonia::core::codecs::{DecoderOptions, CODEC_TYPE_NULL};
+use symphonia::core::errors::Error;
+use symphonia::core::formats::FormatOptions;
+use symphonia::core::io::MediaSourceStream;
+use symphonia::core::meta::MetadataOptions;
+use symphonia::core::probe::Hint;
+
+use crate::commands::resolve_subsample_path;
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NormalizeMode {
+    Peak, // target in dBFS
+    Lufs, // target in LUFS (ITU-R BS.1770 integrated), held back so peaks stay at or below 0 dBFS
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum SampleEdit {
+    // Keep [start, end) given as 0..1 of the file
+    Trim {
+        start: f32,
+        end: f32,
+    },
+    Normalize {
+        mode: NormalizeMode,
+        target_db: f32,
+    },
+    Reverse,
+    Fade {
+        #[serde(default)]
+        fade_in_ms: f32,
+        #[serde(default)]
+        fade_out_ms: f32,
+    },
+    RemoveDc,
+    // Same normalised values the Sampler uses: loop points are relative to the trimmed region
+    CropToLoop {
+        sample_start: f32,
+        sample_end: f32,
+        loop_start: f32,
+        loop_end: f32,
+    },
+    Channels {
+        count: usize, // 1 = mono, 2 = stereo
+    },
+}
+
+impl SampleEdit {
+    fn suffix(&self) -> &'static str {
+        match self {
+            SampleEdit::Trim { .. } => "trim",
+            SampleEdit::Normalize { .. } => "norm",
+            SampleEdit::Reverse => "rev",
+            SampleEdit::Fade { .. } => "fade",
+            SampleEdit::RemoveDc => "dc",
+            SampleEdit::CropToLoop { .. } => "loop",
+            SampleEdit::Channels { count: 1 } => "mono",
+            SampleEdit::Channels { .. } => "stereo",
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct UndoRecord {
+    source: String, // file name (relative to subsamples) the edit was made from
+}
+
+// Interleaved audio
+#[derive(Debug, Clone)]
+struct Audio {
+    data: Vec<f32>,
+    channels: usize,
+    sample_rate: u32,
+}
+
+impl Audio {
+    fn frames(&self) -> usize {
+        self.data.len() / self.channels.max(1)
+    }
+
+    fn slice_frames(&mut self, start: usize, end: usize) {
+        let end = end.min(self.frames());
+        let start = start.min(end);
+        self.data = self.data[start * self.channels..end * self.channels].to_vec();
+    }
+}
+
+fn frac_to_frame(frac: f32, frames: usize) -> usize {
+    ((frac.clamp(0.0, 1.0) as f64) * frames as f64).round() as usize
+}
+
+fn apply_edit(audio: &mut Audio, edit: &SampleEdit) -> Result<(), String> {
+    let ch = audio.channels;
+    let frames = audio.frames();
+    match *edit {
+        SampleEdit::Trim { start, end } => {
+            let (s, e) = (frac_to_frame(start, frames), frac_to_frame(end, frames));
+            if e <= s {
+                return Err("empty_selection".to_string());
+            }
+            audio.slice_frames(s, e);
+        }
+        SampleEdit::CropToLoop {
+            sample_start,
+            sample_end,
+            loop_start,
+            loop_end,
+        } => {
+            let region = |x: f32| {
+                let a = sample_start.clamp(0.0, 1.0);
+                let b = sample_end.clamp(0.0, 1.0);
+                a + x.clamp(0.0, 1.0) * (b - a)
+            };
+            let s = frac_to_frame(region(loop_start), frames);
+            let e = frac_to_frame(region(loop_end), frames);
+            if e <= s {
+                return Err("empty_selection".to_string());
+            }
+            audio.slice_frames(s, e);
+        }
+        SampleEdit::Normalize { mode, target_db } => {
+            let peak = audio.data.iter().fold(0.0f32, |m, s| m.max(s.abs()));
+            if peak < 1e-6 {
+                return Err("silent_sample".to_string());
+            }
+            let headroom_db = -20.0 * peak.log10();
+            let gain_db = match mode {
+                NormalizeMode::Peak => target_db.min(0.0) + headroom_db,
+                NormalizeMode::Lufs => {
+                    let lufs = integrated_loudness(audio).ok_or("silent_sample")?;
+                    (target_db - lufs).min(headroom_db)
+                }
+            };
+            let g = 10f32.powf(gain_db / 20.0);
+            for s in &mut audio.data {
+                *s *= g;
+            }
+        }
+        SampleEdit::Reverse => {
+            let mut out = Vec::with_capacity(audio.data.len());
+            for frame in audio.data.chunks(ch).rev() {
+                out.extend_from_slice(frame);
+            }
+            audio.data = out;
+        }
+        SampleEdit::Fade {
+            fade_in_ms,
+            fade_out_ms,
+        } => {
+            let sr = audio.sample_rate as f32;
+            let fin = ((fade_in_ms.max(0.0) * 0.001 * sr) as usize).min(frames);
+            let fout = ((fade_out_ms.max(0.0) * 0.001 * sr) as usize).min(frames);
+            for (i, frame) in audio.data.chunks_mut(ch).enumerate() {
+                let mut g = 1.0;
+                if i < fin {
+                    g *= i as f32 / fin as f32;
+                }
+                let from_end = frames - 1 - i;
+                if from_end < fout {
+                    g *= from_end as f32 / fout as f32;
+                }
+                for s in frame {
+                    *s *= g;
+                }
+            }
+        }
+        SampleEdit::RemoveDc => {
+            // DC blocker (one-pole high-pass ~10 Hz) run forwards then backwards for zero phase
+            let r = (-2.0 * std::f32::consts::PI * 10.0 / audio.sample_rate as f32).exp();
+            for c in 0..ch {
+                let idx: Vec<usize> = (0..frames).map(|i| i * ch + c).collect();
+                for pass in [idx.clone(), idx.into_iter().rev().collect()] {
+                    let (mut x1, mut y1) = (0.0f32, 0.0f32);
+                    for i in pass {
+                        let x = audio.data[i];
+                        let y = x - x1 + r * y1;
+                        x1 = x;
+                        y1 = y;
+                        audio.data[i] = y;
+                    }
+                }
+            }
+        }
+        SampleEdit::Channels { count } => {
+            let count = count.clamp(1, 2);
+            if count == ch {
+                return Ok(());
+            }
+            let mut out = Vec::with_capacity(frames * count);
+            for frame in audio.data.chunks(ch) {
+                if count == 1 {
+                    out.push(frame.iter().sum::<f32>() / ch as f32);
+                } else {
+                    // Mono (or first two of many) to stereo
+                    let l = frame[0];
+                    let r = if ch > 1 { frame[1] } else { frame[0] };
+                    out.push(l);
+                    out.push(r);
+                }
+            }
+            audio.data = out;
+            audio.channels = count;
+        }
+    }
+    if audio.data.is_empty() {
+        return Err("empty_selection".to_string());
+    }
+    Ok(())
+}
+
+// ---- Loudness (ITU-R BS.1770-4 integrated, gated) ----
+
+// Direct-form-I biquad used for the K-weighting pre-filter
+#[derive(Clone, Copy)]
+struct Biquad {
+    b: [f64; 3],
+    a: [f64; 2],
+    x: [f64; 2],
+    y: [f64; 2],
+}
+
+impl Biquad {
+    fn process(&mut self, x0: f64) -> f64 {
+        let y0 = self.b[0] * x0 + self.b[1] * self.x[0] + self.b[2] * self.x[1]
+            - self.a[0] * self.y[0]
+            - self.a[1] * self.y[1];
+        self.x = [x0, self.x[0]];
+        self.y = [y0, self.y[0]];
+        y0
+    }
+}
+
+// Stage 1 (high shelf) and stage 2 (RLB high-pass) of the K filter for any sample rate
+fn k_weighting(sr: f64) -> [Biquad; 2] {
+    let pi = std::f64::consts::PI;
+    let (f0, g, q) = (1681.974450955533, 3.999843853973347, 0.7071752369554196);
+    let k = (pi * f0 / sr).tan();
+    let vh = 10f64.powf(g / 20.0);
+    let vb = vh.powf(0.4996667741545416);
+    let a0 = 1.0 + k / q + k * k;
+    let shelf = Biquad {
+        b: [
+            (vh + vb * k / q + k * k) / a0,
+            2.0 * (k * k - vh) / a0,
+            (vh - vb * k / q + k * k) / a0,
+        ],
+        a: [2.0 * (k * k - 1.0) / a0, (1.0 - k / q + k * k) / a0],
+        x: [0.0; 2],
+        y: [0.0; 2],
+    };
+    let (f0, q) = (38.13547087602444, 0.5003270373238773);
+    let k = (pi * f0 / sr).tan();
+    let a0 = 1.0 + k / q + k * k;
+    let highpass = Biquad {
+        b: [1.0, -2.0, 1.0],
+        a: [2.0 * (k * k - 1.0) / a0, (1.0 - k / q + k * k) / a0],
+        x: [0.0; 2],
+        y: [0.0; 2],
+    };
+    [shelf, highpass]
+}
+
+// Integrated loudness in LUFS, or None for silence / clips shorter than one 400 ms block
+fn integrated_loudness(audio: &Audio) -> Option<f32> {
+    let sr = audio.sample_rate as f64;
+    let ch = audio.channels;
+    let frames = audio.frames();
+    let block = (0.4 * sr) as usize;
+    let hop = (0.1 * sr) as usize;
+    if block == 0 || frames < block {
+        return None;
+    }
+
+    // K-weighted squared signal summed over channels (L/R/mono weight 1.0)
+    let mut power = vec![0.0f64; frames];
+    for c in 0..ch {
+        let [mut shelf, mut highpass] = k_weighting(sr);
+        for (i, p) in power.iter_mut().enumerate() {
+            let y = highpass.process(shelf.process(audio.data[i * ch + c] as f64));
+            *p += y * y;
+        }
+    }
+
+    let mut blocks = Vec::new();
+    let mut start = 0;
+    while start + block <= frames {
+        let z: f64 = power[start..start + block].iter().sum::<f64>() / block as f64;
+        blocks.push(z);
+        start += hop;
+    }
+    let loudness = |z: f64| -0.691 + 10.0 * z.log10();
+    let gated_mean = |thresh: f64| {
+        let kept: Vec<f64> = blocks
+            .iter()
+            .copied()
+            .filter(|&z| z > 0.0 && loudness(z) > thresh)
+            .collect();
+        if kept.is_empty() {
+            None
+        } else {
+            Some(kept.iter().sum::<f64>() / kept.len() as f64)
+        }
+    };
+    let abs_gated = gated_mean(-70.0)?;
+    let rel_gated = gated_mean(loudness(abs_gated) - 10.0)?;
+    Some(loudness(rel_gated) as f32)
+}
+
+// ---- File I/O ----
+
+fn decode(path: &Path) -> Result<Audio, String> {
+    let file = File::open(path).map_err(|e| e.to_string())?;
+    let mss = MediaSourceStream::new(Box::new(file), Default::default());
+    let mut hint = Hint::new();
+    if let Some(ext) = path.extension().and_then(|e| e.to_str()) {
+        hint.with_extension(ext);
+    }
+    let meta_opts: MetadataOptions = Default::default();
+    let fmt_opts: FormatOptions = Default::default();
+    let probed = symphonia::default::get_probe()
+        .format(&hint, mss, &fmt_opts, &meta_opts)
+        .map_err(|e| e.to_string())?;
+    let mut reader = probed.format;
+    let track = reader
+        .tracks()
+        .iter()
+        .find(|t| t.codec_params.codec != CODEC_TYPE_NULL)
+        .ok_or_else(|| "no supported audio tracks".to_string())?;
+    let dec_opts: DecoderOptions = Default::default();
+    let mut decoder = symphonia::default::get_codecs()
+        .make(&track.codec_params, &dec_opts)
+        .map_err(|e| e.to_string())?;
+    let track_id = track.id;
+
+    let mut audio = Audio {
+        data: Vec::new(),
+        channels: track.codec_params.channels.map(|c| c.count()).unwrap_or(1),
+        sample_rate: track.codec_params.sample_rate.unwrap_or(44100),
+    };
+    loop {
+        let packet = match reader.next_packet() {
+            Ok(packet) => packet,
+            Err(Error::ResetRequired) | Err(Error::IoError(_)) => break,
+            Err(err) => return Err(err.to_string()),
+        };
+        if packet.track_id() != track_id {
+            continue;
+        }
+        let decoded = decoder.decode(&packet).map_err(|e| e.to_string())?;
+        let spec = *decoded.spec();
+        audio.channels = spec.channels.count().max(1);
+        audio.sample_rate = spec.rate;
+        let mut buf = SampleBuffer::<f32>::new(decoded.capacity() as u64, spec);
+        buf.copy_interleaved_ref(decoded);
+        audio.data.extend_from_slice(buf.samples());
+    }
+    if audio.data.is_empty() {
+        return Err("empty sample".to_string());
+    }
+    Ok(audio)
+}
+
+fn write_wav(path: &Path, audio: &Audio) -> Result<(), String> {
+    let spec = hound::WavSpec {
+        channels: audio.channels as u16,
+        sample_rate: audio.sample_rate,
+        bits_per_sample: 32,
+        sample_format: hound::SampleFormat::Float,
+    };
+    let mut writer = hound::WavWriter::create(path, spec).map_err(|e| e.to_string())?;
+    for &s in &audio.data {
+        writer.write_sample(s).map_err(|e| e.to_string())?;
+    }
+    writer.finalize().map_err(|e| e.to_string())
+}
+
+fn undo_record_path(file: &Path) -> PathBuf {
+    let name = file.file_name().and_then(|n| n.to_str()).unwrap_or("");
+    file.with_file_name(format!(".{}.undo.json", name))
+}
+
+// `<stem>_<suffix>.wav`, adding `_2`, `_3`, ... until the name is free
+fn edited_path(dir: &Path, source: &Path, suffix: &str) -> PathBuf {
+    let stem = source
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("sample");
+    let mut candidate = dir.join(format!("{}_{}.wav", stem, suffix));
+    let mut n = 2;
+    while candidate.exists() {
+        candidate = dir.join(format!("{}_{}_{}.wav", stem, suffix, n));
+        n += 1;
+    }
+    candidate
+}
+
+/// Apply one edit to `path` (relative to Documents/subsamples) and return the new file's name.
+#[tauri::command]
+pub fn edit_sample(path: String, edit: SampleEdit) -> Result<String, String> {
+    let documents_dir = dirs::document_dir().ok_or("Could not find documents directory")?;
+    let source = resolve_subsample_path(&documents_dir, &path)?;
+    let dir = source.parent().ok_or("invalid_sample_path")?.to_path_buf();
+
+    let mut audio = decode(&source)?;
+    apply_edit(&mut audio, &edit)?;
+
+    let target = edited_path(&dir, &source, edit.suffix());
+    write_wav(&target, &audio)?;
+    let record = UndoRecord {
+        source: path.clone(),
+    };
+    let json = serde_json::to_string_pretty(&record).map_err(|e| e.to_string())?;
+    fs::write(undo_record_path(&target), json).map_err(|e| e.to_string())?;
+
+    let base = fs::canonicalize(documents_dir.join("subsamples")).map_err(|e| e.to_string())?;
+    let rel = target
+        .strip_prefix(&base)
+        .map_err(|_| "invalid_sample_path".to_string())?;
+    Ok(rel.to_string_lossy().to_string())
+}
+
+/// Discard an edited file and return the name of the file it was made from.
+#[tauri::command]
+pub fn undo_sample_edit(path: String) -> Result<String, String> {
+    let documents_dir = dirs::document_dir().ok_or("Could not find documents directory")?;
+    let edited = resolve_subsample_path(&documents_dir, &path)?;
+    let record_path = undo_record_path(&edited);
+    let text = fs::read_to_string(&record_path).map_err(|_| "no_undo_history".to_string())?;
+    let record: UndoRecord = serde_json::from_str(&text).map_err(|e| e.to_string())?;
+    // The original must still exist before the edit is thrown away
+    resolve_subsample_path(&documents_dir, &record.source)?;
+    fs::remove_file(&edited).map_err(|e| e.to_string())?;
+    let _ = fs::remove_file(&record_path);
+    Ok(record.source)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sine(amp: f32, freq: f32, sr: u32, secs: f32, channels: usize) -> Audio {
+        let n = (secs * sr as f32) as usize;
+        let mut data = Vec::with_capacity(n * channels);
+        for i in 0..n {
+            let s = amp * (2.0 * std::f32::consts::PI * freq * i as f32 / sr as f32).sin();
+            for _ in 0..channels {
+                data.push(s);
+            }
+        }
+        Audio {
+            data,
+            channels,
+            sample_rate: sr,
+        }
+    }
+
+    #[test]
+    fn loudness_of_reference_sine() {
+        // BS.1770: a 0 dBFS 997 Hz sine in one channel reads -3.01 LUFS
+        let audio = sine(1.0, 997.0, 48000, 3.0, 1);
+        let l = integrated_loudness(&audio).unwrap();
+        assert!((l + 3.01).abs() < 0.1, "got {l}");
+    }
+
+    #[test]
+    fn lufs_normalize_hits_target() {
+        let mut audio = sine(0.05, 997.0, 44100, 3.0, 2);
+        let edit = SampleEdit::Normalize {
+            mode: NormalizeMode::Lufs,
+            target_db: -16.0,
+        };
+        apply_edit(&mut audio, &edit).unwrap();
+        let l = integrated_loudness(&audio).unwrap();
+        assert!((l + 16.0).abs() < 0.05, "got {l}");
+    }
+
+    #[test]
+    fn lufs_normalize_never_clips() {
+        // A full-scale sine reads about -3 LUFS; 0 LUFS would need +3 dB
+        let mut audio = sine(0.5, 997.0, 48000, 3.0, 1);
+        let edit = SampleEdit::Normalize {
+            mode: NormalizeMode::Lufs,
+            target_db: 0.0,
+        };
+        apply_edit(&mut audio, &edit).unwrap();
+        let peak = audio.data.iter().fold(0.0f32, |m, s| m.max(s.abs()));
+        assert!(peak <= 1.0 + 1e-5 && peak > 0.999, "peak {peak}");
+    }
+
+    #[test]
+    fn reverse_keeps_channel_order_and_crop_uses_region() {
+        let mut audio = Audio {
+            data: vec![1.0, -1.0, 2.0, -2.0, 3.0, -3.0, 4.0, -4.0],
+            channels: 2,
+            sample_rate: 4,
+        };
+        apply_edit(&mut audio, &SampleEdit::Reverse).unwrap();
+        assert_eq!(audio.data, vec![4.0, -4.0, 3.0, -3.0, 2.0, -2.0, 1.0, -1.0]);
+        // Region is the second half; loop is all of it
+        let crop = SampleEdit::CropToLoop {
+            sample_start: 0.5,
+            sample_end: 1.0,
+            loop_start: 0.0,
+            loop_end: 1.0,
+        };
+        apply_edit(&mut audio, &crop).unwrap();
+        assert_eq!(audio.data, vec![2.0, -2.0, 1.0, -1.0]);
+    }
+}